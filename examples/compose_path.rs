@@ -4,14 +4,14 @@
 
 //! Based on https://fiddle.skia.org/c/@compose_path
 
-use jiao::core::canvas::Canvas;
 use jiao::core::color::colors;
 use jiao::core::paint::Paint;
 use jiao::core::paint_types::PaintStyle;
 use jiao::core::path::Path;
 use jiao::core::path_builder::PathBuilder;
-use jiao::core::path_effect::PathEffect;
 use jiao::core::scalar::Scalar;
+use jiao::core::surface::Surface;
+use jiao::encode::png_encoder;
 
 fn star() -> Path {
     const RADIUS: Scalar = 115.2;
@@ -28,10 +28,12 @@ fn star() -> Path {
 }
 
 fn main() {
-    let canvas = Canvas::new();
+    let mut surface = Surface::new_raster_n32_premul(256, 256).unwrap();
+    let canvas = surface.canvas();
 
     let mut paint = Paint::new();
-    let intervals = [10.0, 5.0, 2.0, 5.0];
+    // TODO(Shaohua): Compose dash and discrete path effects.
+    //    let intervals = [10.0, 5.0, 2.0, 5.0];
     //    paint.set_path_effect(PathEffect::compose(
     //        DashPathEffect::new(&intervals, 0.0),
     //        DiscretePathEffect::new(10.0, 4.0),
@@ -39,9 +41,13 @@ fn main() {
     paint.set_style(PaintStyle::Stroke);
     paint.set_stroke_width(2.0);
     paint.set_anti_alias(true);
-    paint.set_color(0xff4285F4.into());
+    paint.set_color(0xFF4285F4.into());
 
-    canvas.clear(colors::WHITE);
+    canvas.clear_color4f(&colors::WHITE);
     let path: Path = star();
-    canvas.draw_path(path, paint);
+    canvas.draw_path(&path, &paint);
+
+    let pixmap = surface.peek_pixels().unwrap();
+    let data = png_encoder::encode(pixmap, &png_encoder::Options::default()).unwrap();
+    std::fs::write("compose_path.png", data).unwrap();
 }
@@ -11,6 +11,7 @@ pub mod id_change_listener;
 pub mod math;
 pub mod thread_id;
 pub mod time;
+pub mod xml;
 
 pub mod math_priv;
 //pub(crate) mod half;
@@ -0,0 +1,177 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+/// Escapes text to be used as XML character data or attribute value.
+#[must_use]
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// An element of XML document, with its attributes and child elements.
+///
+/// Character data between elements is dropped.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct XmlElement {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Self>,
+}
+
+impl XmlElement {
+    /// Returns value of attribute `name`.
+    #[must_use]
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parses `text` and returns its root element.
+///
+/// Declarations, processing instructions, comments and CDATA sections are skipped.
+///
+/// Returns None if `text` is not well-formed.
+#[must_use]
+pub fn parse(text: &str) -> Option<XmlElement> {
+    let mut stack: Vec<XmlElement> = Vec::new();
+    let mut root = None;
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        if let Some(tail) = rest.strip_prefix("<?") {
+            rest = &tail[tail.find("?>")? + 2..];
+        } else if let Some(tail) = rest.strip_prefix("<!--") {
+            rest = &tail[tail.find("-->")? + 3..];
+        } else if let Some(tail) = rest.strip_prefix("<![CDATA[") {
+            rest = &tail[tail.find("]]>")? + 3..];
+        } else if let Some(tail) = rest.strip_prefix("<!") {
+            rest = skip_declaration(tail)?;
+        } else if let Some(tail) = rest.strip_prefix("</") {
+            let end = tail.find('>')?;
+            let element = stack.pop()?;
+            if tail[..end].trim() != element.name {
+                return None;
+            }
+            rest = &tail[end + 1..];
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(element),
+                None => root = Some(element),
+            }
+        } else {
+            let (element, is_empty, tail) = parse_start_tag(&rest[1..])?;
+            rest = tail;
+            if is_empty {
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            } else {
+                stack.push(element);
+            }
+        }
+        if root.is_some() {
+            break;
+        }
+    }
+    root
+}
+
+/// Skips a `<!DOCTYPE>` like declaration, including its internal subset.
+fn skip_declaration(text: &str) -> Option<&str> {
+    let mut depth = 0_i32;
+    for (index, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '>' if depth == 0 => return Some(&text[index + 1..]),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Parses a start tag without the leading `<`.
+///
+/// Returns the element, whether it is an empty element tag, and text after the tag.
+fn parse_start_tag(text: &str) -> Option<(XmlElement, bool, &str)> {
+    let is_name_end = |c: char| c.is_whitespace() || c == '/' || c == '>';
+    let name_end = text.find(is_name_end)?;
+    if name_end == 0 {
+        return None;
+    }
+    let mut element = XmlElement {
+        name: text[..name_end].to_owned(),
+        ..XmlElement::default()
+    };
+    let mut rest = &text[name_end..];
+    loop {
+        rest = rest.trim_start();
+        if let Some(tail) = rest.strip_prefix("/>") {
+            return Some((element, true, tail));
+        }
+        if let Some(tail) = rest.strip_prefix('>') {
+            return Some((element, false, tail));
+        }
+        let equal = rest.find('=')?;
+        let key = rest[..equal].trim();
+        if key.is_empty() {
+            return None;
+        }
+        rest = rest[equal + 1..].trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        rest = &rest[1..];
+        let value_end = rest.find(quote)?;
+        let value = unescape(&rest[..value_end])?;
+        element.attributes.push((key.to_owned(), value));
+        rest = &rest[value_end + 1..];
+    }
+}
+
+/// Replaces character and predefined entity references in `text`.
+///
+/// Returns None if an entity is unknown or malformed.
+#[must_use]
+pub fn unescape(text: &str) -> Option<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let end = rest.find(';')?;
+        let entity = &rest[..end];
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()?
+                } else {
+                    entity.strip_prefix('#')?.parse().ok()?
+                };
+                char::from_u32(code)?
+            }
+        };
+        out.push(c);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
@@ -0,0 +1,623 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Decoder of Windows BMP images.
+//!
+//! Supports 1, 4, 8, 16, 24 and 32 bits per pixel, `BI_RGB`, `BI_RLE4`, `BI_RLE8`
+//! and `BI_BITFIELDS` compression, and OS/2 core, info, V4 and V5 headers.
+//! Decoded pixels are `ColorType::Rgba8888`.
+
+use crate::core::alpha_type::AlphaType;
+use crate::core::color_type::ColorType;
+use crate::core::image_info::ImageInfo;
+use crate::core::pixmap::Pixmap;
+
+const FILE_HEADER_SIZE: usize = 14;
+const CORE_HEADER_SIZE: usize = 12;
+const INFO_HEADER_SIZE: usize = 40;
+const INFO_V3_HEADER_SIZE: usize = 56;
+
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_RLE4: u32 = 2;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// Images larger than this in either dimension are rejected.
+const MAX_DIMENSION: i32 = 1 << 15;
+
+/// A run of two bytes expands to at most 255 pixels, RLE images with more pixels
+/// than this per byte of compressed data are rejected before allocating.
+///
+/// Pixels skipped by delta and end of bitmap escapes are counted too.
+const MAX_RLE_PIXELS_PER_BYTE: usize = 128;
+
+/// Returns true if `data` starts with BMP signature.
+#[must_use]
+#[inline]
+pub fn is_bmp(data: &[u8]) -> bool {
+    data.starts_with(b"BM")
+}
+
+/// Decodes a BMP file, including its file header.
+///
+/// Returns None if `data` is not a valid or supported BMP image.
+#[must_use]
+pub fn decode(data: &[u8]) -> Option<Pixmap> {
+    if !is_bmp(data) || data.len() < FILE_HEADER_SIZE {
+        return None;
+    }
+    let pixel_offset = read_u32(data, 10)? as usize;
+    let dib = &data[FILE_HEADER_SIZE..];
+    let pixel_offset = pixel_offset.checked_sub(FILE_HEADER_SIZE)?;
+    decode_dib(dib, Some(pixel_offset), false)
+}
+
+/// Masks used to extract one channel from 16 or 32 bit pixels.
+#[derive(Debug, Clone, Copy)]
+struct ChannelMask {
+    mask: u32,
+    shift: u32,
+    bits: u32,
+}
+
+impl ChannelMask {
+    const fn new(mask: u32) -> Self {
+        if mask == 0 {
+            return Self {
+                mask: 0,
+                shift: 0,
+                bits: 0,
+            };
+        }
+        let shift = mask.trailing_zeros();
+        let bits = (mask >> shift).trailing_ones();
+        Self { mask, shift, bits }
+    }
+
+    /// Returns true if bits of mask are contiguous, which is required by `get()`.
+    const fn is_contiguous(&self) -> bool {
+        (self.mask >> self.shift).count_ones() == self.bits
+    }
+
+    /// Returns channel value scaled to 8 bits.
+    #[allow(clippy::cast_possible_truncation)]
+    const fn get(&self, pixel: u32) -> u8 {
+        if self.bits == 0 {
+            return 0;
+        }
+        let value = (pixel & self.mask) >> self.shift;
+        if self.bits >= 8 {
+            (value >> (self.bits - 8)) as u8
+        } else {
+            let max = (1 << self.bits) - 1;
+            ((value * 255 + max / 2) / max) as u8
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Header {
+    width: usize,
+    height: usize,
+    top_down: bool,
+    bits_per_pixel: u16,
+    compression: u32,
+    masks: [ChannelMask; 4],
+    palette: Vec<[u8; 4]>,
+
+    /// Offset of pixel data from start of DIB.
+    pixel_offset: usize,
+}
+
+/// Decodes a device independent bitmap, which is a BMP without its file header.
+///
+/// If `pixel_offset` is None, pixels follow the header and palette immediately.
+/// If `is_ico` is true, height in header is doubled and an AND mask follows
+/// the pixels, as stored in ICO and CUR files.
+///
+/// Returns None if pixel data is missing, if uncompressed pixel data is
+/// truncated by more than one row, or if RLE pixel data is too short for
+/// image size.
+#[must_use]
+pub(crate) fn decode_dib(data: &[u8], pixel_offset: Option<usize>, is_ico: bool) -> Option<Pixmap> {
+    let header = parse_header(data, pixel_offset, is_ico)?;
+    let width = header.width;
+    let height = header.height;
+    let pixel_data = data.get(header.pixel_offset..)?;
+    let is_rle = matches!(header.compression, BI_RLE8 | BI_RLE4);
+    if !is_rle {
+        // Check size of pixel data before allocating pixels, only the last row
+        // may be truncated.
+        let row_bytes = row_bytes(header.width, header.bits_per_pixel)?;
+        if pixel_data.len() < row_bytes || row_bytes.checked_mul(height - 1)? >= pixel_data.len() {
+            return None;
+        }
+    } else if pixel_data.is_empty()
+        || width.checked_mul(height)? / MAX_RLE_PIXELS_PER_BYTE >= pixel_data.len()
+    {
+        return None;
+    }
+    let mut pixels = vec![0_u8; width.checked_mul(height)?.checked_mul(4)?];
+
+    let mut has_alpha = if is_rle {
+        decode_rle(&header, pixel_data, &mut pixels)?;
+        true
+    } else {
+        decode_uncompressed(&header, pixel_data, &mut pixels)?
+    };
+
+    if is_ico && !has_alpha {
+        let row_bytes = row_bytes(header.width, header.bits_per_pixel)?;
+        let mask_offset = row_bytes.checked_mul(height)?;
+        if let Some(mask) = pixel_data.get(mask_offset..) {
+            apply_and_mask(&header, mask, &mut pixels);
+            has_alpha = true;
+        }
+    }
+
+    let alpha_type = if has_alpha {
+        AlphaType::Unpremul
+    } else {
+        AlphaType::Opaque
+    };
+    let info = ImageInfo::from(
+        i32::try_from(header.width).ok()?,
+        i32::try_from(header.height).ok()?,
+        ColorType::Rgba8888,
+        alpha_type,
+        None,
+    );
+    Some(Pixmap::from(info, width * 4, &pixels))
+}
+
+fn parse_header(data: &[u8], pixel_offset: Option<usize>, is_ico: bool) -> Option<Header> {
+    let header_size = read_u32(data, 0)? as usize;
+    let (width, mut height, bits_per_pixel, mut compression, colors_used) =
+        if header_size == CORE_HEADER_SIZE {
+            (
+                i32::from(read_u16(data, 4)?),
+                i32::from(read_u16(data, 6)?),
+                read_u16(data, 10)?,
+                BI_RGB,
+                0,
+            )
+        } else if header_size >= INFO_HEADER_SIZE {
+            (
+                read_i32(data, 4)?,
+                read_i32(data, 8)?,
+                read_u16(data, 14)?,
+                read_u32(data, 16)?,
+                read_u32(data, 32)? as usize,
+            )
+        } else {
+            return None;
+        };
+
+    let top_down = height < 0;
+    height = height.checked_abs()?;
+    if is_ico {
+        height /= 2;
+    }
+    if width <= 0 || height <= 0 || width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return None;
+    }
+    if !matches!(bits_per_pixel, 1 | 4 | 8 | 16 | 24 | 32) {
+        return None;
+    }
+    let has_alpha_mask = header_size >= INFO_V3_HEADER_SIZE || compression == BI_ALPHABITFIELDS;
+    if compression == BI_ALPHABITFIELDS {
+        compression = BI_BITFIELDS;
+    }
+    match (compression, bits_per_pixel) {
+        (BI_RGB, _) | (BI_RLE8, 8) | (BI_RLE4, 4) | (BI_BITFIELDS, 16 | 32) => (),
+        _ => return None,
+    }
+    if top_down && matches!(compression, BI_RLE8 | BI_RLE4) {
+        return None;
+    }
+
+    // Masks are stored in header since V2, or right after info header.
+    let mut offset = header_size;
+    let masks = if compression == BI_BITFIELDS {
+        if header_size == INFO_HEADER_SIZE {
+            offset += if has_alpha_mask { 16 } else { 12 };
+        }
+        let alpha = if has_alpha_mask {
+            read_u32(data, INFO_HEADER_SIZE + 12)?
+        } else {
+            0
+        };
+        [
+            ChannelMask::new(read_u32(data, INFO_HEADER_SIZE)?),
+            ChannelMask::new(read_u32(data, INFO_HEADER_SIZE + 4)?),
+            ChannelMask::new(read_u32(data, INFO_HEADER_SIZE + 8)?),
+            ChannelMask::new(alpha),
+        ]
+    } else {
+        default_masks(bits_per_pixel)
+    };
+    if !masks.iter().all(ChannelMask::is_contiguous) {
+        return None;
+    }
+
+    let mut palette = Vec::new();
+    if bits_per_pixel <= 8 {
+        let entry_size = if header_size == CORE_HEADER_SIZE {
+            3
+        } else {
+            4
+        };
+        palette = read_palette(
+            &data[offset.min(data.len())..],
+            bits_per_pixel,
+            colors_used,
+            entry_size,
+        );
+        let color_count = if colors_used == 0 {
+            palette.len()
+        } else {
+            colors_used.min(palette.len())
+        };
+        offset += color_count * entry_size;
+    }
+
+    Some(Header {
+        width: usize::try_from(width).ok()?,
+        height: usize::try_from(height).ok()?,
+        top_down,
+        bits_per_pixel,
+        compression,
+        masks,
+        palette,
+        pixel_offset: pixel_offset.unwrap_or(offset),
+    })
+}
+
+/// Returns masks of 16 and 32 bits pixels without `BI_BITFIELDS` compression.
+const fn default_masks(bits_per_pixel: u16) -> [ChannelMask; 4] {
+    if bits_per_pixel == 16 {
+        [
+            ChannelMask::new(0x7C00),
+            ChannelMask::new(0x03E0),
+            ChannelMask::new(0x001F),
+            ChannelMask::new(0),
+        ]
+    } else {
+        [
+            ChannelMask::new(0x00FF_0000),
+            ChannelMask::new(0x0000_FF00),
+            ChannelMask::new(0x0000_00FF),
+            ChannelMask::new(0xFF00_0000),
+        ]
+    }
+}
+
+/// Reads color table, missing entries are black.
+fn read_palette(
+    data: &[u8],
+    bits_per_pixel: u16,
+    colors_used: usize,
+    entry_size: usize,
+) -> Vec<[u8; 4]> {
+    let max_colors = 1_usize << bits_per_pixel;
+    let colors_used = if colors_used == 0 {
+        max_colors
+    } else {
+        colors_used.min(max_colors)
+    };
+    let mut palette: Vec<[u8; 4]> = data
+        .chunks_exact(entry_size)
+        .take(colors_used)
+        .map(|entry| [entry[2], entry[1], entry[0], 0xFF])
+        .collect();
+    palette.resize(max_colors, [0, 0, 0, 0xFF]);
+    palette
+}
+
+/// Returns bytes of a row, which is padded to 4 bytes.
+#[must_use]
+fn row_bytes(width: usize, bits_per_pixel: u16) -> Option<usize> {
+    let bits = width.checked_mul(usize::from(bits_per_pixel))?;
+    Some(bits.div_ceil(32) * 4)
+}
+
+/// Returns index of first byte of `row` in output pixels, taking row order into account.
+#[must_use]
+const fn dst_row_offset(header: &Header, row: usize) -> usize {
+    let height = header.height;
+    let dst_row = if header.top_down {
+        row
+    } else {
+        height - 1 - row
+    };
+    dst_row * header.width * 4
+}
+
+/// Decodes pixels without compression.
+///
+/// Returns true if any alpha value is read from pixels.
+fn decode_uncompressed(header: &Header, data: &[u8], pixels: &mut [u8]) -> Option<bool> {
+    let width = header.width;
+    let src_row_bytes = row_bytes(header.width, header.bits_per_pixel)?;
+    let uses_alpha_channel =
+        header.masks[3].bits > 0 && (header.bits_per_pixel == 16 || header.bits_per_pixel == 32);
+    let mut all_alpha_zero = true;
+
+    for row in 0..header.height {
+        let start = row * src_row_bytes;
+        // Truncated images are common, a partial last row is left transparent.
+        let Some(src) = data.get(start..start + src_row_bytes) else {
+            break;
+        };
+        let dst_offset = dst_row_offset(header, row);
+        let dst = &mut pixels[dst_offset..dst_offset + width * 4];
+        for (x, dst_pixel) in dst.chunks_exact_mut(4).enumerate() {
+            let color = match header.bits_per_pixel {
+                1 | 4 | 8 => {
+                    let bpp = usize::from(header.bits_per_pixel);
+                    let bit_offset = x * bpp;
+                    let byte = src[bit_offset / 8];
+                    let shift = 8 - bpp - bit_offset % 8;
+                    let index = (byte >> shift) & ((1 << bpp) - 1);
+                    header.palette[usize::from(index)]
+                }
+                24 => [src[x * 3 + 2], src[x * 3 + 1], src[x * 3], 0xFF],
+                16 | 32 => {
+                    let value = if header.bits_per_pixel == 16 {
+                        u32::from(u16::from_le_bytes([src[x * 2], src[x * 2 + 1]]))
+                    } else {
+                        u32::from_le_bytes([
+                            src[x * 4],
+                            src[x * 4 + 1],
+                            src[x * 4 + 2],
+                            src[x * 4 + 3],
+                        ])
+                    };
+                    let [r, g, b, a] = header.masks;
+                    let alpha = if uses_alpha_channel {
+                        let alpha = a.get(value);
+                        all_alpha_zero &= alpha == 0;
+                        alpha
+                    } else {
+                        0xFF
+                    };
+                    [r.get(value), g.get(value), b.get(value), alpha]
+                }
+                _ => unreachable!(),
+            };
+            dst_pixel.copy_from_slice(&color);
+        }
+    }
+
+    if uses_alpha_channel && all_alpha_zero {
+        // Alpha channel is most probably unused, treat image as opaque.
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel[3] = 0xFF;
+        }
+        return Some(false);
+    }
+    Some(uses_alpha_channel)
+}
+
+/// Decodes run length encoded pixels, skipped pixels are left transparent.
+fn decode_rle(header: &Header, data: &[u8], pixels: &mut [u8]) -> Option<()> {
+    let width = header.width;
+    let height = header.height;
+    let is_rle4 = header.compression == BI_RLE4;
+    let mut x = 0;
+    let mut y = 0;
+    let mut pos = 0;
+
+    let mut set_pixel = |x: usize, y: usize, index: u8| {
+        if x < width && y < height {
+            let offset = dst_row_offset(header, y) + x * 4;
+            pixels[offset..offset + 4].copy_from_slice(&header.palette[usize::from(index)]);
+        }
+    };
+
+    while y < height {
+        let (Some(&count), Some(&value)) = (data.get(pos), data.get(pos + 1)) else {
+            break;
+        };
+        pos += 2;
+        if count > 0 {
+            // Encoded run.
+            for i in 0..usize::from(count) {
+                let index = if is_rle4 {
+                    if i % 2 == 0 {
+                        value >> 4
+                    } else {
+                        value & 0x0F
+                    }
+                } else {
+                    value
+                };
+                set_pixel(x, y, index);
+                x += 1;
+            }
+            continue;
+        }
+        match value {
+            // End of line.
+            0 => {
+                x = 0;
+                y += 1;
+            }
+            // End of bitmap.
+            1 => break,
+            // Delta.
+            2 => {
+                let dx = *data.get(pos)?;
+                let dy = *data.get(pos + 1)?;
+                pos += 2;
+                x += usize::from(dx);
+                y += usize::from(dy);
+            }
+            // Absolute mode, padded to 2 bytes.
+            count => {
+                let count = usize::from(count);
+                let byte_count = if is_rle4 { count.div_ceil(2) } else { count };
+                let run = data.get(pos..pos + byte_count)?;
+                for i in 0..count {
+                    let index = if is_rle4 {
+                        let byte = run[i / 2];
+                        if i % 2 == 0 {
+                            byte >> 4
+                        } else {
+                            byte & 0x0F
+                        }
+                    } else {
+                        run[i]
+                    };
+                    set_pixel(x, y, index);
+                    x += 1;
+                }
+                pos += byte_count + byte_count % 2;
+            }
+        }
+    }
+    Some(())
+}
+
+/// Clears pixels whose bit in 1 bit per pixel AND mask is set.
+fn apply_and_mask(header: &Header, mask: &[u8], pixels: &mut [u8]) {
+    let width = header.width;
+    let Some(mask_row_bytes) = row_bytes(header.width, 1) else {
+        return;
+    };
+    for row in 0..header.height {
+        let start = row * mask_row_bytes;
+        let Some(src) = mask.get(start..start + mask_row_bytes) else {
+            break;
+        };
+        let dst_offset = dst_row_offset(header, row);
+        for x in 0..width {
+            if (src[x / 8] >> (7 - x % 8)) & 1 == 1 {
+                pixels[dst_offset + x * 4 + 3] = 0;
+            }
+        }
+    }
+}
+
+#[inline]
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+#[inline]
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[inline]
+fn read_i32(data: &[u8], offset: usize) -> Option<i32> {
+    read_u32(data, offset).map(|value| i32::from_le_bytes(value.to_le_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a bitmap file with info header, followed by `table` and `pixels`.
+    ///
+    /// `table` holds channel masks or color palette.
+    fn bmp_file(
+        width: i32,
+        height: i32,
+        bits_per_pixel: u16,
+        compression: u32,
+        table: &[u8],
+        pixels: &[u8],
+    ) -> Vec<u8> {
+        let offset = u32::try_from(FILE_HEADER_SIZE + INFO_HEADER_SIZE + table.len()).unwrap();
+        let mut data = b"BM".to_vec();
+        data.extend((offset + u32::try_from(pixels.len()).unwrap()).to_le_bytes());
+        data.extend(0_u32.to_le_bytes());
+        data.extend(offset.to_le_bytes());
+        data.extend(u32::try_from(INFO_HEADER_SIZE).unwrap().to_le_bytes());
+        data.extend(width.to_le_bytes());
+        data.extend(height.to_le_bytes());
+        data.extend(1_u16.to_le_bytes());
+        data.extend(bits_per_pixel.to_le_bytes());
+        data.extend(compression.to_le_bytes());
+        data.extend([0; 20]);
+        data.extend(table);
+        data.extend(pixels);
+        data
+    }
+
+    fn rgb_file(width: i32, height: i32, pixels: &[u8]) -> Vec<u8> {
+        bmp_file(width, height, 24, BI_RGB, &[], pixels)
+    }
+
+    #[test]
+    fn test_decode_bottom_up() {
+        // Bottom row is red and green, top row is blue and white, in BGR order.
+        let pixels = [0, 0, 255, 0, 255, 0, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0];
+        let pixmap = decode(&rgb_file(2, 2, &pixels)).unwrap();
+        assert_eq!(
+            pixmap.pixels(),
+            &[0, 0, 255, 255, 255, 255, 255, 255, 255, 0, 0, 255, 0, 255, 0, 255]
+        );
+    }
+
+    #[test]
+    fn test_decode_bitfields() {
+        // RGB565.
+        let mut masks = Vec::new();
+        for mask in [0xF800_u32, 0x07E0, 0x001F] {
+            masks.extend(mask.to_le_bytes());
+        }
+        let pixels = [0x00, 0xF8, 0x1F, 0x00];
+        let pixmap = decode(&bmp_file(2, 1, 16, BI_BITFIELDS, &masks, &pixels)).unwrap();
+        assert_eq!(pixmap.pixels(), &[255, 0, 0, 255, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_non_contiguous_mask() {
+        let mut masks = Vec::new();
+        for mask in [0xFFFF_0001_u32, 0x0000_FF00, 0x0000_00FE] {
+            masks.extend(mask.to_le_bytes());
+        }
+        let data = bmp_file(1, 1, 32, BI_BITFIELDS, &masks, &[0xFF; 4]);
+        assert!(decode(&data).is_none());
+    }
+
+    #[test]
+    fn test_decode_rle8() {
+        // Black and red.
+        let palette = [0, 0, 0, 0, 0, 0, 255, 0];
+        // Bottom row is a run of 4 red pixels, top row has 3 pixels in absolute mode.
+        let pixels = [4, 1, 0, 0, 0, 3, 1, 0, 1, 0, 0, 0, 0, 1];
+        let pixmap = decode(&bmp_file(4, 2, 8, BI_RLE8, &palette, &pixels)).unwrap();
+        assert_eq!(
+            &pixmap.pixels()[..16],
+            &[255, 0, 0, 255, 0, 0, 0, 255, 255, 0, 0, 255, 0, 0, 0, 0]
+        );
+        assert_eq!(&pixmap.pixels()[16..], &[255, 0, 0, 255].repeat(4)[..]);
+    }
+
+    #[test]
+    fn test_truncated() {
+        let data = rgb_file(2, 2, &[0; 16]);
+        for len in 0..FILE_HEADER_SIZE + INFO_HEADER_SIZE {
+            assert!(decode(&data[..len]).is_none());
+        }
+        assert!(decode(&rgb_file(2, 2, &[])).is_none());
+        // Only the last row may be truncated.
+        assert!(decode(&rgb_file(2, 2, &[0; 10])).is_some());
+        assert!(decode(&rgb_file(30000, 30000, &vec![0; 90000])).is_none());
+    }
+
+    #[test]
+    fn test_rle_too_large_for_data() {
+        let palette = [0; 8];
+        // End of bitmap only.
+        let data = bmp_file(32768, 32768, 8, BI_RLE8, &palette, &[0, 1]);
+        assert!(decode(&data).is_none());
+    }
+}
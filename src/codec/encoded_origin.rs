@@ -3,6 +3,7 @@
 // in the LICENSE file.
 
 use crate::core::matrix::Matrix;
+use crate::core::pixmap::Pixmap;
 
 /// These values match the orientation [exif2](www.exif.org/Exif2-2.PDF).
 #[repr(u8)]
@@ -40,7 +41,7 @@ impl EncodedOrigin {
     /// oriented destination rectangle of [0, 0, w, h].
     #[must_use]
     #[inline]
-    pub const fn to_matrix(self, w: i32, h: i32) -> Matrix {
+    pub fn to_matrix(self, w: i32, h: i32) -> Matrix {
         match self {
             Self::TopLeft => Matrix::identity(),
             Self::TopRight => Matrix::from_i32(-1, 0, w, 0, 1, 0, 0, 0, 1),
@@ -60,4 +61,41 @@ impl EncodedOrigin {
     pub fn swaps_width_height(self) -> bool {
         self >= Self::LeftTop
     }
+
+    /// Returns a copy of `src` transformed to be correctly oriented, where `src` holds
+    /// the pixels in the order they are encoded.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn orient_pixmap(self, src: &Pixmap) -> Pixmap {
+        let width = src.width() as usize;
+        let height = src.height() as usize;
+        let bytes_per_pixel = src.info().bytes_per_pixel() as usize;
+        let (dst_width, dst_height) = if self.swaps_width_height() {
+            (src.height(), src.width())
+        } else {
+            (src.width(), src.height())
+        };
+        let row_bytes = dst_width as usize * bytes_per_pixel;
+        let mut pixels = vec![0; row_bytes * dst_height as usize];
+
+        for y in 0..height {
+            let row = &src.pixels()[y * src.row_bytes()..];
+            for x in 0..width {
+                let (dst_x, dst_y) = match self {
+                    Self::TopLeft => (x, y),
+                    Self::TopRight => (width - 1 - x, y),
+                    Self::BottomRight => (width - 1 - x, height - 1 - y),
+                    Self::BottomLeft => (x, height - 1 - y),
+                    Self::LeftTop => (y, x),
+                    Self::RightTop => (height - 1 - y, x),
+                    Self::RightBottom => (height - 1 - y, width - 1 - x),
+                    Self::LeftBottom => (y, width - 1 - x),
+                };
+                let offset = dst_y * row_bytes + dst_x * bytes_per_pixel;
+                pixels[offset..offset + bytes_per_pixel]
+                    .copy_from_slice(&row[x * bytes_per_pixel..(x + 1) * bytes_per_pixel]);
+            }
+        }
+        Pixmap::from_vec(src.info().from_wh(dst_width, dst_height), row_bytes, pixels)
+    }
 }
@@ -5,6 +5,7 @@
 #![allow(clippy::struct_excessive_bools)]
 
 use super::encoded_origin::EncodedOrigin;
+use super::tiff_utility::TiffImageFileDirectory;
 use crate::core::data::Data;
 
 pub struct ExifMetadata {
@@ -63,19 +64,12 @@ impl ExifMetadata {
     /// Parse the metadata specified in |data|.
     #[must_use]
     pub fn from_data(data: Data) -> Option<Self> {
-        let little_endian = false;
-        let ifd_offset: u32 = 0;
-        //if !TiffImageFileDirectory::parse_header(&data, &mut little_endian, &mut ifd_offset) {
-        //CodecPrintf("Failed to parse Exif header.\n");
-        //return None;
-        //}
-
-        let mut obj = Self {
-            data,
-            ..Default::default()
-        };
-
-        obj.parse_ifd(ifd_offset, little_endian, /*isRoot=*/ true);
+        let (little_endian, ifd_offset) = TiffImageFileDirectory::parse_header(data.bytes())?;
+        let ifd = TiffImageFileDirectory::from_offset(data.bytes(), little_endian, ifd_offset)?;
+
+        let mut obj = Self::default();
+        obj.parse_ifd(&ifd, /*is_root=*/ true);
+        obj.data = data;
         Some(obj)
     }
 
@@ -171,7 +165,83 @@ const PIXEL_Y_DIMENSION_TAG: u16 = 0xa003;
 
 impl ExifMetadata {
     // Helper functions and constants for parsing the data.
-    fn parse_ifd(&mut self, _ifd_offset: u32, _little_endian: bool, _is_root: bool) {
-        todo!()
+    fn parse_ifd(&mut self, ifd: &TiffImageFileDirectory, is_root: bool) {
+        for index in 0..ifd.number_of_entries() {
+            let Some(tag) = ifd.get_entry_tag(index) else {
+                break;
+            };
+            match tag {
+                SUB_IFD_OFFSET_TAG if is_root => {
+                    if let Some(sub_ifd) = ifd.get_entry_unsigned_long(index).and_then(|offset| {
+                        TiffImageFileDirectory::from_offset(ifd.data(), ifd.little_endian(), offset)
+                    }) {
+                        self.parse_ifd(&sub_ifd, /*is_root=*/ false);
+                    }
+                }
+                ORIGIN_TAG if !self.origin_present => {
+                    if let Some(origin) = ifd
+                        .get_entry_unsigned_short(index)
+                        .and_then(origin_from_value)
+                    {
+                        self.origin_present = true;
+                        self.origin_value = origin;
+                    }
+                }
+                MARKER_NOTE_TAG => {
+                    // TODO(Shaohua): Parse HDR headroom in Apple maker note.
+                }
+                X_RESOLUTION_TAG => {
+                    if let Some(value) = ifd.get_entry_rational(index) {
+                        self.x_resolution_present = true;
+                        self.x_resolution_value = value;
+                    }
+                }
+                Y_RESOLUTION_TAG => {
+                    if let Some(value) = ifd.get_entry_rational(index) {
+                        self.y_resolution_present = true;
+                        self.y_resolution_value = value;
+                    }
+                }
+                RESOLUTION_UNIT_TAG => {
+                    if let Some(value) = ifd.get_entry_unsigned_short(index) {
+                        self.resolution_unit_present = true;
+                        self.resolution_unit_value = value;
+                    }
+                }
+                PIXEL_X_DIMENSION_TAG => {
+                    if let Some(value) = get_entry_dimension(ifd, index) {
+                        self.pixel_x_dimension_present = true;
+                        self.pixel_x_dimension_value = value;
+                    }
+                }
+                PIXEL_Y_DIMENSION_TAG => {
+                    if let Some(value) = get_entry_dimension(ifd, index) {
+                        self.pixel_y_dimension_present = true;
+                        self.pixel_y_dimension_value = value;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Pixel dimension may be stored as either unsigned short or unsigned long.
+fn get_entry_dimension(ifd: &TiffImageFileDirectory, index: u16) -> Option<u32> {
+    ifd.get_entry_unsigned_long(index)
+        .or_else(|| ifd.get_entry_unsigned_short(index).map(u32::from))
+}
+
+const fn origin_from_value(value: u16) -> Option<EncodedOrigin> {
+    match value {
+        1 => Some(EncodedOrigin::TopLeft),
+        2 => Some(EncodedOrigin::TopRight),
+        3 => Some(EncodedOrigin::BottomRight),
+        4 => Some(EncodedOrigin::BottomLeft),
+        5 => Some(EncodedOrigin::LeftTop),
+        6 => Some(EncodedOrigin::RightTop),
+        7 => Some(EncodedOrigin::RightBottom),
+        8 => Some(EncodedOrigin::LeftBottom),
+        _ => None,
     }
 }
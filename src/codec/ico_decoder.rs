@@ -0,0 +1,196 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Decoder of Windows ICO and CUR files, which contain one or more images of
+//! different sizes.
+
+use crate::codec::{bmp_decoder, png_decoder};
+use crate::core::pixmap::Pixmap;
+use crate::core::size::ISize;
+
+const HEADER_SIZE: usize = 6;
+const DIRECTORY_ENTRY_SIZE: usize = 16;
+
+/// Returns true if `data` starts with ICO or CUR signature.
+#[must_use]
+#[inline]
+pub fn is_ico(data: &[u8]) -> bool {
+    data.starts_with(b"\x00\x00\x01\x00") || data.starts_with(b"\x00\x00\x02\x00")
+}
+
+/// An image stored in icon directory.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct IconEntry {
+    width: i32,
+    height: i32,
+    bits_per_pixel: u16,
+    offset: usize,
+    size: usize,
+    is_png: bool,
+}
+
+impl IconEntry {
+    #[must_use]
+    #[inline]
+    pub const fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn height(&self) -> i32 {
+        self.height
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn dimensions(&self) -> ISize {
+        ISize::from_wh(self.width, self.height)
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn bits_per_pixel(&self) -> u16 {
+        self.bits_per_pixel
+    }
+
+    /// Returns true if this image is stored as PNG, instead of BMP.
+    #[must_use]
+    #[inline]
+    pub const fn is_png(&self) -> bool {
+        self.is_png
+    }
+}
+
+/// Parses icon directory.
+///
+/// Entries whose data is out of range are skipped.
+/// Returns None if `data` is not an ICO file or if it contains no image.
+#[must_use]
+pub fn entries(data: &[u8]) -> Option<Vec<IconEntry>> {
+    if !is_ico(data) || data.len() < HEADER_SIZE {
+        return None;
+    }
+    let count = usize::from(u16::from_le_bytes([data[4], data[5]]));
+    let mut entries = Vec::with_capacity(count);
+    for index in 0..count {
+        let start = HEADER_SIZE + index * DIRECTORY_ENTRY_SIZE;
+        let Some(entry) = data.get(start..start + DIRECTORY_ENTRY_SIZE) else {
+            break;
+        };
+        let size = u32::from_le_bytes([entry[8], entry[9], entry[10], entry[11]]) as usize;
+        let offset = u32::from_le_bytes([entry[12], entry[13], entry[14], entry[15]]) as usize;
+        let Some(image) = data.get(offset..offset.saturating_add(size)) else {
+            continue;
+        };
+        let is_png = png_decoder::is_png(image);
+
+        // Width and height in directory are 0 for 256 pixels, and may be wrong,
+        // prefer the values in embedded image header.
+        let (width, height, bits_per_pixel) = if is_png {
+            png_dimensions(image)
+        } else {
+            dib_dimensions(image)
+        }
+        .unwrap_or_else(|| {
+            let to_size = |value: u8| if value == 0 { 256 } else { i32::from(value) };
+            (
+                to_size(entry[0]),
+                to_size(entry[1]),
+                u16::from_le_bytes([entry[6], entry[7]]),
+            )
+        });
+
+        entries.push(IconEntry {
+            width,
+            height,
+            bits_per_pixel,
+            offset,
+            size,
+            is_png,
+        });
+    }
+    if entries.is_empty() {
+        None
+    } else {
+        Some(entries)
+    }
+}
+
+/// Decodes the largest image in icon file.
+///
+/// Returns None if `data` is not a valid ICO file.
+#[must_use]
+pub fn decode(data: &[u8]) -> Option<Pixmap> {
+    decode_best(data, ISize::from_wh(i32::MAX, i32::MAX))
+}
+
+/// Decodes the image fitting `size` best.
+///
+/// That is the smallest image which is at least as large as `size`, or the largest
+/// image if none is large enough. Ties are broken by color depth.
+///
+/// Returns None if `data` is not a valid ICO file.
+#[must_use]
+pub fn decode_best(data: &[u8], size: ISize) -> Option<Pixmap> {
+    let entries = entries(data)?;
+    let entry = best_entry(&entries, size)?;
+    decode_entry(data, entry)
+}
+
+/// Decodes one image in icon file.
+#[must_use]
+pub fn decode_entry(data: &[u8], entry: &IconEntry) -> Option<Pixmap> {
+    let image = data.get(entry.offset..entry.offset.checked_add(entry.size)?)?;
+    if entry.is_png {
+        png_decoder::decode(image)
+    } else {
+        bmp_decoder::decode_dib(image, None, true)
+    }
+}
+
+/// Returns entry fitting `size` best, see `decode_best()`.
+#[must_use]
+pub fn best_entry(entries: &[IconEntry], size: ISize) -> Option<&IconEntry> {
+    let fits = |entry: &IconEntry| entry.width >= size.width() && entry.height >= size.height();
+    let area = |entry: &IconEntry| i64::from(entry.width) * i64::from(entry.height);
+    let smallest_fit = entries
+        .iter()
+        .filter(|entry| fits(entry))
+        .min_by_key(|entry| (area(entry), std::cmp::Reverse(entry.bits_per_pixel)));
+    smallest_fit.or_else(|| {
+        entries
+            .iter()
+            .max_by_key(|entry| (area(entry), entry.bits_per_pixel))
+    })
+}
+
+/// Returns width, height and bits per pixel of embedded PNG.
+fn png_dimensions(data: &[u8]) -> Option<(i32, i32, u16)> {
+    // IHDR is the first chunk, right after signature.
+    let ihdr = data.get(8..29)?;
+    if &ihdr[4..8] != b"IHDR" {
+        return None;
+    }
+    let width = i32::try_from(u32::from_be_bytes([ihdr[8], ihdr[9], ihdr[10], ihdr[11]])).ok()?;
+    let height =
+        i32::try_from(u32::from_be_bytes([ihdr[12], ihdr[13], ihdr[14], ihdr[15]])).ok()?;
+    let bit_depth = u16::from(ihdr[16]);
+    let channels = match ihdr[17] {
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => 1,
+    };
+    Some((width, height, bit_depth * channels))
+}
+
+/// Returns width, height and bits per pixel of embedded device independent bitmap.
+fn dib_dimensions(data: &[u8]) -> Option<(i32, i32, u16)> {
+    let header = data.get(..16)?;
+    let width = i32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    let height = i32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+    let bits_per_pixel = u16::from_le_bytes([header[14], header[15]]);
+    Some((width, height.checked_abs()? / 2, bits_per_pixel))
+}
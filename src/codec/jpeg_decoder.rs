@@ -0,0 +1,216 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Decoder of JPEG images.
+//!
+//! Supports baseline, progressive and 8 bit lossless images, with grayscale,
+//! YCbCr, CMYK and YCCK components. Decoded pixels are `ColorType::Gray8` for
+//! grayscale images, and `ColorType::Rgba8888` for others.
+//!
+//! Orientation in Exif metadata is reported by `JpegCodec::origin()`,
+//! and applied by `decode()`.
+
+use std::io::Cursor;
+
+use jpeg_decoder::{Decoder, PixelFormat};
+
+use crate::codec::encoded_origin::EncodedOrigin;
+use crate::codec::exif::ExifMetadata;
+use crate::core::alpha_type::AlphaType;
+use crate::core::color_type::ColorType;
+use crate::core::data::Data;
+use crate::core::image_info::ImageInfo;
+use crate::core::pixmap::Pixmap;
+
+/// Returns true if `data` starts with JPEG start of image marker.
+#[must_use]
+#[inline]
+pub fn is_jpeg(data: &[u8]) -> bool {
+    data.starts_with(b"\xff\xd8\xff")
+}
+
+/// Decodes a JPEG image, and rotates or flips it as specified by Exif orientation.
+///
+/// Returns None if `data` is not a valid or supported JPEG image.
+#[must_use]
+pub fn decode(data: &[u8]) -> Option<Pixmap> {
+    let codec = JpegCodec::new(data)?;
+    let pixmap = codec.decode()?;
+    if codec.origin() == EncodedOrigin::TopLeft {
+        Some(pixmap)
+    } else {
+        Some(codec.origin().orient_pixmap(&pixmap))
+    }
+}
+
+/// Reads header and metadata of JPEG image, and decodes its pixels on demand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JpegCodec<'a> {
+    data: &'a [u8],
+    info: ImageInfo,
+    pixel_format: PixelFormat,
+    origin: EncodedOrigin,
+    icc_profile: Option<Vec<u8>>,
+    exif: Option<Vec<u8>>,
+    xmp: Option<Vec<u8>>,
+}
+
+impl<'a> JpegCodec<'a> {
+    /// Parses markers before image data of `data`.
+    ///
+    /// Returns None if `data` is not a valid JPEG image, or if it has 16 bit samples
+    /// which are not supported.
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        if !is_jpeg(data) {
+            return None;
+        }
+        let mut decoder = Decoder::new(Cursor::new(data));
+        decoder.read_info().ok()?;
+        let jpeg_info = decoder.info()?;
+        let color_type = match jpeg_info.pixel_format {
+            PixelFormat::L8 => ColorType::Gray8,
+            PixelFormat::RGB24 | PixelFormat::CMYK32 => ColorType::Rgba8888,
+            PixelFormat::L16 => return None,
+        };
+        let info = ImageInfo::from(
+            i32::from(jpeg_info.width),
+            i32::from(jpeg_info.height),
+            color_type,
+            AlphaType::Opaque,
+            None,
+        );
+
+        let exif = decoder.exif_data().map(<[u8]>::to_vec);
+        let mut origin = EncodedOrigin::TopLeft;
+        if let Some(metadata) = exif
+            .as_deref()
+            .and_then(|exif| ExifMetadata::from_data(Data::from(exif)))
+        {
+            let _has_origin = metadata.get_origin(&mut origin);
+        }
+
+        Some(Self {
+            data,
+            info,
+            pixel_format: jpeg_info.pixel_format,
+            origin,
+            icc_profile: decoder.icc_profile(),
+            exif,
+            xmp: decoder.xmp_data().map(<[u8]>::to_vec),
+        })
+    }
+
+    /// Returns width, height, `ColorType` and `AlphaType` of decoded pixels,
+    /// before orientation is applied.
+    #[must_use]
+    #[inline]
+    pub const fn info(&self) -> &ImageInfo {
+        &self.info
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn width(&self) -> i32 {
+        self.info.width()
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn height(&self) -> i32 {
+        self.info.height()
+    }
+
+    /// Returns orientation in Exif metadata, or `EncodedOrigin::TopLeft` if not specified.
+    #[must_use]
+    #[inline]
+    pub const fn origin(&self) -> EncodedOrigin {
+        self.origin
+    }
+
+    /// Returns the ICC profile stored in APP2 markers.
+    #[must_use]
+    #[inline]
+    pub fn icc_profile(&self) -> Option<&[u8]> {
+        self.icc_profile.as_deref()
+    }
+
+    /// Returns Exif metadata in APP1 marker, starting at TIFF header.
+    #[must_use]
+    #[inline]
+    pub fn exif_data(&self) -> Option<&[u8]> {
+        self.exif.as_deref()
+    }
+
+    /// Returns XMP packet in APP1 marker, without the namespace signature.
+    #[must_use]
+    #[inline]
+    pub fn xmp_data(&self) -> Option<&[u8]> {
+        self.xmp.as_deref()
+    }
+
+    /// Decodes pixels in the order they are encoded, without applying orientation.
+    ///
+    /// Returns None if image data is corrupted.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn decode(&self) -> Option<Pixmap> {
+        let mut decoder = Decoder::new(Cursor::new(self.data));
+        let samples = decoder.decode().ok()?;
+        let pixels: Vec<u8> = match self.pixel_format {
+            PixelFormat::L8 => samples,
+            PixelFormat::RGB24 => samples
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 0xff])
+                .collect(),
+            PixelFormat::CMYK32 => samples
+                .chunks_exact(4)
+                .flat_map(|cmyk| {
+                    let white = u32::from(255 - cmyk[3]);
+                    let channel = |ink: u8| (u32::from(255 - ink) * white / 255) as u8;
+                    [channel(cmyk[0]), channel(cmyk[1]), channel(cmyk[2]), 0xff]
+                })
+                .collect(),
+            PixelFormat::L16 => return None,
+        };
+
+        let row_bytes = self.info.min_row_bytes();
+        if pixels.len() < self.info.compute_byte_size(row_bytes) {
+            return None;
+        }
+        Some(Pixmap::from_vec(self.info.clone(), row_bytes, pixels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jpeg_file(width: u16, height: u16) -> Vec<u8> {
+        let pixels = vec![128; usize::from(width) * usize::from(height) * 3];
+        let mut data = Vec::new();
+        let encoder = jpeg_encoder::Encoder::new(&mut data, 90);
+        encoder
+            .encode(&pixels, width, height, jpeg_encoder::ColorType::Rgb)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_decode() {
+        let pixmap = decode(&jpeg_file(16, 8)).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (16, 8));
+        assert_eq!(pixmap.info().color_type(), ColorType::Rgba8888);
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(decode(b"").is_none());
+        assert!(decode(b"\xff\xd8\xffjunk").is_none());
+
+        let data = jpeg_file(16, 8);
+        // Keeps SOI marker and a part of headers only.
+        assert!(decode(&data[..20]).is_none());
+    }
+}
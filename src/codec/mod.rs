@@ -2,7 +2,12 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+pub mod bmp_decoder;
 pub mod encoded_origin;
 pub mod exif;
 pub mod gainmap_info;
+pub mod ico_decoder;
+pub mod jpeg_decoder;
+pub mod png_decoder;
+pub mod tiff_utility;
 pub mod xmp;
@@ -0,0 +1,293 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Decoder of PNG images.
+//!
+//! Supports all color types and bit depths, palette with transparency, and
+//! interlaced images. Palette and images with less than 8 bits per channel are
+//! expanded, decoded pixels are `ColorType::Gray8` or `ColorType::Rgba8888`
+//! for 8 bit images, and `ColorType::R16G16B16A16Unorm` for 16 bit images.
+//!
+//! Pixels are not color managed, embedded ICC profile and gamma are exposed by
+//! `PngCodec` so that caller can convert them.
+
+use std::io::Cursor;
+
+use png::{BitDepth, Decoder, Reader, Transformations};
+
+use crate::core::alpha_type::AlphaType;
+use crate::core::color_type::ColorType;
+use crate::core::image_info::ImageInfo;
+use crate::core::pixmap::Pixmap;
+
+pub const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Images larger than this in either dimension are rejected.
+const MAX_DIMENSION: u32 = 1 << 15;
+
+/// Keyword of iTXt chunk which stores XMP packet.
+pub const XMP_KEYWORD: &str = "XML:com.adobe.xmp";
+
+/// Marks a missing channel in `channel_map()`, which is filled with opaque value.
+const OPAQUE_CHANNEL: usize = usize::MAX;
+
+/// Returns true if `data` starts with PNG signature.
+#[must_use]
+#[inline]
+pub fn is_png(data: &[u8]) -> bool {
+    data.starts_with(PNG_SIGNATURE)
+}
+
+/// Decodes a PNG image.
+///
+/// Returns None if `data` is not a valid PNG image.
+#[must_use]
+pub fn decode(data: &[u8]) -> Option<Pixmap> {
+    PngCodec::new(data)?.decode()
+}
+
+/// Reads header and color chunks of PNG image, and decodes its pixels on demand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PngCodec<'a> {
+    data: &'a [u8],
+    info: ImageInfo,
+    bit_depth: u8,
+    interlaced: bool,
+    icc_profile: Option<Vec<u8>>,
+    exif: Option<Vec<u8>>,
+    xmp: Option<Vec<u8>>,
+    gamma: Option<f32>,
+    is_srgb: bool,
+}
+
+impl<'a> PngCodec<'a> {
+    /// Parses chunks before image data of `data`.
+    ///
+    /// Returns None if `data` is not a valid PNG image, or if it is too large.
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        if !is_png(data) {
+            return None;
+        }
+        let reader = make_reader(data)?;
+        let png_info = reader.info();
+        if png_info.width > MAX_DIMENSION || png_info.height > MAX_DIMENSION {
+            return None;
+        }
+
+        let (png_color_type, png_bit_depth) = reader.output_color_type();
+        let samples = png_color_type.samples();
+        let color_type = if png_bit_depth == BitDepth::Sixteen {
+            ColorType::R16G16B16A16Unorm
+        } else if samples == 1 {
+            ColorType::Gray8
+        } else {
+            ColorType::Rgba8888
+        };
+        let alpha_type = if samples == 2 || samples == 4 {
+            AlphaType::Unpremul
+        } else {
+            AlphaType::Opaque
+        };
+        let info = ImageInfo::from(
+            i32::try_from(png_info.width).ok()?,
+            i32::try_from(png_info.height).ok()?,
+            color_type,
+            alpha_type,
+            None,
+        );
+
+        Some(Self {
+            data,
+            info,
+            bit_depth: png_info.bit_depth as u8,
+            interlaced: png_info.interlaced,
+            icc_profile: png_info.icc_profile.as_ref().map(|icc| icc.to_vec()),
+            exif: png_info.exif_metadata.as_ref().map(|exif| exif.to_vec()),
+            xmp: png_info
+                .utf8_text
+                .iter()
+                .find(|chunk| chunk.keyword == XMP_KEYWORD)
+                .and_then(|chunk| chunk.get_text().ok())
+                .map(String::into_bytes),
+            // sRGB chunk overrides gAMA chunk.
+            gamma: png_info.gamma().map(png::ScaledFloat::into_value),
+            is_srgb: png_info.srgb.is_some(),
+        })
+    }
+
+    /// Returns width, height, `ColorType` and `AlphaType` of decoded pixels.
+    #[must_use]
+    #[inline]
+    pub const fn info(&self) -> &ImageInfo {
+        &self.info
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn width(&self) -> i32 {
+        self.info.width()
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn height(&self) -> i32 {
+        self.info.height()
+    }
+
+    /// Returns bits per channel stored in file, 1, 2, 4, 8 or 16.
+    #[must_use]
+    #[inline]
+    pub const fn bit_depth(&self) -> u8 {
+        self.bit_depth
+    }
+
+    /// Returns true if image is stored with Adam7 interlacing.
+    #[must_use]
+    #[inline]
+    pub const fn is_interlaced(&self) -> bool {
+        self.interlaced
+    }
+
+    /// Returns the uncompressed ICC profile in iCCP chunk.
+    #[must_use]
+    #[inline]
+    pub fn icc_profile(&self) -> Option<&[u8]> {
+        self.icc_profile.as_deref()
+    }
+
+    /// Returns Exif metadata in eXIf chunk, starting at TIFF header.
+    #[must_use]
+    #[inline]
+    pub fn exif_data(&self) -> Option<&[u8]> {
+        self.exif.as_deref()
+    }
+
+    /// Returns XMP packet in iTXt chunk with keyword `XMP_KEYWORD`.
+    #[must_use]
+    #[inline]
+    pub fn xmp_data(&self) -> Option<&[u8]> {
+        self.xmp.as_deref()
+    }
+
+    /// Returns exponent of the encoding transfer function, like 1/2.2.
+    ///
+    /// It is read from gAMA chunk, or is the sRGB value if sRGB chunk is present.
+    #[must_use]
+    #[inline]
+    pub const fn gamma(&self) -> Option<f32> {
+        self.gamma
+    }
+
+    /// Returns true if image has sRGB chunk, which means pixels are in sRGB color space.
+    #[must_use]
+    #[inline]
+    pub const fn is_srgb(&self) -> bool {
+        self.is_srgb
+    }
+
+    /// Decodes pixels of the first frame.
+    ///
+    /// 16 bit samples are stored in native byte order.
+    ///
+    /// Returns None if image data is corrupted.
+    #[must_use]
+    pub fn decode(&self) -> Option<Pixmap> {
+        let mut reader = make_reader(self.data)?;
+        let mut buf = vec![0; reader.output_buffer_size()?];
+        let output = reader.next_frame(&mut buf).ok()?;
+
+        let sample_size = if output.bit_depth == BitDepth::Sixteen {
+            2
+        } else {
+            1
+        };
+        let samples = output.color_type.samples();
+        let channels = if self.info.color_type() == ColorType::Gray8 {
+            1
+        } else {
+            4
+        };
+        let map = channel_map(samples);
+        let width = output.width as usize;
+        let height = output.height as usize;
+        let row_bytes = width * channels * sample_size;
+
+        let mut pixels = Vec::with_capacity(row_bytes * height);
+        for row in buf.chunks_exact(output.line_size).take(height) {
+            let row = &row[..width * samples * sample_size];
+            for pixel in row.chunks_exact(samples * sample_size) {
+                for &index in &map[..channels] {
+                    if index == OPAQUE_CHANNEL {
+                        pixels.extend(std::iter::repeat(0xff).take(sample_size));
+                    } else if sample_size == 2 {
+                        let value = u16::from_be_bytes([pixel[index * 2], pixel[index * 2 + 1]]);
+                        pixels.extend_from_slice(&value.to_ne_bytes());
+                    } else {
+                        pixels.push(pixel[index]);
+                    }
+                }
+            }
+        }
+        Some(Pixmap::from_vec(self.info.clone(), row_bytes, pixels))
+    }
+}
+
+/// Creates a reader which expands palette, low bit depth and tRNS chunk,
+/// positioned at the first image data chunk.
+fn make_reader(data: &[u8]) -> Option<Reader<Cursor<&[u8]>>> {
+    let mut decoder = Decoder::new(Cursor::new(data));
+    decoder.set_transformations(Transformations::EXPAND);
+    decoder.read_info().ok()
+}
+
+/// Returns index of source sample for each output channel, for images with
+/// `samples` samples per pixel.
+///
+/// Gray is output as is, gray with alpha and RGB are output as RGBA.
+const fn channel_map(samples: usize) -> [usize; 4] {
+    match samples {
+        1 => [0, 0, 0, OPAQUE_CHANNEL],
+        2 => [0, 0, 0, 1],
+        3 => [0, 1, 2, OPAQUE_CHANNEL],
+        _ => [0, 1, 2, 3],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_file(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(pixels).unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_decode_rgba() {
+        let pixels = [255, 0, 0, 255, 0, 0, 255, 128];
+        let pixmap = decode(&png_file(2, 1, &pixels)).unwrap();
+        assert_eq!(pixmap.info().color_type(), ColorType::Rgba8888);
+        assert_eq!(pixmap.pixels(), &pixels);
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(decode(b"").is_none());
+        assert!(decode(b"not a png").is_none());
+
+        let data = png_file(4, 4, &[7; 4 * 4 * 4]);
+        assert!(decode(&data[..data.len() / 2]).is_none());
+        let mut corrupted = data;
+        // Breaks width field of IHDR chunk.
+        corrupted[16..20].copy_from_slice(&[0; 4]);
+        assert!(decode(&corrupted).is_none());
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Reader of TIFF image file directories, which are used to store Exif metadata.
+
+const HEADER_SIZE: usize = 8;
+const ENTRY_SIZE: usize = 12;
+
+// Data types of IFD entry values.
+const TYPE_UNSIGNED_BYTE: u16 = 1;
+const TYPE_ASCII_STRING: u16 = 2;
+const TYPE_UNSIGNED_SHORT: u16 = 3;
+const TYPE_UNSIGNED_LONG: u16 = 4;
+const TYPE_UNSIGNED_RATIONAL: u16 = 5;
+const TYPE_SIGNED_BYTE: u16 = 6;
+const TYPE_UNDEFINED: u16 = 7;
+const TYPE_SIGNED_SHORT: u16 = 8;
+const TYPE_SIGNED_LONG: u16 = 9;
+const TYPE_SIGNED_RATIONAL: u16 = 10;
+const TYPE_SINGLE: u16 = 11;
+const TYPE_DOUBLE: u16 = 12;
+
+/// Image file directory, a list of tagged entries.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct TiffImageFileDirectory<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+
+    /// Offset of the first entry.
+    entries_offset: usize,
+    number_of_entries: u16,
+    next_ifd_offset: u32,
+}
+
+impl<'a> TiffImageFileDirectory<'a> {
+    /// Parses TIFF header at the start of `data`.
+    ///
+    /// Returns byte order, true for little endian, and offset of the first IFD.
+    #[must_use]
+    pub fn parse_header(data: &[u8]) -> Option<(bool, u32)> {
+        let header = data.get(..HEADER_SIZE)?;
+        let little_endian = match header {
+            [b'I', b'I', 0x2a, 0x00, ..] => true,
+            [b'M', b'M', 0x00, 0x2a, ..] => false,
+            _ => return None,
+        };
+        let ifd_offset = read_u32(header, 4, little_endian)?;
+        Some((little_endian, ifd_offset))
+    }
+
+    /// Creates IFD starting at `ifd_offset` of `data`.
+    ///
+    /// Returns None if IFD is out of range.
+    #[must_use]
+    pub fn from_offset(data: &'a [u8], little_endian: bool, ifd_offset: u32) -> Option<Self> {
+        let ifd_offset = ifd_offset as usize;
+        let number_of_entries = read_u16(data, ifd_offset, little_endian)?;
+        let entries_offset = ifd_offset + 2;
+        let next_offset = entries_offset + usize::from(number_of_entries) * ENTRY_SIZE;
+        if next_offset > data.len() {
+            return None;
+        }
+        // Some writers truncate data right after the last entry.
+        let next_ifd_offset = read_u32(data, next_offset, little_endian).unwrap_or(0);
+        Some(Self {
+            data,
+            little_endian,
+            entries_offset,
+            number_of_entries,
+            next_ifd_offset,
+        })
+    }
+
+    /// Returns the whole TIFF data, which offsets are relative to.
+    #[must_use]
+    #[inline]
+    pub const fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn number_of_entries(&self) -> u16 {
+        self.number_of_entries
+    }
+
+    /// Returns offset of the next IFD, or 0 if this is the last one.
+    #[must_use]
+    #[inline]
+    pub const fn next_ifd_offset(&self) -> u32 {
+        self.next_ifd_offset
+    }
+
+    /// Returns tag of entry at `index`.
+    #[must_use]
+    pub fn get_entry_tag(&self, index: u16) -> Option<u16> {
+        read_u16(self.data, self.entry_offset(index)?, self.little_endian)
+    }
+
+    /// Returns the first value of entry at `index` if its type is unsigned short.
+    #[must_use]
+    pub fn get_entry_unsigned_short(&self, index: u16) -> Option<u16> {
+        let values = self.get_entry_values(index, TYPE_UNSIGNED_SHORT)?;
+        read_u16(values, 0, self.little_endian)
+    }
+
+    /// Returns the first value of entry at `index` if its type is unsigned long.
+    #[must_use]
+    pub fn get_entry_unsigned_long(&self, index: u16) -> Option<u32> {
+        let values = self.get_entry_values(index, TYPE_UNSIGNED_LONG)?;
+        read_u32(values, 0, self.little_endian)
+    }
+
+    /// Returns the first value of entry at `index` if its type is signed or unsigned
+    /// rational.
+    ///
+    /// Returns None if the denominator is zero.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn get_entry_rational(&self, index: u16) -> Option<f32> {
+        let entry_type = self.get_entry_type(index)?;
+        let values = self.get_entry_values(index, entry_type)?;
+        let numerator = read_u32(values, 0, self.little_endian)?;
+        let denominator = read_u32(values, 4, self.little_endian)?;
+        if denominator == 0 {
+            return None;
+        }
+        match entry_type {
+            TYPE_UNSIGNED_RATIONAL => Some(numerator as f32 / denominator as f32),
+            TYPE_SIGNED_RATIONAL => Some(numerator as i32 as f32 / denominator as i32 as f32),
+            _ => None,
+        }
+    }
+
+    /// Returns all bytes of entry at `index` if its type is undefined.
+    #[must_use]
+    pub fn get_entry_undefined_data(&self, index: u16) -> Option<&'a [u8]> {
+        self.get_entry_values(index, TYPE_UNDEFINED)
+    }
+
+    fn entry_offset(&self, index: u16) -> Option<usize> {
+        (index < self.number_of_entries)
+            .then(|| self.entries_offset + usize::from(index) * ENTRY_SIZE)
+    }
+
+    fn get_entry_type(&self, index: u16) -> Option<u16> {
+        read_u16(self.data, self.entry_offset(index)? + 2, self.little_endian)
+    }
+
+    /// Returns bytes of all values of entry at `index`, if its type is `entry_type`.
+    fn get_entry_values(&self, index: u16, entry_type: u16) -> Option<&'a [u8]> {
+        let offset = self.entry_offset(index)?;
+        if read_u16(self.data, offset + 2, self.little_endian)? != entry_type {
+            return None;
+        }
+        let count = read_u32(self.data, offset + 4, self.little_endian)? as usize;
+        if count == 0 {
+            return None;
+        }
+        let size = count.checked_mul(type_size(entry_type)?)?;
+        // Values are stored in the entry if they fit in 4 bytes.
+        let values_offset = if size <= 4 {
+            offset + 8
+        } else {
+            read_u32(self.data, offset + 8, self.little_endian)? as usize
+        };
+        self.data
+            .get(values_offset..values_offset.checked_add(size)?)
+    }
+}
+
+/// Returns size in bytes of one value of `entry_type`.
+const fn type_size(entry_type: u16) -> Option<usize> {
+    match entry_type {
+        TYPE_UNSIGNED_BYTE | TYPE_ASCII_STRING | TYPE_SIGNED_BYTE | TYPE_UNDEFINED => Some(1),
+        TYPE_UNSIGNED_SHORT | TYPE_SIGNED_SHORT => Some(2),
+        TYPE_UNSIGNED_LONG | TYPE_SIGNED_LONG | TYPE_SINGLE => Some(4),
+        TYPE_UNSIGNED_RATIONAL | TYPE_SIGNED_RATIONAL | TYPE_DOUBLE => Some(8),
+        _ => None,
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize, little_endian: bool) -> Option<u16> {
+    let bytes = [*data.get(offset)?, *data.get(offset + 1)?];
+    if little_endian {
+        Some(u16::from_le_bytes(bytes))
+    } else {
+        Some(u16::from_be_bytes(bytes))
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize, little_endian: bool) -> Option<u32> {
+    let bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+    if little_endian {
+        Some(u32::from_le_bytes(bytes))
+    } else {
+        Some(u32::from_be_bytes(bytes))
+    }
+}
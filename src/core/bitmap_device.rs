@@ -0,0 +1,679 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::rc::Rc;
+
+use crate::core::blend_mode::BlendMode;
+use crate::core::blitter::Blitter;
+use crate::core::clip_op::ClipOp;
+use crate::core::color::Color4f;
+use crate::core::device::Device;
+use crate::core::image_filter::{FilterImage, MapDirection};
+use crate::core::image_info::ImageInfo;
+use crate::core::irect::IRect;
+use crate::core::mask::{Mask, MaskFormat};
+use crate::core::mask_filter::MaskFilter;
+use crate::core::matrix::Matrix;
+use crate::core::paint::Paint;
+use crate::core::paint_types::{PaintStyle, StrokeJoin};
+use crate::core::path::Path;
+use crate::core::path_utils::PathStroker;
+use crate::core::pixmap::Pixmap;
+use crate::core::point::{IPoint, Point};
+use crate::core::rasterizer::Rasterizer;
+use crate::core::rect::Rect;
+use crate::core::sampling_options::SamplingOptions;
+use crate::core::scalar::Scalar;
+use crate::core::surface_props::SurfaceProps;
+use crate::core::tile_mode::TileMode;
+use crate::shaders::image_shader::ImageShader;
+
+/// Raster device which draws into pixels in memory.
+#[derive(Debug, Clone)]
+pub struct BitmapDevice {
+    pixmap: Pixmap,
+    props: SurfaceProps,
+
+    /// Coverage of clip for each pixel, row by row. None if drawing is not clipped.
+    clip: Option<Rc<[Scalar]>>,
+
+    /// Bounds of pixels not clipped out, which are bounds of device if not clipped.
+    clip_bounds: IRect,
+
+    /// Maps local coordinates of shaders and filters to device coordinates.
+    local_to_device: Matrix,
+
+    /// States saved by `save()` and `save_layer()`.
+    saved_states: Vec<SavedState>,
+}
+
+/// State of `BitmapDevice` saved by `save()` and `save_layer()`.
+#[derive(Debug, Clone)]
+struct SavedState {
+    clip: Option<Rc<[Scalar]>>,
+    clip_bounds: IRect,
+
+    /// Layer saved by `save_layer()`, None if saved by `save()`.
+    layer: Option<SavedLayer>,
+}
+
+#[derive(Debug, Clone)]
+struct SavedLayer {
+    /// Pixels below the layer, to draw layer into on restore.
+    pixmap: Pixmap,
+    bounds: IRect,
+    paint: Paint,
+
+    /// Local to device matrix when layer was saved, which maps image filter of `paint`.
+    matrix: Matrix,
+}
+
+impl BitmapDevice {
+    /// Creates device drawing into `pixmap`.
+    #[must_use]
+    pub const fn new(pixmap: Pixmap, props: SurfaceProps) -> Self {
+        let clip_bounds = IRect::from_wh(pixmap.width(), pixmap.height());
+        Self {
+            pixmap,
+            props,
+            clip: None,
+            clip_bounds,
+            local_to_device: Matrix::identity(),
+            saved_states: Vec::new(),
+        }
+    }
+
+    /// Allocates zero initialized pixels described by `info` and `row_bytes`.
+    ///
+    /// Pass zero for `row_bytes` to use `info.min_row_bytes()`.
+    ///
+    /// Returns None if `info` is empty or invalid, or if `row_bytes` is too small.
+    #[must_use]
+    pub fn alloc(info: &ImageInfo, row_bytes: usize, props: SurfaceProps) -> Option<Self> {
+        let row_bytes = if row_bytes == 0 {
+            info.min_row_bytes()
+        } else {
+            row_bytes
+        };
+        if !info.is_valid() || !info.valid_row_bytes(row_bytes) {
+            return None;
+        }
+        let byte_size = info.compute_byte_size(row_bytes);
+        if ImageInfo::byte_size_overflowed(byte_size) {
+            return None;
+        }
+        let pixmap = Pixmap::from_vec(info.clone(), row_bytes, vec![0; byte_size]);
+        Some(Self::new(pixmap, props))
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn pixmap(&self) -> &Pixmap {
+        &self.pixmap
+    }
+
+    #[inline]
+    pub fn pixmap_mut(&mut self) -> &mut Pixmap {
+        &mut self.pixmap
+    }
+
+    /// Draws `path` into a transparent layer, and draws the layer with image filter
+    /// and blend mode of `paint`.
+    ///
+    /// The layer covers the part of `path` which the filter needs to fill clip bounds,
+    /// so it may extend beyond the device.
+    #[allow(clippy::cast_precision_loss)]
+    fn draw_path_with_image_filter(&mut self, path: &Path, paint: &Paint) {
+        let Some(image_filter) = paint.get_image_filter() else {
+            return;
+        };
+        let mut bounds = image_filter.filter_bounds(
+            &self.clip_bounds,
+            &self.local_to_device,
+            MapDirection::Reverse,
+        );
+        if !path.fill_type().is_inverse() {
+            let Some(path_bounds) = path_bounds(path, 0.0) else {
+                return;
+            };
+            if !bounds.intersect(&path_bounds) {
+                return;
+            }
+        }
+        let info = ImageInfo::new_n32_premul(bounds.width(), bounds.height(), None);
+        let Some(mut layer) = Self::alloc(&info, 0, self.props.clone()) else {
+            return;
+        };
+        let to_layer = Matrix::from_translate(-bounds.left() as Scalar, -bounds.top() as Scalar);
+        layer.set_local_to_device(&Matrix::from_concat(&to_layer, &self.local_to_device));
+        let mut layer_paint = paint.clone();
+        layer_paint.set_image_filter(None);
+        layer_paint.set_blend_mode(BlendMode::SrcOver);
+        layer.draw_path(&path.transform(&to_layer), &layer_paint);
+
+        let mut composite_paint = Paint::new();
+        composite_paint.set_image_filter(paint.get_image_filter().cloned());
+        composite_paint.set_blend_mode(paint.get_blend_mode());
+        let matrix = self.local_to_device.clone();
+        let clip_bounds = self.clip_bounds.clone();
+        self.draw_layer(
+            layer.pixmap(),
+            IPoint::from_xy(bounds.left(), bounds.top()),
+            &clip_bounds,
+            &composite_paint,
+            &matrix,
+        );
+    }
+
+    /// Draws pixels of `layer`, whose top left corner is at `origin`, inside `bounds`
+    /// with alpha, color filter, image filter and blend mode of `paint`.
+    ///
+    /// Parameters of image filter are mapped by `ctm`.
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_sign_loss)]
+    fn draw_layer(
+        &mut self,
+        layer: &Pixmap,
+        origin: IPoint,
+        bounds: &IRect,
+        paint: &Paint,
+        ctm: &Matrix,
+    ) {
+        let filtered;
+        let (pixmap, origin) = if let Some(image_filter) = paint.get_image_filter() {
+            let Some(source) = FilterImage::from_pixmap(layer, origin.x(), origin.y()) else {
+                return;
+            };
+            let image = image_filter.filter_image(&source, ctm);
+            let Some(pixmap) = image.to_pixmap() else {
+                return;
+            };
+            filtered = pixmap;
+            (&filtered, image.bounds().clone())
+        } else {
+            (
+                layer,
+                IRect::from_xywh(origin.x(), origin.y(), layer.width(), layer.height()),
+            )
+        };
+        let mut bounds = bounds.clone();
+        if !bounds.intersect(&origin) || !bounds.intersect(&self.clip_bounds) {
+            return;
+        }
+
+        let matrix = Matrix::from_translate(origin.left() as Scalar, origin.top() as Scalar);
+        let Some(shader) = ImageShader::new(
+            pixmap,
+            TileMode::Decal,
+            TileMode::Decal,
+            &SamplingOptions::default(),
+            Some(&matrix),
+        ) else {
+            return;
+        };
+        let mut composite_paint = paint.clone();
+        composite_paint.set_shader(Some(Rc::new(shader)));
+        composite_paint.set_image_filter(None);
+        composite_paint.set_mask_filter(None);
+        composite_paint.set_path_effect(None);
+        let stride = self.pixmap.width() as usize;
+        // Image shader of layer is in device coordinates already.
+        let Some(mut blitter) =
+            Blitter::new(&mut self.pixmap, &composite_paint, &Matrix::identity())
+        else {
+            return;
+        };
+        let clip = self.clip.as_deref();
+        let left = bounds.left() as usize;
+        let coverage = vec![1.0; bounds.width() as usize];
+        let mut row = vec![0.0; coverage.len()];
+        for y in bounds.top()..bounds.bottom() {
+            let y = y as usize;
+            blitter.blit_span(
+                left,
+                y,
+                clip_span(clip, stride, left, y, &coverage, &mut row),
+            );
+        }
+    }
+
+    /// Returns bounds of pixels which may be covered by `path`, inside of clip bounds.
+    ///
+    /// Returns None if `path` covers no pixels inside of clip bounds.
+    fn path_device_bounds(&self, path: &Path) -> Option<IRect> {
+        let mut bounds = self.clip_bounds.clone();
+        if path.fill_type().is_inverse() {
+            return (!bounds.is_empty()).then_some(bounds);
+        }
+        let path_bounds = path_bounds(path, 0.0)?;
+        bounds.intersect(&path_bounds).then_some(bounds)
+    }
+
+    /// Rasterizes `path` into a mask, filters it with `mask_filter`, and blits the
+    /// filtered mask inside clip.
+    ///
+    /// The mask covers `path` inside of clip bounds outset by blur radius, so that
+    /// shapes just outside of clip still cast their blur into it.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn draw_path_with_mask_filter(&mut self, path: &Path, paint: &Paint, mask_filter: &MaskFilter) {
+        let radius = mask_filter.blur_radius();
+        let mut bounds = self.clip_bounds.clone();
+        bounds.outset(radius, radius);
+        if !path.fill_type().is_inverse() {
+            let Some(path_bounds) = path_bounds(path, 0.0) else {
+                return;
+            };
+            if !bounds.intersect(&path_bounds) {
+                return;
+            }
+        }
+        let mask_width = bounds.width() as usize;
+        let mut image = vec![0; mask_width * bounds.height() as usize];
+        rasterize(path, &bounds, paint.is_anti_alias(), |y, coverage| {
+            let row = &mut image[y * mask_width..(y + 1) * mask_width];
+            for (alpha, &value) in row.iter_mut().zip(coverage) {
+                *alpha = (value * 255.0).round() as u8;
+            }
+        });
+        let Some(mut mask) = Mask::from_vec(image, bounds) else {
+            return;
+        };
+        mask.set_format(MaskFormat::A8);
+        mask.set_row_bytes(mask_width);
+        let Some(filtered) = mask_filter.filter_mask(&mask) else {
+            return;
+        };
+
+        let mask_bounds = filtered.bounds();
+        let mut bounds = mask_bounds.clone();
+        if !bounds.intersect(&self.clip_bounds) {
+            return;
+        }
+        let stride = self.pixmap.width() as usize;
+        let Some(mut blitter) = Blitter::new(&mut self.pixmap, paint, &self.local_to_device) else {
+            return;
+        };
+        let clip = self.clip.as_deref();
+        let left = bounds.left() as usize;
+        let mask_left = (bounds.left() - mask_bounds.left()) as usize;
+        let row_bytes = filtered.row_bytes();
+        let mut coverage = vec![0.0; bounds.width() as usize];
+        let mut clipped = vec![0.0; coverage.len()];
+        for y in bounds.top()..bounds.bottom() {
+            let start = (y - mask_bounds.top()) as usize * row_bytes + mask_left;
+            let row = &filtered.image()[start..start + coverage.len()];
+            for (value, &alpha) in coverage.iter_mut().zip(row) {
+                *value = Scalar::from(alpha) / 255.0;
+            }
+            let y = y as usize;
+            blitter.blit_span(
+                left,
+                y,
+                clip_span(clip, stride, left, y, &coverage, &mut clipped),
+            );
+        }
+    }
+}
+
+impl Device for BitmapDevice {
+    fn image_info(&self) -> &ImageInfo {
+        self.pixmap.info()
+    }
+
+    fn surface_props(&self) -> &SurfaceProps {
+        &self.props
+    }
+
+    fn peek_pixels(&self) -> Option<&Pixmap> {
+        Some(&self.pixmap)
+    }
+
+    fn peek_pixels_mut(&mut self) -> Option<&mut Pixmap> {
+        Some(&mut self.pixmap)
+    }
+
+    fn set_local_to_device(&mut self, matrix: &Matrix) {
+        self.local_to_device = matrix.clone();
+    }
+
+    fn save(&mut self) {
+        self.saved_states.push(SavedState {
+            clip: self.clip.clone(),
+            clip_bounds: self.clip_bounds.clone(),
+            layer: None,
+        });
+    }
+
+    fn save_layer(&mut self, bounds: Option<&Rect>, paint: Option<&Paint>) {
+        let info = self.pixmap.info().clone();
+        let row_bytes = self.pixmap.row_bytes();
+        let pixels = vec![0; info.compute_byte_size(row_bytes)];
+        let layer = Pixmap::from_vec(info, row_bytes, pixels);
+        let device_bounds = IRect::from_wh(self.pixmap.width(), self.pixmap.height());
+        let bounds = bounds.map_or(device_bounds, Rect::round_out_to_irect);
+        self.saved_states.push(SavedState {
+            clip: self.clip.clone(),
+            clip_bounds: self.clip_bounds.clone(),
+            layer: Some(SavedLayer {
+                pixmap: std::mem::replace(&mut self.pixmap, layer),
+                bounds,
+                paint: paint.cloned().unwrap_or_default(),
+                matrix: self.local_to_device.clone(),
+            }),
+        });
+    }
+
+    fn restore(&mut self) {
+        let Some(state) = self.saved_states.pop() else {
+            return;
+        };
+        self.clip = state.clip;
+        self.clip_bounds = state.clip_bounds;
+        if let Some(SavedLayer {
+            pixmap,
+            bounds,
+            paint,
+            matrix,
+        }) = state.layer
+        {
+            let layer = std::mem::replace(&mut self.pixmap, pixmap);
+            self.draw_layer(&layer, IPoint::from_xy(0, 0), &bounds, &paint, &matrix);
+        }
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    fn clip_path(&mut self, path: &Path, op: ClipOp, anti_alias: bool) {
+        let width = self.pixmap.width().unsigned_abs() as usize;
+        let height = self.pixmap.height().unsigned_abs() as usize;
+        let bounds = self.path_device_bounds(path);
+        let mut mask = match (op, &self.clip) {
+            (ClipOp::Difference, Some(clip)) => clip.to_vec(),
+            (ClipOp::Difference, None) => vec![1.0; width * height],
+            (ClipOp::Intersect, _) => vec![0.0; width * height],
+        };
+        if let Some(bounds) = &bounds {
+            let clip = self.clip.as_deref();
+            let left = bounds.left() as usize;
+            let top = bounds.top() as usize;
+            rasterize(path, bounds, anti_alias, |y, coverage| {
+                let start = (top + y) * width + left;
+                let row = &mut mask[start..start + coverage.len()];
+                if op == ClipOp::Difference {
+                    for (value, &coverage) in row.iter_mut().zip(coverage) {
+                        *value *= 1.0 - coverage;
+                    }
+                    return;
+                }
+                row.copy_from_slice(coverage);
+                if let Some(clip) = clip {
+                    for (value, &clip_value) in row.iter_mut().zip(&clip[start..]) {
+                        *value *= clip_value;
+                    }
+                }
+            });
+        }
+        if op == ClipOp::Intersect {
+            self.clip_bounds = bounds.unwrap_or_default();
+        }
+        self.clip = Some(mask.into());
+    }
+
+    fn erase(&mut self, color: &Color4f) {
+        let Some(clip) = self.clip.clone() else {
+            self.pixmap.erase_with_color4f(color, None);
+            return;
+        };
+        let width = self.pixmap.width().unsigned_abs() as usize;
+        let mut paint = Paint::from_color(color);
+        paint.set_blend_mode(BlendMode::Src);
+        let Some(mut blitter) = Blitter::new(&mut self.pixmap, &paint, &Matrix::identity()) else {
+            return;
+        };
+        let bounds = &self.clip_bounds;
+        let left = bounds.left().unsigned_abs() as usize;
+        let right = bounds.right().unsigned_abs() as usize;
+        for y in bounds.top().unsigned_abs()..bounds.bottom().unsigned_abs() {
+            let start = y as usize * width;
+            blitter.blit_span(left, y as usize, &clip[start + left..start + right]);
+        }
+    }
+
+    fn draw_path(&mut self, path: &Path, paint: &Paint) {
+        if paint.nothing_to_draw() {
+            return;
+        }
+        if paint.get_image_filter().is_some() {
+            self.draw_path_with_image_filter(path, paint);
+            return;
+        }
+        let outline_path;
+        let path = if paint.get_style() == PaintStyle::Fill {
+            path
+        } else {
+            let is_stroke_and_fill = paint.get_style() == PaintStyle::StrokeAndFill;
+            let mut stroker = PathStroker::from_paint(paint);
+            if stroker.is_hairline() && !is_stroke_and_fill {
+                // Hairlines are one pixel wide in device space, without joins.
+                stroker.set_width(1.0);
+                stroker.set_join(StrokeJoin::Bevel);
+            }
+            match stroker.stroke(path) {
+                Some(outline) => {
+                    outline_path = outline;
+                    &outline_path
+                }
+                // As in Skia, stroke and fill with zero width only fills the path.
+                None if is_stroke_and_fill => path,
+                None => return,
+            }
+        };
+        if let Some(mask_filter) = paint.get_mask_filter() {
+            let sigma = mask_filter.compute_xformed_sigma(&self.local_to_device);
+            let mask_filter = MaskFilter::new(mask_filter.style(), sigma, false);
+            self.draw_path_with_mask_filter(path, paint, &mask_filter);
+            return;
+        }
+
+        let Some(bounds) = self.path_device_bounds(path) else {
+            return;
+        };
+        let stride = self.pixmap.width().unsigned_abs() as usize;
+        let Some(mut blitter) = Blitter::new(&mut self.pixmap, paint, &self.local_to_device) else {
+            return;
+        };
+        let clip = self.clip.as_deref();
+        let left = bounds.left().unsigned_abs() as usize;
+        let top = bounds.top().unsigned_abs() as usize;
+        let mut row = vec![0.0; bounds.width().unsigned_abs() as usize];
+        rasterize(path, &bounds, paint.is_anti_alias(), |y, coverage| {
+            let y = top + y;
+            blitter.blit_span(
+                left,
+                y,
+                clip_span(clip, stride, left, y, coverage, &mut row),
+            );
+        });
+    }
+}
+
+/// Returns `coverage` of pixels in row `y` starting at column `x`, multiplied by
+/// coverage of `clip` whose rows are `stride` pixels long, using `row` as storage.
+///
+/// Returns `coverage` directly if `clip` is None.
+fn clip_span<'a>(
+    clip: Option<&[Scalar]>,
+    stride: usize,
+    x: usize,
+    y: usize,
+    coverage: &'a [Scalar],
+    row: &'a mut [Scalar],
+) -> &'a [Scalar] {
+    let Some(clip) = clip else {
+        return coverage;
+    };
+    let start = y * stride + x;
+    let clip = &clip[start..start + coverage.len()];
+    for ((value, &coverage), &clip) in row.iter_mut().zip(coverage).zip(clip) {
+        *value = coverage * clip;
+    }
+    &row[..coverage.len()]
+}
+
+/// Returns bounds of pixels touched by control points of `path`, outset by `outset`.
+///
+/// Returns None if `path` is empty or has points which are not finite.
+fn path_bounds(path: &Path, outset: Scalar) -> Option<IRect> {
+    let points = path.points();
+    if points.is_empty() {
+        return None;
+    }
+    if !points.iter().all(Point::is_finite) {
+        return None;
+    }
+    let mut bounds = Rect::from_points(points);
+    bounds.outset(outset, outset);
+    Some(bounds.round_out_to_irect())
+}
+
+/// Rasterizes `path` inside of `bounds`, calling `blit_row` with row index relative
+/// to top of `bounds`, and coverage of pixels starting at left of `bounds`.
+#[allow(clippy::cast_precision_loss)]
+fn rasterize(
+    path: &Path,
+    bounds: &IRect,
+    anti_alias: bool,
+    blit_row: impl FnMut(usize, &[Scalar]),
+) {
+    let origin = Point::from_xy(bounds.left() as Scalar, bounds.top() as Scalar);
+    let width = bounds.width().unsigned_abs() as usize;
+    let height = bounds.height().unsigned_abs() as usize;
+    let mut rasterizer = Rasterizer::with_origin(width, height, origin);
+    rasterizer.add_path(path);
+    rasterizer.fill(path.fill_type(), anti_alias, blit_row);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::blur_types::BlurStyle;
+    use crate::core::color::Color;
+    use crate::core::color_filter::ColorFilter;
+    use crate::core::path_builder::PathBuilder;
+    use crate::core::path_types::PathFillType;
+    use crate::effects::offset_image_filter::OffsetImageFilter;
+
+    const SIZE: i32 = 20;
+
+    fn new_device() -> BitmapDevice {
+        let info = ImageInfo::new_n32_premul(SIZE, SIZE, None);
+        BitmapDevice::alloc(&info, 0, SurfaceProps::default()).unwrap()
+    }
+
+    fn alpha_at(device: &BitmapDevice, x: i32, y: i32) -> u8 {
+        device.pixmap().get_color(x, y).unwrap().alpha()
+    }
+
+    fn black_paint() -> Paint {
+        let mut paint = Paint::from_color(&Color4f::from_rgba(0.0, 0.0, 0.0, 1.0));
+        paint.set_anti_alias(true);
+        paint
+    }
+
+    /// Circle centered at top left corner of device, mostly outside of it.
+    fn corner_circle(fill_type: PathFillType) -> Path {
+        let mut builder = PathBuilder::new();
+        builder.add_circle(0.0, 0.0, 12.5);
+        builder.set_fill_type(fill_type);
+        builder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_draw_path_matches_device_coverage() {
+        for fill_type in [PathFillType::Winding, PathFillType::InverseWinding] {
+            let path = corner_circle(fill_type);
+            let mut device = new_device();
+            device.draw_path(&path, &black_paint());
+
+            let size = SIZE as usize;
+            let mut rasterizer = Rasterizer::new(size, size);
+            rasterizer.add_path(&path);
+            let mut expected = vec![0.0; size * size];
+            rasterizer.fill(fill_type, true, |y, coverage| {
+                expected[y * size..(y + 1) * size].copy_from_slice(coverage);
+            });
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    let coverage = expected[usize::try_from(y * SIZE + x).unwrap()];
+                    let alpha = Scalar::from(alpha_at(&device, x, y));
+                    assert!((alpha - coverage * 255.0).abs() <= 1.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_clip_path_intersect_and_difference() {
+        let mut device = new_device();
+        let outer = PathBuilder::from_rect(&Rect::from_ltrb(4.0, 4.0, 16.0, 16.0)).unwrap();
+        let inner = PathBuilder::from_rect(&Rect::from_ltrb(8.0, 8.0, 12.0, 12.0)).unwrap();
+        device.clip_path(&outer, ClipOp::Intersect, true);
+        device.clip_path(&inner, ClipOp::Difference, true);
+        device.erase(&Color4f::from_rgba(0.0, 0.0, 0.0, 1.0));
+        assert_eq!(alpha_at(&device, 5, 5), 255);
+        assert_eq!(alpha_at(&device, 10, 10), 0);
+        assert_eq!(alpha_at(&device, 2, 2), 0);
+        assert_eq!(alpha_at(&device, 17, 10), 0);
+
+        let mut device = new_device();
+        let outside = PathBuilder::from_rect(&Rect::from_ltrb(30.0, 30.0, 40.0, 40.0)).unwrap();
+        device.clip_path(&outside, ClipOp::Intersect, true);
+        device.draw_path(&corner_circle(PathFillType::InverseWinding), &black_paint());
+        assert_eq!(alpha_at(&device, 18, 18), 0);
+    }
+
+    #[test]
+    fn test_mask_filter_blurs_shape_outside_device() {
+        let mut device = new_device();
+        let mut paint = black_paint();
+        paint.set_mask_filter(Some(Rc::new(MaskFilter::new(
+            BlurStyle::Normal,
+            2.0,
+            false,
+        ))));
+        let path = PathBuilder::from_rect(&Rect::from_ltrb(-8.0, 0.0, -1.0, 20.0)).unwrap();
+        device.draw_path(&path, &paint);
+        assert!(alpha_at(&device, 0, 10) > 0);
+        assert_eq!(alpha_at(&device, 15, 10), 0);
+    }
+
+    #[test]
+    fn test_image_filter_moves_shape_from_outside_device() {
+        let mut device = new_device();
+        let mut paint = black_paint();
+        let filter = OffsetImageFilter::new(20.0, 0.0, None, None).unwrap();
+        paint.set_image_filter(Some(Rc::new(filter)));
+        let path = PathBuilder::from_rect(&Rect::from_ltrb(-15.0, 5.0, -5.0, 10.0)).unwrap();
+        device.draw_path(&path, &paint);
+        assert_eq!(alpha_at(&device, 6, 7), 255);
+        assert_eq!(alpha_at(&device, 16, 7), 0);
+        assert_eq!(alpha_at(&device, 6, 12), 0);
+    }
+
+    #[test]
+    fn test_color_filter_applies_to_paint_color() {
+        let mut device = new_device();
+        let mut paint = black_paint();
+        let filter = ColorFilter::blend(Color::from_argb(255, 255, 0, 0), BlendMode::Src);
+        paint.set_color_filter(Some(filter));
+        let path = PathBuilder::from_rect(&Rect::from_ltrb(5.0, 5.0, 10.0, 10.0)).unwrap();
+        device.draw_path(&path, &paint);
+        let color = device.pixmap().get_color(7, 7).unwrap();
+        assert_eq!(
+            (color.red(), color.green(), color.blue(), color.alpha()),
+            (255, 0, 0, 255)
+        );
+        assert_eq!(alpha_at(&device, 12, 7), 0);
+    }
+}
@@ -253,4 +253,296 @@ impl BlendMode {
             Self::Luminosity => "Luminosity",
         }
     }
+
+    /// Returns result of blending premultiplied `src` color over premultiplied `dst` color.
+    ///
+    /// Colors are in [red, green, blue, alpha] order, and the result is premultiplied
+    /// and clamped to [0, 1].
+    #[must_use]
+    pub fn blend(self, src: &[f32; 4], dst: &[f32; 4]) -> [f32; 4] {
+        let mut src_coeff = BlendModeCoeff::Zero;
+        let mut dst_coeff = BlendModeCoeff::Zero;
+        if self.as_coeff(&mut src_coeff, true) && self.as_coeff(&mut dst_coeff, false) {
+            let mut out = [0.0; 4];
+            for (index, value) in out.iter_mut().enumerate() {
+                *value = src[index].mul_add(
+                    src_coeff.value(index, src, dst),
+                    dst[index] * dst_coeff.value(index, src, dst),
+                );
+            }
+            return out.map(|value| value.clamp(0.0, 1.0));
+        }
+
+        let (sa, da) = (src[3], dst[3]);
+        let alpha = sa.mul_add(-da, sa + da);
+        let out = match self {
+            Self::Hue | Self::Saturation | Self::Color | Self::Luminosity => {
+                let [red, green, blue] = self.blend_non_separable(src, dst);
+                [red, green, blue, alpha]
+            }
+            _ => {
+                let mut out = [alpha; 4];
+                for index in 0..3 {
+                    out[index] = self.blend_separable(src[index], dst[index], sa, da);
+                }
+                out
+            }
+        };
+        out.map(|value| value.clamp(0.0, 1.0))
+    }
+
+    /// Blends one premultiplied channel of separable modes after `Screen`.
+    #[allow(clippy::float_cmp)]
+    fn blend_separable(self, s: f32, d: f32, sa: f32, da: f32) -> f32 {
+        // Parts of src outside of dst, and parts of dst outside of src.
+        let uncovered = s.mul_add(1.0 - da, d * (1.0 - sa));
+        match self {
+            Self::Overlay => uncovered + hard_light(d, s, da, sa),
+            Self::Darken => s + d - (s * da).max(d * sa),
+            Self::Lighten => s + d - (s * da).min(d * sa),
+            Self::ColorDodge => {
+                if d == 0.0 {
+                    s * (1.0 - da)
+                } else if s == sa {
+                    d.mul_add(1.0 - sa, s)
+                } else {
+                    sa.mul_add(da.min(d * sa / (sa - s)), uncovered)
+                }
+            }
+            Self::ColorBurn => {
+                if d == da {
+                    s.mul_add(1.0 - da, d)
+                } else if s == 0.0 {
+                    d * (1.0 - sa)
+                } else {
+                    sa.mul_add(da - da.min((da - d) * sa / s), uncovered)
+                }
+            }
+            Self::HardLight => uncovered + hard_light(s, d, sa, da),
+            Self::SoftLight => {
+                let m = if da > 0.0 { d / da } else { 0.0 };
+                let s2 = 2.0 * s;
+                let m4 = 4.0 * m;
+                let dark_src = d * (s2 - sa).mul_add(1.0 - m, sa);
+                let dark_dst = m4.mul_add(m4, m4).mul_add(m - 1.0, 7.0 * m);
+                let lite_dst = m.sqrt() - m;
+                let lite_src = (da * (s2 - sa))
+                    .mul_add(if 4.0 * d <= da { dark_dst } else { lite_dst }, d * sa);
+                uncovered + if s2 <= sa { dark_src } else { lite_src }
+            }
+            Self::Difference => 2.0f32.mul_add(-(s * da).min(d * sa), s + d),
+            Self::Exclusion => (2.0 * s).mul_add(-d, s + d),
+            Self::Multiply => s.mul_add(d, uncovered),
+            _ => {
+                debug_assert!(false, "{self:?} is not a separable blend mode");
+                s
+            }
+        }
+    }
+
+    /// Blends premultiplied RGB of `Hue`, `Saturation`, `Color` and `Luminosity` modes.
+    fn blend_non_separable(self, src: &[f32; 4], dst: &[f32; 4]) -> [f32; 3] {
+        let (sa, da) = (src[3], dst[3]);
+        let src_rgb = [src[0], src[1], src[2]];
+        let dst_rgb = [dst[0], dst[1], dst[2]];
+        let mut rgb = match self {
+            Self::Hue => {
+                let rgb = set_sat(src_rgb.map(|value| value * sa), sat(dst_rgb) * sa);
+                set_lum(rgb, lum(dst_rgb) * sa)
+            }
+            Self::Saturation => {
+                let rgb = set_sat(dst_rgb.map(|value| value * sa), sat(src_rgb) * da);
+                set_lum(rgb, lum(dst_rgb) * sa)
+            }
+            Self::Color => set_lum(src_rgb.map(|value| value * da), lum(dst_rgb) * sa),
+            _ => set_lum(dst_rgb.map(|value| value * sa), lum(src_rgb) * da),
+        };
+        rgb = clip_color(rgb, sa * da);
+        for (index, value) in rgb.iter_mut().enumerate() {
+            *value += src[index].mul_add(1.0 - da, dst[index] * (1.0 - sa));
+        }
+        rgb
+    }
+}
+
+impl BlendModeCoeff {
+    /// Returns value of coefficient for channel `index`, with premultiplied `src`
+    /// and `dst` colors.
+    #[must_use]
+    pub fn value(self, index: usize, src: &[f32; 4], dst: &[f32; 4]) -> f32 {
+        match self {
+            Self::Zero => 0.0,
+            Self::One => 1.0,
+            Self::SC => src[index],
+            Self::ISC => 1.0 - src[index],
+            Self::DC => dst[index],
+            Self::IDC => 1.0 - dst[index],
+            Self::SA => src[3],
+            Self::ISA => 1.0 - src[3],
+            Self::DA => dst[3],
+            Self::IDA => 1.0 - dst[3],
+        }
+    }
+}
+
+/// Hard light of premultiplied `s` over `d`, without uncovered parts.
+///
+/// Overlay is hard light with src and dst swapped.
+fn hard_light(s: f32, d: f32, sa: f32, da: f32) -> f32 {
+    if 2.0 * s <= sa {
+        2.0 * s * d
+    } else {
+        sa.mul_add(da, -2.0 * (da - d) * (sa - s))
+    }
+}
+
+/// Returns saturation of `rgb`, as defined by non-separable blend modes.
+fn sat(rgb: [f32; 3]) -> f32 {
+    rgb[0].max(rgb[1]).max(rgb[2]) - rgb[0].min(rgb[1]).min(rgb[2])
+}
+
+/// Returns luminosity of `rgb`, as defined by non-separable blend modes.
+fn lum(rgb: [f32; 3]) -> f32 {
+    rgb[0].mul_add(0.30, rgb[1].mul_add(0.59, rgb[2] * 0.11))
+}
+
+fn set_sat(rgb: [f32; 3], saturation: f32) -> [f32; 3] {
+    let min = rgb[0].min(rgb[1]).min(rgb[2]);
+    let current = sat(rgb);
+    let scale = if current > 0.0 {
+        saturation / current
+    } else {
+        0.0
+    };
+    rgb.map(|value| (value - min) * scale)
+}
+
+fn set_lum(rgb: [f32; 3], luminosity: f32) -> [f32; 3] {
+    let diff = luminosity - lum(rgb);
+    rgb.map(|value| value + diff)
+}
+
+/// Moves `rgb` into [0, `alpha`] while keeping its luminosity.
+fn clip_color(rgb: [f32; 3], alpha: f32) -> [f32; 3] {
+    let min = rgb[0].min(rgb[1]).min(rgb[2]);
+    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+    let l = lum(rgb);
+    rgb.map(|mut value| {
+        if min < 0.0 && l - min > 0.0 {
+            value = (value - l).mul_add(l / (l - min), l);
+        }
+        if max > alpha && max - l > 0.0 {
+            value = (value - l).mul_add((alpha - l) / (max - l), l);
+        }
+        value.max(0.0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: [f32; 4] = [0.8, 0.4, 0.2, 1.0];
+    const DST: [f32; 4] = [0.25, 0.5, 0.75, 1.0];
+    const TRANSPARENT: [f32; 4] = [0.0; 4];
+
+    /// Blend modes which are not Porter-Duff operators.
+    const ADVANCED_MODES: [BlendMode; 15] = [
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+        BlendMode::ColorDodge,
+        BlendMode::ColorBurn,
+        BlendMode::HardLight,
+        BlendMode::SoftLight,
+        BlendMode::Difference,
+        BlendMode::Exclusion,
+        BlendMode::Multiply,
+        BlendMode::Hue,
+        BlendMode::Saturation,
+        BlendMode::Color,
+        BlendMode::Luminosity,
+    ];
+
+    fn assert_color(mode: BlendMode, src: &[f32; 4], dst: &[f32; 4], expected: [f32; 4]) {
+        let out = mode.blend(src, dst);
+        for (value, expected) in out.iter().zip(expected) {
+            assert!(
+                (value - expected).abs() < 1e-4,
+                "{mode:?}: {out:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_porter_duff() {
+        // Half transparent red over opaque blue, premultiplied.
+        let src = [0.4, 0.0, 0.0, 0.5];
+        let dst = [0.0, 0.0, 1.0, 1.0];
+        assert_color(BlendMode::Clear, &src, &dst, [0.0, 0.0, 0.0, 0.0]);
+        assert_color(BlendMode::Src, &src, &dst, src);
+        assert_color(BlendMode::Dst, &src, &dst, dst);
+        assert_color(BlendMode::SrcOver, &src, &dst, [0.4, 0.0, 0.5, 1.0]);
+        assert_color(BlendMode::DstOver, &src, &dst, dst);
+        assert_color(BlendMode::SrcIn, &src, &dst, src);
+        assert_color(BlendMode::DstIn, &src, &dst, [0.0, 0.0, 0.5, 0.5]);
+        assert_color(BlendMode::SrcOut, &src, &dst, TRANSPARENT);
+        assert_color(BlendMode::DstOut, &src, &dst, [0.0, 0.0, 0.5, 0.5]);
+        assert_color(BlendMode::SrcATop, &src, &dst, [0.4, 0.0, 0.5, 1.0]);
+        assert_color(BlendMode::DstATop, &src, &dst, [0.0, 0.0, 0.5, 0.5]);
+        assert_color(BlendMode::Xor, &src, &dst, [0.0, 0.0, 0.5, 0.5]);
+        assert_color(BlendMode::Plus, &src, &dst, [0.4, 0.0, 1.0, 1.0]);
+        assert_color(BlendMode::Modulate, &src, &dst, [0.0, 0.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn test_separable_modes() {
+        assert_color(BlendMode::Multiply, &SRC, &DST, [0.2, 0.2, 0.15, 1.0]);
+        assert_color(BlendMode::Screen, &SRC, &DST, [0.85, 0.7, 0.8, 1.0]);
+        assert_color(BlendMode::Darken, &SRC, &DST, [0.25, 0.4, 0.2, 1.0]);
+        assert_color(BlendMode::Lighten, &SRC, &DST, [0.8, 0.5, 0.75, 1.0]);
+        assert_color(BlendMode::Difference, &SRC, &DST, [0.55, 0.1, 0.55, 1.0]);
+        assert_color(BlendMode::Exclusion, &SRC, &DST, [0.65, 0.5, 0.65, 1.0]);
+        assert_color(BlendMode::Overlay, &SRC, &DST, [0.4, 0.4, 0.6, 1.0]);
+        assert_color(BlendMode::HardLight, &SRC, &DST, [0.7, 0.4, 0.3, 1.0]);
+        // Dodge and burn are clamped to [0, 1].
+        assert_color(
+            BlendMode::ColorDodge,
+            &SRC,
+            &DST,
+            [1.0, 0.8333, 0.9375, 1.0],
+        );
+        assert_color(BlendMode::ColorBurn, &SRC, &DST, [0.0625, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_non_separable_modes() {
+        // Luminosity of SRC is 0.498, and of DST is 0.4525.
+        assert_color(
+            BlendMode::Luminosity,
+            &SRC,
+            &DST,
+            [0.2955, 0.5455, 0.7955, 1.0],
+        );
+        assert_color(BlendMode::Color, &SRC, &DST, [0.7545, 0.3545, 0.1545, 1.0]);
+
+        // Gray has no hue or saturation, only its luminosity is kept.
+        let gray = [0.5, 0.5, 0.5, 1.0];
+        assert_color(BlendMode::Hue, &gray, &DST, [0.4525, 0.4525, 0.4525, 1.0]);
+        assert_color(
+            BlendMode::Saturation,
+            &gray,
+            &DST,
+            [0.4525, 0.4525, 0.4525, 1.0],
+        );
+    }
+
+    #[test]
+    fn test_advanced_modes_with_transparent_color() {
+        for mode in ADVANCED_MODES {
+            assert_color(mode, &SRC, &TRANSPARENT, SRC);
+            assert_color(mode, &TRANSPARENT, &DST, DST);
+        }
+    }
 }
@@ -0,0 +1,169 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Blitter writes rows of coverage produced by rasterizer into pixels.
+
+use std::rc::Rc;
+
+use crate::core::blend_mode::BlendMode;
+use crate::core::color::Color4f;
+use crate::core::color_filter::ColorFilter;
+use crate::core::color_space::ColorSpace;
+use crate::core::matrix::Matrix;
+use crate::core::paint::Paint;
+use crate::core::pixmap::{pack_color4f, unpack_color4f, Pixmap};
+use crate::core::shader::Shader;
+
+/// Blends color or shader of paint into pixels with its blend mode.
+#[derive(Debug)]
+pub struct Blitter<'a> {
+    pixmap: &'a mut Pixmap,
+
+    /// Premultiplied color of paint.
+    color: [f32; 4],
+
+    /// Shader of paint, whose colors are modulated by alpha of paint.
+    shader: Option<Rc<dyn Shader>>,
+
+    /// Maps device coordinates to local coordinates of shader.
+    device_to_local: Matrix,
+
+    /// Color filter of paint, applied to colors of shader.
+    ///
+    /// Color of paint is filtered once when blitter is created.
+    color_filter: Option<Rc<ColorFilter>>,
+
+    blend_mode: BlendMode,
+}
+
+impl<'a> Blitter<'a> {
+    /// Creates blitter drawing with color, shader, color filter and blend mode of `paint`.
+    ///
+    /// `matrix` maps local coordinates of shader to device coordinates.
+    ///
+    /// Returns None if color type of `pixmap` is not supported yet, or if `paint`
+    /// has a shader and `matrix` can not be inverted.
+    #[must_use]
+    pub fn new(pixmap: &'a mut Pixmap, paint: &Paint, matrix: &Matrix) -> Option<Self> {
+        let color = paint.get_color4f();
+        pack_color4f(pixmap.color_type(), pixmap.alpha_type(), color)?;
+        unpack_color4f(pixmap.color_type(), pixmap.alpha_type(), &[0; 16])?;
+
+        let shader = paint.get_shader().cloned();
+        let mut device_to_local = Matrix::identity();
+        if shader.is_some() && !matrix.invert(&mut device_to_local) {
+            return None;
+        }
+        let color_filter = paint.get_color_filter().cloned();
+        let color = match (&shader, &color_filter) {
+            (None, Some(color_filter)) => premultiply(&filter_color(color_filter, color), 1.0),
+            _ => premultiply(color, 1.0),
+        };
+        Some(Self {
+            pixmap,
+            color,
+            shader,
+            device_to_local,
+            color_filter,
+            blend_mode: paint.get_blend_mode(),
+        })
+    }
+
+    /// Blends color into pixels of row `y`, weighted by `coverage` of each pixel.
+    ///
+    /// `coverage` starts at the left edge of pixmap. Shader is sampled at pixel centers,
+    /// mapped to its local coordinates.
+    #[inline]
+    pub fn blit_row(&mut self, y: usize, coverage: &[f32]) {
+        self.blit_span(0, y, coverage);
+    }
+
+    /// Blends color into pixels of row `y` starting at column `x`, weighted by
+    /// `coverage` of each pixel.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn blit_span(&mut self, x: usize, y: usize, coverage: &[f32]) {
+        let color_type = self.pixmap.color_type();
+        let alpha_type = self.pixmap.alpha_type();
+        let bytes_per_pixel = self.pixmap.info().bytes_per_pixel().unsigned_abs() as usize;
+        let width = self.pixmap.width().unsigned_abs() as usize;
+        let row_bytes = self.pixmap.row_bytes();
+        let src = self.color;
+        let blend_mode = self.blend_mode;
+        if x >= width {
+            return;
+        }
+        let Some(row) = self
+            .pixmap
+            .addr_mut()
+            .get_mut(y * row_bytes + x * bytes_per_pixel..y * row_bytes + width * bytes_per_pixel)
+        else {
+            return;
+        };
+
+        for (x, (pixel, &coverage)) in row
+            .chunks_exact_mut(bytes_per_pixel)
+            .zip(coverage)
+            .enumerate()
+            .map(|(index, item)| (x + index, item))
+        {
+            if coverage <= 0.0 {
+                continue;
+            }
+            let src = self.shader.as_ref().map_or(src, |shader| {
+                let center = self.device_to_local.map_xy(x as f32 + 0.5, y as f32 + 0.5);
+                let color = shader.color_at(&center);
+                let Some(color_filter) = &self.color_filter else {
+                    return premultiply(&color, src[3]);
+                };
+                let color = Color4f::from_rgba(
+                    color.red(),
+                    color.green(),
+                    color.blue(),
+                    color.alpha().clamp(0.0, 1.0) * src[3],
+                );
+                premultiply(&filter_color(color_filter, &color), 1.0)
+            });
+            let Some(dst) = unpack_color4f(color_type, alpha_type, pixel) else {
+                return;
+            };
+            let dst_alpha = dst.alpha();
+            let dst = [
+                dst.red() * dst_alpha,
+                dst.green() * dst_alpha,
+                dst.blue() * dst_alpha,
+                dst_alpha,
+            ];
+            let mut out = blend_mode.blend(&src, &dst);
+            for (value, dst_value) in out.iter_mut().zip(dst) {
+                *value = (*value - dst_value).mul_add(coverage, dst_value);
+            }
+
+            let alpha = out[3];
+            let color = if alpha > 0.0 {
+                Color4f::from_rgba(out[0] / alpha, out[1] / alpha, out[2] / alpha, alpha)
+            } else {
+                Color4f::from_rgba(0.0, 0.0, 0.0, 0.0)
+            };
+            if let Some(packed) = pack_color4f(color_type, alpha_type, &color) {
+                pixel.copy_from_slice(&packed[..bytes_per_pixel]);
+            }
+        }
+    }
+}
+
+fn filter_color(color_filter: &ColorFilter, color: &Color4f) -> Color4f {
+    let mut dst_cs = ColorSpace::default();
+    color_filter.filter_color4f(color, &ColorSpace::default(), &mut dst_cs)
+}
+
+/// Returns premultiplied components of `color`, whose alpha is scaled by `alpha`.
+fn premultiply(color: &Color4f, alpha: f32) -> [f32; 4] {
+    let alpha = color.alpha().clamp(0.0, 1.0) * alpha;
+    [
+        color.red().clamp(0.0, 1.0) * alpha,
+        color.green().clamp(0.0, 1.0) * alpha,
+        color.blue().clamp(0.0, 1.0) * alpha,
+        alpha,
+    ]
+}
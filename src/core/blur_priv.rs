@@ -0,0 +1,156 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Separable Gaussian blur shared by mask filters and image filters.
+
+use crate::core::scalar::Scalar;
+
+/// Gaussian kernel is cut off at this multiple of sigma.
+const BLUR_SIGMA_SCALE: Scalar = 3.0;
+
+/// Larger sigma is clamped, to limit size of blurred images.
+const MAX_BLUR_SIGMA: Scalar = 532.0;
+
+/// Returns number of pixels a blur with `sigma` spreads on each side.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn blur_radius(sigma: Scalar) -> i32 {
+    if sigma > 0.0 {
+        (sigma.min(MAX_BLUR_SIGMA) * BLUR_SIGMA_SCALE).ceil() as i32
+    } else {
+        0
+    }
+}
+
+/// Returns normalized weights of Gaussian kernel with `sigma`, from `-radius` to `radius`
+/// where radius is `blur_radius(sigma)`.
+///
+/// The kernel is a single weight of 1 if `sigma` is not larger than zero.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+#[allow(clippy::cast_sign_loss)]
+pub fn gaussian_kernel(sigma: Scalar) -> Vec<Scalar> {
+    let radius = blur_radius(sigma) as usize;
+    if radius == 0 {
+        return vec![1.0];
+    }
+    let sigma = sigma.min(MAX_BLUR_SIGMA);
+    let denominator = -0.5 / (sigma * sigma);
+    let mut kernel: Vec<Scalar> = (0..=radius * 2)
+        .map(|index| {
+            let offset = index as Scalar - radius as Scalar;
+            (offset * offset * denominator).exp()
+        })
+        .collect();
+    let sum: Scalar = kernel.iter().sum();
+    for weight in &mut kernel {
+        *weight /= sum;
+    }
+    kernel
+}
+
+/// Blurs `plane` of `width` by `height` values, first along rows with `kernel_x`,
+/// then along columns with `kernel_y`.
+///
+/// Values outside of `plane` are zero, so `plane` shall be padded by radius of
+/// kernels to keep all of the result.
+#[must_use]
+pub fn blur_plane(
+    plane: &[Scalar],
+    width: usize,
+    height: usize,
+    kernel_x: &[Scalar],
+    kernel_y: &[Scalar],
+) -> Vec<Scalar> {
+    let mut temp = vec![0.0; plane.len()];
+    convolve(plane, &mut temp, width, height, kernel_x, true);
+    let mut blurred = vec![0.0; plane.len()];
+    convolve(&temp, &mut blurred, width, height, kernel_y, false);
+    blurred
+}
+
+/// Convolves rows (or columns if `horizontal` is false) of `src` with `kernel`,
+/// values outside of `src` are zero.
+fn convolve(
+    src: &[Scalar],
+    dst: &mut [Scalar],
+    width: usize,
+    height: usize,
+    kernel: &[Scalar],
+    horizontal: bool,
+) {
+    let radius = kernel.len() / 2;
+    let (lines, length, step, line_step) = if horizontal {
+        (height, width, 1, width)
+    } else {
+        (width, height, width, 1)
+    };
+    for line in 0..lines {
+        let base = line * line_step;
+        for index in 0..length {
+            let first = index.saturating_sub(radius);
+            let last = (index + radius).min(length - 1);
+            let mut sum = 0.0;
+            for source in first..=last {
+                let weight = kernel[source + radius - index];
+                sum = src[base + source * step].mul_add(weight, sum);
+            }
+            dst[base + index * step] = sum;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blur_radius() {
+        assert_eq!(blur_radius(0.0), 0);
+        assert_eq!(blur_radius(-1.0), 0);
+        assert_eq!(blur_radius(1.0), 3);
+        assert_eq!(blur_radius(1.5), 5);
+        assert_eq!(blur_radius(1000.0), 1596);
+    }
+
+    #[test]
+    fn test_gaussian_kernel() {
+        assert_eq!(gaussian_kernel(0.0), vec![1.0]);
+
+        let kernel = gaussian_kernel(2.0);
+        assert_eq!(kernel.len(), 13);
+        let sum: Scalar = kernel.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-5);
+        for (left, right) in kernel.iter().zip(kernel.iter().rev()) {
+            assert!((left - right).abs() < 1e-6);
+        }
+        assert!(kernel.windows(2).take(6).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_blur_plane() {
+        // Impulse in the middle of a 9x9 plane.
+        let mut plane = vec![0.0; 81];
+        plane[40] = 1.0;
+        let kernel_x = gaussian_kernel(1.0);
+        let kernel_y = [1.0];
+        let blurred = blur_plane(&plane, 9, 9, &kernel_x, &kernel_y);
+
+        // Spreads along the row only, by kernel weights.
+        for (index, value) in blurred.iter().enumerate() {
+            let (x, y) = (index % 9, index / 9);
+            let expected = if y == 4 && (1..8).contains(&x) {
+                kernel_x[x - 1]
+            } else {
+                0.0
+            };
+            assert!((value - expected).abs() < 1e-6);
+        }
+
+        let blurred = blur_plane(&plane, 9, 9, &kernel_x, &kernel_x);
+        let sum: Scalar = blurred.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-5);
+        assert!(kernel_x[0].mul_add(-kernel_x[3], blurred[13]).abs() < 1e-6);
+    }
+}
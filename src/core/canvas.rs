@@ -25,15 +25,45 @@
 //! This approach may be deprecated in the future.
 
 use std::ffi::c_void;
-use std::rc::Rc;
 
 use crate::core::bitmap::Bitmap;
-use crate::core::color::PMColor;
+use crate::core::bitmap_device::BitmapDevice;
+use crate::core::clip_op::ClipOp;
+use crate::core::color::{Color, Color4f, PMColor};
+use crate::core::device::{Device, NoPixelsDevice};
 use crate::core::image_info::ImageInfo;
+use crate::core::matrix::Matrix;
+use crate::core::paint::Paint;
+use crate::core::paint_types::PaintStyle;
+use crate::core::path::Path;
+use crate::core::path_builder::PathBuilder;
+use crate::core::path_utils::PathStroker;
+use crate::core::pixmap::Pixmap;
 use crate::core::point::IPoint;
+use crate::core::rect::Rect;
+use crate::core::rrect::RRect;
+use crate::core::scalar::Scalar;
+use crate::core::surface::Surface;
 use crate::core::surface_props::SurfaceProps;
 
-pub struct Canvas {}
+pub struct Canvas {
+    device: Box<dyn Device>,
+
+    /// Number of saved states plus one.
+    save_count: usize,
+
+    /// Transforms geometry from local coordinates to device.
+    matrix: Matrix,
+
+    /// Matrices saved by `save()` and `save_layer()`.
+    saved_matrices: Vec<Matrix>,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Canvas {
     /// Allocates raster Canvas that will draw directly into pixels.
@@ -121,8 +151,8 @@ impl Canvas {
     /// example: Canvas_empty_constructor
     #[must_use]
     #[inline]
-    pub const fn new() -> Self {
-        Self {}
+    pub fn new() -> Self {
+        Self::with_width_height(0, 0, None)
     }
 
     /// Creates Canvas of the specified dimensions without a Surface.
@@ -143,15 +173,27 @@ impl Canvas {
     /// Returns Canvas placeholder with dimensions
     ///
     /// example: Canvas_int_int_const_SurfaceProps_star
-    pub fn with_width_height(_width: i32, _height: i32, _props: Option<&SurfaceProps>) -> Self {
-        todo!()
+    #[must_use]
+    pub fn with_width_height(width: i32, height: i32, props: Option<&SurfaceProps>) -> Self {
+        let props = props.cloned().unwrap_or_default();
+        Self::with_device(Box::new(NoPixelsDevice::new(width, height, props)))
     }
 
     /// Private.
     ///
     /// For internal use only.
-    fn with_device(_device: Rc<Device>) -> Self {
-        todo!()
+    pub(crate) fn with_device(device: Box<dyn Device>) -> Self {
+        Self {
+            device,
+            save_count: 1,
+            matrix: Matrix::identity(),
+            saved_matrices: Vec::new(),
+        }
+    }
+
+    /// Returns the device that Canvas draws into.
+    pub(crate) fn device(&self) -> &dyn Device {
+        self.device.as_ref()
     }
 
     /// Constructs a canvas that draws into bitmap.
@@ -169,8 +211,9 @@ impl Canvas {
     /// Returns Canvas that can be used to draw into bitmap
     ///
     /// example: Canvas_copy_const_Bitmap
-    pub fn with_bitmap(_bitmap: Bitmap) -> Self {
-        todo!()
+    #[must_use]
+    pub fn with_bitmap(bitmap: &Bitmap) -> Self {
+        Self::with_bitmap_props(bitmap, SurfaceProps::new())
     }
 
     /// Constructs a canvas that draws into bitmap.
@@ -178,7 +221,8 @@ impl Canvas {
     /// Use `props` to match the device characteristics, like LCD striping.
     ///
     /// `bitmap` is copied so that subsequently editing bitmap will not affect
-    /// constructed Canvas.
+    /// constructed Canvas, and drawing does not change `bitmap`. Call `peek_pixels()`
+    /// or `read_pixels()` to access pixels drawn.
     ///
     /// Parameters:
     /// - `bitmap`: width, height, ColorType, AlphaType, and pixel storage of raster surface
@@ -187,8 +231,10 @@ impl Canvas {
     /// Returns Canvas that can be used to draw into bitmap
     ///
     /// example: Canvas_const_Bitmap_const_SurfaceProps
-    pub fn with_bitmap_props(_bitmap: Bitmap, _props: SurfaceProps) -> Self {
-        todo!()
+    #[must_use]
+    pub fn with_bitmap_props(bitmap: &Bitmap, props: SurfaceProps) -> Self {
+        let device = BitmapDevice::new(bitmap.pixmap().clone(), props);
+        Self::with_device(Box::new(device))
     }
 
     /// Returns `ImageInfo` for Canvas.
@@ -201,7 +247,7 @@ impl Canvas {
     /// example: Canvas_imageInfo
     #[must_use]
     pub fn image_info(&self) -> ImageInfo {
-        unimplemented!()
+        self.device.image_info().clone()
     }
 
     /// Copies `SurfaceProps`, if Canvas is associated with raster surface or
@@ -223,8 +269,9 @@ impl Canvas {
     /// (i.e., at the base of the layer stack).
     ///
     /// Returns base `SurfaceProps`
-    pub const fn get_base_props(&self) -> SurfaceProps {
-        todo!()
+    #[must_use]
+    pub fn get_base_props(&self) -> SurfaceProps {
+        self.device.surface_props().clone()
     }
 
     /// Returns the `SurfaceProps` associated with the canvas that are currently active
@@ -234,8 +281,10 @@ impl Canvas {
     /// (see SaveLayerFlagsSet).
     ///
     /// Returns `SurfaceProps` active in the current/top layer
-    pub const fn get_top_props(&self) -> SurfaceProps {
-        todo!()
+    #[must_use]
+    pub fn get_top_props(&self) -> SurfaceProps {
+        // TODO(Shaohua): Returns props of top layer.
+        self.get_base_props()
     }
 
     /// Creates Surface matching info and props, and associates it with Canvas.
@@ -252,20 +301,10 @@ impl Canvas {
     /// Returns Surface matching info and props, or nullptr if no match is available
     ///
     /// example: Canvas_makeSurface
-    pub fn make_surface(
-        &mut self,
-        _info: &ImageInfo,
-        _props: Option<&SurfaceProps>,
-    ) -> Rc<Surface> {
-        todo!();
-    }
-
-    /// Sometimes a canvas is owned by a surface.
-    ///
-    /// If it is, getSurface() will return a bare pointer to that surface,
-    /// else this will return nullptr.
-    pub fn get_surface_mut(&self) -> &Surface {
-        todo!()
+    #[must_use]
+    pub fn make_surface(&self, info: &ImageInfo, props: Option<&SurfaceProps>) -> Option<Surface> {
+        let props = props.cloned().unwrap_or_else(|| self.get_base_props());
+        Surface::new_raster_with_props(info, 0, Some(&props))
     }
 
     /// Returns the pixel base address, ImageInfo, row_bytes, and origin if the pixels
@@ -288,11 +327,402 @@ impl Canvas {
     /// example: Canvas_accessTopLayerPixels_b
     pub fn access_top_layer_pixels(
         &mut self,
-        _info: &mut ImageInfo,
-        _row_bytes: &mut usize,
-        _origin: &mut IPoint,
+        info: &mut ImageInfo,
+        row_bytes: &mut usize,
+        origin: &mut IPoint,
     ) -> *mut c_void {
-        todo!()
+        let Some(pixmap) = self.device.peek_pixels_mut() else {
+            return std::ptr::null_mut();
+        };
+        *info = pixmap.info().clone();
+        *row_bytes = pixmap.row_bytes();
+        // Layers cover the whole device.
+        *origin = IPoint::new();
+        pixmap.addr_mut().as_mut_ptr().cast()
+    }
+
+    /// Returns Pixmap of Canvas if its pixels can be read directly.
+    ///
+    /// If a layer is saved by `save_layer()`, pixels of the layer are returned.
+    ///
+    /// Returns None if Canvas is not drawing into pixels in memory, like
+    /// document based Canvas.
+    #[must_use]
+    pub fn peek_pixels(&self) -> Option<&Pixmap> {
+        self.device.peek_pixels()
+    }
+
+    /// Copies `Rect` of pixels from Canvas into `dst_pixels`.
+    ///
+    /// Source `Rect` corners are (`src_x`, `src_y`) and (`image_info().width()`,
+    /// `image_info().height()`). Destination `Rect` corners are (0, 0) and
+    /// (`dst_info.width()`, `dst_info.height()`).
+    /// Copies each readable pixel intersecting both rectangles, converting
+    /// pixels to `dst_info.color_type()` and `dst_info.alpha_type()` if required.
+    ///
+    /// Parameters:
+    /// - `dst_info`: width, height, `ColorType`, and `AlphaType` of `dst_pixels`
+    /// - `dst_pixels`: storage for pixels
+    /// - `dst_row_bytes`: size of one destination row
+    /// - `src_x`: offset into readable pixels on x-axis; may be negative
+    /// - `src_y`: offset into readable pixels on y-axis; may be negative
+    ///
+    /// Returns true if pixels were copied, false if Canvas pixels can not be read.
+    pub fn read_pixels_with_info(
+        &self,
+        dst_info: &ImageInfo,
+        dst_pixels: &mut [u8],
+        dst_row_bytes: usize,
+        src_x: i32,
+        src_y: i32,
+    ) -> bool {
+        self.peek_pixels().is_some_and(|pixmap| {
+            pixmap.read_pixels_with_info_at(dst_info, dst_pixels, dst_row_bytes, src_x, src_y)
+        })
+    }
+
+    /// Copies `Rect` of pixels from Canvas into `dst`, see `read_pixels_with_info()`.
+    ///
+    /// Parameters:
+    /// - `dst`: destination Pixmap: `ImageInfo`, pixels, row bytes
+    /// - `src_x`: offset into readable pixels on x-axis; may be negative
+    /// - `src_y`: offset into readable pixels on y-axis; may be negative
+    ///
+    /// Returns true if pixels were copied, false if Canvas pixels can not be read.
+    pub fn read_pixels(&self, dst: &mut Pixmap, src_x: i32, src_y: i32) -> bool {
+        self.peek_pixels()
+            .is_some_and(|pixmap| pixmap.read_pixels_at(dst, src_x, src_y))
+    }
+
+    /// Saves Matrix and clip.
+    ///
+    /// Calling `restore()` discards changes to Matrix and clip, restoring Matrix
+    /// and clip to their state when `save()` was called.
+    ///
+    /// Matrix may be changed by `translate()`, `scale()`, `rotate()`, `concat()`
+    /// and `set_matrix()`.
+    ///
+    /// Clip may be changed by `clip_rect()`, `clip_rrect()` and `clip_path()`.
+    ///
+    /// Saved Canvas state is put on a stack; multiple calls to `save()` should be
+    /// balanced by an equal number of calls to `restore()`.
+    ///
+    /// Call `restore_to_count()` with result to restore this and subsequent saves.
+    ///
+    /// Returns depth of saved stack.
+    pub fn save(&mut self) -> usize {
+        let save_count = self.save_count;
+        self.device.save();
+        self.saved_matrices.push(self.matrix.clone());
+        self.save_count += 1;
+        save_count
+    }
+
+    /// Saves Matrix and clip, and allocates a layer for subsequent drawing.
+    ///
+    /// Calling `restore()` discards changes to Matrix and clip, and draws layer.
+    ///
+    /// `bounds` suggests but does not define the layer size. To clip drawing to
+    /// a specific rectangle, use `clip_rect()`.
+    ///
+    /// Optional Paint `paint` applies alpha, `ColorFilter`, `ImageFilter`, and
+    /// `BlendMode` when `restore()` is called.
+    ///
+    /// Call `restore_to_count()` with returned value to restore this and subsequent saves.
+    ///
+    /// # Parameters
+    /// - `bounds` - hint to limit the size of the layer; may be None
+    /// - `paint` - graphics state for layer; may be None
+    ///
+    /// Returns depth of saved stack.
+    pub fn save_layer(&mut self, bounds: Option<&Rect>, paint: Option<&Paint>) -> usize {
+        let save_count = self.save_count;
+        let bounds = bounds.map(|bounds| self.matrix.map_rect(bounds));
+        self.device.save_layer(bounds.as_ref(), paint);
+        self.saved_matrices.push(self.matrix.clone());
+        self.save_count += 1;
+        save_count
+    }
+
+    /// Saves Matrix and clip, and allocates a layer for subsequent drawing.
+    ///
+    /// Calling `restore()` discards changes to Matrix and clip, and blends layer with alpha
+    /// opacity onto prior layer.
+    ///
+    /// # Parameters
+    /// - `bounds` - hint to limit the size of layer; may be None
+    /// - `alpha` - opacity of layer
+    ///
+    /// Returns depth of saved stack.
+    pub fn save_layer_alpha(&mut self, bounds: Option<&Rect>, alpha: u8) -> usize {
+        let mut paint = Paint::new();
+        paint.set_alpha(alpha);
+        self.save_layer(bounds, Some(&paint))
+    }
+
+    /// Removes changes to Matrix and clip since Canvas state was last saved.
+    ///
+    /// The state is removed from the stack, and the layer saved by `save_layer()`
+    /// is drawn, if any.
+    ///
+    /// Does nothing if the stack is empty.
+    pub fn restore(&mut self) {
+        if self.save_count > 1 {
+            self.device.restore();
+            if let Some(matrix) = self.saved_matrices.pop() {
+                self.matrix = matrix;
+                self.device.set_local_to_device(&self.matrix);
+            }
+            self.save_count -= 1;
+        }
+    }
+
+    /// Returns the number of saved states, each containing Matrix and clip.
+    ///
+    /// Equals the number of `save()` calls less the number of `restore()` calls plus one.
+    /// The save count of a new canvas is one.
+    #[must_use]
+    #[inline]
+    pub const fn get_save_count(&self) -> usize {
+        self.save_count
+    }
+
+    /// Restores state to Matrix and clip values when `save()` returned `save_count`.
+    ///
+    /// Does nothing if `save_count` is greater than state stack count.
+    /// Restores state to initial values if `save_count` is less than or equal to one.
+    ///
+    /// # Parameters
+    /// - `save_count` - depth of state stack to restore
+    pub fn restore_to_count(&mut self, save_count: usize) {
+        let save_count = save_count.max(1);
+        while self.save_count > save_count {
+            self.restore();
+        }
+    }
+
+    /// Translates Matrix by `dx` along the x-axis and `dy` along the y-axis.
+    ///
+    /// Mathematically, replaces Matrix with a translation matrix premultiplied
+    /// with Matrix. This has the effect of moving the drawing by (`dx`, `dy`)
+    /// before transforming the result with Matrix.
+    ///
+    /// # Parameters
+    /// - `dx` - distance to translate on x-axis
+    /// - `dy` - distance to translate on y-axis
+    pub fn translate(&mut self, dx: Scalar, dy: Scalar) {
+        self.concat(&Matrix::from_translate(dx, dy));
+    }
+
+    /// Scales Matrix by `sx` on the x-axis and `sy` on the y-axis.
+    ///
+    /// Mathematically, replaces Matrix with a scale matrix premultiplied with Matrix.
+    /// This has the effect of scaling the drawing by (`sx`, `sy`) before
+    /// transforming the result with Matrix.
+    ///
+    /// # Parameters
+    /// - `sx` - amount to scale on x-axis
+    /// - `sy` - amount to scale on y-axis
+    pub fn scale(&mut self, sx: Scalar, sy: Scalar) {
+        self.concat(&Matrix::from_scale(sx, sy));
+    }
+
+    /// Rotates Matrix by `degrees`. Positive degrees rotates clockwise.
+    ///
+    /// Mathematically, replaces Matrix with a rotation matrix premultiplied with
+    /// Matrix. This has the effect of rotating the drawing by `degrees` before
+    /// transforming the result with Matrix.
+    ///
+    /// # Parameters
+    /// - `degrees` - amount to rotate, in degrees
+    pub fn rotate(&mut self, degrees: Scalar) {
+        self.concat(&Matrix::from_rotate(degrees));
+    }
+
+    /// Replaces Matrix with `matrix` premultiplied with existing Matrix.
+    ///
+    /// This has the effect of transforming the drawn geometry by `matrix`,
+    /// before transforming the result with existing Matrix.
+    ///
+    /// # Parameters
+    /// - `matrix` - matrix to premultiply with existing Matrix
+    pub fn concat(&mut self, matrix: &Matrix) {
+        self.matrix = Matrix::from_concat(&self.matrix, matrix);
+        self.device.set_local_to_device(&self.matrix);
+    }
+
+    /// Replaces Matrix with `matrix`.
+    ///
+    /// Unlike `concat()`, any prior matrix state is overwritten.
+    ///
+    /// # Parameters
+    /// - `matrix` - matrix to copy, replacing existing Matrix
+    pub fn set_matrix(&mut self, matrix: &Matrix) {
+        self.matrix = matrix.clone();
+        self.device.set_local_to_device(&self.matrix);
+    }
+
+    /// Sets Matrix to the identity matrix.
+    ///
+    /// Any prior matrix state is overwritten.
+    pub fn reset_matrix(&mut self) {
+        self.matrix = Matrix::identity();
+        self.device.set_local_to_device(&self.matrix);
+    }
+
+    /// Returns Matrix, which maps local coordinates to device coordinates.
+    #[must_use]
+    #[inline]
+    pub const fn get_total_matrix(&self) -> &Matrix {
+        &self.matrix
+    }
+
+    /// Replaces clip with the intersection or difference of clip and `rect`,
+    /// with an aliased or anti-aliased clip edge.
+    ///
+    /// # Parameters
+    /// - `rect` - Rect to combine with clip
+    /// - `op` - `ClipOp` to apply to clip
+    /// - `do_anti_alias` - true if clip is to be anti-aliased
+    pub fn clip_rect(&mut self, rect: &Rect, op: ClipOp, do_anti_alias: bool) {
+        let mut rect = rect.clone();
+        rect.sort();
+        let path = PathBuilder::from_rect(&rect).unwrap_or_default();
+        self.clip_path(&path, op, do_anti_alias);
+    }
+
+    /// Replaces clip with the intersection or difference of clip and `rrect`,
+    /// with an aliased or anti-aliased clip edge.
+    ///
+    /// # Parameters
+    /// - `rrect` - `RRect` to combine with clip
+    /// - `op` - `ClipOp` to apply to clip
+    /// - `do_anti_alias` - true if clip is to be anti-aliased
+    pub fn clip_rrect(&mut self, rrect: &RRect, op: ClipOp, do_anti_alias: bool) {
+        let mut builder = PathBuilder::new();
+        builder.add_rrect(rrect);
+        let path = builder.finish().unwrap_or_default();
+        self.clip_path(&path, op, do_anti_alias);
+    }
+
+    /// Replaces clip with the intersection or difference of clip and `path`,
+    /// with an aliased or anti-aliased clip edge.
+    ///
+    /// `PathFillType` of `path` determines if `path` describes the area inside
+    /// or outside its contours; and if path contour overlaps itself or another
+    /// path contour, whether the overlaps form part of the area.
+    ///
+    /// `path` is transformed by Matrix before it is combined with clip.
+    ///
+    /// # Parameters
+    /// - `path` - Path to combine with clip
+    /// - `op` - `ClipOp` to apply to clip
+    /// - `do_anti_alias` - true if clip is to be anti-aliased
+    pub fn clip_path(&mut self, path: &Path, op: ClipOp, do_anti_alias: bool) {
+        if self.matrix.is_identity() {
+            self.device.clip_path(path, op, do_anti_alias);
+        } else {
+            self.device
+                .clip_path(&path.transform(&self.matrix), op, do_anti_alias);
+        }
+    }
+
+    /// Fills clip with color `color` using `BlendMode::Src`.
+    ///
+    /// This has the effect of replacing all pixels contained by clip with `color`.
+    ///
+    /// # Parameters
+    /// - `color` - unpremultiplied ARGB
+    #[inline]
+    pub fn clear(&mut self, color: Color) {
+        self.clear_color4f(&color.into());
+    }
+
+    /// Fills clip with color `color` using `BlendMode::Src`, see `clear()`.
+    pub fn clear_color4f(&mut self, color: &Color4f) {
+        self.device.erase(color);
+    }
+
+    /// Draws `path` using clip, Matrix, and `paint`.
+    ///
+    /// `path` contains an array of path contour, each of which may be open or closed.
+    ///
+    /// In `paint`: `PaintStyle` determines if path is stroked or filled:
+    /// if filled, `PathFillType` determines whether path contour describes inside or
+    /// outside of fill; if stroked, Paint stroke width describes the line thickness,
+    /// `StrokeCap` describes line ends, and `StrokeJoin` describes how corners are drawn.
+    ///
+    /// # Parameters
+    /// - `path` - Path to draw
+    /// - `paint` - stroke, blend, color, and so on, used to draw
+    pub fn draw_path(&mut self, path: &Path, paint: &Paint) {
+        if self.matrix.is_identity() {
+            self.device.draw_path(path, paint);
+            return;
+        }
+        if paint.get_style() != PaintStyle::Fill && paint.get_stroke_width() > 0.0 {
+            // Stroke in local coordinates, so that stroke width is transformed too.
+            // Hairlines are drawn one pixel wide in device coordinates instead.
+            let Some(outline) = PathStroker::from_paint(paint).stroke(path) else {
+                return;
+            };
+            let mut fill_paint = paint.clone();
+            fill_paint.set_style(PaintStyle::Fill);
+            self.device
+                .draw_path(&outline.transform(&self.matrix), &fill_paint);
+        } else {
+            self.device.draw_path(&path.transform(&self.matrix), paint);
+        }
+    }
+
+    /// Draws Rect `rect` using clip, Matrix, and Paint `paint`.
+    ///
+    /// In `paint`: `PaintStyle` determines if rectangle is stroked or filled;
+    /// if stroked, Paint stroke width describes the line thickness, and
+    /// `StrokeJoin` draws the corners rounded or square.
+    pub fn draw_rect(&mut self, rect: &Rect, paint: &Paint) {
+        if let Some(path) = PathBuilder::from_rect(rect) {
+            self.draw_path(&path, paint);
+        }
+    }
+
+    /// Draws oval `oval` using clip, Matrix, and Paint.
+    ///
+    /// In `paint`: `PaintStyle` determines if oval is stroked or filled;
+    /// if stroked, Paint stroke width describes the line thickness.
+    pub fn draw_oval(&mut self, oval: &Rect, paint: &Paint) {
+        if let Some(path) = PathBuilder::from_oval(oval) {
+            self.draw_path(&path, paint);
+        }
+    }
+
+    /// Draws `RRect` `rrect` using clip, Matrix, and Paint `paint`.
+    ///
+    /// In `paint`: `PaintStyle` determines if `rrect` is stroked or filled;
+    /// if stroked, Paint stroke width describes the line thickness.
+    ///
+    /// `rrect` may represent a rectangle, circle, oval, uniformly rounded rectangle,
+    /// or may have any combination of positive non-square radii for the four corners.
+    pub fn draw_rrect(&mut self, rrect: &RRect, paint: &Paint) {
+        let mut builder = PathBuilder::new();
+        builder.add_rrect(rrect);
+        if let Some(path) = builder.finish() {
+            self.draw_path(&path, paint);
+        }
+    }
+
+    /// Draws circle at (`cx`, `cy`) with radius using clip, Matrix, and Paint `paint`.
+    ///
+    /// If radius is zero or less, nothing is drawn.
+    /// In `paint`: `PaintStyle` determines if circle is stroked or filled;
+    /// if stroked, Paint stroke width describes the line thickness.
+    pub fn draw_circle(&mut self, cx: f32, cy: f32, radius: f32, paint: &Paint) {
+        if radius <= 0.0 {
+            return;
+        }
+        if let Some(path) = PathBuilder::from_circle(cx, cy, radius) {
+            self.draw_path(&path, paint);
+        }
     }
 }
 
@@ -303,7 +733,7 @@ impl Drop for Canvas {
     ///
     /// example: Canvas_destructor
     fn drop(&mut self) {
-        todo!()
+        self.restore_to_count(1);
     }
 }
 
@@ -333,3 +763,125 @@ pub trait CanvasTrait {
     fn recorder(&self) -> &grphite::Recorder;
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::core::blur_types::BlurStyle;
+    use crate::core::mask_filter::MaskFilter;
+    use crate::core::point::Point;
+    use crate::core::tile_mode::TileMode;
+    use crate::effects::offset_image_filter::OffsetImageFilter;
+    use crate::shaders::gradients::gradient::{Gradient, Interpolation};
+    use crate::shaders::gradients::linear_gradient::LinearGradient;
+
+    const SIZE: i32 = 40;
+
+    /// Draws with `draw` into a new surface, and returns colors of its pixels.
+    fn render(draw: impl FnOnce(&mut Canvas)) -> Vec<Color> {
+        let mut surface = Surface::new_raster_n32_premul(SIZE, SIZE).unwrap();
+        draw(surface.canvas());
+        let pixmap = surface.peek_pixels().unwrap();
+        (0..SIZE)
+            .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+            .map(|(x, y)| pixmap.get_color(x, y).unwrap())
+            .collect()
+    }
+
+    fn color_at(pixels: &[Color], x: i32, y: i32) -> Color {
+        pixels[usize::try_from(y * SIZE + x).unwrap()]
+    }
+
+    fn gradient_paint() -> Paint {
+        let colors = [
+            Color4f::from_rgba(0.0, 0.0, 0.0, 1.0),
+            Color4f::from_rgba(1.0, 1.0, 1.0, 1.0),
+        ];
+        let gradient =
+            Gradient::new(&colors, None, TileMode::Clamp, Interpolation::default()).unwrap();
+        let shader = LinearGradient::new(
+            Point::from_xy(0.0, 0.0),
+            Point::from_xy(20.0, 0.0),
+            gradient,
+        );
+        let mut paint = Paint::new();
+        paint.set_shader(Some(Rc::new(shader.unwrap())));
+        paint
+    }
+
+    #[test]
+    fn test_shader_follows_translate() {
+        let rect = Rect::from_wh(20.0, 20.0);
+        let plain = render(|canvas| canvas.draw_rect(&rect, &gradient_paint()));
+        let moved = render(|canvas| {
+            canvas.translate(10.0, 5.0);
+            canvas.draw_rect(&rect, &gradient_paint());
+        });
+        for y in 0..20 {
+            for x in 0..20 {
+                assert_eq!(color_at(&moved, x + 10, y + 5), color_at(&plain, x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_shader_follows_scale() {
+        let plain =
+            render(|canvas| canvas.draw_rect(&Rect::from_wh(20.0, 20.0), &gradient_paint()));
+        let scaled = render(|canvas| {
+            canvas.scale(2.0, 2.0);
+            canvas.draw_rect(&Rect::from_wh(10.0, 10.0), &gradient_paint());
+        });
+        // Gradient spans 40 pixels after scale, so pixel 2x matches pixel x unscaled.
+        for x in 0..10 {
+            let expected = color_at(&plain, x, 0);
+            let actual = color_at(&scaled, x * 2, 0);
+            assert!(
+                actual.red().abs_diff(expected.red()) <= 8,
+                "{x}: {actual:?} {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_blur_sigma_follows_scale() {
+        let mut paint = Paint::new();
+        paint.set_color(Color::from_argb(255, 0, 0, 255));
+        let mut unscaled_paint = paint.clone();
+        paint.set_mask_filter(Some(Rc::new(
+            MaskFilter::blur(BlurStyle::Normal, 1.0).unwrap(),
+        )));
+        unscaled_paint.set_mask_filter(Some(Rc::new(
+            MaskFilter::blur(BlurStyle::Normal, 4.0).unwrap(),
+        )));
+
+        let scaled = render(|canvas| {
+            canvas.scale(4.0, 4.0);
+            canvas.draw_rect(&Rect::from_ltrb(3.0, 3.0, 7.0, 7.0), &paint);
+        });
+        let expected = render(|canvas| {
+            canvas.draw_rect(&Rect::from_ltrb(12.0, 12.0, 28.0, 28.0), &unscaled_paint);
+        });
+        assert_eq!(scaled, expected);
+        // Blur spreads beyond the rect, by about three sigma in device space.
+        assert!(color_at(&scaled, 5, 20).alpha() > 0);
+    }
+
+    #[test]
+    fn test_image_filter_offset_follows_scale() {
+        let mut paint = Paint::new();
+        paint.set_color(Color::from_argb(255, 255, 0, 0));
+        let filter = OffsetImageFilter::new(3.0, 0.0, None, None).unwrap();
+        paint.set_image_filter(Some(Rc::new(filter)));
+        let pixels = render(|canvas| {
+            canvas.scale(2.0, 2.0);
+            canvas.draw_rect(&Rect::from_wh(2.0, 2.0), &paint);
+        });
+        // Rect covers 4 pixels in device space, and is moved by 6 pixels.
+        assert_eq!(color_at(&pixels, 1, 1).alpha(), 0);
+        assert_eq!(color_at(&pixels, 7, 1).alpha(), 255);
+        assert_eq!(color_at(&pixels, 10, 1).alpha(), 0);
+    }
+}
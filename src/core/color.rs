@@ -648,20 +648,34 @@ impl<T> Rgba4f<T> {
 pub type Color4f = Rgba4f<alpha_type_mod::Unpremul>;
 
 impl From<Color> for Color4f {
-    fn from(_color: Color) -> Self {
-        unimplemented!()
+    fn from(color: Color) -> Self {
+        const SCALE: f32 = 1.0 / 255.0;
+        Self::from_rgba(
+            f32::from(color.red()) * SCALE,
+            f32::from(color.green()) * SCALE,
+            f32::from(color.blue()) * SCALE,
+            f32::from(color.alpha()) * SCALE,
+        )
     }
 }
 
 impl From<&Color4f> for Color {
-    fn from(_color: &Color4f) -> Self {
-        unimplemented!()
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    fn from(color: &Color4f) -> Self {
+        let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Self::from_argb(
+            to_byte(color.alpha()),
+            to_byte(color.red()),
+            to_byte(color.green()),
+            to_byte(color.blue()),
+        )
     }
 }
 
 impl From<Color4f> for Color {
-    fn from(_color: Color4f) -> Self {
-        unimplemented!()
+    fn from(color: Color4f) -> Self {
+        Self::from(&color)
     }
 }
 
@@ -672,6 +686,28 @@ impl Color4f {
         self.alpha = alpha;
     }
 
+    /// Returns color interpolated from self to `other` by `t`, in premultiplied space.
+    ///
+    /// `t` is clamped to [0, 1].
+    #[must_use]
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let alpha = (other.alpha - self.alpha).mul_add(t, self.alpha);
+        if alpha <= 0.0 {
+            return colors::TRANSPARENT;
+        }
+        let channel = |a: f32, b: f32| {
+            let from = a * self.alpha;
+            b.mul_add(other.alpha, -from).mul_add(t, from) / alpha
+        };
+        Self::from_rgba(
+            channel(self.red, other.red),
+            channel(self.green, other.green),
+            channel(self.blue, other.blue),
+            alpha,
+        )
+    }
+
     #[must_use]
     pub const fn to_bytes_rgba(&self) -> u32 {
         unimplemented!()
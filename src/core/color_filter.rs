@@ -7,6 +7,7 @@ use std::rc::Rc;
 use crate::core::blend_mode::BlendMode;
 use crate::core::color::Color;
 use crate::core::color::Color4f;
+use crate::core::color_priv::{linear_to_srgb, srgb_to_linear, unit_scalar_clamp_to_byte};
 use crate::core::color_space::ColorSpace;
 use crate::core::color_table::ColorTable;
 use crate::effects::color_matrix::ColorMatrix;
@@ -18,16 +19,67 @@ use crate::effects::color_matrix::ColorMatrix;
 ///
 /// All subclasses are required to be reentrant-safe : it must be legal to share
 /// the same instance between several threads.
-pub struct ColorFilter {}
+#[derive(Debug, Clone)]
+pub struct ColorFilter {
+    kind: ColorFilterKind,
+}
+
+#[derive(Debug, Clone)]
+enum ColorFilterKind {
+    /// Row major 4x5 matrix applied to unpremultiplied RGBA, or HSLA if `hsla` is true.
+    Matrix {
+        matrix: [f32; 20],
+        hsla: bool,
+    },
+
+    /// Blends the constant color as src with input color as dst.
+    Blend {
+        color: Color4f,
+        mode: BlendMode,
+    },
+
+    /// Lookup tables of unpremultiplied channels.
+    Table(ColorTable),
+
+    /// Applies `inner` then `outer`.
+    Compose {
+        outer: Rc<ColorFilter>,
+        inner: Rc<ColorFilter>,
+    },
+
+    /// Interpolates between results of `dst` and `src` filters by `t`.
+    Lerp {
+        t: f32,
+        dst: Rc<ColorFilter>,
+        src: Rc<ColorFilter>,
+    },
+
+    LinearToSrgbGamma,
+    SrgbToLinearGamma,
+}
 
 impl ColorFilter {
+    const fn from_kind(kind: ColorFilterKind) -> Self {
+        Self { kind }
+    }
+
     /// If the filter can be represented by a source color plus Mode, this
     /// returns true, and sets (if not NULL) the color and mode appropriately.
     ///
     /// If not, this returns false and ignores the parameters.
     #[must_use]
-    pub fn as_a_color_mode(&self, _color: &mut Color, _mode: &mut BlendMode) -> bool {
-        unimplemented!()
+    pub fn as_a_color_mode(&self, color: &mut Color, mode: &mut BlendMode) -> bool {
+        if let ColorFilterKind::Blend {
+            color: blend_color,
+            mode: blend_mode,
+        } = &self.kind
+        {
+            *color = Color::from(blend_color);
+            *mode = *blend_mode;
+            true
+        } else {
+            false
+        }
     }
 
     /// If the filter can be represented by a 5x4 matrix, this returns true,
@@ -35,96 +87,212 @@ impl ColorFilter {
     ///
     /// If not, this returns false and ignores the parameter.
     #[must_use]
-    pub fn as_a_color_matrix(&self, _matrix: &mut [f32; 20]) -> bool {
-        unimplemented!()
+    pub fn as_a_color_matrix(&self, matrix: &mut [f32; 20]) -> bool {
+        if let ColorFilterKind::Matrix {
+            matrix: row_major,
+            hsla: false,
+        } = &self.kind
+        {
+            *matrix = *row_major;
+            true
+        } else {
+            false
+        }
     }
 
     /// Returns true if the filter is guaranteed to never change the alpha of a color it filters.
     #[must_use]
     pub fn is_alpha_unchanged(&self) -> bool {
-        unimplemented!()
+        match &self.kind {
+            ColorFilterKind::Matrix { matrix, .. } => matrix[15..] == [0.0, 0.0, 0.0, 1.0, 0.0],
+            ColorFilterKind::Blend { mode, .. } => *mode == BlendMode::Dst,
+            ColorFilterKind::Table(table) => table
+                .alpha_table()
+                .is_some_and(|table| table.iter().enumerate().all(|(i, &a)| usize::from(a) == i)),
+            ColorFilterKind::Compose { outer, inner } => {
+                outer.is_alpha_unchanged() && inner.is_alpha_unchanged()
+            }
+            ColorFilterKind::Lerp { dst, src, .. } => {
+                dst.is_alpha_unchanged() && src.is_alpha_unchanged()
+            }
+            ColorFilterKind::LinearToSrgbGamma | ColorFilterKind::SrgbToLinearGamma => true,
+        }
     }
 
     #[must_use]
-    pub fn filter_color(&self, _color: Color) -> Color {
-        unimplemented!()
+    pub fn filter_color(&self, color: Color) -> Color {
+        Color::from(self.filter_unpremul(&Color4f::from(color)))
     }
 
     /// Converts the src color (in src colorspace), into the dst colorspace,
     /// then applies this filter to it, returning the filtered color in the dst colorspace.
+    // TODO(Shaohua): Convert between color spaces.
     #[must_use]
     pub fn filter_color4f(
         &self,
-        _src_color: &Color4f,
+        src_color: &Color4f,
         _src_cs: &ColorSpace,
         _dst_cs: &mut ColorSpace,
     ) -> Color4f {
-        unimplemented!()
+        self.filter_unpremul(src_color)
     }
 
     /// Construct a colorfilter whose effect is to first apply the inner filter and
     /// then apply this filter, applied to the output of the inner filter.
     /// `result = this(inner(...))`
     #[must_use]
-    pub fn from_composed(&self, _inner: &Rc<Self>) -> Rc<Self> {
-        unimplemented!()
+    pub fn from_composed(&self, inner: &Rc<Self>) -> Rc<Self> {
+        Self::compose(&Rc::new(self.clone()), inner)
+    }
+
+    /// Applies filter to unpremultiplied `color`, and returns unpremultiplied color
+    /// with components in [0, 1].
+    fn filter_unpremul(&self, color: &Color4f) -> Color4f {
+        let rgba = color.to_vec().map(|value| value.clamp(0.0, 1.0));
+        let [red, green, blue, alpha] = match &self.kind {
+            ColorFilterKind::Matrix { matrix, hsla } => {
+                if *hsla {
+                    let [hue, saturation, lightness] = rgb_to_hsl(rgba[0], rgba[1], rgba[2]);
+                    let [hue, saturation, lightness, alpha] =
+                        apply_matrix(matrix, [hue, saturation, lightness, rgba[3]]);
+                    let [red, green, blue] = hsl_to_rgb(hue, saturation, lightness);
+                    [red, green, blue, alpha]
+                } else {
+                    apply_matrix(matrix, rgba)
+                }
+            }
+            ColorFilterKind::Blend { color, mode } => {
+                let src = premultiply(color.to_vec());
+                let dst = premultiply(rgba);
+                unpremultiply(mode.blend(&src, &dst))
+            }
+            ColorFilterKind::Table(table) => {
+                let lookup = |table: Option<&[u8]>, value: f32| {
+                    table.map_or(value, |table| {
+                        f32::from(table[usize::from(unit_scalar_clamp_to_byte(value))]) / 255.0
+                    })
+                };
+                [
+                    lookup(table.red_table(), rgba[0]),
+                    lookup(table.green_table(), rgba[1]),
+                    lookup(table.blue_table(), rgba[2]),
+                    lookup(table.alpha_table(), rgba[3]),
+                ]
+            }
+            ColorFilterKind::Compose { outer, inner } => outer
+                .filter_unpremul(&inner.filter_unpremul(color))
+                .to_vec(),
+            ColorFilterKind::Lerp { t, dst, src } => {
+                let dst = premultiply(dst.filter_unpremul(color).to_vec());
+                let src = premultiply(src.filter_unpremul(color).to_vec());
+                let mut mixed = [0.0; 4];
+                for (index, value) in mixed.iter_mut().enumerate() {
+                    *value = (src[index] - dst[index]).mul_add(*t, dst[index]);
+                }
+                unpremultiply(mixed)
+            }
+            ColorFilterKind::LinearToSrgbGamma => map_rgb(rgba, linear_to_srgb),
+            ColorFilterKind::SrgbToLinearGamma => map_rgb(rgba, srgb_to_linear),
+        };
+        Color4f::from_rgba(
+            red.clamp(0.0, 1.0),
+            green.clamp(0.0, 1.0),
+            blue.clamp(0.0, 1.0),
+            alpha.clamp(0.0, 1.0),
+        )
     }
 }
 
 impl ColorFilter {
     #[must_use]
     pub fn compose(outer: &Rc<Self>, inner: &Rc<Self>) -> Rc<Self> {
-        outer.from_composed(inner)
+        Rc::new(Self::from_kind(ColorFilterKind::Compose {
+            outer: outer.clone(),
+            inner: inner.clone(),
+        }))
     }
 
     /// Blends between the constant color (src) and input color (dst) based on the `BlendMode`.
     ///
     /// If the color space is null, the constant color is assumed to be defined in `sRGB`.
+    // TODO(Shaohua): Convert color from color space.
     #[must_use]
-    pub fn blend_with_cs(_color: &Color4f, _cs: &ColorSpace, _mode: BlendMode) -> Rc<Self> {
-        unimplemented!()
+    pub fn blend_with_cs(color: &Color4f, _cs: &ColorSpace, mode: BlendMode) -> Rc<Self> {
+        Rc::new(Self::from_kind(ColorFilterKind::Blend {
+            color: color.clone(),
+            mode,
+        }))
     }
 
     #[must_use]
-    pub fn blend(_color: Color, _mode: BlendMode) -> Rc<Self> {
-        unimplemented!()
+    pub fn blend(color: Color, mode: BlendMode) -> Rc<Self> {
+        Self::blend_with_cs(&Color4f::from(color), &ColorSpace::default(), mode)
     }
 
     #[must_use]
-    pub fn matrix(_matrix: &ColorMatrix) -> Rc<Self> {
-        unimplemented!()
+    pub fn matrix(matrix: &ColorMatrix) -> Option<Rc<Self>> {
+        Self::matrix_row_major(matrix.get_row_major())
     }
 
+    /// Creates a filter which multiplies unpremultiplied [R, G, B, A, 1] by the 4x5
+    /// matrix in row major order.
+    ///
+    /// Translation in the fifth column is in [0, 1] units.
+    ///
+    /// Returns None if any value in matrix is not finite.
     #[must_use]
-    pub fn matrix_row_major(_row_major: &[f32; 20]) -> Rc<Self> {
-        unimplemented!()
+    pub fn matrix_row_major(row_major: &[f32; 20]) -> Option<Rc<Self>> {
+        Self::new_matrix(row_major, false)
     }
 
     /// A version of Matrix which operates in HSLA space instead of RGBA.
     /// I.e. HSLA-to-RGBA(Matrix(RGBA-to-HSLA(input))).
     #[must_use]
-    pub fn hsla_matrix(_matrix: &ColorMatrix) -> Rc<Self> {
-        unimplemented!()
+    pub fn hsla_matrix(matrix: &ColorMatrix) -> Option<Rc<Self>> {
+        Self::hsla_matrix_row_major(matrix.get_row_major())
     }
 
     #[must_use]
-    pub fn hsla_matrix_row_major(_row_major: &[f32; 20]) -> Rc<Self> {
-        unimplemented!()
+    pub fn hsla_matrix_row_major(row_major: &[f32; 20]) -> Option<Rc<Self>> {
+        Self::new_matrix(row_major, true)
+    }
+
+    fn new_matrix(row_major: &[f32; 20], hsla: bool) -> Option<Rc<Self>> {
+        if !row_major.iter().all(|value| value.is_finite()) {
+            return None;
+        }
+        Some(Rc::new(Self::from_kind(ColorFilterKind::Matrix {
+            matrix: *row_major,
+            hsla,
+        })))
     }
 
     #[must_use]
     pub fn linear_to_srgb_gamma() -> Rc<Self> {
-        unimplemented!()
+        Rc::new(Self::from_kind(ColorFilterKind::LinearToSrgbGamma))
     }
 
     #[must_use]
     pub fn srgb_to_linear_gamma() -> Rc<Self> {
-        unimplemented!()
+        Rc::new(Self::from_kind(ColorFilterKind::SrgbToLinearGamma))
     }
 
+    /// Creates a filter which interpolates between results of `dst` and `src` by `t`.
+    ///
+    /// Returns `dst` if `t` is not larger than 0, and `src` if `t` is not less than 1.
     #[must_use]
-    pub fn lerp(_t: f32, _dst: &Rc<Self>, _src: &Rc<Self>) -> Rc<Self> {
-        unimplemented!()
+    pub fn lerp(t: f32, dst: &Rc<Self>, src: &Rc<Self>) -> Rc<Self> {
+        if t.is_nan() || t <= 0.0 {
+            return dst.clone();
+        }
+        if t >= 1.0 {
+            return src.clone();
+        }
+        Rc::new(Self::from_kind(ColorFilterKind::Lerp {
+            t,
+            dst: dst.clone(),
+            src: src.clone(),
+        }))
     }
 
     /// Create a table colorfilter, copying the table into the filter, and
@@ -137,29 +305,31 @@ impl ColorFilter {
     /// If the incomming colors are premultiplied, they are temporarily unpremultiplied,
     /// then the table is applied, and then the result is remultiplied.
     #[must_use]
-    pub fn table_slice(_table: &[u8; 256]) -> Rc<Self> {
-        unimplemented!()
+    pub fn table_slice(table: &[u8; 256]) -> Rc<Self> {
+        Self::table(&ColorTable::from_table(table))
     }
 
     /// Create a table colorfilter, with a different table for each
     /// component [A, R, G, B].
-    ///
-    /// If a given table is NULL, then it is treated as identity, with the component left unchanged.
-    /// If a table is not null, then its contents are copied into the filter.
     #[must_use]
     pub fn table_argb(
-        _table_alpha: &[u8; 256],
-        _table_red: &[u8; 256],
-        _table_green: &[u8; 256],
-        _table_blue: &[u8; 256],
+        table_alpha: &[u8; 256],
+        table_red: &[u8; 256],
+        table_green: &[u8; 256],
+        table_blue: &[u8; 256],
     ) -> Rc<Self> {
-        unimplemented!()
+        Self::table(&ColorTable::from_tables(
+            table_alpha,
+            table_red,
+            table_green,
+            table_blue,
+        ))
     }
 
     /// Create a table colorfilter that holds a ref to the shared color table.
     #[must_use]
-    pub fn table(_table: &ColorTable) -> Rc<Self> {
-        unimplemented!()
+    pub fn table(table: &ColorTable) -> Rc<Self> {
+        Rc::new(Self::from_kind(ColorFilterKind::Table(table.clone())))
     }
 
     /// Create a colorfilter that multiplies the RGB channels by one color, and
@@ -168,7 +338,309 @@ impl ColorFilter {
     ///
     /// The alpha components of the mul and add arguments are ignored.
     #[must_use]
-    pub fn lighting(_mul: Color, _add: Color) -> Rc<Self> {
-        unimplemented!()
+    #[rustfmt::skip]
+    pub fn lighting(mul: Color, add: Color) -> Rc<Self> {
+        let unit = |value: u8| f32::from(value) / 255.0;
+        let matrix = [
+            unit(mul.red()), 0.0, 0.0, 0.0, unit(add.red()),
+            0.0, unit(mul.green()), 0.0, 0.0, unit(add.green()),
+            0.0, 0.0, unit(mul.blue()), 0.0, unit(add.blue()),
+            0.0, 0.0, 0.0, 1.0, 0.0,
+        ];
+        Rc::new(Self::from_kind(ColorFilterKind::Matrix {
+            matrix,
+            hsla: false,
+        }))
+    }
+}
+
+/// Multiplies [`values`, 1] by row major 4x5 `matrix`.
+fn apply_matrix(matrix: &[f32; 20], values: [f32; 4]) -> [f32; 4] {
+    let mut result = [0.0; 4];
+    for (row, value) in result.iter_mut().enumerate() {
+        let m = &matrix[row * 5..row * 5 + 5];
+        *value = m[0].mul_add(
+            values[0],
+            m[1].mul_add(
+                values[1],
+                m[2].mul_add(values[2], m[3].mul_add(values[3], m[4])),
+            ),
+        );
+    }
+    result
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn map_rgb(rgba: [f32; 4], transfer: fn(f64) -> f64) -> [f32; 4] {
+    [
+        transfer(f64::from(rgba[0])) as f32,
+        transfer(f64::from(rgba[1])) as f32,
+        transfer(f64::from(rgba[2])) as f32,
+        rgba[3],
+    ]
+}
+
+fn premultiply(rgba: [f32; 4]) -> [f32; 4] {
+    let alpha = rgba[3].clamp(0.0, 1.0);
+    [rgba[0] * alpha, rgba[1] * alpha, rgba[2] * alpha, alpha]
+}
+
+fn unpremultiply(premul: [f32; 4]) -> [f32; 4] {
+    let alpha = premul[3];
+    if alpha <= 0.0 {
+        return [0.0; 4];
+    }
+    [
+        premul[0] / alpha,
+        premul[1] / alpha,
+        premul[2] / alpha,
+        alpha,
+    ]
+}
+
+/// Converts RGB to hue, saturation and lightness, all in [0, 1].
+#[allow(clippy::float_cmp)]
+fn rgb_to_hsl(red: f32, green: f32, blue: f32) -> [f32; 3] {
+    let max = red.max(green).max(blue);
+    let min = red.min(green).min(blue);
+    let lightness = (max + min) * 0.5;
+    let delta = max - min;
+    if delta <= 0.0 {
+        return [0.0, 0.0, lightness];
+    }
+    let saturation = if lightness > 0.5 {
+        delta / (2.0 - max - min)
+    } else {
+        delta / (max + min)
+    };
+    let hue = if max == red {
+        (green - blue) / delta + if green < blue { 6.0 } else { 0.0 }
+    } else if max == green {
+        (blue - red) / delta + 2.0
+    } else {
+        (red - green) / delta + 4.0
+    };
+    [hue / 6.0, saturation, lightness]
+}
+
+/// Converts hue, saturation and lightness in [0, 1] to RGB.
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+    let saturation = saturation.clamp(0.0, 1.0);
+    let lightness = lightness.clamp(0.0, 1.0);
+    if saturation <= 0.0 {
+        return [lightness; 3];
+    }
+    let q = if lightness < 0.5 {
+        lightness * (1.0 + saturation)
+    } else {
+        lightness.mul_add(-saturation, lightness + saturation)
+    };
+    let p = 2.0f32.mul_add(lightness, -q);
+    let channel = |t: f32| {
+        let t = t.rem_euclid(1.0);
+        if t < 1.0 / 6.0 {
+            ((q - p) * 6.0).mul_add(t, p)
+        } else if t < 0.5 {
+            q
+        } else if t < 2.0 / 3.0 {
+            ((q - p) * 6.0).mul_add(2.0 / 3.0 - t, p)
+        } else {
+            p
+        }
+    };
+    [
+        channel(hue + 1.0 / 3.0),
+        channel(hue),
+        channel(hue - 1.0 / 3.0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::color::colors;
+
+    fn is_near(color: &Color4f, expected: [f32; 4]) -> bool {
+        color
+            .to_vec()
+            .iter()
+            .zip(expected)
+            .all(|(value, expected)| (value - expected).abs() < 1e-3)
+    }
+
+    fn filter(filter: &ColorFilter, color: [f32; 4]) -> Color4f {
+        let color = Color4f::from_rgba(color[0], color[1], color[2], color[3]);
+        filter.filter_color4f(&color, &ColorSpace::default(), &mut ColorSpace::default())
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_matrix() {
+        // Swaps red and blue, and adds 0.25 to green.
+        let row_major = [
+            0.0, 0.0, 1.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0, 0.25,
+            1.0, 0.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 1.0, 0.0,
+        ];
+        let swap = ColorFilter::matrix_row_major(&row_major).unwrap();
+        assert!(is_near(&filter(&swap, [0.2, 0.9, 0.6, 0.5]), [0.6, 1.0, 0.2, 0.5]));
+        assert!(swap.is_alpha_unchanged());
+        let mut matrix = [0.0; 20];
+        assert!(swap.as_a_color_matrix(&mut matrix));
+        assert!(matrix.iter().zip(row_major).all(|(value, expected)| (value - expected).abs() < 1e-6));
+
+        let mut invalid = row_major;
+        invalid[3] = f32::NAN;
+        assert!(ColorFilter::matrix_row_major(&invalid).is_none());
+    }
+
+    #[test]
+    fn test_color_matrix() {
+        let mut matrix = ColorMatrix::new();
+        matrix.set_saturation(0.0);
+        let gray = ColorFilter::matrix(&matrix).unwrap();
+        let color = filter(&gray, [1.0, 0.0, 0.0, 1.0]);
+        assert!(is_near(&color, [0.213, 0.213, 0.213, 1.0]));
+
+        matrix.set_scale_with_alpha(1.0, 1.0, 1.0, 0.5);
+        let half = ColorFilter::matrix(&matrix).unwrap();
+        assert!(!half.is_alpha_unchanged());
+        assert!(is_near(
+            &filter(&half, [1.0, 0.0, 0.0, 1.0]),
+            [1.0, 0.0, 0.0, 0.5]
+        ));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_hsla_matrix() {
+        // Rotates hue by a third, red becomes green.
+        let row_major = [
+            1.0, 0.0, 0.0, 0.0, 1.0 / 3.0,
+            0.0, 1.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 0.0, 1.0, 0.0,
+        ];
+        let rotate = ColorFilter::hsla_matrix_row_major(&row_major).unwrap();
+        assert!(is_near(&filter(&rotate, [1.0, 0.0, 0.0, 1.0]), [0.0, 1.0, 0.0, 1.0]));
+        let mut matrix = [0.0; 20];
+        assert!(!rotate.as_a_color_matrix(&mut matrix));
+
+        let identity = ColorFilter::hsla_matrix(&ColorMatrix::new()).unwrap();
+        let color = [0.3, 0.6, 0.9, 0.8];
+        assert!(is_near(&filter(&identity, color), color));
+    }
+
+    #[test]
+    fn test_blend() {
+        let blend = ColorFilter::blend(Color::from_argb(255, 255, 0, 0), BlendMode::SrcIn);
+        let mut color = Color::default();
+        let mut mode = BlendMode::default();
+        assert!(blend.as_a_color_mode(&mut color, &mut mode));
+        assert_eq!(mode, BlendMode::SrcIn);
+        assert!(!blend.is_alpha_unchanged());
+        // Keeps alpha of input color.
+        assert!(is_near(
+            &filter(&blend, [0.0, 0.0, 1.0, 0.5]),
+            [1.0, 0.0, 0.0, 0.5]
+        ));
+
+        let dst = ColorFilter::blend(Color::from_argb(255, 255, 0, 0), BlendMode::Dst);
+        assert!(dst.is_alpha_unchanged());
+        assert!(is_near(
+            &filter(&dst, [0.0, 0.0, 1.0, 0.5]),
+            [0.0, 0.0, 1.0, 0.5]
+        ));
+    }
+
+    #[test]
+    fn test_table() {
+        let mut invert = [0; 256];
+        for (index, value) in invert.iter_mut().enumerate() {
+            *value = 255 - u8::try_from(index).unwrap();
+        }
+        let table = ColorFilter::table_slice(&invert);
+        assert!(!table.is_alpha_unchanged());
+        assert!(is_near(
+            &filter(&table, [1.0, 0.0, 0.2, 1.0]),
+            [0.0, 1.0, 0.8, 0.0]
+        ));
+
+        let mut identity = [0; 256];
+        for (index, value) in identity.iter_mut().enumerate() {
+            *value = u8::try_from(index).unwrap();
+        }
+        let table = ColorFilter::table_argb(&identity, &invert, &identity, &identity);
+        assert!(table.is_alpha_unchanged());
+        assert!(is_near(
+            &filter(&table, [1.0, 0.0, 0.2, 0.6]),
+            [0.0, 0.0, 0.2, 0.6]
+        ));
+    }
+
+    #[test]
+    fn test_compose() {
+        let red = ColorFilter::blend(Color::from_argb(255, 255, 0, 0), BlendMode::Src);
+        let mut matrix = ColorMatrix::new();
+        matrix.set_scale(0.5, 1.0, 1.0);
+        let half_red = ColorFilter::matrix(&matrix).unwrap();
+        let composed = ColorFilter::compose(&half_red, &red);
+        assert!(is_near(
+            &filter(&composed, [0.0, 1.0, 0.0, 1.0]),
+            [0.5, 0.0, 0.0, 1.0]
+        ));
+        let composed = half_red.from_composed(&red);
+        assert!(is_near(
+            &filter(&composed, [0.0, 1.0, 0.0, 1.0]),
+            [0.5, 0.0, 0.0, 1.0]
+        ));
+        let composed = ColorFilter::compose(&red, &half_red);
+        assert!(is_near(
+            &filter(&composed, [0.0, 1.0, 0.0, 1.0]),
+            [1.0, 0.0, 0.0, 1.0]
+        ));
+    }
+
+    #[test]
+    fn test_lerp() {
+        let red = ColorFilter::blend(Color::from_argb(255, 255, 0, 0), BlendMode::Src);
+        let blue = ColorFilter::blend(Color::from_argb(255, 0, 0, 255), BlendMode::Src);
+        assert!(Rc::ptr_eq(&ColorFilter::lerp(0.0, &red, &blue), &red));
+        assert!(Rc::ptr_eq(&ColorFilter::lerp(1.5, &red, &blue), &blue));
+        assert!(Rc::ptr_eq(&ColorFilter::lerp(f32::NAN, &red, &blue), &red));
+        let mixed = ColorFilter::lerp(0.25, &red, &blue);
+        assert!(is_near(
+            &filter(&mixed, [0.0, 1.0, 0.0, 1.0]),
+            [0.75, 0.0, 0.25, 1.0]
+        ));
+    }
+
+    #[test]
+    fn test_gamma() {
+        let to_srgb = ColorFilter::linear_to_srgb_gamma();
+        let to_linear = ColorFilter::srgb_to_linear_gamma();
+        let color = filter(&to_srgb, [0.2, 0.0, 1.0, 0.5]);
+        assert!(color.red() > 0.45 && color.red() < 0.5);
+        let round_trip = ColorFilter::compose(&to_linear, &to_srgb);
+        assert!(is_near(
+            &filter(&round_trip, [0.2, 0.0, 1.0, 0.5]),
+            [0.2, 0.0, 1.0, 0.5]
+        ));
+    }
+
+    #[test]
+    fn test_lighting() {
+        let lighting = ColorFilter::lighting(
+            Color::from_argb(0, 255, 128, 0),
+            Color::from_argb(0, 0, 0, 51),
+        );
+        let color = filter(&lighting, [0.5, 1.0, 1.0, 0.5]);
+        assert!(is_near(&color, [0.5, 0.502, 0.2, 0.5]));
+        assert_eq!(Color::from(color).alpha(), 128);
+        assert!(is_near(
+            &filter(&lighting, colors::WHITE.to_vec()),
+            [1.0, 0.502, 0.2, 1.0]
+        ));
     }
 }
@@ -122,3 +122,25 @@ pub const fn alpha_mul_q(c: u32, scale: u32) -> u32 {
     let ag: u32 = ((c >> 8) & mask) * scale;
     (rb & mask) | (ag & !mask)
 }
+
+/// Converts encoded sRGB `value` to linear, with the sRGB transfer function.
+#[must_use]
+pub fn srgb_to_linear(value: f64) -> f64 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts linear `value` to encoded sRGB, the reverse of `srgb_to_linear()`.
+#[must_use]
+pub fn linear_to_srgb(value: f64) -> f64 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055f64.mul_add(value.powf(1.0 / 2.4), -0.055)
+    }
+}
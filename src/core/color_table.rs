@@ -2,16 +2,18 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
-use crate::core::bitmap::Bitmap;
+use crate::core::image_info::ImageInfo;
+use crate::core::pixmap::Pixmap;
 
 /// `ColorTable` holds the lookup tables for each channel (ARGB) used to define the filter behavior
 /// of `ColorFilters::Table`, and provides a way to share the table data between client code and
 /// the returned `ColorFilter`.
 ///
 /// Once created, a `ColorTable` is immutable.
+#[derive(Debug, Clone)]
 pub struct ColorTable {
     /// A 256x4 A8 image
-    table: Bitmap,
+    table: Pixmap,
 }
 
 impl ColorTable {
@@ -25,47 +27,53 @@ impl ColorTable {
 
     /// Creates a new `ColorTable` with the per-channel lookup tables.
     ///
-    /// Each non-null table is copied into the `ColorTable`.
-    /// Null parameters are interpreted as the identity table.
+    /// Each table is copied into the `ColorTable`.
     #[must_use]
     pub fn from_tables(
-        _table_a: &[u8; 256],
-        _table_r: &[u8; 256],
-        _table_g: &[u8; 256],
-        _table_b: &[u8; 256],
+        table_a: &[u8; 256],
+        table_r: &[u8; 256],
+        table_g: &[u8; 256],
+        table_b: &[u8; 256],
     ) -> Self {
-        unimplemented!()
+        let info = ImageInfo::new_a8(256, 4);
+        let pixels = [&table_a[..], table_r, table_g, table_b].concat();
+        Self::from_pixmap(Pixmap::from_vec(info, 256, pixels))
     }
 
     /// Per-channel constant value lookup (0-255).
     #[must_use]
     pub fn alpha_table(&self) -> Option<&[u8]> {
-        self.table.addr8_at(0, 0)
+        self.row(0)
     }
 
     #[must_use]
     pub fn red_table(&self) -> Option<&[u8]> {
-        self.table.addr8_at(0, 1)
+        self.row(1)
     }
 
     #[must_use]
     pub fn green_table(&self) -> Option<&[u8]> {
-        self.table.addr8_at(0, 2)
+        self.row(2)
     }
 
     #[must_use]
     pub fn blue_table(&self) -> Option<&[u8]> {
-        self.table.addr8_at(0, 3)
+        self.row(3)
+    }
+
+    /// Returns the 256 entries in row `y` of table image.
+    fn row(&self, y: i32) -> Option<&[u8]> {
+        self.table.addr8_at(0, y).and_then(|row| row.get(..256))
     }
 
     #[must_use]
-    const fn from_bitmap(table: Bitmap) -> Self {
+    const fn from_pixmap(table: Pixmap) -> Self {
         Self { table }
     }
 
-    /// The returned Bitmap is immutable.
+    /// The returned Pixmap is immutable.
     #[must_use]
-    const fn bitmap(&self) -> &Bitmap {
+    pub const fn pixmap(&self) -> &Pixmap {
         &self.table
     }
 }
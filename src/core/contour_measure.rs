@@ -4,11 +4,23 @@
 
 use bitflags::bitflags;
 
+use crate::core::geometry::{
+    chop_cubic_range, chop_quad_range, eval_cubic_at, eval_cubic_tangent_at, eval_quad_at,
+    eval_quad_tangent_at, lerp, Conic,
+};
 use crate::core::matrix::Matrix;
 use crate::core::path::Path;
+use crate::core::path_builder::PathBuilder;
+use crate::core::path_types::PathVerb;
 use crate::core::point::{Point, Vector};
 use crate::core::scalar::Scalar;
 
+/// Curves are subdivided until control points are closer than this to the chord.
+const CHEAP_DIST_LIMIT: Scalar = 0.5;
+
+/// Minimum span of t value to subdivide further.
+const MIN_T_SPAN: Scalar = 1.0 / 1_048_576.0;
+
 #[derive(Debug, Clone)]
 pub struct ContourMeasure {
     segments: Vec<Segment>,
@@ -29,6 +41,14 @@ bitflags! {
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum SegType {
+    Line,
+    Quad,
+    Cubic,
+    Conic,
+}
+
 #[derive(Debug, Clone)]
 struct Segment {
     /// total distance up to this point
@@ -37,28 +57,24 @@ struct Segment {
     /// index into the points array
     point_index: usize,
 
-    t_value: [u8; 4],
-    // actually the enum SegType
-    // TODO(Shaohua): Add SegType.
-    type_: u8,
+    /// t value of curve at the end of this segment, in [0, 1]
+    t_value: Scalar,
+
+    seg_type: SegType,
+
+    /// Weight of conic curve, unused by other types.
+    weight: Scalar,
 }
 
 impl Segment {
-    #[must_use]
-    fn get_scalar_t() -> Scalar {
-        unimplemented!()
-    }
-
-    /*
-    fn next(seg: &Self) -> &Self {
-        unimplemented!()
-        unsigned ptIndex = seg->fPtIndex;
-        do {
-            ++seg;
-        } while (seg->fPtIndex == ptIndex);
-        return seg;
+    /// Returns number of points of the curve, including start point.
+    const fn point_count(&self) -> usize {
+        match self.seg_type {
+            SegType::Line => 2,
+            SegType::Quad | SegType::Conic => 3,
+            SegType::Cubic => 4,
+        }
     }
-    */
 }
 
 impl ContourMeasure {
@@ -70,14 +86,36 @@ impl ContourMeasure {
 
     /// Pins distance to `0 <= distance <= length()`, and then computes the corresponding
     /// position and tangent.
+    ///
+    /// Returns false if there is no path, or a zero-length path was specified, in which case
+    /// position and tangent are unchanged.
     #[must_use]
     pub fn get_pos_tan(
         &self,
-        _distance: Scalar,
-        _position: &mut Point,
-        _tangent: &mut Vector,
+        distance: Scalar,
+        position: &mut Point,
+        tangent: &mut Vector,
     ) -> bool {
-        unimplemented!()
+        let length = self.length();
+        if !length.is_finite() || self.segments.is_empty() {
+            return false;
+        }
+        // Also catches NaN.
+        let distance = if distance >= 0.0 {
+            distance.min(length)
+        } else {
+            0.0
+        };
+
+        let (index, t) = self.distance_to_segment(distance);
+        if !t.is_finite() {
+            return false;
+        }
+        let (pos, tan) = self.compute_pos_tan(&self.segments[index], t);
+        *position = pos;
+        *tangent = tan;
+        let _ = tangent.normalize();
+        true
     }
 
     /// Pins distance to `0 <= distance <= length()`, and then computes the corresponding
@@ -86,8 +124,25 @@ impl ContourMeasure {
     /// Returns false if there is no path, or a zero-length path was specified, in which case
     /// matrix is unchanged.
     #[must_use]
-    pub fn get_matrix(&self, _distance: Scalar, _matrix: &mut Matrix, _flags: MatrixFlags) -> bool {
-        unimplemented!()
+    pub fn get_matrix(&self, distance: Scalar, matrix: &mut Matrix, flags: MatrixFlags) -> bool {
+        let mut position = Point::new();
+        let mut tangent = Vector::new();
+        if !self.get_pos_tan(distance, &mut position, &mut tangent) {
+            return false;
+        }
+        // Rotates x-axis onto the tangent.
+        let (sin, cos) = if flags.contains(MatrixFlags::GetTangent) {
+            (tangent.y(), tangent.x())
+        } else {
+            (0.0, 1.0)
+        };
+        let (dx, dy) = if flags.contains(MatrixFlags::GetPosition) {
+            (position.x(), position.y())
+        } else {
+            (0.0, 0.0)
+        };
+        *matrix = Matrix::from_all(cos, -sin, dx, sin, cos, dy, 0.0, 0.0, 1.0);
+        true
     }
 
     /// Given a start and stop distance, return in dst the intervening segment(s).
@@ -99,12 +154,47 @@ impl ContourMeasure {
     #[must_use]
     pub fn get_segment(
         &self,
-        _start_d: Scalar,
-        _stop_d: Scalar,
-        _dst: &mut Path,
-        _start_with_move_to: bool,
+        start_d: Scalar,
+        stop_d: Scalar,
+        dst: &mut PathBuilder,
+        start_with_move_to: bool,
     ) -> bool {
-        unimplemented!()
+        let length = self.length();
+        let start_d = start_d.max(0.0);
+        let stop_d = stop_d.min(length);
+        if start_d.is_nan() || stop_d.is_nan() || start_d > stop_d || self.segments.is_empty() {
+            return false;
+        }
+
+        let (mut index, mut start_t) = self.distance_to_segment(start_d);
+        if !start_t.is_finite() {
+            return false;
+        }
+        let (stop_index, stop_t) = self.distance_to_segment(stop_d);
+        if !stop_t.is_finite() {
+            return false;
+        }
+
+        if start_with_move_to {
+            let (pos, _tan) = self.compute_pos_tan(&self.segments[index], start_t);
+            dst.move_to_point(pos);
+        }
+
+        let stop_point_index = self.segments[stop_index].point_index;
+        if self.segments[index].point_index == stop_point_index {
+            self.segment_to(&self.segments[index], start_t, stop_t, dst);
+        } else {
+            loop {
+                self.segment_to(&self.segments[index], start_t, 1.0, dst);
+                index = self.next_curve(index);
+                start_t = 0.0;
+                if self.segments[index].point_index == stop_point_index {
+                    break;
+                }
+            }
+            self.segment_to(&self.segments[index], 0.0, stop_t, dst);
+        }
+        true
     }
 
     /// Return true if the contour is `closed()`
@@ -113,8 +203,99 @@ impl ContourMeasure {
         self.is_closed
     }
 
-    fn distance_to_segment(_distance: Scalar, _target: &mut Scalar) -> &Segment {
-        unimplemented!()
+    /// Returns index of segment at `distance`, and t value of curve at `distance`.
+    fn distance_to_segment(&self, distance: Scalar) -> (usize, Scalar) {
+        debug_assert!(distance >= 0.0 && distance <= self.length);
+
+        // Find the first segment whose distance is not less than `distance`.
+        let index = self
+            .segments
+            .partition_point(|seg| seg.distance < distance)
+            .min(self.segments.len() - 1);
+        let seg = &self.segments[index];
+
+        let mut start_t = 0.0;
+        let mut start_d = 0.0;
+        // Check if the prev segment is legal, and references the same set of points.
+        if index > 0 {
+            let prev = &self.segments[index - 1];
+            start_d = prev.distance;
+            if prev.point_index == seg.point_index {
+                start_t = prev.t_value;
+            }
+        }
+
+        debug_assert!(seg.distance > start_d);
+        let t = (seg.t_value - start_t)
+            .mul_add((distance - start_d) / (seg.distance - start_d), start_t);
+        (index, t)
+    }
+
+    /// Returns index of the first segment of next curve.
+    fn next_curve(&self, index: usize) -> usize {
+        let point_index = self.segments[index].point_index;
+        let mut next = index + 1;
+        while self.segments[next].point_index == point_index {
+            next += 1;
+        }
+        next
+    }
+
+    fn curve_points(&self, seg: &Segment) -> &[Point] {
+        &self.points[seg.point_index..seg.point_index + seg.point_count()]
+    }
+
+    fn compute_pos_tan(&self, seg: &Segment, t: Scalar) -> (Point, Vector) {
+        let pts = self.curve_points(seg);
+        match seg.seg_type {
+            SegType::Line => (lerp(pts[0], pts[1], t), pts[1] - pts[0]),
+            SegType::Quad => (eval_quad_at(pts, t), eval_quad_tangent_at(pts, t)),
+            SegType::Conic => {
+                let conic = Conic::new(pts[0], pts[1], pts[2], seg.weight);
+                (conic.eval_at(t), conic.eval_tangent_at(t))
+            }
+            SegType::Cubic => (eval_cubic_at(pts, t), eval_cubic_tangent_at(pts, t)),
+        }
+    }
+
+    /// Appends the part of curve of `seg` between `start_t` and `stop_t` to `dst`.
+    #[allow(clippy::float_cmp)]
+    fn segment_to(&self, seg: &Segment, start_t: Scalar, stop_t: Scalar, dst: &mut PathBuilder) {
+        debug_assert!((0.0..=1.0).contains(&start_t) && (0.0..=1.0).contains(&stop_t));
+        debug_assert!(start_t <= stop_t);
+
+        if start_t == stop_t {
+            // If the dash as a zero-length on segment, add a corresponding zero-length line.
+            // The stroke code will add end caps to zero length lines as appropriate.
+            if let Some(last_point) = dst.last_point() {
+                dst.line_to_point(last_point);
+            }
+            return;
+        }
+
+        let pts = self.curve_points(seg);
+        match seg.seg_type {
+            SegType::Line => {
+                if stop_t == 1.0 {
+                    dst.line_to_point(pts[1]);
+                } else {
+                    dst.line_to_point(lerp(pts[0], pts[1], stop_t));
+                }
+            }
+            SegType::Quad => {
+                let quad = chop_quad_range(pts, start_t, stop_t);
+                dst.quad_to_point(quad[1], quad[2]);
+            }
+            SegType::Conic => {
+                let conic =
+                    Conic::new(pts[0], pts[1], pts[2], seg.weight).chop_range(start_t, stop_t);
+                dst.conic_to_point(conic.pts[1], conic.pts[2], conic.weight);
+            }
+            SegType::Cubic => {
+                let cubic = chop_cubic_range(pts, start_t, stop_t);
+                dst.cubic_to_point(cubic[1], cubic[2], cubic[3]);
+            }
+        }
     }
 }
 
@@ -123,6 +304,11 @@ pub struct ContourMeasureIter {
     path: Path,
     force_closed: bool,
     res_scale: Scalar,
+
+    // Position of next contour in path.
+    verb_index: usize,
+    point_index: usize,
+    weight_index: usize,
 }
 
 impl ContourMeasureIter {
@@ -139,6 +325,9 @@ impl ContourMeasureIter {
             path,
             force_closed,
             res_scale,
+            verb_index: 0,
+            point_index: 0,
+            weight_index: 0,
         }
     }
 
@@ -146,9 +335,225 @@ impl ContourMeasureIter {
     /// The parts of the path that are needed are copied, so the client is free to modify/delete
     /// the path after this call.
     pub fn reset(&mut self, path: Path, force_closed: bool, res_scale: Scalar) {
-        self.path = path;
-        self.force_closed = force_closed;
-        self.res_scale = res_scale;
+        *self = Self::new(path, force_closed, res_scale);
+    }
+
+    /// Builds segments of next contour, which may have zero length.
+    ///
+    /// Returns None if there is no more contour.
+    fn build_next(&mut self) -> Option<ContourMeasure> {
+        let verbs = self.path.verbs();
+        if self.verb_index >= verbs.len() {
+            return None;
+        }
+
+        let mut builder = SegmentBuilder {
+            segments: Vec::new(),
+            points: Vec::new(),
+            distance: 0.0,
+            tolerance: CHEAP_DIST_LIMIT / self.res_scale,
+        };
+        let path_points = self.path.points();
+        let mut have_seen_close = self.force_closed;
+        let mut have_seen_move_to = false;
+
+        while let Some(&verb) = verbs.get(self.verb_index) {
+            if have_seen_move_to && verb == PathVerb::Move {
+                break;
+            }
+            self.verb_index += 1;
+
+            let count = match verb {
+                PathVerb::Close => 0,
+                PathVerb::Move => 1,
+                _ => verb.points() - 1,
+            };
+            let pts = &path_points[self.point_index..self.point_index + count];
+            self.point_index += count;
+            // Segments before the first move are not valid, skip them.
+            if verb != PathVerb::Move && builder.points.is_empty() {
+                continue;
+            }
+
+            match verb {
+                PathVerb::Move => {
+                    builder.points.push(pts[0]);
+                    have_seen_move_to = true;
+                }
+                PathVerb::Line => builder.add_line(pts[0]),
+                PathVerb::Quad => builder.add_quad(pts[0], pts[1]),
+                PathVerb::Conic => {
+                    let weight = self.path.conic_weights()[self.weight_index];
+                    self.weight_index += 1;
+                    builder.add_conic(pts[0], pts[1], weight);
+                }
+                PathVerb::Cubic => builder.add_cubic(pts[0], pts[1], pts[2]),
+                PathVerb::Close => have_seen_close = true,
+            }
+        }
+
+        if have_seen_close && !builder.points.is_empty() {
+            builder.add_line(builder.points[0]);
+        }
+
+        Some(ContourMeasure {
+            segments: builder.segments,
+            points: builder.points,
+            length: builder.distance,
+            is_closed: have_seen_close,
+        })
+    }
+}
+
+/// Accumulates segments of one contour.
+struct SegmentBuilder {
+    segments: Vec<Segment>,
+    points: Vec<Point>,
+    distance: Scalar,
+    tolerance: Scalar,
+}
+
+impl SegmentBuilder {
+    /// Index of the start point of next curve.
+    fn start_index(&self) -> usize {
+        self.points.len() - 1
+    }
+
+    fn cheap_dist_exceeds_limit(&self, point: Point, x: Scalar, y: Scalar) -> bool {
+        let dist = (x - point.x()).abs().max((y - point.y()).abs());
+        dist > self.tolerance
+    }
+
+    fn push_segment(
+        &mut self,
+        distance: Scalar,
+        t_value: Scalar,
+        seg_type: SegType,
+        weight: Scalar,
+    ) {
+        self.segments.push(Segment {
+            distance,
+            point_index: self.start_index(),
+            t_value,
+            seg_type,
+            weight,
+        });
+    }
+
+    fn add_line(&mut self, point: Point) {
+        let prev = self.points[self.start_index()];
+        let d = prev.distance(point);
+        let distance = self.distance + d;
+        // Also rejects NaN.
+        if distance > self.distance {
+            self.push_segment(distance, 1.0, SegType::Line, 1.0);
+            self.distance = distance;
+            self.points.push(point);
+        }
+    }
+
+    fn add_quad(&mut self, p1: Point, p2: Point) {
+        let pts = [self.points[self.start_index()], p1, p2];
+        let prev_d = self.distance;
+        self.compute_quad_segs(&pts, 0.0, 1.0);
+        if self.distance > prev_d {
+            self.points.extend_from_slice(&pts[1..]);
+        }
+    }
+
+    fn add_conic(&mut self, p1: Point, p2: Point, weight: Scalar) {
+        let conic = Conic::new(self.points[self.start_index()], p1, p2, weight);
+        let prev_d = self.distance;
+        self.compute_conic_segs(&conic, 0.0, conic.pts[0], 1.0, conic.pts[2]);
+        if self.distance > prev_d {
+            self.points.extend_from_slice(&conic.pts[1..]);
+        }
+    }
+
+    fn add_cubic(&mut self, p1: Point, p2: Point, p3: Point) {
+        let pts = [self.points[self.start_index()], p1, p2, p3];
+        let prev_d = self.distance;
+        self.compute_cubic_segs(&pts, 0.0, 1.0);
+        if self.distance > prev_d {
+            self.points.extend_from_slice(&pts[1..]);
+        }
+    }
+
+    fn quad_too_curvy(&self, pts: &[Point; 3]) -> bool {
+        // diff = (a/4 + b/2 + c/4) - (a/2 + c/2)
+        // diff = -a/4 + b/2 - c/4
+        let dx = pts[1].x() / 2.0 - (pts[0].x() + pts[2].x()) / 4.0;
+        let dy = pts[1].y() / 2.0 - (pts[0].y() + pts[2].y()) / 4.0;
+        let dist = dx.abs().max(dy.abs());
+        dist > self.tolerance
+    }
+
+    fn conic_too_curvy(&self, first: Point, mid: Point, last: Point) -> bool {
+        let x = (first.x() + last.x()) / 2.0;
+        let y = (first.y() + last.y()) / 2.0;
+        self.cheap_dist_exceeds_limit(mid, x, y)
+    }
+
+    fn cubic_too_curvy(&self, pts: &[Point; 4]) -> bool {
+        let one_third = lerp(pts[0], pts[3], 1.0 / 3.0);
+        let two_third = lerp(pts[0], pts[3], 2.0 / 3.0);
+        self.cheap_dist_exceeds_limit(pts[1], one_third.x(), one_third.y())
+            || self.cheap_dist_exceeds_limit(pts[2], two_third.x(), two_third.y())
+    }
+
+    /// `pts` is the part of curve between `min_t` and `max_t`.
+    fn compute_quad_segs(&mut self, pts: &[Point; 3], min_t: Scalar, max_t: Scalar) {
+        if max_t - min_t > MIN_T_SPAN && self.quad_too_curvy(pts) {
+            let half_t = (min_t + max_t) / 2.0;
+            self.compute_quad_segs(&chop_quad_range(pts, 0.0, 0.5), min_t, half_t);
+            self.compute_quad_segs(&chop_quad_range(pts, 0.5, 1.0), half_t, max_t);
+        } else {
+            let distance = self.distance + pts[0].distance(pts[2]);
+            if distance > self.distance {
+                self.push_segment(distance, max_t, SegType::Quad, 1.0);
+                self.distance = distance;
+            }
+        }
+    }
+
+    fn compute_conic_segs(
+        &mut self,
+        conic: &Conic,
+        min_t: Scalar,
+        min_point: Point,
+        max_t: Scalar,
+        max_point: Point,
+    ) {
+        let half_t = (min_t + max_t) / 2.0;
+        let half_point = conic.eval_at(half_t);
+        if !half_point.is_finite() {
+            return;
+        }
+        if max_t - min_t > MIN_T_SPAN && self.conic_too_curvy(min_point, half_point, max_point) {
+            self.compute_conic_segs(conic, min_t, min_point, half_t, half_point);
+            self.compute_conic_segs(conic, half_t, half_point, max_t, max_point);
+        } else {
+            let distance = self.distance + min_point.distance(max_point);
+            if distance > self.distance {
+                self.push_segment(distance, max_t, SegType::Conic, conic.weight);
+                self.distance = distance;
+            }
+        }
+    }
+
+    /// `pts` is the part of curve between `min_t` and `max_t`.
+    fn compute_cubic_segs(&mut self, pts: &[Point; 4], min_t: Scalar, max_t: Scalar) {
+        if max_t - min_t > MIN_T_SPAN && self.cubic_too_curvy(pts) {
+            let half_t = (min_t + max_t) / 2.0;
+            self.compute_cubic_segs(&chop_cubic_range(pts, 0.0, 0.5), min_t, half_t);
+            self.compute_cubic_segs(&chop_cubic_range(pts, 0.5, 1.0), half_t, max_t);
+        } else {
+            let distance = self.distance + pts[0].distance(pts[3]);
+            if distance > self.distance {
+                self.push_segment(distance, max_t, SegType::Cubic, 1.0);
+                self.distance = distance;
+            }
+        }
     }
 }
 
@@ -166,6 +571,114 @@ impl Iterator for ContourMeasureIter {
     ///
     /// If it encounters a zero-length contour, it is skipped.
     fn next(&mut self) -> Option<Self::Item> {
-        unimplemented!()
+        loop {
+            let contour = self.build_next()?;
+            if !contour.segments.is_empty() && contour.length.is_finite() {
+                return Some(contour);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rect::Rect;
+
+    fn is_near(value: Scalar, expected: Scalar) -> bool {
+        (value - expected).abs() < 1e-3
+    }
+
+    fn measure(path: Path, force_closed: bool) -> ContourMeasure {
+        ContourMeasureIter::new(path, force_closed, 1.0)
+            .next()
+            .unwrap()
+    }
+
+    /// Open polyline from (0, 0) to (10, 0) then (10, 10).
+    fn polyline() -> Path {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0);
+        builder.line_to(10.0, 0.0);
+        builder.line_to(10.0, 10.0);
+        builder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_length() {
+        assert!(is_near(measure(polyline(), false).length(), 20.0));
+        assert!(is_near(
+            measure(polyline(), true).length(),
+            20.0 + 200.0_f32.sqrt()
+        ));
+
+        let rect = PathBuilder::from_rect(&Rect::from_wh(10.0, 5.0)).unwrap();
+        let contour = measure(rect, false);
+        assert!(contour.is_closed());
+        assert!(is_near(contour.length(), 30.0));
+
+        // Curves are measured along chords, which are closer with larger `res_scale`.
+        let mut builder = PathBuilder::new();
+        builder.add_circle(0.0, 0.0, 10.0);
+        let circle = builder.finish().unwrap();
+        let expected = 20.0 * std::f32::consts::PI;
+        let coarse = measure(circle.clone(), false).length();
+        let fine = ContourMeasureIter::new(circle, false, 10.0)
+            .next()
+            .unwrap()
+            .length();
+        assert!(coarse < fine && fine <= expected);
+        assert!(expected - coarse < expected * 0.02);
+        assert!(expected - fine < expected * 0.002);
+    }
+
+    #[test]
+    fn test_get_pos_tan() {
+        let contour = measure(polyline(), false);
+        let mut position = Point::new();
+        let mut tangent = Vector::new();
+        assert!(contour.get_pos_tan(15.0, &mut position, &mut tangent));
+        assert!(is_near(position.x(), 10.0) && is_near(position.y(), 5.0));
+        assert!(is_near(tangent.x(), 0.0) && is_near(tangent.y(), 1.0));
+
+        // Distance is pinned to the contour.
+        assert!(contour.get_pos_tan(-5.0, &mut position, &mut tangent));
+        assert!(is_near(position.x(), 0.0) && is_near(tangent.x(), 1.0));
+        assert!(contour.get_pos_tan(100.0, &mut position, &mut tangent));
+        assert!(is_near(position.x(), 10.0) && is_near(position.y(), 10.0));
+    }
+
+    #[test]
+    fn test_get_matrix() {
+        let contour = measure(polyline(), false);
+        let mut matrix = Matrix::identity();
+        assert!(contour.get_matrix(15.0, &mut matrix, MatrixFlags::GetPosAndTan));
+        // Rotated by 90 degrees, then moved to (10, 5).
+        let point = matrix.map_xy(2.0, 0.0);
+        assert!(is_near(point.x(), 10.0) && is_near(point.y(), 7.0));
+
+        assert!(contour.get_matrix(15.0, &mut matrix, MatrixFlags::GetPosition));
+        let point = matrix.map_xy(2.0, 0.0);
+        assert!(is_near(point.x(), 12.0) && is_near(point.y(), 5.0));
+
+        assert!(contour.get_matrix(15.0, &mut matrix, MatrixFlags::GetTangent));
+        let point = matrix.map_xy(2.0, 0.0);
+        assert!(is_near(point.x(), 0.0) && is_near(point.y(), 2.0));
+    }
+
+    #[test]
+    fn test_get_segment() {
+        let contour = measure(polyline(), false);
+        let mut builder = PathBuilder::new();
+        assert!(contour.get_segment(5.0, 15.0, &mut builder, true));
+        let segment = builder.finish().unwrap();
+        let points = segment.points();
+        assert!(is_near(points[0].x(), 5.0) && is_near(points[0].y(), 0.0));
+        let last = points[points.len() - 1];
+        assert!(is_near(last.x(), 10.0) && is_near(last.y(), 5.0));
+        assert!(is_near(measure(segment, false).length(), 10.0));
+
+        let mut builder = PathBuilder::new();
+        assert!(!contour.get_segment(15.0, 5.0, &mut builder, true));
     }
 }
@@ -0,0 +1,132 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::fmt;
+
+use crate::core::clip_op::ClipOp;
+use crate::core::color::Color4f;
+use crate::core::image_info::ImageInfo;
+use crate::core::irect::IRect;
+use crate::core::matrix::Matrix;
+use crate::core::paint::Paint;
+use crate::core::path::Path;
+use crate::core::pixmap::Pixmap;
+use crate::core::rect::Rect;
+use crate::core::surface_props::SurfaceProps;
+
+/// Device is the destination Canvas draws into.
+///
+/// Raster device draws into pixels in memory, while document devices record
+/// the drawing commands, like PDF and SVG.
+pub trait Device: fmt::Debug {
+    /// Returns width, height, `ColorType` and `AlphaType` of device.
+    fn image_info(&self) -> &ImageInfo;
+
+    /// Returns LCD striping orientation and setting for device independent fonts.
+    fn surface_props(&self) -> &SurfaceProps;
+
+    #[must_use]
+    fn width(&self) -> i32 {
+        self.image_info().width()
+    }
+
+    #[must_use]
+    fn height(&self) -> i32 {
+        self.image_info().height()
+    }
+
+    /// Returns `IRect { 0, 0, width(), height() }`.
+    #[must_use]
+    fn bounds(&self) -> IRect {
+        self.image_info().bounds()
+    }
+
+    /// Returns pixels if they are stored in memory and can be read directly.
+    #[must_use]
+    fn peek_pixels(&self) -> Option<&Pixmap> {
+        None
+    }
+
+    /// Returns writable pixels if they are stored in memory.
+    #[must_use]
+    fn peek_pixels_mut(&mut self) -> Option<&mut Pixmap> {
+        None
+    }
+
+    /// Sets matrix mapping local coordinates of shaders, mask filters and image
+    /// filters of paint to device coordinates.
+    ///
+    /// Paths are passed to the device already mapped by this matrix.
+    /// The default implementation ignores `matrix`.
+    fn set_local_to_device(&mut self, _matrix: &Matrix) {}
+
+    /// Saves clip of device, to be restored by `restore()`.
+    fn save(&mut self);
+
+    /// Saves clip, and draws into a transparent layer until `restore()`.
+    ///
+    /// On `restore()`, the layer is drawn inside `bounds` with alpha, color filter,
+    /// image filter and blend mode of `paint`. If `bounds` is None, layer covers
+    /// the whole device. If `paint` is None, layer is drawn with `BlendMode::SrcOver`.
+    fn save_layer(&mut self, bounds: Option<&Rect>, paint: Option<&Paint>);
+
+    /// Restores clip saved by the last `save()` or `save_layer()`, drawing the
+    /// layer if any.
+    ///
+    /// Does nothing if there is no saved clip.
+    fn restore(&mut self);
+
+    /// Replaces clip with the intersection or difference of clip and `path`,
+    /// in device coordinates.
+    ///
+    /// If `anti_alias` is true, edges of clip are anti-aliased.
+    fn clip_path(&mut self, path: &Path, op: ClipOp, anti_alias: bool);
+
+    /// Replaces all pixels inside clip with `color`, without blending.
+    fn erase(&mut self, color: &Color4f);
+
+    /// Draws `path` with `paint` inside clip, in device coordinates.
+    fn draw_path(&mut self, path: &Path, paint: &Paint);
+}
+
+/// A device with dimensions but no pixels, which discards all drawing.
+///
+/// This is used by Canvas which has no backing surface.
+#[derive(Debug, Clone)]
+pub struct NoPixelsDevice {
+    info: ImageInfo,
+    props: SurfaceProps,
+}
+
+impl NoPixelsDevice {
+    #[must_use]
+    pub fn new(width: i32, height: i32, props: SurfaceProps) -> Self {
+        Self {
+            info: ImageInfo::new_unknown(width.max(0), height.max(0)),
+            props,
+        }
+    }
+}
+
+impl Device for NoPixelsDevice {
+    fn image_info(&self) -> &ImageInfo {
+        &self.info
+    }
+
+    fn surface_props(&self) -> &SurfaceProps {
+        &self.props
+    }
+
+    fn save(&mut self) {}
+
+    fn save_layer(&mut self, _bounds: Option<&Rect>, _paint: Option<&Paint>) {}
+
+    fn restore(&mut self) {}
+
+    fn clip_path(&mut self, _path: &Path, _op: ClipOp, _anti_alias: bool) {}
+
+    fn erase(&mut self, _color: &Color4f) {}
+
+    fn draw_path(&mut self, _path: &Path, _paint: &Paint) {}
+}
@@ -0,0 +1,132 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Descriptive metadata attached to exported documents.
+//!
+//! The same `DocumentMetadata` is understood by all export paths:
+//! - PDF writes it into the document information dictionary and an XMP stream;
+//! - SVG writes it into a `<metadata>` element as Dublin Core RDF;
+//! - PNG writes it as `tEXt` or `iTXt` chunks.
+
+use crate::core::time::DateTime;
+
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct DocumentMetadata {
+    /// The document's title.
+    pub title: String,
+
+    /// The name of the person who created the document.
+    pub author: String,
+
+    /// The subject of the document.
+    pub subject: String,
+
+    /// Keywords associated with the document.
+    pub keywords: Vec<String>,
+
+    /// If the document was converted from another format, the name of the conforming
+    /// product that created the original document from which it was converted.
+    pub creator: String,
+
+    /// The product that is converting this document.
+    pub producer: String,
+
+    /// The date and time the document was created.
+    pub creation: Option<DateTime>,
+
+    /// The date and time the document was most recently modified.
+    pub modified: Option<DateTime>,
+
+    /// The natural language of the text in the document, as a BCP 47 language tag,
+    /// e.g. "en-US".
+    pub language: String,
+}
+
+impl DocumentMetadata {
+    /// Creates an empty metadata.
+    ///
+    /// `producer` is left empty, exporters fill it in with this library
+    /// at export time, see [`Self::producer_or_default`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_title(mut self, title: &str) -> Self {
+        title.clone_into(&mut self.title);
+        self
+    }
+
+    #[must_use]
+    pub fn with_author(mut self, author: &str) -> Self {
+        author.clone_into(&mut self.author);
+        self
+    }
+
+    #[must_use]
+    pub fn with_subject(mut self, subject: &str) -> Self {
+        subject.clone_into(&mut self.subject);
+        self
+    }
+
+    #[must_use]
+    pub fn with_keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|s| (*s).to_owned()).collect();
+        self
+    }
+
+    #[must_use]
+    pub const fn with_creation(mut self, creation: DateTime) -> Self {
+        self.creation = Some(creation);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_modified(mut self, modified: DateTime) -> Self {
+        self.modified = Some(modified);
+        self
+    }
+
+    #[must_use]
+    pub fn with_language(mut self, language: &str) -> Self {
+        language.clone_into(&mut self.language);
+        self
+    }
+
+    /// Returns keywords joined by commas, the format used by PDF and PNG.
+    #[must_use]
+    pub fn joined_keywords(&self) -> String {
+        self.keywords.join(", ")
+    }
+
+    /// Returns `producer`, or name and version of this library if it is empty.
+    #[must_use]
+    pub fn producer_or_default(&self) -> String {
+        if self.producer.is_empty() {
+            default_producer()
+        } else {
+            self.producer.clone()
+        }
+    }
+
+    /// Returns true if no field is set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.title.is_empty()
+            && self.author.is_empty()
+            && self.subject.is_empty()
+            && self.keywords.is_empty()
+            && self.creator.is_empty()
+            && self.producer.is_empty()
+            && self.creation.is_none()
+            && self.modified.is_none()
+            && self.language.is_empty()
+    }
+}
+
+#[must_use]
+fn default_producer() -> String {
+    format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+}
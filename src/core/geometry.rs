@@ -0,0 +1,207 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Evaluation and subdivision of quadratic, conic and cubic Bezier curves.
+//!
+//! Sub-curves are computed with blossoms (polar forms): the control points of
+//! the part of a curve between t0 and t1 are the blossom of the curve evaluated
+//! with each combination of t0 and t1.
+
+use crate::core::point::{Point, Vector};
+use crate::core::scalar::Scalar;
+
+#[must_use]
+#[inline]
+pub fn lerp(a: Point, b: Point, t: Scalar) -> Point {
+    Point::from_xy(
+        (b.x() - a.x()).mul_add(t, a.x()),
+        (b.y() - a.y()).mul_add(t, a.y()),
+    )
+}
+
+/// Returns the blossom of quadratic curve `pts` at (a, b).
+#[must_use]
+fn quad_blossom(pts: &[Point], a: Scalar, b: Scalar) -> Point {
+    lerp(lerp(pts[0], pts[1], a), lerp(pts[1], pts[2], a), b)
+}
+
+/// Returns the blossom of cubic curve `pts` at (a, b, c).
+#[must_use]
+fn cubic_blossom(pts: &[Point], a: Scalar, b: Scalar, c: Scalar) -> Point {
+    let ab = lerp(pts[0], pts[1], a);
+    let bc = lerp(pts[1], pts[2], a);
+    let cd = lerp(pts[2], pts[3], a);
+    lerp(lerp(ab, bc, b), lerp(bc, cd, b), c)
+}
+
+/// Returns point on quadratic curve `pts` at `t`.
+#[must_use]
+pub fn eval_quad_at(pts: &[Point], t: Scalar) -> Point {
+    quad_blossom(pts, t, t)
+}
+
+/// Returns tangent direction of quadratic curve `pts` at `t`, not normalized.
+#[must_use]
+#[allow(clippy::float_cmp)]
+pub fn eval_quad_tangent_at(pts: &[Point], t: Scalar) -> Vector {
+    // The derivative is zero at an end point which equals the control point,
+    // use the chord instead.
+    if (t == 0.0 && pts[0] == pts[1]) || (t == 1.0 && pts[1] == pts[2]) {
+        return pts[2] - pts[0];
+    }
+    lerp(pts[1] - pts[0], pts[2] - pts[1], t)
+}
+
+/// Returns control points of the part of quadratic curve `pts` between `t0` and `t1`.
+#[must_use]
+pub fn chop_quad_range(pts: &[Point], t0: Scalar, t1: Scalar) -> [Point; 3] {
+    [
+        quad_blossom(pts, t0, t0),
+        quad_blossom(pts, t0, t1),
+        quad_blossom(pts, t1, t1),
+    ]
+}
+
+/// Returns point on cubic curve `pts` at `t`.
+#[must_use]
+pub fn eval_cubic_at(pts: &[Point], t: Scalar) -> Point {
+    cubic_blossom(pts, t, t, t)
+}
+
+/// Returns tangent direction of cubic curve `pts` at `t`, not normalized.
+#[must_use]
+#[allow(clippy::float_cmp)]
+pub fn eval_cubic_tangent_at(pts: &[Point], t: Scalar) -> Vector {
+    if (t == 0.0 && pts[0] == pts[1]) || (t == 1.0 && pts[2] == pts[3]) {
+        let tangent = if t == 0.0 {
+            pts[2] - pts[0]
+        } else {
+            pts[3] - pts[1]
+        };
+        if tangent.is_zero() {
+            return pts[3] - pts[0];
+        }
+        return tangent;
+    }
+    let ab = lerp(pts[0], pts[1], t);
+    let bc = lerp(pts[1], pts[2], t);
+    let cd = lerp(pts[2], pts[3], t);
+    lerp(bc, cd, t) - lerp(ab, bc, t)
+}
+
+/// Returns control points of the part of cubic curve `pts` between `t0` and `t1`.
+#[must_use]
+pub fn chop_cubic_range(pts: &[Point], t0: Scalar, t1: Scalar) -> [Point; 4] {
+    [
+        cubic_blossom(pts, t0, t0, t0),
+        cubic_blossom(pts, t0, t0, t1),
+        cubic_blossom(pts, t0, t1, t1),
+        cubic_blossom(pts, t1, t1, t1),
+    ]
+}
+
+/// Rational quadratic curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Conic {
+    pub pts: [Point; 3],
+    pub weight: Scalar,
+}
+
+/// Point in homogeneous coordinates.
+#[derive(Debug, Clone, Copy)]
+struct Point3 {
+    x: Scalar,
+    y: Scalar,
+    z: Scalar,
+}
+
+impl Point3 {
+    fn lerp(self, other: Self, t: Scalar) -> Self {
+        Self {
+            x: (other.x - self.x).mul_add(t, self.x),
+            y: (other.y - self.y).mul_add(t, self.y),
+            z: (other.z - self.z).mul_add(t, self.z),
+        }
+    }
+
+    fn project(self) -> Point {
+        Point::from_xy(self.x / self.z, self.y / self.z)
+    }
+}
+
+impl Conic {
+    #[must_use]
+    pub const fn new(p0: Point, p1: Point, p2: Point, weight: Scalar) -> Self {
+        Self {
+            pts: [p0, p1, p2],
+            weight,
+        }
+    }
+
+    fn homogeneous(&self) -> [Point3; 3] {
+        let [p0, p1, p2] = self.pts;
+        let w = self.weight;
+        [
+            Point3 {
+                x: p0.x(),
+                y: p0.y(),
+                z: 1.0,
+            },
+            Point3 {
+                x: p1.x() * w,
+                y: p1.y() * w,
+                z: w,
+            },
+            Point3 {
+                x: p2.x(),
+                y: p2.y(),
+                z: 1.0,
+            },
+        ]
+    }
+
+    fn blossom(&self, a: Scalar, b: Scalar) -> Point3 {
+        let [p0, p1, p2] = self.homogeneous();
+        p0.lerp(p1, a).lerp(p1.lerp(p2, a), b)
+    }
+
+    /// Returns point on curve at `t`.
+    #[must_use]
+    pub fn eval_at(&self, t: Scalar) -> Point {
+        self.blossom(t, t).project()
+    }
+
+    /// Returns tangent direction at `t`, not normalized.
+    #[must_use]
+    #[allow(clippy::float_cmp)]
+    pub fn eval_tangent_at(&self, t: Scalar) -> Vector {
+        let [p0, p1, p2] = self.pts;
+        if (t == 0.0 && p0 == p1) || (t == 1.0 && p1 == p2) {
+            return p2 - p0;
+        }
+        // Derivative of N(t) / D(t) has the direction of N'(t) * D(t) - N(t) * D'(t).
+        let value = self.blossom(t, t);
+        let [h0, h1, h2] = self.homogeneous();
+        let d0 = h0.lerp(h1, t);
+        let d1 = h1.lerp(h2, t);
+        let dx = d1.x - d0.x;
+        let dy = d1.y - d0.y;
+        let dz = d1.z - d0.z;
+        Point::from_xy(
+            dx.mul_add(value.z, -value.x * dz),
+            dy.mul_add(value.z, -value.y * dz),
+        )
+    }
+
+    /// Returns the part of curve between `t0` and `t1`.
+    #[must_use]
+    pub fn chop_range(&self, t0: Scalar, t1: Scalar) -> Self {
+        let a = self.blossom(t0, t0);
+        let b = self.blossom(t0, t1);
+        let c = self.blossom(t1, t1);
+        // Normalize so that both end points have unit weight.
+        let weight = b.z / (a.z * c.z).sqrt();
+        Self::new(a.project(), b.project(), c.project(), weight)
+    }
+}
@@ -0,0 +1,438 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::borrow::Cow;
+
+use crate::core::alpha_type::AlphaType;
+use crate::core::color_space::ColorSpace;
+use crate::core::color_type::ColorType;
+use crate::core::image_info::ImageInfo;
+use crate::core::irect::IRect;
+use crate::core::pixmap::{pixel_alphaf, Pixmap};
+use crate::core::size::ISize;
+
+/// Image describes a two dimensional array of pixels to draw.
+///
+/// The pixels are immutable. They may be borrowed from caller, for example
+/// a buffer filled by a sensor, or owned by Image.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Image<'a> {
+    info: ImageInfo,
+    row_bytes: usize,
+    pixels: Cow<'a, [u8]>,
+}
+
+impl<'a> Image<'a> {
+    /// Creates Image referencing `bytes`, without copying pixels.
+    ///
+    /// Returns None if parameters are invalid, see `validate_raw()`.
+    ///
+    /// # Parameters
+    /// - `bytes` - pixel storage, at least `info.compute_byte_size(row_bytes)` bytes
+    /// - `info` - width, height, `ColorType`, `AlphaType` and `ColorSpace` of pixels
+    /// - `row_bytes` - size of one row of pixels; width times pixel size, or larger
+    #[must_use]
+    pub fn from_raw(bytes: &'a [u8], info: ImageInfo, row_bytes: usize) -> Option<Self> {
+        let byte_size = validate_raw(bytes.len(), &info, row_bytes)?;
+        Some(Self {
+            info,
+            row_bytes,
+            pixels: Cow::Borrowed(&bytes[..byte_size]),
+        })
+    }
+
+    /// Returns Image with a copy of pixels, which does not borrow from `self`.
+    #[must_use]
+    pub fn into_owned(self) -> Image<'static> {
+        Image {
+            info: self.info,
+            row_bytes: self.row_bytes,
+            pixels: Cow::Owned(self.pixels.into_owned()),
+        }
+    }
+
+    /// Returns true if pixels are borrowed from caller.
+    #[must_use]
+    #[inline]
+    pub const fn is_borrowed(&self) -> bool {
+        matches!(self.pixels, Cow::Borrowed(_))
+    }
+
+    /// Returns a `ImageInfo` describing the width, height, color type, alpha type,
+    /// and color space of the Image.
+    #[must_use]
+    #[inline]
+    pub const fn info(&self) -> &ImageInfo {
+        &self.info
+    }
+
+    /// Returns pixel count in each row.
+    #[must_use]
+    #[inline]
+    pub const fn width(&self) -> i32 {
+        self.info.width()
+    }
+
+    /// Returns pixel row count.
+    #[must_use]
+    #[inline]
+    pub const fn height(&self) -> i32 {
+        self.info.height()
+    }
+
+    /// Returns `ISize { width(), height() }`.
+    #[must_use]
+    #[inline]
+    pub const fn dimensions(&self) -> ISize {
+        self.info.dimensions()
+    }
+
+    /// Returns `IRect { 0, 0, width(), height() }`.
+    #[must_use]
+    #[inline]
+    pub const fn bounds(&self) -> IRect {
+        self.info.bounds()
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn color_type(&self) -> ColorType {
+        self.info.color_type()
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn alpha_type(&self) -> AlphaType {
+        self.info.alpha_type()
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn color_space(&self) -> &Option<ColorSpace> {
+        self.info.color_space()
+    }
+
+    /// Returns true if Image pixels represent transparency only.
+    #[must_use]
+    #[inline]
+    pub fn is_alpha_only(&self) -> bool {
+        self.color_type().is_alpha_only()
+    }
+
+    /// Returns true if pixels ignore their alpha value and are treated as fully opaque.
+    #[must_use]
+    #[inline]
+    pub fn is_opaque(&self) -> bool {
+        self.info.is_opaque()
+    }
+
+    /// Returns row bytes, the interval from one pixel row to the next.
+    #[must_use]
+    #[inline]
+    pub const fn row_bytes(&self) -> usize {
+        self.row_bytes
+    }
+
+    /// Returns pixels, without trailing bytes after the last pixel.
+    #[must_use]
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns Image referencing the pixels of `self` inside `subset`, without copying.
+    ///
+    /// Returns None if `subset` is empty or not contained by `bounds()`.
+    #[must_use]
+    pub fn subset(&self, subset: &IRect) -> Option<Image<'_>> {
+        if subset.is_empty() || !self.bounds().contains_rect(subset) {
+            return None;
+        }
+        let offset = self
+            .info
+            .compute_offset(subset.left(), subset.top(), self.row_bytes);
+        Image::from_raw(
+            &self.pixels[offset..],
+            self.info.from_dimensions(subset.size()),
+            self.row_bytes,
+        )
+    }
+
+    /// Returns the bounding box of pixels whose alpha is larger than `alpha_threshold`.
+    ///
+    /// `alpha_threshold` is normalized to [0..1]; use 0.0 to find all pixels which are
+    /// not fully transparent.
+    ///
+    /// Returns None if no pixel is above threshold.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn content_bounds(&self, alpha_threshold: f32) -> Option<IRect> {
+        if self.is_opaque() || self.color_type().is_always_opaque() {
+            return (alpha_threshold < 1.0).then(|| self.bounds());
+        }
+
+        let color_type = self.color_type();
+        let bytes_per_pixel = self.info.bytes_per_pixel() as usize;
+        let width = self.width() as usize;
+        let mut left = usize::MAX;
+        let mut right = 0;
+        let mut top = usize::MAX;
+        let mut bottom = 0;
+        for y in 0..self.height() as usize {
+            let row = &self.pixels[y * self.row_bytes..];
+            let is_visible =
+                |x: usize| pixel_alphaf(color_type, &row[x * bytes_per_pixel..]) > alpha_threshold;
+            let Some(first) = (0..width).find(|&x| is_visible(x)) else {
+                continue;
+            };
+            // Pixels between left and right are already included.
+            let last = (first.max(right)..width)
+                .rev()
+                .find(|&x| is_visible(x))
+                .unwrap_or(first);
+            left = left.min(first);
+            right = right.max(last + 1);
+            top = top.min(y);
+            bottom = y + 1;
+        }
+
+        if top == usize::MAX {
+            return None;
+        }
+        Some(IRect::from_ltrb(
+            left as i32,
+            top as i32,
+            right as i32,
+            bottom as i32,
+        ))
+    }
+
+    /// Returns Image referencing the minimal area containing all pixels which are not
+    /// fully transparent.
+    ///
+    /// Returns None if all pixels are transparent.
+    #[must_use]
+    pub fn trim(&self) -> Option<Image<'_>> {
+        let bounds = self.content_bounds(0.0)?;
+        self.subset(&bounds)
+    }
+
+    /// Labels connected regions of pixels whose alpha is larger than `alpha_threshold`,
+    /// and returns labels with bounds and area of each region.
+    ///
+    /// Pixels are connected to their four edge neighbors, and also to their four
+    /// corner neighbors if `eight_connected` is true. Components are labeled from 1,
+    /// in order of their first pixel when scanning rows from top to bottom.
+    ///
+    /// `alpha_threshold` is normalized to [0..1]; use 0.0 to find regions of pixels
+    /// which are not fully transparent.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn label_components(&self, alpha_threshold: f32, eight_connected: bool) -> ComponentLabels {
+        let color_type = self.color_type();
+        let bytes_per_pixel = self.info.bytes_per_pixel() as usize;
+        let width = self.width().max(0) as usize;
+        let height = self.height().max(0) as usize;
+        let is_opaque = self.is_opaque() || color_type.is_always_opaque();
+        let is_visible = |index: usize| {
+            if is_opaque {
+                return alpha_threshold < 1.0;
+            }
+            let offset = (index / width) * self.row_bytes + (index % width) * bytes_per_pixel;
+            pixel_alphaf(color_type, &self.pixels[offset..]) > alpha_threshold
+        };
+        let neighbors = if eight_connected {
+            &NEIGHBORS[..]
+        } else {
+            &NEIGHBORS[..4]
+        };
+
+        let mut labels = vec![0; width * height];
+        let mut components = Vec::new();
+        let mut stack = Vec::new();
+        for start in 0..labels.len() {
+            if labels[start] != 0 || !is_visible(start) {
+                continue;
+            }
+            let label = components.len() as u32 + 1;
+            labels[start] = label;
+            stack.push(start);
+            let (mut left, mut top, mut right, mut bottom) = (usize::MAX, usize::MAX, 0, 0);
+            let mut area = 0;
+            while let Some(index) = stack.pop() {
+                let (x, y) = (index % width, index / width);
+                area += 1;
+                left = left.min(x);
+                top = top.min(y);
+                right = right.max(x + 1);
+                bottom = bottom.max(y + 1);
+                for &(dx, dy) in neighbors {
+                    let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                    else {
+                        continue;
+                    };
+                    if nx >= width || ny >= height {
+                        continue;
+                    }
+                    let neighbor = ny * width + nx;
+                    if labels[neighbor] == 0 && is_visible(neighbor) {
+                        labels[neighbor] = label;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            components.push(Component {
+                label,
+                bounds: IRect::from_ltrb(left as i32, top as i32, right as i32, bottom as i32),
+                area,
+            });
+        }
+
+        ComponentLabels {
+            width,
+            labels,
+            components,
+        }
+    }
+
+    /// Returns a copy of pixels as Pixmap.
+    #[must_use]
+    pub fn to_pixmap(&self) -> Pixmap {
+        Pixmap::from(self.info.clone(), self.row_bytes, &self.pixels)
+    }
+}
+
+/// Offsets of edge neighbors of a pixel, followed by corner neighbors.
+const NEIGHBORS: [(isize, isize); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+/// Region of connected pixels found by `Image::label_components()`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Component {
+    /// Label of pixels in this component, starting from 1.
+    pub label: u32,
+
+    /// Bounding box of pixels in this component.
+    pub bounds: IRect,
+
+    /// Number of pixels in this component.
+    pub area: usize,
+}
+
+/// Labels of pixels and components returned by `Image::label_components()`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ComponentLabels {
+    width: usize,
+
+    /// Label of each pixel, row by row; 0 for pixels not in any component.
+    labels: Vec<u32>,
+
+    /// Components ordered by their labels.
+    components: Vec<Component>,
+}
+
+impl ComponentLabels {
+    /// Returns label of each pixel, row by row; 0 for pixels not in any component.
+    #[must_use]
+    #[inline]
+    pub fn labels(&self) -> &[u32] {
+        &self.labels
+    }
+
+    /// Returns components ordered by their labels.
+    #[must_use]
+    #[inline]
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Returns label of pixel at (x, y), or 0 if pixel is not in any component
+    /// or is out of bounds.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn label_at(&self, x: i32, y: i32) -> u32 {
+        if x < 0 || y < 0 || x as usize >= self.width {
+            return 0;
+        }
+        self.labels
+            .get(y as usize * self.width + x as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns component containing pixel at (x, y), for example to select
+    /// the region under a pointer.
+    #[must_use]
+    pub fn component_at(&self, x: i32, y: i32) -> Option<&Component> {
+        let label = self.label_at(x, y);
+        self.components.get((label as usize).checked_sub(1)?)
+    }
+}
+
+impl Image<'static> {
+    /// Creates Image with a copy of `bytes`.
+    ///
+    /// Only the bytes required by `info` and `row_bytes` are copied.
+    ///
+    /// Returns None if parameters are invalid, see `validate_raw()`.
+    #[must_use]
+    pub fn from_raw_copy(bytes: &[u8], info: ImageInfo, row_bytes: usize) -> Option<Self> {
+        Image::from_raw(bytes, info, row_bytes).map(Image::into_owned)
+    }
+
+    /// Creates Image taking ownership of `bytes`, without copying pixels.
+    ///
+    /// Returns None if parameters are invalid, see `validate_raw()`.
+    #[must_use]
+    pub fn from_vec(mut bytes: Vec<u8>, info: ImageInfo, row_bytes: usize) -> Option<Self> {
+        let byte_size = validate_raw(bytes.len(), &info, row_bytes)?;
+        bytes.truncate(byte_size);
+        Some(Self {
+            info,
+            row_bytes,
+            pixels: Cow::Owned(bytes),
+        })
+    }
+
+    /// Creates Image with a copy of pixels in `pixmap`.
+    ///
+    /// Returns None if `pixmap` is empty or invalid.
+    #[must_use]
+    pub fn from_pixmap(pixmap: &Pixmap) -> Option<Self> {
+        Self::from_raw_copy(pixmap.pixels(), pixmap.info().clone(), pixmap.row_bytes())
+    }
+}
+
+/// Checks that `len` bytes described by `info` and `row_bytes` form a valid image.
+///
+/// These are checked:
+/// - width and height are positive and not too large;
+/// - color type and alpha type are known;
+/// - `row_bytes` is at least `info.min_row_bytes()` and a multiple of pixel size;
+/// - byte size of pixels does not overflow and is at most `len`.
+///
+/// Returns byte size of pixels if valid.
+#[must_use]
+pub fn validate_raw(len: usize, info: &ImageInfo, row_bytes: usize) -> Option<usize> {
+    if !info.is_valid() || !info.valid_row_bytes(row_bytes) {
+        return None;
+    }
+    let byte_size = info.compute_byte_size(row_bytes);
+    if ImageInfo::byte_size_overflowed(byte_size) || byte_size > len {
+        return None;
+    }
+    Some(byte_size)
+}
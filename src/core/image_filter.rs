@@ -2,13 +2,216 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+use std::fmt;
+use std::rc::Rc;
+
+use crate::core::color::Color4f;
 use crate::core::color_filter::ColorFilter;
-use crate::core::flattenable::{Flattenable, Type};
+use crate::core::image_info::ImageInfo;
 use crate::core::irect::IRect;
 use crate::core::matrix::Matrix;
+use crate::core::pixmap::{pack_color4f, Pixmap};
 use crate::core::rect::Rect;
 
-/// Base class for image filters.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MapDirection {
+    Forward,
+    Reverse,
+}
+
+/// Premultiplied pixels read and written by image filters, placed at `bounds`
+/// in device space.
+///
+/// Pixels outside of `bounds` are transparent black.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterImage {
+    bounds: IRect,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl FilterImage {
+    /// Creates a transparent image covering `bounds`.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn new(bounds: IRect) -> Self {
+        if bounds.is_empty() {
+            return Self {
+                bounds: IRect::new(),
+                pixels: Vec::new(),
+            };
+        }
+        let len = bounds.width() as usize * bounds.height() as usize;
+        Self {
+            bounds,
+            pixels: vec![[0.0; 4]; len],
+        }
+    }
+
+    /// Copies pixels of `pixmap`, with its top left corner placed at (`x`, `y`).
+    ///
+    /// Returns None if color type of `pixmap` is not supported yet.
+    #[must_use]
+    pub fn from_pixmap(pixmap: &Pixmap, x: i32, y: i32) -> Option<Self> {
+        let mut image = Self::new(IRect::from_xywh(x, y, pixmap.width(), pixmap.height()));
+        for row in 0..pixmap.height() {
+            for column in 0..pixmap.width() {
+                let color = pixmap.get_color4f(column, row)?;
+                let alpha = color.alpha();
+                image.set_pixel(
+                    x + column,
+                    y + row,
+                    [
+                        color.red() * alpha,
+                        color.green() * alpha,
+                        color.blue() * alpha,
+                        alpha,
+                    ],
+                );
+            }
+        }
+        Some(image)
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn bounds(&self) -> &IRect {
+        &self.bounds
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// Returns pixels row by row.
+    #[must_use]
+    #[inline]
+    pub fn pixels(&self) -> &[[f32; 4]] {
+        &self.pixels
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    const fn index_of(&self, x: i32, y: i32) -> Option<usize> {
+        if self.bounds.contains(x, y) {
+            let row = (y - self.bounds.top()) as usize;
+            let column = (x - self.bounds.left()) as usize;
+            Some(row * self.bounds.width() as usize + column)
+        } else {
+            None
+        }
+    }
+
+    /// Returns premultiplied color of pixel at (`x`, `y`) in device space.
+    #[must_use]
+    pub fn pixel_at(&self, x: i32, y: i32) -> [f32; 4] {
+        self.index_of(x, y)
+            .map_or([0.0; 4], |index| self.pixels[index])
+    }
+
+    /// Sets premultiplied color of pixel at (`x`, `y`) in device space.
+    ///
+    /// Has no effect if the pixel is outside of bounds.
+    pub fn set_pixel(&mut self, x: i32, y: i32, pixel: [f32; 4]) {
+        if let Some(index) = self.index_of(x, y) {
+            self.pixels[index] = pixel;
+        }
+    }
+
+    /// Moves image by (`dx`, `dy`) pixels.
+    pub fn offset(&mut self, dx: i32, dy: i32) {
+        if !self.is_empty() {
+            self.bounds.offset(dx, dy);
+        }
+    }
+
+    /// Returns part of image inside of `rect`.
+    #[must_use]
+    pub fn crop(&self, rect: &IRect) -> Self {
+        let mut bounds = self.bounds.clone();
+        if !bounds.intersect(rect) {
+            return Self::new(IRect::new());
+        }
+        if bounds == self.bounds {
+            return self.clone();
+        }
+        let mut image = Self::new(bounds);
+        image.draw(self, |src, _dst| src);
+        image
+    }
+
+    /// Draws `images` one over another with source-over blending, into an image
+    /// covering all of them.
+    #[must_use]
+    pub fn merge(images: &[Self]) -> Self {
+        let mut bounds = IRect::new();
+        for image in images {
+            bounds.join(&image.bounds);
+        }
+        let mut merged = Self::new(bounds);
+        for image in images {
+            merged.draw(image, |src, dst| {
+                let scale = 1.0 - src[3];
+                [
+                    dst[0].mul_add(scale, src[0]),
+                    dst[1].mul_add(scale, src[1]),
+                    dst[2].mul_add(scale, src[2]),
+                    dst[3].mul_add(scale, src[3]),
+                ]
+            });
+        }
+        merged
+    }
+
+    /// Replaces each pixel covered by both images with result of `blend`, which is
+    /// called with pixel of `src` and pixel of self.
+    fn draw(&mut self, src: &Self, blend: impl Fn([f32; 4], [f32; 4]) -> [f32; 4]) {
+        let mut area = self.bounds.clone();
+        if !area.intersect(&src.bounds) {
+            return;
+        }
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                if let Some(index) = self.index_of(x, y) {
+                    self.pixels[index] = blend(src.pixel_at(x, y), self.pixels[index]);
+                }
+            }
+        }
+    }
+
+    /// Converts image to a premultiplied N32 pixmap, whose top left corner is at
+    /// top left corner of bounds.
+    ///
+    /// Returns None if image is empty.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn to_pixmap(&self) -> Option<Pixmap> {
+        if self.is_empty() {
+            return None;
+        }
+        let info = ImageInfo::new_n32_premul(self.bounds.width(), self.bounds.height(), None);
+        let row_bytes = info.min_row_bytes();
+        let bytes_per_pixel = info.bytes_per_pixel() as usize;
+        let mut data = vec![0; info.compute_byte_size(row_bytes)];
+        for (pixel, premul) in data.chunks_exact_mut(bytes_per_pixel).zip(&self.pixels) {
+            let alpha = premul[3].clamp(0.0, 1.0);
+            if alpha <= 0.0 {
+                continue;
+            }
+            let color = Color4f::from_rgba(
+                premul[0] / alpha,
+                premul[1] / alpha,
+                premul[2] / alpha,
+                alpha,
+            );
+            let packed = pack_color4f(info.color_type(), info.alpha_type(), &color)?;
+            pixel.copy_from_slice(&packed[..bytes_per_pixel]);
+        }
+        Some(Pixmap::from_vec(info, row_bytes, data))
+    }
+}
+
+/// Base trait for image filters.
 ///
 /// If one is installed in the paint, then all drawing occurs as usual, but it is
 /// as if the drawing happened into an offscreen (before the xfermode is applied).
@@ -16,28 +219,70 @@ use crate::core::rect::Rect;
 /// creates a new bitmap which is what will finally be drawn to the device
 /// (using the original xfermode).
 ///
+/// Image filters form a DAG: each filter reads its inputs, where a missing input
+/// is the source image, and its result may be cropped by its crop rect.
+///
 /// The local space of image filters matches the local space of the drawn geometry.
-/// For instance if there is rotation on the canvas, the blur will be computed
-/// along those rotated axes and not in the device space.
-/// In order to achieve this result, the actual drawing of the geometry may happen
-/// in an unrotated coordinate system so that the filtered image can be computed more easily,
-/// and then it will be post transformed to match what would have been produced
-/// if the geometry were drawn with the total canvas matrix to begin with.
-pub struct ImageFilter {}
+/// Filters map their parameters and crop rects to device space with `ctm`,
+/// which is the total matrix of Canvas when the filter is drawn.
+pub trait ImageFilter: fmt::Debug {
+    /// Returns the number of inputs this filter will accept (some inputs can be None).
+    fn count_inputs(&self) -> usize;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub enum MapDirection {
-    Forward,
-    Reverse,
-}
+    /// Returns the input filter at a given index, or None if no input is connected.
+    ///
+    /// The indices used are filter-specific.
+    fn get_input(&self, index: usize) -> Option<&Rc<dyn ImageFilter>>;
 
-impl Flattenable for ImageFilter {
-    fn get_flattenable_type(&self) -> Type {
-        Type::ImageFilter
+    /// Returns rect which the result of this filter is cropped to, or None.
+    fn crop_rect(&self) -> Option<&Rect> {
+        None
+    }
+
+    /// Returns the color filter if this filter is a color filter node without
+    /// inputs, which can be applied directly when drawing.
+    fn as_a_color_filter(&self) -> Option<Rc<ColorFilter>> {
+        None
+    }
+
+    /// Returns crop rect mapped to device space by `ctm`, rounded out to pixels.
+    fn device_crop_rect(&self, ctm: &Matrix) -> Option<IRect> {
+        self.crop_rect()
+            .map(|crop_rect| ctm.map_rect(crop_rect).round_out_to_irect())
+    }
+
+    /// Applies this filter to its inputs, where `source` is used for missing inputs.
+    ///
+    /// Crop rect is applied by `filter_image()`.
+    fn on_filter_image(&self, source: &FilterImage, ctm: &Matrix) -> FilterImage;
+
+    /// Maps `src` through this filter node, without its inputs and crop rect.
+    ///
+    /// See `filter_bounds()` for meaning of `ctm` and `dir`.
+    /// The default implementation returns `src`.
+    fn on_filter_node_bounds(&self, src: &IRect, _ctm: &Matrix, _dir: MapDirection) -> IRect {
+        src.clone()
+    }
+
+    /// Applies this filter to its inputs and crops the result.
+    ///
+    /// `source` is the image drawn with the paint, used for missing inputs.
+    /// `ctm` maps local space of filter to device space.
+    fn filter_image(&self, source: &FilterImage, ctm: &Matrix) -> FilterImage {
+        let image = self.on_filter_image(source, ctm);
+        match self.device_crop_rect(ctm) {
+            Some(crop_rect) => image.crop(&crop_rect),
+            None => image,
+        }
+    }
+
+    /// Returns result of input at `index` applied to `source`, or `source` if
+    /// the input is missing.
+    fn filter_input(&self, index: usize, source: &FilterImage, ctm: &Matrix) -> FilterImage {
+        self.get_input(index)
+            .map_or_else(|| source.clone(), |input| input.filter_image(source, ctm))
     }
-}
 
-impl ImageFilter {
     /// Map a device-space rect recursively forward or backward through the filter DAG.
     ///
     /// `MapDirection::Forward` is used to determine which pixels of the destination
@@ -45,73 +290,152 @@ impl ImageFilter {
     /// `MapDirection::Reverse` is used to determine which rect of the source image
     /// would be required to fill the given rect (typically, clip bounds).
     /// Used for clipping and temp-buffer allocations, so the result need not be exact,
-    /// but should never be smaller than the real answer. The default implementation
-    /// recursively unions all input bounds, or returns the source rect if no inputs.
-    ///
-    /// In Reverse mode, `input_rect` is the device-space bounds of the input pixels.
-    /// In Forward mode it should always be null.
-    /// If `input_rect` is null in Reverse mode the resulting answer may be incorrect.
-    #[must_use]
-    pub const fn filter_bounds(
-        &self,
-        _src: &IRect,
-        _ctm: &Matrix,
-        _dir: MapDirection,
-        _input_rect: Option<&IRect>,
-    ) -> IRect {
-        unimplemented!()
-    }
-
-    /// Returns whether this image filter is a color filter and puts the color filter into the
-    /// "filter" parameter if it can.
+    /// but should never be smaller than the real answer.
     ///
-    /// Does nothing otherwise.
-    /// If this returns false, then the filter is unchanged.
-    /// If this returns true, then if filter is not null, it must be set to a ref'd colorfitler
-    /// (i.e. it may not be set to NULL).
-    #[must_use]
-    pub fn is_color_filter_node(&self, _filter: &mut ColorFilter) -> bool {
-        unimplemented!()
+    /// `ctm` maps local space of filter to device space.
+    fn filter_bounds(&self, src: &IRect, ctm: &Matrix, dir: MapDirection) -> IRect {
+        let crop_rect = self.device_crop_rect(ctm);
+        let crop = |mut bounds: IRect| {
+            if let Some(crop_rect) = &crop_rect {
+                if !bounds.intersect(crop_rect) {
+                    bounds.set_empty();
+                }
+            }
+            bounds
+        };
+        match dir {
+            MapDirection::Forward => {
+                let bounds = self.filter_input_bounds(src, ctm, dir);
+                crop(self.on_filter_node_bounds(&bounds, ctm, dir))
+            }
+            MapDirection::Reverse => {
+                let bounds = self.on_filter_node_bounds(&crop(src.clone()), ctm, dir);
+                self.filter_input_bounds(&bounds, ctm, dir)
+            }
+        }
     }
 
-    /// Returns true (and optionally returns a ref'd filter) if this imagefilter can be completely
-    /// replaced by the returned colorfilter. i.e. the two effects will affect drawing in the same
-    /// way.
-    #[must_use]
-    pub fn as_a_color_filter(&self, _filter: &mut ColorFilter) -> bool {
-        unimplemented!()
+    /// Returns union of `src` mapped through each input, or `src` if there is no input.
+    fn filter_input_bounds(&self, src: &IRect, ctm: &Matrix, dir: MapDirection) -> IRect {
+        if self.count_inputs() == 0 {
+            return src.clone();
+        }
+        let mut bounds = IRect::new();
+        for index in 0..self.count_inputs() {
+            let input_bounds = self
+                .get_input(index)
+                .map_or_else(|| src.clone(), |input| input.filter_bounds(src, ctm, dir));
+            bounds.join(&input_bounds);
+        }
+        bounds
     }
+}
 
-    /// Returns the number of inputs this filter will accept (some inputs can be NULL).
-    #[must_use]
-    pub fn count_inputs(&self) -> i32 {
-        unimplemented!()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::offset_image_filter::OffsetImageFilter;
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const HALF_BLUE: [f32; 4] = [0.0, 0.0, 0.5, 0.5];
+
+    fn is_near(pixel: [f32; 4], expected: [f32; 4]) -> bool {
+        pixel
+            .iter()
+            .zip(expected)
+            .all(|(value, expected)| (value - expected).abs() < 0.01)
     }
 
-    /// Returns the input filter at a given index, or NULL if no input is connected.
-    /// The indices used are filter-specific.
-    #[must_use]
-    pub fn get_input(&self, _index: i32) -> &Self {
-        unimplemented!()
+    fn solid(bounds: &IRect, pixel: [f32; 4]) -> FilterImage {
+        let mut image = FilterImage::new(bounds.clone());
+        for y in bounds.top()..bounds.bottom() {
+            for x in bounds.left()..bounds.right() {
+                image.set_pixel(x, y, pixel);
+            }
+        }
+        image
     }
 
-    /// Can this filter DAG compute the resulting bounds of an object-space rectangle?
-    #[must_use]
-    pub fn can_compute_fast_bounds(&self) -> bool {
-        unimplemented!()
+    #[test]
+    fn test_new() {
+        let image = FilterImage::new(IRect::from_xywh(2, 3, 4, 5));
+        assert_eq!(image.pixels().len(), 20);
+        assert!(image.pixels().iter().all(|pixel| is_near(*pixel, [0.0; 4])));
+        assert!(FilterImage::new(IRect::from_xywh(2, 3, 0, 5)).is_empty());
     }
 
-    /// If this filter can be represented by another filter + a `local_matrix`,
-    /// return that filter, else return null.
-    #[must_use]
-    pub fn with_local_matrix(_matrix: &Matrix) -> Option<Self> {
-        unimplemented!()
+    #[test]
+    fn test_pixel_at() {
+        let mut image = solid(&IRect::from_xywh(2, 3, 4, 5), RED);
+        assert!(is_near(image.pixel_at(2, 3), RED));
+        assert!(is_near(image.pixel_at(5, 7), RED));
+        assert!(is_near(image.pixel_at(6, 7), [0.0; 4]));
+        assert!(is_near(image.pixel_at(1, 3), [0.0; 4]));
+        image.set_pixel(10, 10, HALF_BLUE);
+        assert!(is_near(image.pixel_at(10, 10), [0.0; 4]));
+
+        image.offset(-2, -3);
+        assert_eq!(*image.bounds(), IRect::from_xywh(0, 0, 4, 5));
+        assert!(is_near(image.pixel_at(0, 0), RED));
     }
-}
 
-pub trait ImageFilterTrait {
-    /// Default impl returns union of all input bounds.
-    fn compute_fast_bounds(&self, _bounds: &Rect) -> Rect {
-        unimplemented!()
+    #[test]
+    fn test_crop() {
+        let image = solid(&IRect::from_xywh(0, 0, 4, 4), RED);
+        let cropped = image.crop(&IRect::from_xywh(2, -1, 10, 2));
+        assert_eq!(*cropped.bounds(), IRect::from_xywh(2, 0, 2, 1));
+        assert!(cropped.pixels().iter().all(|pixel| is_near(*pixel, RED)));
+        assert!(image.crop(&IRect::from_xywh(8, 8, 2, 2)).is_empty());
+    }
+
+    #[test]
+    fn test_merge() {
+        let red = solid(&IRect::from_xywh(0, 0, 2, 2), RED);
+        let blue = solid(&IRect::from_xywh(1, 1, 2, 2), HALF_BLUE);
+        let merged = FilterImage::merge(&[red, blue]);
+        assert_eq!(*merged.bounds(), IRect::from_xywh(0, 0, 3, 3));
+        assert!(is_near(merged.pixel_at(0, 0), RED));
+        assert!(is_near(merged.pixel_at(1, 1), [0.5, 0.0, 0.5, 1.0]));
+        assert!(is_near(merged.pixel_at(2, 2), HALF_BLUE));
+        assert!(is_near(merged.pixel_at(2, 0), [0.0; 4]));
+    }
+
+    #[test]
+    fn test_to_pixmap() {
+        let mut image = solid(&IRect::from_xywh(5, 5, 2, 1), RED);
+        image.set_pixel(6, 5, HALF_BLUE);
+        let pixmap = image.to_pixmap().unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (2, 1));
+        let copy = FilterImage::from_pixmap(&pixmap, 5, 5).unwrap();
+        for (pixel, expected) in copy.pixels().iter().zip(image.pixels()) {
+            assert!(is_near(*pixel, *expected));
+        }
+        assert!(FilterImage::new(IRect::new()).to_pixmap().is_none());
+    }
+
+    #[test]
+    fn test_filter_crop_rect() {
+        let crop_rect = Rect::from_xywh(0.0, 0.0, 4.0, 4.0);
+        let filter = OffsetImageFilter::new(2.0, 0.0, None, Some(&crop_rect)).unwrap();
+        let ctm = Matrix::from_scale(2.0, 2.0);
+        assert_eq!(
+            filter.device_crop_rect(&ctm),
+            Some(IRect::from_xywh(0, 0, 8, 8))
+        );
+
+        // Moved by 4 device pixels, then cropped.
+        let source = solid(&IRect::from_xywh(0, 0, 6, 6), RED);
+        let image = filter.filter_image(&source, &ctm);
+        assert_eq!(*image.bounds(), IRect::from_xywh(4, 0, 4, 6));
+
+        let src = IRect::from_xywh(0, 0, 6, 6);
+        assert_eq!(
+            filter.filter_bounds(&src, &ctm, MapDirection::Forward),
+            IRect::from_xywh(4, 0, 4, 6)
+        );
+        assert_eq!(
+            filter.filter_bounds(&src, &ctm, MapDirection::Reverse),
+            IRect::from_xywh(-4, 0, 6, 6)
+        );
     }
 }
@@ -334,7 +334,7 @@ impl ImageInfo {
     #[must_use]
     pub const fn new_n32_premul(width: i32, height: i32, cs: Option<ColorSpace>) -> Self {
         Self {
-            color_info: ColorInfo::from(ColorType::Alpha8, AlphaType::Premul, cs),
+            color_info: ColorInfo::from(color_type::N32, AlphaType::Premul, cs),
             dimensions: ISize::from_wh(width, height),
         }
     }
@@ -585,8 +585,14 @@ impl ImageInfo {
     /// - `y` - row index, zero or greater, and less than `height()`
     /// - `row_bytes` - size of pixel row or larger
     #[must_use]
-    pub const fn compute_offset(&self, _x: i32, _y: i32, _row_bytes: usize) -> usize {
-        unimplemented!()
+    #[allow(clippy::cast_sign_loss)]
+    pub const fn compute_offset(&self, x: i32, y: i32, row_bytes: usize) -> usize {
+        debug_assert!(x >= 0 && x < self.width());
+        debug_assert!(y >= 0 && y < self.height());
+        if matches!(self.color_type(), ColorType::Unknown) {
+            return 0;
+        }
+        (y as usize) * row_bytes + ((x as usize) << self.shift_per_pixel())
     }
 
     /// Returns storage required by pixel array, given `ImageInfo` dimensions,
@@ -597,8 +603,26 @@ impl ImageInfo {
     /// Returns zero if height is zero.
     /// Returns `usize::MAX` if answer exceeds the range of usize.
     #[must_use]
-    pub const fn compute_byte_size(&self, _row_bytes: usize) -> usize {
-        unimplemented!()
+    #[allow(clippy::cast_sign_loss)]
+    pub const fn compute_byte_size(&self, row_bytes: usize) -> usize {
+        if self.height() <= 0 {
+            return 0;
+        }
+        let Some(rows) = ((self.height() - 1) as usize).checked_mul(row_bytes) else {
+            return usize::MAX;
+        };
+        let Some(last_row) = (self.width() as usize).checked_mul(self.bytes_per_pixel() as usize)
+        else {
+            return usize::MAX;
+        };
+        let Some(bytes) = rows.checked_add(last_row) else {
+            return usize::MAX;
+        };
+        // Some memory operations take signed 32-bit offset from the base.
+        if bytes > MAX_S32 as usize {
+            return usize::MAX;
+        }
+        bytes
     }
 
     /// Returns storage required by pixel array, given `ImageInfo` dimensions,
@@ -446,8 +446,8 @@ impl IRect {
         // TODO(Shaohua): Check add overflow
         self.left += dx;
         self.top += dy;
-        self.right += dx;
-        self.bottom += dy;
+        self.right -= dx;
+        self.bottom -= dy;
     }
 
     /// Outsets `IRect` by (dx, dy).
@@ -512,7 +512,7 @@ impl IRect {
     #[must_use]
     pub const fn contains_rect(&self, other: &Self) -> bool {
         !other.is_empty()
-            && self.is_empty()
+            && !self.is_empty()
             && self.left <= other.left
             && self.top <= other.top
             && self.right >= other.right
@@ -538,16 +538,35 @@ impl IRect {
     ///
     /// Returns false if `IRect` does not intersect r, and leaves `IRect` unchanged.
     /// Returns false if either r or `IRect` is empty, leaving `IRect` unchanged.
-    #[must_use]
-    pub const fn intersect(&self, _r: &Self) -> bool {
-        unimplemented!()
+    pub fn intersect(&mut self, r: &Self) -> bool {
+        let left = self.left.max(r.left);
+        let top = self.top.max(r.top);
+        let right = self.right.min(r.right);
+        let bottom = self.bottom.min(r.bottom);
+        if self.is_empty() || r.is_empty() || left >= right || top >= bottom {
+            return false;
+        }
+        self.set_ltrb(left, top, right, bottom);
+        true
     }
 
     /// Sets `IRect` to the union of itself and r.
     ///
     /// Has no effect if r is empty. Otherwise, if `IRect` is empty, sets `IRect` to r.
-    pub fn join(&mut self, _r: &Self) {
-        unimplemented!()
+    pub fn join(&mut self, r: &Self) {
+        if r.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            self.set_ltrb(r.left, r.top, r.right, r.bottom);
+        } else {
+            self.set_ltrb(
+                self.left.min(r.left),
+                self.top.min(r.top),
+                self.right.max(r.right),
+                self.bottom.max(r.bottom),
+            );
+        }
     }
 
     /// Swaps left and right if left is greater than right;
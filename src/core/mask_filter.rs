@@ -2,7 +2,10 @@
 // Use of this source is governed by General Public License that can be found
 // in the LICENSE file.
 
+use crate::core::blur_priv::{blur_plane, blur_radius, gaussian_kernel};
 use crate::core::blur_types::BlurStyle;
+use crate::core::mask::{Mask, MaskFormat};
+use crate::core::matrix::Matrix;
 use crate::core::rect::Rect;
 use crate::core::scalar::Scalar;
 
@@ -35,11 +38,232 @@ impl MaskFilter {
         }
     }
 
+    /// Create a blur maskfilter whose sigma is modified by the CTM.
+    ///
+    /// # Parameters
+    /// - `style` - The `BlurStyle` to use
+    /// - `sigma` - Standard deviation of the Gaussian blur to apply.
+    ///
+    /// Returns None if `sigma` is not larger than zero or not finite.
+    #[must_use]
+    pub fn blur(style: BlurStyle, sigma: Scalar) -> Option<Self> {
+        if !(sigma > 0.0 && sigma.is_finite()) {
+            return None;
+        }
+        Some(Self::new(style, sigma, true))
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn style(&self) -> BlurStyle {
+        self.style
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn sigma(&self) -> Scalar {
+        self.sigma
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn respect_ctm(&self) -> bool {
+        self.respect_ctm
+    }
+
+    /// Returns sigma in device space, which is mapped by `ctm` if `respect_ctm()`
+    /// is true.
+    #[must_use]
+    pub fn compute_xformed_sigma(&self, ctm: &Matrix) -> Scalar {
+        if self.respect_ctm {
+            ctm.map_radius(self.sigma)
+        } else {
+            self.sigma
+        }
+    }
+
+    /// Returns number of pixels the blur spreads on each side.
+    #[must_use]
+    #[inline]
+    pub fn blur_radius(&self) -> i32 {
+        blur_radius(self.sigma)
+    }
+
     /// Returns the approximate bounds that would result from filtering the src rect.
     ///
     /// The actual result may be different, but it should be contained within the returned bounds.
     #[must_use]
-    pub const fn approximate_filtered_bounds(_src: &Rect) -> Rect {
-        unimplemented!()
+    #[allow(clippy::cast_precision_loss)]
+    pub fn approximate_filtered_bounds(&self, src: &Rect) -> Rect {
+        let mut bounds = src.clone();
+        let radius = self.blur_radius() as Scalar;
+        bounds.outset(radius, radius);
+        bounds
+    }
+
+    /// Blurs `src` mask with the Gaussian blur and style of this filter.
+    ///
+    /// The returned mask is outset by `blur_radius()` on each side.
+    ///
+    /// Returns None if `src` is not an `MaskFormat::A8` mask with pixels, or if the
+    /// result is too large.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub(crate) fn filter_mask(&self, src: &Mask) -> Option<Mask> {
+        let width = src.width().max(0) as usize;
+        let height = src.height().max(0) as usize;
+        if src.format() != MaskFormat::A8
+            || src.is_empty()
+            || src.row_bytes() < width
+            || src.image().len() < src.compute_image_size()
+        {
+            return None;
+        }
+
+        let radius = self.blur_radius();
+        let mut dst = src.prepare_destination(radius, radius);
+        if dst.is_empty() {
+            return None;
+        }
+        let radius = radius as usize;
+        let dst_width = dst.width() as usize;
+        let dst_height = dst.height() as usize;
+
+        // Coverage of src at its place in dst.
+        let mut coverage = vec![0.0; dst_width * dst_height];
+        for (y, row) in src.image().chunks(src.row_bytes()).take(height).enumerate() {
+            let start = (y + radius) * dst_width + radius;
+            for (value, &alpha) in coverage[start..start + width].iter_mut().zip(row) {
+                *value = Scalar::from(alpha) / 255.0;
+            }
+        }
+
+        let kernel = gaussian_kernel(self.sigma);
+        let blurred = blur_plane(&coverage, dst_width, dst_height, &kernel, &kernel);
+
+        let image = dst.image_mut();
+        for ((pixel, &blur), &src) in image.iter_mut().zip(&blurred).zip(&coverage) {
+            let value = match self.style {
+                BlurStyle::Normal => blur,
+                BlurStyle::Solid => blur.mul_add(1.0 - src, src),
+                BlurStyle::Outer => blur * (1.0 - src),
+                BlurStyle::Inner => blur * src,
+            };
+            *pixel = alpha_from_scalar(value);
+        }
+        Some(dst)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn alpha_from_scalar(value: Scalar) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::irect::IRect;
+
+    /// Returns a fully covered A8 mask of 10x10 at (20, 30).
+    fn square_mask() -> Mask {
+        let mut mask = Mask::from_vec(vec![255; 100], IRect::from_xywh(20, 30, 10, 10)).unwrap();
+        mask.set_format(MaskFormat::A8);
+        mask.set_row_bytes(10);
+        mask
+    }
+
+    fn filter(style: BlurStyle) -> (Mask, usize) {
+        let filter = MaskFilter::blur(style, 2.0).unwrap();
+        let radius = usize::try_from(filter.blur_radius()).unwrap();
+        (filter.filter_mask(&square_mask()).unwrap(), radius)
+    }
+
+    /// Returns alpha at (x, y) relative to the source mask.
+    fn alpha_at(mask: &Mask, radius: usize, x: usize, y: usize) -> u8 {
+        let width = usize::try_from(mask.width()).unwrap();
+        mask.image()[(y + radius) * width + x + radius]
+    }
+
+    #[test]
+    fn test_blur() {
+        assert!(MaskFilter::blur(BlurStyle::Normal, 0.0).is_none());
+        assert!(MaskFilter::blur(BlurStyle::Normal, -1.0).is_none());
+        assert!(MaskFilter::blur(BlurStyle::Normal, Scalar::NAN).is_none());
+        let filter = MaskFilter::blur(BlurStyle::Normal, 2.0).unwrap();
+        assert!(filter.respect_ctm());
+        assert_eq!(filter.blur_radius(), 6);
+    }
+
+    #[test]
+    fn test_compute_xformed_sigma() {
+        let scale = Matrix::from_scale(3.0, 3.0);
+        let filter = MaskFilter::new(BlurStyle::Normal, 2.0, true);
+        assert!((filter.compute_xformed_sigma(&scale) - 6.0).abs() < 1e-4);
+        let filter = MaskFilter::new(BlurStyle::Normal, 2.0, false);
+        assert!((filter.compute_xformed_sigma(&scale) - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_approximate_filtered_bounds() {
+        let filter = MaskFilter::blur(BlurStyle::Normal, 2.0).unwrap();
+        let bounds = filter.approximate_filtered_bounds(&Rect::from_xywh(20.0, 30.0, 10.0, 10.0));
+        assert_eq!(bounds, Rect::from_xywh(14.0, 24.0, 22.0, 22.0));
+    }
+
+    #[test]
+    fn test_normal() {
+        let (mask, radius) = filter(BlurStyle::Normal);
+        assert_eq!(mask.format(), MaskFormat::A8);
+        assert_eq!(*mask.bounds(), IRect::from_xywh(14, 24, 22, 22));
+
+        // Blur keeps total coverage.
+        let total: u32 = mask.image().iter().map(|&alpha| u32::from(alpha)).sum();
+        let expected = 100 * 255;
+        assert!(total.abs_diff(expected) < expected / 100);
+
+        // Center stays covered, edges are half covered and corners of result are empty.
+        assert!(alpha_at(&mask, radius, 5, 5) > 240);
+        assert!(alpha_at(&mask, radius, 0, 5).abs_diff(150) < 20);
+        assert_eq!(mask.image()[0], 0);
+        assert!(mask
+            .image()
+            .iter()
+            .all(|&alpha| alpha <= alpha_at(&mask, radius, 5, 5)));
+    }
+
+    #[test]
+    fn test_styles() {
+        let (normal, radius) = filter(BlurStyle::Normal);
+        let (solid, _) = filter(BlurStyle::Solid);
+        let (outer, _) = filter(BlurStyle::Outer);
+        let (inner, _) = filter(BlurStyle::Inner);
+
+        // Inside of source.
+        assert_eq!(alpha_at(&solid, radius, 0, 0), 255);
+        assert_eq!(alpha_at(&outer, radius, 0, 0), 0);
+        assert_eq!(
+            alpha_at(&inner, radius, 0, 0),
+            alpha_at(&normal, radius, 0, 0)
+        );
+
+        // Outside of source.
+        let x = radius - 2;
+        let width = usize::try_from(normal.width()).unwrap();
+        let outside = (radius + 5) * width + x;
+        assert!(normal.image()[outside] > 0);
+        assert_eq!(solid.image()[outside], normal.image()[outside]);
+        assert_eq!(outer.image()[outside], normal.image()[outside]);
+        assert_eq!(inner.image()[outside], 0);
+    }
+
+    #[test]
+    fn test_filter_mask_rejects_bw() {
+        let filter = MaskFilter::blur(BlurStyle::Normal, 2.0).unwrap();
+        let mut mask = square_mask();
+        mask.set_format(MaskFormat::Bw);
+        assert!(filter.filter_mask(&mask).is_none());
+        assert!(filter.filter_mask(&Mask::new()).is_none());
     }
 }
@@ -5,7 +5,8 @@
 use bitflags::bitflags;
 
 use crate::core::point::Point;
-use crate::core::scalar::Scalar;
+use crate::core::rect::Rect;
+use crate::core::scalar::{Scalar, ScalarExt, SCALAR_NEARLY_ZERO};
 
 /// When we transform points through a matrix containing perspective (the bottom row is something
 /// other than 0,0,1), the bruteforce math can produce confusing results (since we might divide
@@ -143,7 +144,7 @@ pub const MAP_PTS_PROCS: &[MapPtsProc] = &[
     Matrix::identity_pts,
     Matrix::trans_pts,
     Matrix::scale_pts,
-    Matrix::scale_pts,
+    Matrix::scale_trans_pts,
     Matrix::affine_vpts,
     Matrix::affine_vpts,
     Matrix::affine_vpts,
@@ -163,14 +164,14 @@ impl TypeMask {
     #[must_use]
     pub fn get_map_xy_proc(self) -> MapXYProc {
         debug_assert!((self & !Self::ALL_MASKS) == Self::empty());
-        let index = (self & Self::ALL_MASKS).bits() as usize;
+        let index = (self & Self::ORABLE_MASKS).bits() as usize;
         MAP_XY_PROCS[index]
     }
 
     #[must_use]
     pub fn get_map_pts_proc(self) -> MapPtsProc {
         debug_assert!((self & !Self::ALL_MASKS) == Self::empty());
-        let index = (self & Self::ALL_MASKS).bits() as usize;
+        let index = (self & Self::ORABLE_MASKS).bits() as usize;
         MAP_PTS_PROCS[index]
     }
 }
@@ -181,26 +182,204 @@ pub struct Matrix {
     type_mask: TypeMask,
 }
 
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
 impl Matrix {
+    /// Returns identity Matrix, which maps points to themselves.
     #[must_use]
     pub const fn identity() -> Self {
-        unimplemented!()
+        #[rustfmt::skip]
+        let matrix = Self::from(
+            1.0, 0.0, 0.0,
+            0.0, 1.0, 0.0,
+            0.0, 0.0, 1.0,
+            TypeMask::RECT_STAYS_RECT,
+        );
+        matrix
     }
 
+    /// Creates Matrix from integer values, in row-major order.
     #[must_use]
     #[allow(clippy::too_many_arguments)]
-    pub const fn from_i32(
-        _m00: i32,
-        _m01: i32,
-        _m02: i32,
-        _m10: i32,
-        _m11: i32,
-        _m12: i32,
-        _m20: i32,
-        _m21: i32,
-        _m22: i32,
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_i32(
+        m00: i32,
+        m01: i32,
+        m02: i32,
+        m10: i32,
+        m11: i32,
+        m12: i32,
+        m20: i32,
+        m21: i32,
+        m22: i32,
     ) -> Self {
-        unimplemented!()
+        Self::from_all(
+            m00 as Scalar,
+            m01 as Scalar,
+            m02 as Scalar,
+            m10 as Scalar,
+            m11 as Scalar,
+            m12 as Scalar,
+            m20 as Scalar,
+            m21 as Scalar,
+            m22 as Scalar,
+        )
+    }
+
+    /// Creates Matrix from all nine values, in row-major order.
+    ///
+    /// # Parameters
+    /// - `scale_x` - horizontal scale factor
+    /// - `skew_x` - horizontal skew factor
+    /// - `trans_x` - horizontal translation
+    /// - `skew_y` - vertical skew factor
+    /// - `scale_y` - vertical scale factor
+    /// - `trans_y` - vertical translation
+    /// - `pers_0` - input x-axis perspective factor
+    /// - `pers_1` - input y-axis perspective factor
+    /// - `pers_2` - perspective scale factor
+    #[must_use]
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_all(
+        scale_x: Scalar,
+        skew_x: Scalar,
+        trans_x: Scalar,
+        skew_y: Scalar,
+        scale_y: Scalar,
+        trans_y: Scalar,
+        pers_0: Scalar,
+        pers_1: Scalar,
+        pers_2: Scalar,
+    ) -> Self {
+        #[rustfmt::skip]
+        let mut matrix = Self::from(
+            scale_x, skew_x, trans_x,
+            skew_y, scale_y, trans_y,
+            pers_0, pers_1, pers_2,
+            TypeMask::UNKNOWN,
+        );
+        matrix.type_mask = matrix.compute_type_mask();
+        matrix
+    }
+
+    /// Creates Matrix which translates by (dx, dy).
+    #[must_use]
+    pub fn from_translate(dx: Scalar, dy: Scalar) -> Self {
+        Self::from_all(1.0, 0.0, dx, 0.0, 1.0, dy, 0.0, 0.0, 1.0)
+    }
+
+    /// Creates Matrix which scales by (sx, sy) about the origin.
+    #[must_use]
+    pub fn from_scale(sx: Scalar, sy: Scalar) -> Self {
+        Self::from_all(sx, 0.0, 0.0, 0.0, sy, 0.0, 0.0, 0.0, 1.0)
+    }
+
+    /// Creates Matrix which rotates by `degrees` about the origin.
+    ///
+    /// Positive degrees rotates clockwise, as y-axis points down.
+    #[must_use]
+    pub fn from_rotate(degrees: Scalar) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        // Snap values of multiples of 90 degrees.
+        let snap = |value: Scalar| {
+            if value.nearly_zero() {
+                0.0
+            } else {
+                value
+            }
+        };
+        let (sin, cos) = (snap(sin), snap(cos));
+        Self::from_all(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0)
+    }
+
+    /// Returns Matrix `a` multiplied by Matrix `b`, which maps points by `b` first.
+    #[must_use]
+    pub fn from_concat(a: &Self, b: &Self) -> Self {
+        if a.is_trivially_identity() {
+            return b.clone();
+        }
+        if b.is_trivially_identity() {
+            return a.clone();
+        }
+        let mut mat = [0.0; 9];
+        for row in 0..3 {
+            for col in 0..3 {
+                mat[row * 3 + col] = a.mat[row * 3].mul_add(
+                    b.mat[col],
+                    a.mat[row * 3 + 1].mul_add(b.mat[3 + col], a.mat[row * 3 + 2] * b.mat[6 + col]),
+                );
+            }
+        }
+        Self::from_all(
+            mat[0], mat[1], mat[2], mat[3], mat[4], mat[5], mat[6], mat[7], mat[8],
+        )
+    }
+
+    /// Returns one matrix value, `index` is one of `M_SCALE_X` to `M_PERSP_2`.
+    #[must_use]
+    #[inline]
+    pub const fn get(&self, index: usize) -> Scalar {
+        debug_assert!(index < 9);
+        self.mat[index]
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn scale_x(&self) -> Scalar {
+        self.mat[M_SCALE_X]
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn scale_y(&self) -> Scalar {
+        self.mat[M_SCALE_Y]
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn skew_x(&self) -> Scalar {
+        self.mat[M_SKEW_X]
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn skew_y(&self) -> Scalar {
+        self.mat[M_SKEW_Y]
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn translate_x(&self) -> Scalar {
+        self.mat[M_TRANS_X]
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn translate_y(&self) -> Scalar {
+        self.mat[M_TRANS_Y]
+    }
+
+    /// Returns true if Matrix is identity.
+    #[must_use]
+    pub fn is_identity(&self) -> bool {
+        self.type_mask_now() & TypeMask::ORABLE_MASKS == TypeMask::empty()
+    }
+
+    /// Returns true if Matrix contains perspective elements.
+    #[must_use]
+    pub fn has_perspective(&self) -> bool {
+        self.type_mask_now().contains(TypeMask::PERSPECTIVE)
+    }
+
+    /// Returns true if Matrix maps rectangles to rectangles, which is true for
+    /// scaling, and for rotating by multiples of 90 degrees.
+    #[must_use]
+    pub fn rect_stays_rect(&self) -> bool {
+        self.type_mask_now().contains(TypeMask::RECT_STAYS_RECT)
     }
 
     /// Returns a bit field describing the transformations the matrix may perform.
@@ -217,6 +396,73 @@ impl Matrix {
         // only return the public masks
         self.type_mask & TypeMask::ORABLE_MASKS
     }
+
+    /// Sets inverse to the inverse of Matrix.
+    ///
+    /// Returns false and leaves `inverse` unchanged if Matrix can not be inverted.
+    #[must_use]
+    pub fn invert(&self, inverse: &mut Self) -> bool {
+        if self.is_identity() {
+            *inverse = Self::identity();
+            return true;
+        }
+        self.invert_non_identity(inverse)
+    }
+
+    /// Returns point (x, y) mapped by Matrix.
+    #[must_use]
+    pub fn map_xy(&self, x: Scalar, y: Scalar) -> Point {
+        let mut point = Point::new();
+        let proc = self.type_mask_now().get_map_xy_proc();
+        proc(self, x, y, &mut point);
+        point
+    }
+
+    /// Maps `src` points to `dst` by Matrix.
+    ///
+    /// Only the first `min(src.len(), dst.len())` points are mapped.
+    pub fn map_points(&self, dst: &mut [Point], src: &[Point]) {
+        let count = src.len().min(dst.len());
+        let proc = self.type_mask_now().get_map_pts_proc();
+        proc(self, dst, src, count);
+    }
+
+    /// Returns vector (`dx`, `dy`) mapped by Matrix, ignoring translation.
+    ///
+    /// Perspective components are ignored.
+    #[must_use]
+    pub fn map_vector(&self, dx: Scalar, dy: Scalar) -> Point {
+        let m = &self.mat;
+        Point::from_xy(
+            m[M_SCALE_X].mul_add(dx, m[M_SKEW_X] * dy),
+            m[M_SKEW_Y].mul_add(dx, m[M_SCALE_Y] * dy),
+        )
+    }
+
+    /// Returns bounds of the four corners of `src` mapped by Matrix.
+    #[must_use]
+    pub fn map_rect(&self, src: &Rect) -> Rect {
+        let corners = [
+            Point::from_xy(src.left(), src.top()),
+            Point::from_xy(src.right(), src.top()),
+            Point::from_xy(src.right(), src.bottom()),
+            Point::from_xy(src.left(), src.bottom()),
+        ];
+        let mut mapped = [Point::new(); 4];
+        self.map_points(&mut mapped, &corners);
+        Rect::from_points(&mapped)
+    }
+
+    /// Returns geometric mean of lengths of vectors (`radius`, 0) and (0, `radius`)
+    /// mapped by Matrix.
+    ///
+    /// This is the mapped radius of a circle, if Matrix scales uniformly.
+    #[must_use]
+    pub fn map_radius(&self, radius: Scalar) -> Scalar {
+        let d0 = self.map_vector(radius, 0.0).length();
+        let d1 = self.map_vector(0.0, radius).length();
+        (d0 * d1).sqrt()
+    }
 }
 
 // Private methods
@@ -239,20 +485,79 @@ impl Matrix {
         }
     }
 
-    fn compute_inv(_dst: &mut [Scalar; 9], _src: &[Scalar; 9], _inv_det: f64, _is_persp: bool) {
-        unimplemented!()
+    /// Returns type mask without caching it.
+    #[must_use]
+    fn type_mask_now(&self) -> TypeMask {
+        if self.type_mask.contains(TypeMask::UNKNOWN) {
+            self.compute_type_mask()
+        } else {
+            self.type_mask
+        }
+    }
+
+    /// Writes adjugate of `src` scaled by `inv_det` to `dst`.
+    #[allow(clippy::cast_possible_truncation)]
+    fn compute_inv(dst: &mut [Scalar; 9], src: &[Scalar; 9], inv_det: f64, is_persp: bool) {
+        let m = src.map(f64::from);
+        let cross = |a: usize, b: usize, c: usize, d: usize| {
+            (m[a].mul_add(m[b], -m[c] * m[d]) * inv_det) as Scalar
+        };
+        dst[M_SCALE_X] = cross(M_SCALE_Y, M_PERSP_2, M_TRANS_Y, M_PERSP_1);
+        dst[M_SKEW_X] = cross(M_TRANS_X, M_PERSP_1, M_SKEW_X, M_PERSP_2);
+        dst[M_TRANS_X] = cross(M_SKEW_X, M_TRANS_Y, M_TRANS_X, M_SCALE_Y);
+        dst[M_SKEW_Y] = cross(M_TRANS_Y, M_PERSP_0, M_SKEW_Y, M_PERSP_2);
+        dst[M_SCALE_Y] = cross(M_SCALE_X, M_PERSP_2, M_TRANS_X, M_PERSP_0);
+        dst[M_TRANS_Y] = cross(M_TRANS_X, M_SKEW_Y, M_SCALE_X, M_TRANS_Y);
+        if is_persp {
+            dst[M_PERSP_0] = cross(M_SKEW_Y, M_PERSP_1, M_SCALE_Y, M_PERSP_0);
+            dst[M_PERSP_1] = cross(M_SKEW_X, M_PERSP_0, M_SCALE_X, M_PERSP_1);
+            dst[M_PERSP_2] = cross(M_SCALE_X, M_SCALE_Y, M_SKEW_X, M_SKEW_Y);
+        } else {
+            dst[M_PERSP_0] = 0.0;
+            dst[M_PERSP_1] = 0.0;
+            dst[M_PERSP_2] = 1.0;
+        }
     }
 
     #[must_use]
+    #[allow(clippy::float_cmp)]
     fn compute_type_mask(&self) -> TypeMask {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+        let m = &self.mat;
+        if m[M_PERSP_0] != 0.0 || m[M_PERSP_1] != 0.0 || m[M_PERSP_2] != 1.0 {
+            // Once it is determined that this is a perspective transform,
+            // all other flags are moot as far as optimizations are concerned.
+            return TypeMask::ORABLE_MASKS;
+        }
+
+        let mut mask = TypeMask::empty();
+        if m[M_TRANS_X] != 0.0 || m[M_TRANS_Y] != 0.0 {
+            mask |= TypeMask::TRANSLATE;
+        }
+        let rect_stays_rect = if m[M_SKEW_X] != 0.0 || m[M_SKEW_Y] != 0.0 {
+            // The skew components may be scale-inducing, unless we are dealing
+            // with a pure rotation.
+            mask |= TypeMask::AFFINE | TypeMask::SCALE;
+            m[M_SCALE_X] == 0.0 && m[M_SCALE_Y] == 0.0
+        } else {
+            if m[M_SCALE_X] != 1.0 || m[M_SCALE_Y] != 1.0 {
+                mask |= TypeMask::SCALE;
+            }
+            m[M_SCALE_X] != 0.0 && m[M_SCALE_Y] != 0.0
+        };
+        if rect_stays_rect {
+            mask |= TypeMask::RECT_STAYS_RECT;
+        }
+        mask
     }
 
     #[must_use]
+    #[allow(clippy::float_cmp)]
     fn compute_perspective_type_mask(&self) -> TypeMask {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+        let m = &self.mat;
+        if m[M_PERSP_0] != 0.0 || m[M_PERSP_1] != 0.0 || m[M_PERSP_2] != 1.0 {
+            return TypeMask::ORABLE_MASKS;
+        }
+        TypeMask::ONLY_PERSPECTIVE_VALID | TypeMask::UNKNOWN
     }
 
     fn set_type_mask(&mut self, mask: TypeMask) {
@@ -312,9 +617,47 @@ impl Matrix {
     }
 
     #[must_use]
-    fn invert_non_identity(&self, _inverse: &mut Self) -> bool {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn invert_non_identity(&self, inverse: &mut Self) -> bool {
+        let mask = self.type_mask_now();
+        if !mask.intersects(TypeMask::SCALE | TypeMask::AFFINE | TypeMask::PERSPECTIVE) {
+            // Translation only.
+            *inverse = Self::from_translate(-self.mat[M_TRANS_X], -self.mat[M_TRANS_Y]);
+            return inverse.is_finite();
+        }
+
+        let is_persp = mask.contains(TypeMask::PERSPECTIVE);
+        let m = self.mat.map(f64::from);
+        let det = if is_persp {
+            m[M_SCALE_X].mul_add(
+                m[M_SCALE_Y].mul_add(m[M_PERSP_2], -m[M_TRANS_Y] * m[M_PERSP_1]),
+                m[M_SKEW_X].mul_add(
+                    m[M_TRANS_Y].mul_add(m[M_PERSP_0], -m[M_SKEW_Y] * m[M_PERSP_2]),
+                    m[M_TRANS_X] * m[M_SKEW_Y].mul_add(m[M_PERSP_1], -m[M_SCALE_Y] * m[M_PERSP_0]),
+                ),
+            )
+        } else {
+            m[M_SCALE_X].mul_add(m[M_SCALE_Y], -m[M_SKEW_X] * m[M_SKEW_Y])
+        };
+        let tolerance = f64::from(SCALAR_NEARLY_ZERO).powi(3);
+        if det.abs() <= tolerance || !det.is_finite() {
+            return false;
+        }
+
+        let mut mat = [0.0; 9];
+        Self::compute_inv(&mut mat, &self.mat, det.recip(), is_persp);
+        let result = Self::from_all(
+            mat[0], mat[1], mat[2], mat[3], mat[4], mat[5], mat[6], mat[7], mat[8],
+        );
+        if !result.is_finite() {
+            return false;
+        }
+        *inverse = result;
+        true
+    }
+
+    #[must_use]
+    fn is_finite(&self) -> bool {
+        self.mat.iter().all(|value| value.is_finite())
     }
 
     #[must_use]
@@ -332,69 +675,90 @@ impl Matrix {
         unimplemented!()
     }
 
-    fn identity_xy(&self, _x: Scalar, _y: Scalar, _point: &mut Point) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    #[allow(clippy::unused_self)]
+    fn identity_xy(&self, x: Scalar, y: Scalar, point: &mut Point) {
+        point.set(x, y);
     }
 
-    fn trans_xy(&self, _x: Scalar, _y: Scalar, _point: &mut Point) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn trans_xy(&self, x: Scalar, y: Scalar, point: &mut Point) {
+        point.set(x + self.mat[M_TRANS_X], y + self.mat[M_TRANS_Y]);
     }
 
-    fn scale_xy(&self, _x: Scalar, _y: Scalar, _point: &mut Point) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn scale_xy(&self, x: Scalar, y: Scalar, point: &mut Point) {
+        point.set(x * self.mat[M_SCALE_X], y * self.mat[M_SCALE_Y]);
     }
 
-    fn scale_trans_xy(&self, _x: Scalar, _y: Scalar, _point: &mut Point) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn scale_trans_xy(&self, x: Scalar, y: Scalar, point: &mut Point) {
+        point.set(
+            x.mul_add(self.mat[M_SCALE_X], self.mat[M_TRANS_X]),
+            y.mul_add(self.mat[M_SCALE_Y], self.mat[M_TRANS_Y]),
+        );
     }
 
-    fn rot_xy(&self, _x: Scalar, _y: Scalar, _point: &mut Point) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn rot_xy(&self, x: Scalar, y: Scalar, point: &mut Point) {
+        point.set(
+            x.mul_add(self.mat[M_SCALE_X], y * self.mat[M_SKEW_X]),
+            x.mul_add(self.mat[M_SKEW_Y], y * self.mat[M_SCALE_Y]),
+        );
     }
 
-    fn rot_trans_xy(&self, _x: Scalar, _y: Scalar, _point: &mut Point) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn rot_trans_xy(&self, x: Scalar, y: Scalar, point: &mut Point) {
+        point.set(
+            x.mul_add(
+                self.mat[M_SCALE_X],
+                y.mul_add(self.mat[M_SKEW_X], self.mat[M_TRANS_X]),
+            ),
+            x.mul_add(
+                self.mat[M_SKEW_Y],
+                y.mul_add(self.mat[M_SCALE_Y], self.mat[M_TRANS_Y]),
+            ),
+        );
     }
 
-    fn persp_xy(&self, _x: Scalar, _y: Scalar, _point: &mut Point) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn persp_xy(&self, x: Scalar, y: Scalar, point: &mut Point) {
+        let m = &self.mat;
+        let px = x.mul_add(m[M_SCALE_X], y.mul_add(m[M_SKEW_X], m[M_TRANS_X]));
+        let py = x.mul_add(m[M_SKEW_Y], y.mul_add(m[M_SCALE_Y], m[M_TRANS_Y]));
+        let mut z = x.mul_add(m[M_PERSP_0], y.mul_add(m[M_PERSP_1], m[M_PERSP_2]));
+        if z != 0.0 {
+            z = z.recip();
+        }
+        point.set(px * z, py * z);
     }
 
-    fn identity_pts(&self, _dest: &mut [Point], _src: &[Point], _count: usize) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    #[allow(clippy::unused_self)]
+    fn identity_pts(&self, dest: &mut [Point], src: &[Point], count: usize) {
+        dest[..count].copy_from_slice(&src[..count]);
     }
 
-    fn trans_pts(&self, _dest: &mut [Point], _src: &[Point], _count: usize) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn trans_pts(&self, dest: &mut [Point], src: &[Point], count: usize) {
+        for (dst, src) in dest[..count].iter_mut().zip(src) {
+            self.trans_xy(src.x(), src.y(), dst);
+        }
     }
 
-    fn scale_pts(&self, _dest: &mut [Point], _src: &[Point], _count: usize) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn scale_pts(&self, dest: &mut [Point], src: &[Point], count: usize) {
+        for (dst, src) in dest[..count].iter_mut().zip(src) {
+            self.scale_xy(src.x(), src.y(), dst);
+        }
     }
 
-    fn scale_trans_pts(&self, _dest: &mut [Point], _src: &[Point], _count: usize) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn scale_trans_pts(&self, dest: &mut [Point], src: &[Point], count: usize) {
+        for (dst, src) in dest[..count].iter_mut().zip(src) {
+            self.scale_trans_xy(src.x(), src.y(), dst);
+        }
     }
 
-    fn persp_pts(&self, _dest: &mut [Point], _src: &[Point], _count: usize) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn persp_pts(&self, dest: &mut [Point], src: &[Point], count: usize) {
+        for (dst, src) in dest[..count].iter_mut().zip(src) {
+            self.persp_xy(src.x(), src.y(), dst);
+        }
     }
 
-    fn affine_vpts(&self, _dest: &mut [Point], _src: &[Point], _count: usize) {
-        debug_assert!(self.type_mask == TypeMask::empty());
-        unimplemented!()
+    fn affine_vpts(&self, dest: &mut [Point], src: &[Point], count: usize) {
+        for (dst, src) in dest[..count].iter_mut().zip(src) {
+            self.rot_trans_xy(src.x(), src.y(), dst);
+        }
     }
 
     /// return the number of bytes written, whether or not buffer is null
@@ -6,6 +6,7 @@ pub mod advanced_typeface_metrics;
 pub mod alpha_type;
 pub mod annotation;
 pub mod bitmap;
+pub mod bitmap_device;
 pub mod blend_mode;
 pub mod blur_types;
 pub mod canvas;
@@ -20,12 +21,15 @@ pub mod contour_measure;
 pub mod coverage_mode;
 pub mod cubic_map;
 pub mod data;
+pub mod device;
+pub mod document_metadata;
 pub mod flattenable;
 pub mod font_arguments;
 pub mod font_metrics;
 pub mod font_parameters;
 pub mod font_style;
 pub mod font_types;
+pub mod image;
 pub mod image_filter;
 pub mod image_info;
 pub mod irect;
@@ -42,6 +46,8 @@ pub mod path_effect;
 pub mod path_types;
 pub mod path_utils;
 pub mod pathops;
+pub mod picture;
+pub mod picture_recorder;
 pub mod pixel_ref;
 pub mod pixmap;
 pub mod point;
@@ -51,12 +57,15 @@ pub mod rrect;
 pub mod rsx_form;
 pub mod sampling_options;
 pub mod scalar;
+pub mod shader;
 pub mod size;
 pub mod sl_type_shared;
+pub mod surface;
 pub mod surface_props;
 pub mod swizzle;
 pub mod texture_compression_type;
 pub mod tile_mode;
+pub mod time;
 pub mod types;
 pub mod vertices;
 pub mod yuva_info;
@@ -66,10 +75,16 @@ pub mod yuva_pixmap;
 //pub mod typeface;
 
 // Private modules
+pub(crate) mod blitter;
+pub(crate) mod blur_priv;
 pub(crate) mod color_priv;
 pub(crate) mod cubic_clipper;
+pub(crate) mod geometry;
 pub(crate) mod line_clipper;
 pub(crate) mod mask;
 pub(crate) mod mipmap;
 pub(crate) mod path_builder_priv;
+pub(crate) mod pathops_priv;
+pub(crate) mod picture_priv;
 pub(crate) mod point_priv;
+pub(crate) mod rasterizer;
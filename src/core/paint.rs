@@ -12,22 +12,37 @@
 //! For instance, Paint does not directly implement dashing or blur, but contains
 //! the objects that do so.
 
+use std::rc::Rc;
+
+use crate::core::blend_mode::BlendMode;
 use crate::core::color::{colors::BLACK, Color, Color4f};
+use crate::core::color_filter::ColorFilter;
 use crate::core::color_space::ColorSpace;
 use crate::core::font_types::FontHinting;
+use crate::core::image_filter::ImageFilter;
+use crate::core::mask_filter::MaskFilter;
 use crate::core::paint_types::{PaintStyle, StrokeCap, StrokeJoin};
+use crate::core::path_effect::PathEffect;
 use crate::core::scalar::Scalar;
+use crate::core::shader::Shader;
 
 pub const DEFAULT_TEXT_SIZE: Scalar = 12.0;
 pub const DEFAULT_FONT_HINTING: FontHinting = FontHinting::Normal;
 pub const DEFAULT_MITER_LIMIT: Scalar = 4.0;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Paint {
     color_space: Option<ColorSpace>,
     color4f: Color4f,
     stroke_width: Scalar,
     miter_limit: Scalar,
+    blend_mode: BlendMode,
+
+    shader: Option<Rc<dyn Shader>>,
+    color_filter: Option<Rc<ColorFilter>>,
+    mask_filter: Option<Rc<MaskFilter>>,
+    image_filter: Option<Rc<dyn ImageFilter>>,
+    path_effect: Option<Rc<dyn PathEffect>>,
 
     anti_alias: bool,
     dither: bool,
@@ -45,6 +60,13 @@ impl Paint {
             color4f: BLACK,
             stroke_width: 0.0,
             miter_limit: DEFAULT_MITER_LIMIT,
+            blend_mode: BlendMode::SrcOver,
+
+            shader: None,
+            color_filter: None,
+            mask_filter: None,
+            image_filter: None,
+            path_effect: None,
 
             anti_alias: false,
             dither: false,
@@ -285,6 +307,104 @@ impl Paint {
     pub fn set_argb(&mut self, alpha: u8, red: u8, green: u8, blue: u8) {
         self.set_color(Color::from_argb(alpha, red, green, blue));
     }
+
+    /// Returns `BlendMode` used to combine source color with destination.
+    #[must_use]
+    pub const fn get_blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Returns true if `BlendMode` is `BlendMode::SrcOver`, the default.
+    #[must_use]
+    pub fn is_src_over(&self) -> bool {
+        self.blend_mode == BlendMode::SrcOver
+    }
+
+    /// Sets `BlendMode` used to combine source color with destination.
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    /// Returns optional colors used when filling a path, such as a gradient.
+    #[must_use]
+    pub const fn get_shader(&self) -> Option<&Rc<dyn Shader>> {
+        self.shader.as_ref()
+    }
+
+    /// Sets optional colors used when filling a path, such as a gradient.
+    ///
+    /// When a shader is set, paint color is ignored except for its alpha,
+    /// which modulates the shader.
+    pub fn set_shader(&mut self, shader: Option<Rc<dyn Shader>>) {
+        self.shader = shader;
+    }
+
+    /// Returns `ColorFilter` if set, or None.
+    #[must_use]
+    pub const fn get_color_filter(&self) -> Option<&Rc<ColorFilter>> {
+        self.color_filter.as_ref()
+    }
+
+    /// Sets `ColorFilter` to filter, pass None to clear `ColorFilter`.
+    pub fn set_color_filter(&mut self, color_filter: Option<Rc<ColorFilter>>) {
+        self.color_filter = color_filter;
+    }
+
+    /// Returns `MaskFilter` if set, or None.
+    #[must_use]
+    pub const fn get_mask_filter(&self) -> Option<&Rc<MaskFilter>> {
+        self.mask_filter.as_ref()
+    }
+
+    /// Sets `MaskFilter` to modify the clipping mask generated from drawn geometry,
+    /// pass None to clear `MaskFilter`.
+    pub fn set_mask_filter(&mut self, mask_filter: Option<Rc<MaskFilter>>) {
+        self.mask_filter = mask_filter;
+    }
+
+    /// Returns `ImageFilter` if set, or None.
+    #[must_use]
+    pub const fn get_image_filter(&self) -> Option<&Rc<dyn ImageFilter>> {
+        self.image_filter.as_ref()
+    }
+
+    /// Sets `ImageFilter` to alter the pixels drawn, pass None to clear `ImageFilter`.
+    pub fn set_image_filter(&mut self, image_filter: Option<Rc<dyn ImageFilter>>) {
+        self.image_filter = image_filter;
+    }
+
+    /// Returns `PathEffect` if set, or None.
+    #[must_use]
+    pub const fn get_path_effect(&self) -> Option<&Rc<dyn PathEffect>> {
+        self.path_effect.as_ref()
+    }
+
+    /// Sets `PathEffect` to modify the geometry before drawing, pass None to clear
+    /// `PathEffect`.
+    pub fn set_path_effect(&mut self, path_effect: Option<Rc<dyn PathEffect>>) {
+        self.path_effect = path_effect;
+    }
+
+    /// Returns true if Paint prevents all drawing.
+    ///
+    /// Drawing is skipped when the color is fully transparent and `BlendMode` keeps
+    /// destination unchanged for transparent sources, or when `BlendMode` is `Dst`.
+    #[must_use]
+    pub fn nothing_to_draw(&self) -> bool {
+        match self.blend_mode {
+            BlendMode::SrcOver
+            | BlendMode::SrcATop
+            | BlendMode::DstOut
+            | BlendMode::DstOver
+            | BlendMode::Plus => {
+                self.get_alphaf() == 0.0
+                    && self.color_filter.is_none()
+                    && self.image_filter.is_none()
+            }
+            BlendMode::Dst => true,
+            _ => false,
+        }
+    }
 }
 
 impl Default for Paint {
@@ -293,4 +413,34 @@ impl Default for Paint {
     }
 }
 
-// TODO(Shaohua): Impl PartialEq
+/// Compares effects by reference, two effects are equal only if they are the same object.
+fn rc_option_eq<T: ?Sized>(a: Option<&Rc<T>>, b: Option<&Rc<T>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Two paints are equal if their values and effects are identical.
+///
+/// Effects are compared by reference, not by value.
+impl PartialEq for Paint {
+    fn eq(&self, other: &Self) -> bool {
+        self.color_space == other.color_space
+            && self.color4f == other.color4f
+            && self.stroke_width == other.stroke_width
+            && self.miter_limit == other.miter_limit
+            && self.blend_mode == other.blend_mode
+            && rc_option_eq(self.shader.as_ref(), other.shader.as_ref())
+            && rc_option_eq(self.color_filter.as_ref(), other.color_filter.as_ref())
+            && rc_option_eq(self.mask_filter.as_ref(), other.mask_filter.as_ref())
+            && rc_option_eq(self.image_filter.as_ref(), other.image_filter.as_ref())
+            && rc_option_eq(self.path_effect.as_ref(), other.path_effect.as_ref())
+            && self.anti_alias == other.anti_alias
+            && self.dither == other.dither
+            && self.style == other.style
+            && self.cap == other.cap
+            && self.join == other.join
+    }
+}
@@ -2,6 +2,7 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+use crate::core::matrix::Matrix;
 use crate::core::path_builder::PathBuilder;
 use crate::core::path_types::{PathFillType, PathVerb};
 use crate::core::point::Point;
@@ -69,6 +70,35 @@ impl Path {
     pub fn conic_weights(&self) -> &[Scalar] {
         &self.conic_weights
     }
+
+    /// Returns a copy of this path with its points mapped by `matrix`.
+    // TODO(Shaohua): Convert conics to quads if matrix has perspective.
+    #[must_use]
+    pub fn transform(&self, matrix: &Matrix) -> Self {
+        let mut points = vec![Point::new(); self.points.len()];
+        matrix.map_points(&mut points, &self.points);
+        let bounds = Rect::from_points(&points);
+        Self::new(
+            points,
+            self.verbs.clone(),
+            self.conic_weights.clone(),
+            bounds,
+            self.fill_type,
+        )
+    }
+}
+
+impl Default for Path {
+    /// Returns an empty path, with `PathFillType::Winding`.
+    fn default() -> Self {
+        Self::new(
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            Rect::new(),
+            PathFillType::Winding,
+        )
+    }
 }
 
 impl From<Path> for PathBuilder {
@@ -2,6 +2,7 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+use crate::core::matrix::Matrix;
 use crate::core::path::Path;
 use crate::core::path_builder_priv::PointIter;
 use crate::core::path_types::ArcSize;
@@ -11,7 +12,7 @@ use crate::core::path_types::{PathFillType, PathVerb};
 use crate::core::point::Point;
 use crate::core::rect::Rect;
 use crate::core::rrect::RRect;
-use crate::core::scalar::{Scalar, SCALAR_ROOT_2_OVER_2};
+use crate::core::scalar::{Scalar, ScalarExt, SCALAR_ROOT_2_OVER_2};
 
 #[derive(Debug, Clone)]
 pub struct PathBuilder {
@@ -147,6 +148,17 @@ impl PathBuilder {
         pb.finish()
     }
 
+    /// Creates a new `Path` from a ring between two concentric circles.
+    ///
+    /// The outer circle is clockwise and the inner circle is counter-clockwise,
+    /// see `add_donut()`.
+    #[must_use]
+    pub fn from_donut(cx: f32, cy: f32, outer_radius: f32, inner_radius: f32) -> Option<Path> {
+        let mut pb = Self::new();
+        pb.add_donut(cx, cy, outer_radius, inner_radius, PathDirection::Cw);
+        pb.finish()
+    }
+
     /// Creates a new `Path` from an oval.
     ///
     /// Segments are created clockwise.
@@ -243,11 +255,11 @@ impl PathBuilder {
         if self.is_empty() {
             return None;
         }
-        let bounds = Rect::from_points(&self.points);
-        if bounds.is_empty() {
+        // Lines and points have zero width or height, but are still stroked.
+        if !self.points.iter().all(Point::is_finite) {
             return None;
         }
-        Some(bounds)
+        Some(Rect::from_points(&self.points))
     }
 
     #[inline]
@@ -274,6 +286,13 @@ impl PathBuilder {
         self.verbs.is_empty()
     }
 
+    /// Returns the last point, or None if the builder is empty.
+    #[must_use]
+    #[inline]
+    pub fn last_point(&self) -> Option<Point> {
+        self.points.last().copied()
+    }
+
     pub fn offset(&mut self, _dx: Scalar, _dy: Scalar) -> &mut Self {
         unimplemented!()
     }
@@ -410,8 +429,16 @@ impl PathBuilder {
     }
 
     /// Append a series of Line.
-    #[inline]
-    pub fn polyline_to(&mut self, _points: &[Point]) -> &mut Self {
+    pub fn polyline_to(&mut self, points: &[Point]) -> &mut Self {
+        if !points.is_empty() {
+            self.ensure_move();
+
+            self.points.extend_from_slice(points);
+            self.verbs
+                .extend(std::iter::repeat(PathVerb::Line).take(points.len()));
+
+            self.segment_mask |= PathSegmentMask::Line;
+        }
         self
     }
 
@@ -585,13 +612,67 @@ impl PathBuilder {
         self.add_rrect_detail(rrect, PathDirection::Cw, 0)
     }
 
+    /// Adds a rounded rectangle contour.
+    ///
+    /// `start_index` selects the initial point of contour, which is one of the start
+    /// and end points of the four sides, in clockwise order from the left point of
+    /// the top side.
+    ///
+    /// Rectangle and oval are added as `add_rect_detail()` and `add_oval_detail()`.
     pub fn add_rrect_detail(
         &mut self,
-        _rrect: &RRect,
-        _dir: PathDirection,
-        _start_index: usize,
+        rrect: &RRect,
+        dir: PathDirection,
+        start_index: usize,
     ) -> &mut Self {
-        // TODO(Shaohua):
+        let prev_isa = self.is_a;
+        let bounds = rrect.get_bounds();
+
+        if rrect.is_rect() || rrect.is_empty() {
+            // degenerate(rect) => radii points are collapsing
+            self.add_rect_detail(bounds, dir, start_index.div_ceil(2));
+        } else if rrect.is_oval() {
+            // degenerate(oval) => line points are collapsing
+            self.add_oval_detail(bounds, dir, start_index / 2);
+        } else {
+            // moveTo + 4 lines + 4 conics(2 pts each)
+            const POINTS: usize = 13;
+            // moveTo + 4 lines + 4 conics + close
+            const VERBS: usize = 10;
+            self.reserve(VERBS, POINTS);
+
+            let starts_with_conic = (start_index & 1 == 1) == (dir == PathDirection::Cw);
+            let mut rrect_iter = PointIter::new_rrect(rrect, dir, start_index);
+            // Corner iterator indices follow the collapsed radii model,
+            // adjusted such that the start pt is "behind" the radii start pt.
+            let rect_start_index = match dir {
+                PathDirection::Cw => start_index / 2,
+                PathDirection::Ccw => start_index / 2 + 1,
+            };
+            let mut rect_iter = PointIter::new_rect(bounds, dir, rect_start_index);
+
+            self.move_to_point(rrect_iter.current());
+            if starts_with_conic {
+                for _i in 0..3 {
+                    self.conic_to_point(rect_iter.next(), rrect_iter.next(), SCALAR_ROOT_2_OVER_2);
+                    self.line_to_point(rrect_iter.next());
+                }
+                // final lineTo handled by close().
+                self.conic_to_point(rect_iter.next(), rrect_iter.next(), SCALAR_ROOT_2_OVER_2);
+            } else {
+                for _i in 0..4 {
+                    self.line_to_point(rrect_iter.next());
+                    self.conic_to_point(rect_iter.next(), rrect_iter.next(), SCALAR_ROOT_2_OVER_2);
+                }
+            }
+            self.close();
+        }
+
+        if prev_isa == IsA::JustMoves {
+            self.is_a = IsA::RRect;
+            self.is_a_ccw = dir == PathDirection::Ccw;
+            self.is_a_start = start_index % 8;
+        }
         self
     }
 
@@ -603,22 +684,135 @@ impl PathBuilder {
         self.add_circle_detail(center_x, center_y, radius, PathDirection::Cw)
     }
 
+    /// Add a circular contour with specific direction.
+    ///
+    /// Does nothing if `radius` is negative.
     pub fn add_circle_detail(
         &mut self,
-        _center_x: Scalar,
-        _center_y: Scalar,
-        _radius: Scalar,
-        _dir: PathDirection,
+        center_x: Scalar,
+        center_y: Scalar,
+        radius: Scalar,
+        dir: PathDirection,
     ) -> &mut Self {
-        // TODO(Shaohua):
+        if radius >= 0.0 {
+            let oval = Rect::from_ltrb(
+                center_x - radius,
+                center_y - radius,
+                center_x + radius,
+                center_y + radius,
+            );
+            self.add_oval_detail(&oval, dir, 1);
+        }
         self
     }
 
-    pub fn add_polygon(&mut self, _points: &[Point], _is_closed: bool) -> &mut Self {
-        // TODO(Shaohua):
+    /// Add a ring between two concentric circles.
+    ///
+    /// The outer contour has direction `dir`, and the inner contour has the opposite
+    /// direction, so that the hole is not filled with either `PathFillType::Winding`
+    /// or `PathFillType::EvenOdd`.
+    ///
+    /// Only the outer circle is added if `inner_radius` is not positive,
+    /// and nothing is added if `inner_radius` is not less than `outer_radius`.
+    pub fn add_donut(
+        &mut self,
+        center_x: Scalar,
+        center_y: Scalar,
+        outer_radius: Scalar,
+        inner_radius: Scalar,
+        dir: PathDirection,
+    ) -> &mut Self {
+        if inner_radius >= outer_radius {
+            return self;
+        }
+        self.add_circle_detail(center_x, center_y, outer_radius, dir);
+        if inner_radius > 0.0 {
+            self.add_circle_detail(center_x, center_y, inner_radius, dir.reverse());
+        }
+        self
+    }
+
+    /// Adds contour created from line array.
+    ///
+    /// If `is_closed` is true, appends `PathVerb::Close` to the contour.
+    /// Polygon direction follows the order of `points`; use `reverse_add_path()`
+    /// or reverse `points` to add holes.
+    pub fn add_polygon(&mut self, points: &[Point], is_closed: bool) -> &mut Self {
+        let Some((first, rest)) = points.split_first() else {
+            return self;
+        };
+        self.move_to_point(*first);
+        self.polyline_to(rest);
+        if is_closed {
+            self.close();
+        }
         self
     }
 
+    /// Appends contours of `path`, with each contour in reversed direction.
+    ///
+    /// This is how holes, like the counters of glyph outlines, are cut from
+    /// a shape of opposite direction with `PathFillType::Winding`.
+    pub fn reverse_add_path(&mut self, path: &Path) -> &mut Self {
+        let points = path.points();
+        let mut weights = path.conic_weights().iter();
+        let mut point_index = 0;
+        // Segments of current contour: verb, index of its start point, conic weight.
+        let mut segments: Vec<(PathVerb, usize, Scalar)> = Vec::new();
+
+        for &verb in path.verbs() {
+            match verb {
+                PathVerb::Move => {
+                    self.reverse_add_contour(points, &segments, false);
+                    segments.clear();
+                    point_index += 1;
+                }
+                PathVerb::Close => {
+                    self.reverse_add_contour(points, &segments, true);
+                    segments.clear();
+                }
+                _ => {
+                    let weight = if verb == PathVerb::Conic {
+                        weights.next().copied().unwrap_or(1.0)
+                    } else {
+                        1.0
+                    };
+                    if point_index > 0 {
+                        segments.push((verb, point_index - 1, weight));
+                    }
+                    point_index += verb.points() - 1;
+                }
+            }
+        }
+        self.reverse_add_contour(points, &segments, false);
+        self
+    }
+
+    fn reverse_add_contour(
+        &mut self,
+        points: &[Point],
+        segments: &[(PathVerb, usize, Scalar)],
+        is_closed: bool,
+    ) {
+        let Some(&(last_verb, last_start, _)) = segments.last() else {
+            return;
+        };
+        self.move_to_point(points[last_start + last_verb.points() - 1]);
+        for &(verb, start, weight) in segments.iter().rev() {
+            let pts = &points[start..start + verb.points()];
+            match verb {
+                PathVerb::Line => self.line_to_point(pts[0]),
+                PathVerb::Quad => self.quad_to_point(pts[1], pts[0]),
+                PathVerb::Conic => self.conic_to_point(pts[1], pts[0], weight),
+                PathVerb::Cubic => self.cubic_to_point(pts[2], pts[1], pts[0]),
+                PathVerb::Move | PathVerb::Close => self,
+            };
+        }
+        if is_closed {
+            self.close();
+        }
+    }
+
     /// Appends arc to the builder.
     ///
     /// Arc added is part of ellipse bounded by oval, from `start_angle` through `sweep_angle`.
@@ -693,15 +887,94 @@ impl PathBuilder {
     /// - `large_arc` - chooses smaller or larger arc
     /// - `sweep` - chooses clockwise or counterclockwise arc
     /// - `xy` - end point of arc
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_sign_loss)]
     pub fn arc_to_rotate(
         &mut self,
-        _radii: Point,
-        _x_axis_rotate: Scalar,
-        _large_arc: ArcSize,
-        _sweep: PathDirection,
-        _xy: Point,
+        radii: Point,
+        x_axis_rotate: Scalar,
+        large_arc: ArcSize,
+        sweep: PathDirection,
+        xy: Point,
     ) -> &mut Self {
-        // TODO(Shaohua):
+        let start = self.last_point().unwrap_or_default();
+        let mut rx = radii.x().abs();
+        let mut ry = radii.y().abs();
+        if rx.nearly_zero() || ry.nearly_zero() || start == xy {
+            return self.line_to_point(xy);
+        }
+
+        // Scale up radii if they are too small to reach the end point.
+        let mid_point_distance = (start - xy) * 0.5;
+        let transformed_mid_point = Matrix::from_rotate(-x_axis_rotate)
+            .map_xy(mid_point_distance.x(), mid_point_distance.y());
+        let radii_scale = (transformed_mid_point.x() * transformed_mid_point.x()) / (rx * rx)
+            + (transformed_mid_point.y() * transformed_mid_point.y()) / (ry * ry);
+        if radii_scale > 1.0 {
+            let radii_scale = radii_scale.sqrt();
+            rx *= radii_scale;
+            ry *= radii_scale;
+        }
+
+        // Map end points onto unit circle, and find its center.
+        let point_transform = Matrix::from_concat(
+            &Matrix::from_scale(1.0 / rx, 1.0 / ry),
+            &Matrix::from_rotate(-x_axis_rotate),
+        );
+        let mut unit_pts = [Point::new(); 2];
+        point_transform.map_points(&mut unit_pts, &[start, xy]);
+        let delta = unit_pts[1] - unit_pts[0];
+        let d = delta.x().mul_add(delta.x(), delta.y() * delta.y());
+        let mut scale_factor = (1.0 / d - 0.25).max(0.0).sqrt();
+        if (sweep == PathDirection::Ccw) != (large_arc == ArcSize::Large) {
+            scale_factor = -scale_factor;
+        }
+        let delta = delta * scale_factor;
+        let center = (unit_pts[0] + unit_pts[1]) * 0.5 + Point::from_xy(-delta.y(), delta.x());
+        unit_pts[0] -= center;
+        unit_pts[1] -= center;
+        let theta1 = unit_pts[0].y().atan2(unit_pts[0].x());
+        let theta2 = unit_pts[1].y().atan2(unit_pts[1].x());
+        let mut theta_arc = theta2 - theta1;
+        if theta_arc < 0.0 && sweep == PathDirection::Cw {
+            theta_arc += std::f32::consts::TAU;
+        } else if theta_arc > 0.0 && sweep != PathDirection::Cw {
+            theta_arc -= std::f32::consts::TAU;
+        }
+        // Very tiny angles cause our subsequent math to go wonky.
+        if theta_arc.abs() < std::f32::consts::PI / 1_000_000.0 {
+            return self.line_to_point(xy);
+        }
+
+        // Each conic spans at most 120 degrees.
+        let point_transform = Matrix::from_concat(
+            &Matrix::from_rotate(x_axis_rotate),
+            &Matrix::from_scale(rx, ry),
+        );
+        let segments = (theta_arc.abs() / (std::f32::consts::TAU / 3.0)).ceil() as usize;
+        let theta_width = theta_arc / segments as Scalar;
+        let t = (0.5 * theta_width).tan();
+        if !t.is_finite() {
+            return self;
+        }
+        let weight = 0.5f32.mul_add(theta_width.cos(), 0.5).sqrt();
+        let mut start_theta = theta1;
+        for index in 0..segments {
+            let end_theta = start_theta + theta_width;
+            let sin_end_theta = end_theta.sin_snap_to_zero();
+            let cos_end_theta = end_theta.cos_snap_to_zero();
+            let end = Point::from_xy(cos_end_theta, sin_end_theta) + center;
+            let ctrl = end + Point::from_xy(t * sin_end_theta, -t * cos_end_theta);
+            let mut mapped = [Point::new(); 2];
+            point_transform.map_points(&mut mapped, &[ctrl, end]);
+            if index + 1 == segments {
+                // Land exactly on the end point.
+                mapped[1] = xy;
+            }
+            self.conic_to_point(mapped[0], mapped[1], weight);
+            start_theta = end_theta;
+        }
         self
     }
 
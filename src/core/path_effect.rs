@@ -2,6 +2,8 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+use std::fmt;
+
 use crate::core::flattenable::{Flattenable, Type};
 use crate::core::matrix::Matrix;
 use crate::core::path::Path;
@@ -59,7 +61,7 @@ impl DashInfo {
 /// `PathEffect` is the trait for objects in the Paint that affect
 /// the geometry of a drawing primitive before it is transformed by the
 /// canvas' matrix and drawn.
-pub trait PathEffect {
+pub trait PathEffect: fmt::Debug {
     /// Given a src path (input) and a stroke-rec (input and output), apply
     /// this effect to the src path, returning the new path in dst, and return
     /// true. If this effect cannot be applied, return false and ignore dst
@@ -67,6 +67,21 @@ pub enum PathDirection {
     Ccw,
 }
 
+impl PathDirection {
+    /// Returns the opposite direction.
+    ///
+    /// Holes are added in the opposite direction of their outer contour, so that
+    /// they are not filled with `PathFillType::Winding`.
+    #[must_use]
+    #[inline]
+    pub const fn reverse(self) -> Self {
+        match self {
+            Self::Cw => Self::Ccw,
+            Self::Ccw => Self::Cw,
+        }
+    }
+}
+
 bitflags! {
     #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
     pub struct PathSegmentMask : u8 {
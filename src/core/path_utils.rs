@@ -4,9 +4,14 @@
 
 use crate::core::matrix::Matrix;
 use crate::core::paint::Paint;
+use crate::core::paint_types::{PaintStyle, StrokeCap, StrokeJoin};
 use crate::core::path::Path;
+use crate::core::path_builder::PathBuilder;
+use crate::core::path_types::PathDirection;
+use crate::core::point::{Point, Vector};
+use crate::core::rasterizer::{flatten_contours, FLATTEN_TOLERANCE};
 use crate::core::rect::Rect;
-use crate::core::scalar::Scalar;
+use crate::core::scalar::{Scalar, SCALAR_NEARLY_ZERO, SCALAR_PI};
 
 /// Returns the filled equivalent of the stroked path.
 ///
@@ -14,7 +19,7 @@ use crate::core::scalar::Scalar;
 /// - `src` - Path read to create a filled version
 /// - `paint` - Paint, from which attributes such as stroke cap, width, miter, and join,
 ///             as well as path effect will be used.
-/// - `dst` - resulting Path; may be the same as src
+/// - `dst` - resulting Path
 /// - `cull_rect` - optional limit passed to `PathEffect`
 /// - `res_scale` - if > 1.0, increase precision, else if (`0.0 < res_scale < 1.0`)
 ///                 reduce precision to favor speed and size
@@ -22,27 +27,389 @@ use crate::core::scalar::Scalar;
 /// Returns true if the dst path was updated, false if it was not
 /// (e.g. if the path represents hairline and cannot be filled).
 pub fn fill_path_with_paint_and_scale(
-    _src: &Path,
-    _paint: &Paint,
-    _dst: &mut Path,
+    src: &Path,
+    paint: &Paint,
+    dst: &mut Path,
     _cull_rect: &Rect,
-    _res_scale: Scalar,
-) {
-    unimplemented!()
+    res_scale: Scalar,
+) -> bool {
+    if !src.points().iter().all(Point::is_finite) {
+        return false;
+    }
+
+    // TODO(Shaohua): Apply path effect of paint.
+    let mut stroker = PathStroker::from_paint(paint);
+    stroker.set_res_scale(res_scale);
+    if paint.get_style() == PaintStyle::Fill {
+        *dst = src.clone();
+        return true;
+    }
+    if stroker.is_hairline() {
+        *dst = src.clone();
+        return false;
+    }
+    let Some(path) = stroker.stroke(src) else {
+        return false;
+    };
+    *dst = path;
+    true
 }
 
+/// Returns the filled equivalent of the stroked path, see `fill_path_with_paint_and_scale()`.
+///
+/// Precision is chosen for drawing with `ctm`.
 pub fn fill_path_with_paint_and_cull_rect(
-    _src: &Path,
-    _paint: &Paint,
-    _dst: &mut Path,
-    _cull_rect: &Rect,
+    src: &Path,
+    paint: &Paint,
+    dst: &mut Path,
+    cull_rect: &Rect,
     _ctm: &Matrix,
-) {
-    unimplemented!()
+) -> bool {
+    // TODO(Shaohua): Compute res scale from scale factors of ctm.
+    fill_path_with_paint_and_scale(src, paint, dst, cull_rect, 1.0)
 }
 
-pub fn fill_path_with_paint(_src: &Path, _paint: &Paint, _dst: &mut Path) {
-    unimplemented!()
+/// Returns the filled equivalent of the stroked path, see `fill_path_with_paint_and_scale()`.
+pub fn fill_path_with_paint(src: &Path, paint: &Paint, dst: &mut Path) -> bool {
+    fill_path_with_paint_and_scale(src, paint, dst, &Rect::new(), 1.0)
+}
+
+/// Converts stroked paths to outlines which can be filled with `PathFillType::Winding`.
+///
+/// Curves are flattened to lines with precision controlled by `res_scale`, and
+/// round joins and caps are added as conics.
+///
+/// For a closed contour, the outline is a pair of contours on both sides of it in
+/// opposite directions. For an open contour, it is a single contour around the
+/// contour and its caps. Every outline has negative area, so that overlapping parts
+/// add up instead of canceling each other.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathStroker {
+    width: Scalar,
+    cap: StrokeCap,
+    join: StrokeJoin,
+    miter_limit: Scalar,
+    res_scale: Scalar,
+    do_fill: bool,
+}
+
+impl PathStroker {
+    /// Creates stroker with `width`, `cap`, `join` and `miter_limit`.
+    #[must_use]
+    pub const fn new(width: Scalar, cap: StrokeCap, join: StrokeJoin, miter_limit: Scalar) -> Self {
+        Self {
+            width,
+            cap,
+            join,
+            miter_limit,
+            res_scale: 1.0,
+            do_fill: false,
+        }
+    }
+
+    /// Creates stroker with stroke width, cap, join and miter limit of `paint`.
+    ///
+    /// Interior is filled too if style of `paint` is `PaintStyle::StrokeAndFill`.
+    #[must_use]
+    pub fn from_paint(paint: &Paint) -> Self {
+        let mut stroker = Self::new(
+            paint.get_stroke_width(),
+            paint.get_stroke_cap(),
+            paint.get_stroke_join(),
+            paint.get_stroke_miter(),
+        );
+        stroker.set_do_fill(paint.get_style() == PaintStyle::StrokeAndFill);
+        stroker
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn width(&self) -> Scalar {
+        self.width
+    }
+
+    #[inline]
+    pub fn set_width(&mut self, width: Scalar) {
+        self.width = width;
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn cap(&self) -> StrokeCap {
+        self.cap
+    }
+
+    #[inline]
+    pub fn set_cap(&mut self, cap: StrokeCap) {
+        self.cap = cap;
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn join(&self) -> StrokeJoin {
+        self.join
+    }
+
+    #[inline]
+    pub fn set_join(&mut self, join: StrokeJoin) {
+        self.join = join;
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn miter_limit(&self) -> Scalar {
+        self.miter_limit
+    }
+
+    /// Sets limit of miter length relative to stroke width.
+    ///
+    /// Miter joins longer than this are drawn as bevel joins.
+    #[inline]
+    pub fn set_miter_limit(&mut self, miter_limit: Scalar) {
+        self.miter_limit = miter_limit;
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn res_scale(&self) -> Scalar {
+        self.res_scale
+    }
+
+    /// Sets precision of outlines: if > 1.0, increase precision, else if
+    /// (`0.0 < res_scale < 1.0`) reduce precision to favor speed and size.
+    ///
+    /// Invalid values are ignored.
+    #[inline]
+    pub fn set_res_scale(&mut self, res_scale: Scalar) {
+        if res_scale > 0.0 && res_scale.is_finite() {
+            self.res_scale = res_scale;
+        }
+    }
+
+    /// Returns true if interior of source path is added to outline.
+    #[must_use]
+    #[inline]
+    pub const fn do_fill(&self) -> bool {
+        self.do_fill
+    }
+
+    #[inline]
+    pub fn set_do_fill(&mut self, do_fill: bool) {
+        self.do_fill = do_fill;
+    }
+
+    /// Returns true if stroke is drawn as hairline, one pixel wide regardless of scale,
+    /// which has no outline.
+    #[must_use]
+    #[inline]
+    pub fn is_hairline(&self) -> bool {
+        self.width <= 0.0
+    }
+
+    /// Returns outline of stroked `src`, with `PathFillType::Winding`.
+    ///
+    /// Returns None if stroke is hairline or width is not finite, or if `src` has
+    /// nothing to stroke.
+    #[must_use]
+    pub fn stroke(&self, src: &Path) -> Option<Path> {
+        if self.is_hairline() || !self.width.is_finite() {
+            return None;
+        }
+        let tolerance = FLATTEN_TOLERANCE / self.res_scale;
+        let mut builder = PathBuilder::new();
+        flatten_contours(src, tolerance, |points, closed| {
+            self.stroke_contour(&mut builder, points, closed);
+            if self.do_fill {
+                add_fill_contour(&mut builder, points);
+            }
+        });
+        builder.finish()
+    }
+
+    fn stroke_contour(&self, builder: &mut PathBuilder, points: &[Point], closed: bool) {
+        let mut pts: Vec<Point> = Vec::with_capacity(points.len());
+        for &point in points {
+            if pts
+                .last()
+                .map_or(true, |last| last.distance(point) > SCALAR_NEARLY_ZERO)
+            {
+                pts.push(point);
+            }
+        }
+        if closed && pts.len() > 1 && pts[0].distance(pts[pts.len() - 1]) <= SCALAR_NEARLY_ZERO {
+            pts.pop();
+        }
+
+        let radius = self.width * 0.5;
+        if pts.len() == 1 {
+            // Zero length contour only has caps.
+            let center = pts[0];
+            match self.cap {
+                StrokeCap::Butt => {}
+                StrokeCap::Round => {
+                    builder.add_circle_detail(center.x(), center.y(), radius, PathDirection::Ccw);
+                }
+                StrokeCap::Square => {
+                    let rect = Rect::from_ltrb(
+                        center.x() - radius,
+                        center.y() - radius,
+                        center.x() + radius,
+                        center.y() + radius,
+                    );
+                    builder.add_rect_detail(&rect, PathDirection::Ccw, 0);
+                }
+            }
+            return;
+        }
+
+        let reversed: Vec<Point> = pts.iter().rev().copied().collect();
+        if closed {
+            self.add_side(builder, &pts, true, true, radius);
+            builder.close();
+            self.add_side(builder, &reversed, true, true, radius);
+        } else {
+            self.add_side(builder, &pts, false, true, radius);
+            let last = pts.len() - 1;
+            self.add_cap(builder, pts[last], normal(pts[last - 1], pts[last], radius));
+            self.add_side(builder, &reversed, false, false, radius);
+            self.add_cap(builder, pts[0], normal(pts[1], pts[0], radius));
+        }
+        builder.close();
+    }
+
+    /// Adds offset of polyline `pts` on the side of its normals, see `normal()`.
+    ///
+    /// Starts a new contour if `move_to` is true, otherwise continues current one.
+    fn add_side(
+        &self,
+        builder: &mut PathBuilder,
+        pts: &[Point],
+        closed: bool,
+        move_to: bool,
+        radius: Scalar,
+    ) {
+        let count = pts.len();
+        let segments = if closed { count } else { count - 1 };
+        let normals: Vec<Vector> = (0..segments)
+            .map(|i| normal(pts[i], pts[(i + 1) % count], radius))
+            .collect();
+
+        let start = pts[0] + normals[0];
+        if move_to {
+            builder.move_to_point(start);
+        } else {
+            builder.line_to_point(start);
+        }
+        for i in 1..segments {
+            builder.line_to_point(pts[i] + normals[i - 1]);
+            self.add_join(builder, pts[i], normals[i - 1], normals[i]);
+        }
+        let last = segments - 1;
+        builder.line_to_point(pts[segments % count] + normals[last]);
+        if closed {
+            self.add_join(builder, pts[0], normals[last], normals[0]);
+        }
+    }
+
+    /// Connects `pivot + before` to `pivot + after`, the offsets of two segments
+    /// meeting at `pivot`.
+    fn add_join(&self, builder: &mut PathBuilder, pivot: Point, before: Vector, after: Vector) {
+        let radius_sqd = before.length_sqd();
+        let cross = before.cross(&after) / radius_sqd;
+        let dot = before.dot(&after) / radius_sqd;
+        if cross.abs() <= SCALAR_NEARLY_ZERO && dot > 0.0 {
+            // Segments are nearly collinear.
+            builder.line_to_point(pivot + after);
+            return;
+        }
+        if cross >= 0.0 {
+            // This is the inner side, the other side covers the join.
+            builder.line_to_point(pivot);
+            builder.line_to_point(pivot + after);
+            return;
+        }
+
+        match self.join {
+            StrokeJoin::Bevel => {}
+            StrokeJoin::Miter => {
+                // Ratio of miter length to stroke width is 1 / cos(half angle).
+                let cos_half = ((1.0 + dot) * 0.5).sqrt();
+                if cos_half * self.miter_limit >= 1.0 {
+                    let mut bisector = before + after;
+                    if bisector.set_length(radius_sqd.sqrt() / cos_half) {
+                        builder.line_to_point(pivot + bisector);
+                    }
+                }
+            }
+            StrokeJoin::Round => add_arc(builder, pivot, before, -dot.clamp(-1.0, 1.0).acos()),
+        }
+        builder.line_to_point(pivot + after);
+    }
+
+    /// Adds cap at end of contour, from `pivot + normal` to `pivot - normal`.
+    fn add_cap(&self, builder: &mut PathBuilder, pivot: Point, normal: Vector) {
+        // Direction of contour at its end.
+        let direction = Point::from_xy(normal.y(), -normal.x());
+        match self.cap {
+            StrokeCap::Butt => {}
+            StrokeCap::Round => add_arc(builder, pivot, normal, -SCALAR_PI),
+            StrokeCap::Square => {
+                builder.line_to_point(pivot + normal + direction);
+                builder.line_to_point(pivot - normal + direction);
+            }
+        }
+        builder.line_to_point(pivot - normal);
+    }
+}
+
+/// Returns normal of line from `p0` to `p1` with length `radius`.
+///
+/// The normal is the direction rotated by +90 degrees, (-dy, dx).
+fn normal(p0: Point, p1: Point, radius: Scalar) -> Vector {
+    let mut normal = Point::from_xy(p0.y() - p1.y(), p1.x() - p0.x());
+    if !normal.set_length(radius) {
+        normal = Point::new();
+    }
+    normal
+}
+
+/// Adds circular arc around `center` starting at `center + start`, sweeping `sweep`
+/// radians, with positive angles rotating towards normals.
+fn add_arc(builder: &mut PathBuilder, center: Point, start: Vector, sweep: Scalar) {
+    let quarter = SCALAR_PI * 0.5;
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    let count = (sweep.abs() / quarter).ceil().max(1.0) as usize;
+    #[allow(clippy::cast_precision_loss)]
+    let step = sweep / count as Scalar;
+    let (sin, cos) = step.sin_cos();
+    let weight = (step * 0.5).cos();
+
+    let mut from = start;
+    for _ in 0..count {
+        let to = Point::from_xy(
+            from.x().mul_add(cos, -from.y() * sin),
+            from.x().mul_add(sin, from.y() * cos),
+        );
+        // Control point is the intersection of tangents at both ends.
+        let control = (from + to) * (0.5 / (weight * weight));
+        builder.conic_to_point(center + control, center + to, weight);
+        from = to;
+    }
+}
+
+/// Adds closed polyline `points` with negative area, matching direction of outlines.
+fn add_fill_contour(builder: &mut PathBuilder, points: &[Point]) {
+    let count = points.len();
+    let area: Scalar = (0..count)
+        .map(|i| points[i].cross(&points[(i + 1) % count]))
+        .sum();
+    if area > 0.0 {
+        let reversed: Vec<Point> = points.iter().rev().copied().collect();
+        builder.add_polygon(&reversed, true);
+    } else {
+        builder.add_polygon(points, true);
+    }
 }
 
 /*
@@ -90,3 +457,99 @@ fn fill_path_with_paint_impl(src: &Path, paint: &Paint, dst: &mut Path,
     return !rec.isHairlineStyle();
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::rasterizer::Rasterizer;
+
+    const SIZE: usize = 20;
+
+    /// Returns coverage of pixel (`x`, `y`) by outline of stroked `path`.
+    fn coverage_at(stroker: &PathStroker, path: &Path, x: usize, y: usize) -> Scalar {
+        let outline = stroker.stroke(path).unwrap();
+        let mut rasterizer = Rasterizer::new(SIZE, SIZE);
+        rasterizer.add_path(&outline);
+        let mut value = 0.0;
+        rasterizer.fill(outline.fill_type(), true, |row, coverage| {
+            if row == y {
+                value = coverage[x];
+            }
+        });
+        value
+    }
+
+    fn is_coverage(value: Scalar, expected: Scalar) -> bool {
+        (value - expected).abs() < 1e-3
+    }
+
+    fn polyline(points: &[(Scalar, Scalar)]) -> Path {
+        let mut builder = PathBuilder::new();
+        builder.move_to(points[0].0, points[0].1);
+        for &(x, y) in &points[1..] {
+            builder.line_to(x, y);
+        }
+        builder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_stroke_width_and_caps() {
+        let line = polyline(&[(5.0, 10.0), (15.0, 10.0)]);
+        let stroker = |cap| PathStroker::new(4.0, cap, StrokeJoin::Miter, 4.0);
+
+        let butt = stroker(StrokeCap::Butt);
+        assert!(is_coverage(coverage_at(&butt, &line, 10, 8), 1.0));
+        assert!(is_coverage(coverage_at(&butt, &line, 10, 11), 1.0));
+        assert!(is_coverage(coverage_at(&butt, &line, 10, 12), 0.0));
+        assert!(is_coverage(coverage_at(&butt, &line, 4, 9), 0.0));
+
+        // Square cap extends by half of width.
+        let square = stroker(StrokeCap::Square);
+        assert!(is_coverage(coverage_at(&square, &line, 3, 8), 1.0));
+        assert!(is_coverage(coverage_at(&square, &line, 2, 9), 0.0));
+
+        // Round cap covers the pixel next to the end, and part of the corner.
+        let round = stroker(StrokeCap::Round);
+        assert!(coverage_at(&round, &line, 4, 9) > 0.9);
+        let corner = coverage_at(&round, &line, 3, 8);
+        assert!(corner > 0.0 && corner < 0.5);
+    }
+
+    #[test]
+    fn test_stroke_joins() {
+        // Right angle, whose outer corner of miter is at (2, 2).
+        let corner = polyline(&[(4.0, 16.0), (4.0, 4.0), (16.0, 4.0)]);
+        let stroker = |join, miter_limit| PathStroker::new(4.0, StrokeCap::Butt, join, miter_limit);
+
+        let miter = coverage_at(&stroker(StrokeJoin::Miter, 4.0), &corner, 2, 2);
+        let round = coverage_at(&stroker(StrokeJoin::Round, 4.0), &corner, 2, 2);
+        let bevel = coverage_at(&stroker(StrokeJoin::Bevel, 4.0), &corner, 2, 2);
+        assert!(is_coverage(miter, 1.0));
+        assert!(is_coverage(bevel, 0.0));
+        assert!(round > bevel && round < miter);
+
+        // Miter of right angle is sqrt(2) times longer than half of width.
+        let limited = coverage_at(&stroker(StrokeJoin::Miter, 1.4), &corner, 2, 2);
+        assert!(is_coverage(limited, 0.0));
+    }
+
+    #[test]
+    fn test_stroke_closed_contour() {
+        let rect = PathBuilder::from_rect(&Rect::from_ltrb(4.0, 4.0, 16.0, 16.0)).unwrap();
+        let mut stroker = PathStroker::new(2.0, StrokeCap::Butt, StrokeJoin::Miter, 4.0);
+        assert!(is_coverage(coverage_at(&stroker, &rect, 4, 10), 1.0));
+        assert!(is_coverage(coverage_at(&stroker, &rect, 3, 3), 1.0));
+        assert!(is_coverage(coverage_at(&stroker, &rect, 10, 10), 0.0));
+
+        stroker.set_do_fill(true);
+        assert!(is_coverage(coverage_at(&stroker, &rect, 10, 10), 1.0));
+    }
+
+    #[test]
+    fn test_stroke_hairline() {
+        let line = polyline(&[(5.0, 10.0), (15.0, 10.0)]);
+        let stroker = PathStroker::new(0.0, StrokeCap::Butt, StrokeJoin::Miter, 4.0);
+        assert!(stroker.is_hairline());
+        assert!(stroker.stroke(&line).is_none());
+    }
+}
@@ -2,7 +2,11 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+use crate::core::geometry::{eval_cubic_at, eval_quad_at, Conic};
 use crate::core::path::Path;
+use crate::core::path_types::PathVerb;
+use crate::core::pathops_priv::combine_paths;
+use crate::core::point::Point;
 use crate::core::rect::Rect;
 
 // TODO(Shaohua): move everything below into the PathBuilder
@@ -25,14 +29,51 @@ pub enum PathOp {
     ReverseDifference,
 }
 
+impl PathOp {
+    /// Returns the result of applying `op` to `one` and `two`: (one op two).
+    ///
+    /// Contours may self-intersect, overlap or touch each other, and fill types of
+    /// both paths are respected.
+    ///
+    /// Unlike Skia, curves are not preserved: they are approximated with lines within
+    /// 0.01 units, so the result is always polygonal, made of non-overlapping polygons
+    /// with `PathFillType::Winding`, or with `PathFillType::InverseWinding` if it
+    /// covers infinite area. An empty area results in an empty path.
+    ///
+    /// For E lines after approximation, finding intersections and computing winding
+    /// numbers of edges both take O(E^2) time in the worst case.
+    ///
+    /// # Parameters
+    /// - `one` - The first operand (for difference, the minuend)
+    /// - `two` - The second operand (for difference, the subtrahend)
+    /// - `op` - The operator to apply.
+    ///
+    /// Returns None if points of `one` or `two` are not finite.
+    #[must_use]
+    pub fn apply(one: &Path, two: &Path, op: Self) -> Option<Path> {
+        combine_paths(&[one, two], |fills| op.contains(fills[0], fills[1]))
+    }
+
+    /// Returns true if a point is inside of the result, given whether it is inside of
+    /// the first and the second operand.
+    #[must_use]
+    pub const fn contains(self, one: bool, two: bool) -> bool {
+        match self {
+            Self::Difference => one && !two,
+            Self::Intersect => one && two,
+            Self::Union => one || two,
+            Self::Xor => one != two,
+            Self::ReverseDifference => two && !one,
+        }
+    }
+}
+
 /// Set this path to the result of applying the Op to this path and the
 /// specified path: this = (this op operand).
 ///
-/// The resulting path will be constructed from non-overlapping contours.
-/// The curve order is reduced where possible so that cubics may be turned
-/// into quadratics, and quadratics maybe turned into lines.
+/// The resulting path will be constructed from non-overlapping contours,
+/// see `PathOp::apply()`.
 ///
-/// # Parameters
 /// Returns true if operation was able to produce a result;
 /// otherwise, result is unmodified.
 ///
@@ -40,61 +81,157 @@ pub enum PathOp {
 /// - `one` - The first operand (for difference, the minuend)
 /// - `two` - The second operand (for difference, the subtrahend)
 /// - `op` - The operator to apply.
-/// - `result` - The product of the operands. The result may be one of the inputs.
+/// - `result` - The product of the operands.
 ///
-/// Returns true if the operation succeeded.
-pub fn op(_one: &Path, _two: &Path, _op: PathOp, _result: &mut Path) -> bool {
-    unimplemented!()
+/// Returns true if the operation succeeded, even if the product is empty,
+/// false if points of operands are not finite.
+pub fn op(one: &Path, two: &Path, op: PathOp, result: &mut Path) -> bool {
+    let Some(path) = PathOp::apply(one, two, op) else {
+        return false;
+    };
+    *result = path;
+    true
 }
 
 /// Set this path to a set of non-overlapping contours that describe the
 /// same area as the original path.
 ///
-/// The curve order is reduced where possible so that cubics may
-/// be turned into quadratics, and quadratics maybe turned into lines.
+/// Curves are approximated with lines, see `PathOp::apply()`.
 ///
 /// Returns true if operation was able to produce a result;
 /// otherwise, result is unmodified.
 ///
 /// # Parameters
 /// - `path` - The path to simplify.
-/// - `result` The simplified path. The result may be the input.
+/// - `result` The simplified path.
 ///
-/// Returns true if simplification succeeded.
-pub fn simplify(_path: &Path, _result: &mut Path) -> bool {
-    unimplemented!()
+/// Returns true if simplification succeeded, even if path is empty,
+/// false if points of path are not finite.
+pub fn simplify(path: &Path, result: &mut Path) -> bool {
+    let Some(simplified) = combine_paths(&[path], |fills| fills[0]) else {
+        return false;
+    };
+    *result = simplified;
+    true
 }
 
 /// Set the resulting rectangle to the tight bounds of the path.
 ///
+/// Unlike bounds of points, control points off the curves are excluded.
+///
 /// # Parameters
 /// - `path` - The path measured.
 /// - `result` - The tight bounds of the path.
 ///
 /// Returns true if the bounds could be computed.
-pub fn tight_bounds(_path: &Path, _result: &mut Rect) -> bool {
-    unimplemented!()
+pub fn tight_bounds(path: &Path, result: &mut Rect) -> bool {
+    let points = path.points();
+    let mut weights = path.conic_weights().iter();
+    let mut extremes: Vec<Point> = Vec::with_capacity(points.len());
+    let mut point_index = 0;
+    let mut last = Point::new();
+    for verb in path.verbs() {
+        match verb {
+            PathVerb::Move | PathVerb::Line => {
+                last = points[point_index];
+                extremes.push(last);
+                point_index += 1;
+            }
+            PathVerb::Quad => {
+                let pts = [last, points[point_index], points[point_index + 1]];
+                for axis in [Point::x, Point::y] {
+                    let [p0, p1, p2] = pts.map(|point| axis(&point));
+                    for t in unit_quad_roots(0.0, p0 - 2.0f32.mul_add(p1, -p2), p1 - p0) {
+                        extremes.push(eval_quad_at(&pts, t));
+                    }
+                }
+                last = pts[2];
+                extremes.push(last);
+                point_index += 2;
+            }
+            PathVerb::Conic => {
+                let weight = weights.next().copied().unwrap_or(1.0);
+                let conic = Conic::new(last, points[point_index], points[point_index + 1], weight);
+                for axis in [Point::x, Point::y] {
+                    let [p0, p1, p2] = conic.pts.map(|point| axis(&point));
+                    let p20 = p2 - p0;
+                    let w_p10 = weight * (p1 - p0);
+                    let a = weight.mul_add(p20, -p20);
+                    let b = 2.0f32.mul_add(-w_p10, p20);
+                    for t in unit_quad_roots(a, b, w_p10) {
+                        extremes.push(conic.eval_at(t));
+                    }
+                }
+                last = conic.pts[2];
+                extremes.push(last);
+                point_index += 2;
+            }
+            PathVerb::Cubic => {
+                let pts = [
+                    last,
+                    points[point_index],
+                    points[point_index + 1],
+                    points[point_index + 2],
+                ];
+                for axis in [Point::x, Point::y] {
+                    let [p0, p1, p2, p3] = pts.map(|point| axis(&point));
+                    // Derivative divided by 3.
+                    let a = 3.0f32.mul_add(p1 - p2, p3 - p0);
+                    let b = 2.0 * 2.0f32.mul_add(-p1, p0 + p2);
+                    for t in unit_quad_roots(a, b, p1 - p0) {
+                        extremes.push(eval_cubic_at(&pts, t));
+                    }
+                }
+                last = pts[3];
+                extremes.push(last);
+                point_index += 3;
+            }
+            PathVerb::Close => {}
+        }
+    }
+    if extremes.is_empty() || !extremes.iter().all(Point::is_finite) {
+        return false;
+    }
+    *result = Rect::from_points(&extremes);
+    true
+}
+
+/// Returns roots of a * t^2 + b * t + c = 0 in range (0, 1).
+fn unit_quad_roots(a: f32, b: f32, c: f32) -> impl Iterator<Item = f32> {
+    let mut roots = [f32::NAN; 2];
+    if a.abs() <= f32::EPSILON * (b.abs() + c.abs()) {
+        roots[0] = -c / b;
+    } else {
+        let discriminant = b.mul_add(b, -4.0 * a * c);
+        if discriminant >= 0.0 {
+            // Avoid cancellation by computing the root with larger magnitude first.
+            let q = -0.5 * (b + discriminant.sqrt().copysign(b));
+            roots[0] = q / a;
+            roots[1] = c / q;
+        }
+    }
+    roots.into_iter().filter(|&t| t > 0.0 && t < 1.0)
 }
 
 /// Set the result with fill type winding to area equivalent to path.
 ///
-/// Returns true if successful. Does not detect if path contains contours which
-/// contain self-crossings or cross other contours; in these cases, may return
-/// true even though result does not fill same area as path.
+/// Unlike Skia, contours are rebuilt as in `simplify()`, so that self-crossing
+/// contours are also converted correctly.
 ///
 /// Returns true if operation was able to produce a result;
-/// otherwise, result is unmodified. The result may be the input.
+/// otherwise, result is unmodified.
 ///
 /// # Parameters
 /// - `path` - The path typically with fill type set to even odd.
 /// - `result` - The equivalent path with fill type set to winding.
 ///
 /// Returns true if winding path was set.
-pub fn as_winding(_path: &Path, _result: &mut Path) -> bool {
-    unimplemented!()
+pub fn as_winding(path: &Path, result: &mut Path) -> bool {
+    simplify(path, result)
 }
 
 /// Perform a series of path operations, optimized for unioning many paths together.
+#[derive(Debug, Default, Clone)]
 pub struct OpBuilder {
     // TODO(Shaohua): Replace Path with PathRef.
     path_refs: Vec<Path>,
@@ -102,6 +239,11 @@ pub struct OpBuilder {
 }
 
 impl OpBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
     /// Add one or more paths and their operand.
     ///
     /// The builder is empty before the first path is added, so the result
@@ -113,7 +255,6 @@ impl OpBuilder {
     pub fn add(&mut self, path: &Path, operator: PathOp) {
         self.path_refs.push(path.clone());
         self.ops.push(operator);
-        unimplemented!()
     }
 
     /// Computes the sum of all paths and operands, and resets the builder to its
@@ -122,22 +263,86 @@ impl OpBuilder {
     /// # Parameters
     /// - `result` - The product of the operands.
     ///
-    /// Returns true if the operation succeeded.
-    pub fn resolve(&mut self, _result: &mut Path) -> bool {
-        unimplemented!()
-    }
+    /// Returns true if the operation succeeded, even if the product is empty,
+    /// false if points of operands are not finite.
+    pub fn resolve(&mut self, result: &mut Path) -> bool {
+        let mut sum = Some(Path::default());
+        for (path, &op) in self.path_refs.iter().zip(&self.ops) {
+            sum = sum.and_then(|sum| {
+                combine_paths(&[&sum, path], |fills| op.contains(fills[0], fills[1]))
+            });
+        }
+        self.reset();
 
-    fn fix_winding(_path: &mut Path) -> bool {
-        unimplemented!()
-    }
-
-    fn reverse_path(_path: &mut Path) {
-        unimplemented!()
+        let Some(sum) = sum else {
+            return false;
+        };
+        *result = sum;
+        true
     }
 
     fn reset(&mut self) {
         self.path_refs.clear();
         self.ops.clear();
-        unimplemented!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::path_builder::PathBuilder;
+    use crate::core::rasterizer::{flatten_contours, FLATTEN_TOLERANCE};
+    use crate::core::rect::Rect;
+
+    fn rect(left: f32, top: f32, right: f32, bottom: f32) -> Path {
+        let mut builder = PathBuilder::new();
+        builder.add_rect(&Rect::from_ltrb(left, top, right, bottom));
+        builder.finish().unwrap()
+    }
+
+    /// Sum of absolute area of all contours, which does not overlap in outputs of ops.
+    fn area(path: &Path) -> f32 {
+        let mut total = 0.0;
+        flatten_contours(path, FLATTEN_TOLERANCE, |points, _closed| {
+            let mut signed = 0.0;
+            for (i, p0) in points.iter().enumerate() {
+                let p1 = points[(i + 1) % points.len()];
+                signed += p0.x().mul_add(p1.y(), -p1.x() * p0.y());
+            }
+            total += (signed / 2.0).abs();
+        });
+        total
+    }
+
+    #[test]
+    fn test_overlapping_rects() {
+        let one = rect(10.0, 10.0, 50.0, 50.0);
+        let two = rect(30.0, 30.0, 70.0, 70.0);
+        let cases = [
+            (PathOp::Union, 2800.0),
+            (PathOp::Intersect, 400.0),
+            (PathOp::Difference, 1200.0),
+            (PathOp::ReverseDifference, 1200.0),
+            (PathOp::Xor, 2400.0),
+        ];
+        for (path_op, expected) in cases {
+            let result = PathOp::apply(&one, &two, path_op).unwrap();
+            assert!((area(&result) - expected).abs() < 0.5, "{path_op:?}");
+        }
+    }
+
+    #[test]
+    fn test_disjoint_rects() {
+        let one = rect(10.0, 10.0, 20.0, 20.0);
+        let two = rect(30.0, 30.0, 40.0, 40.0);
+
+        let mut result = one.clone();
+        assert!(op(&one, &two, PathOp::Intersect, &mut result));
+        assert!(result.verbs().is_empty());
+
+        let union = PathOp::apply(&one, &two, PathOp::Union).unwrap();
+        assert!((area(&union) - 200.0).abs() < 0.5);
+        let difference = PathOp::apply(&one, &two, PathOp::Difference).unwrap();
+        assert!((area(&difference) - 100.0).abs() < 0.5);
     }
 }
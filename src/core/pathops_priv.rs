@@ -0,0 +1,454 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Boolean operations on polygons approximating paths.
+//!
+//! Edges of all operands are split at their intersections and snapped to a fine
+//! grid, so that they only meet at end points. Winding numbers of each operand on
+//! both sides of every edge are then computed by ray casting, and the edges which
+//! separate the result from its complement are linked into contours.
+//!
+//! Self-intersecting and overlapping contours need no special handling, as only
+//! winding numbers decide which side of an edge is inside.
+//!
+//! Winding numbers are computed for every edge by testing all other edges, which
+//! takes O(E^2) time for E edges.
+
+use std::collections::BTreeMap;
+
+use crate::core::path::Path;
+use crate::core::path_builder::PathBuilder;
+use crate::core::path_types::PathFillType;
+use crate::core::point::Point;
+use crate::core::rasterizer::flatten_contours;
+use crate::core::rect::Rect;
+use crate::core::scalar::Scalar;
+
+/// Maximum distance between curves and the lines approximating them.
+const TOLERANCE: Scalar = 0.01;
+
+/// Vertices are snapped to multiples of 1 / `GRID_SCALE`.
+const GRID_SCALE: f64 = 1024.0;
+
+/// Parameters closer than this to end points of a segment do not split it.
+const PARAM_EPSILON: f64 = 1e-9;
+
+type Vertex = [f64; 2];
+
+/// Line segment of an operand, before splitting.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: Vertex,
+    end: Vertex,
+    operand: usize,
+}
+
+/// Edge between two snapped vertices, with `start < end`.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    start: usize,
+    end: usize,
+
+    /// Sum of winding directions of each operand, +1 for segments from `start` to `end`.
+    deltas: [i32; 2],
+}
+
+/// Computes outline of the area where `combine` returns true.
+///
+/// `combine` is called with whether a point is inside of each operand. At most
+/// two operands are supported. The result has `PathFillType::Winding`, or
+/// `PathFillType::InverseWinding` if the area is unbounded, and is an empty path
+/// if the area is empty or covers the whole plane.
+///
+/// Returns None if points of operands are not finite.
+#[must_use]
+pub fn combine_paths(operands: &[&Path], combine: impl Fn(&[bool]) -> bool) -> Option<Path> {
+    debug_assert!(operands.len() <= 2);
+    if !operands
+        .iter()
+        .all(|path| path.points().iter().all(Point::is_finite))
+    {
+        return None;
+    }
+    let fill_types: Vec<PathFillType> = operands.iter().map(|path| path.fill_type()).collect();
+    let inverse = combine(
+        &fill_types
+            .iter()
+            .map(|fill_type| fill_type.is_inverse())
+            .collect::<Vec<_>>(),
+    );
+    let inside = |winding: &[i32; 2]| -> bool {
+        let fills: Vec<bool> = fill_types
+            .iter()
+            .zip(winding)
+            .map(|(fill_type, &winding)| {
+                let fill = if fill_type.is_event_odd() {
+                    winding % 2 != 0
+                } else {
+                    winding != 0
+                };
+                fill != fill_type.is_inverse()
+            })
+            .collect();
+        combine(&fills) != inverse
+    };
+
+    let mut segments = Vec::new();
+    for (operand, path) in operands.iter().enumerate() {
+        add_segments(&mut segments, path, operand);
+    }
+    let (vertices, edges) = build_edges(&segments);
+    let directed = boundary_edges(&vertices, &edges, inside);
+    let contours = link_edges(&vertices, &directed);
+
+    let fill_type = if inverse {
+        PathFillType::InverseWinding
+    } else {
+        PathFillType::Winding
+    };
+    if contours.is_empty() {
+        return Some(Path::new(
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            Rect::new(),
+            fill_type,
+        ));
+    }
+    let mut builder = PathBuilder::new();
+    builder.set_fill_type(fill_type);
+    for contour in &contours {
+        #[allow(clippy::cast_possible_truncation)]
+        let points: Vec<Point> = contour
+            .iter()
+            .map(|&index| Point::from_xy(vertices[index][0] as f32, vertices[index][1] as f32))
+            .collect();
+        builder.add_polygon(&points, true);
+    }
+    builder.finish()
+}
+
+/// Appends segments of closed polygons approximating contours of `path`.
+fn add_segments(segments: &mut Vec<Segment>, path: &Path, operand: usize) {
+    flatten_contours(path, TOLERANCE, |points, _closed| {
+        let count = points.len();
+        for i in 0..count {
+            let start = points[i];
+            let end = points[(i + 1) % count];
+            if start != end {
+                segments.push(Segment {
+                    start: [f64::from(start.x()), f64::from(start.y())],
+                    end: [f64::from(end.x()), f64::from(end.y())],
+                    operand,
+                });
+            }
+        }
+    });
+}
+
+fn sub(a: Vertex, b: Vertex) -> Vertex {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn cross(a: Vertex, b: Vertex) -> f64 {
+    a[0].mul_add(b[1], -a[1] * b[0])
+}
+
+fn dot(a: Vertex, b: Vertex) -> f64 {
+    a[0].mul_add(b[0], a[1] * b[1])
+}
+
+fn lerp(a: Vertex, b: Vertex, t: f64) -> Vertex {
+    [
+        (b[0] - a[0]).mul_add(t, a[0]),
+        (b[1] - a[1]).mul_add(t, a[1]),
+    ]
+}
+
+/// Splits segments at their intersections, snaps them to grid and merges duplicated edges.
+///
+/// Returns snapped vertices and edges between them.
+fn build_edges(segments: &[Segment]) -> (Vec<Vertex>, Vec<Edge>) {
+    // Split points of each segment, with their parameters.
+    let mut splits: Vec<Vec<(f64, Vertex)>> = vec![Vec::new(); segments.len()];
+
+    // Sweep segments sorted by left end, only segments overlapping in x may intersect.
+    let min_x = |segment: &Segment| segment.start[0].min(segment.end[0]);
+    let max_x = |segment: &Segment| segment.start[0].max(segment.end[0]);
+    let mut order: Vec<usize> = (0..segments.len()).collect();
+    order.sort_by(|&a, &b| min_x(&segments[a]).total_cmp(&min_x(&segments[b])));
+    for (position, &i) in order.iter().enumerate() {
+        let a = &segments[i];
+        let a_max_x = max_x(a);
+        for &j in &order[position + 1..] {
+            let b = &segments[j];
+            if min_x(b) > a_max_x {
+                break;
+            }
+            if a.start[1].max(a.end[1]) < b.start[1].min(b.end[1])
+                || b.start[1].max(b.end[1]) < a.start[1].min(a.end[1])
+            {
+                continue;
+            }
+            intersect(a, b, |t, u, point| {
+                if let Some(t) = t {
+                    splits[i].push((t, point));
+                }
+                if let Some(u) = u {
+                    splits[j].push((u, point));
+                }
+            });
+        }
+    }
+
+    let mut vertex_map: BTreeMap<(i64, i64), usize> = BTreeMap::new();
+    let mut vertices: Vec<Vertex> = Vec::new();
+    let mut snap = |point: Vertex| -> usize {
+        #[allow(clippy::cast_possible_truncation)]
+        let key = (
+            (point[0] * GRID_SCALE).round() as i64,
+            (point[1] * GRID_SCALE).round() as i64,
+        );
+        *vertex_map.entry(key).or_insert_with(|| {
+            #[allow(clippy::cast_precision_loss)]
+            vertices.push([key.0 as f64 / GRID_SCALE, key.1 as f64 / GRID_SCALE]);
+            vertices.len() - 1
+        })
+    };
+
+    let mut edge_map: BTreeMap<(usize, usize), [i32; 2]> = BTreeMap::new();
+    for (segment, mut points) in segments.iter().zip(splits) {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut prev = snap(segment.start);
+        let rest = points.iter().map(|&(_t, point)| point);
+        for point in rest.chain(std::iter::once(segment.end)) {
+            let next = snap(point);
+            if next != prev {
+                let (key, delta) = if prev < next {
+                    ((prev, next), 1)
+                } else {
+                    ((next, prev), -1)
+                };
+                edge_map.entry(key).or_insert([0, 0])[segment.operand] += delta;
+            }
+            prev = next;
+        }
+    }
+
+    let edges = edge_map
+        .into_iter()
+        .filter(|(_key, deltas)| deltas.iter().any(|&delta| delta != 0))
+        .map(|((start, end), deltas)| Edge { start, end, deltas })
+        .collect();
+    (vertices, edges)
+}
+
+/// Finds intersections of segments `a` and `b`, and calls `split` with parameter on
+/// `a` if it splits `a`, parameter on `b` if it splits `b`, and the split point.
+fn intersect(a: &Segment, b: &Segment, mut split: impl FnMut(Option<f64>, Option<f64>, Vertex)) {
+    let da = sub(a.end, a.start);
+    let db = sub(b.end, b.start);
+    let offset = sub(b.start, a.start);
+    let denom = cross(da, db);
+    let inner = |t: f64| t > PARAM_EPSILON && t < 1.0 - PARAM_EPSILON;
+    let within = |t: f64| (-PARAM_EPSILON..=1.0 + PARAM_EPSILON).contains(&t);
+
+    let length_product = dot(da, da).sqrt() * dot(db, db).sqrt();
+    if denom.abs() > 1e-12 * length_product {
+        let t = cross(offset, db) / denom;
+        let u = cross(offset, da) / denom;
+        if !within(t) || !within(u) {
+            return;
+        }
+        // Use existing end points, so that touching segments share vertices.
+        let point = if !inner(u) {
+            if u < 0.5 {
+                b.start
+            } else {
+                b.end
+            }
+        } else if !inner(t) {
+            if t < 0.5 {
+                a.start
+            } else {
+                a.end
+            }
+        } else {
+            lerp(a.start, a.end, t)
+        };
+        split(inner(t).then_some(t), inner(u).then_some(u), point);
+        return;
+    }
+
+    // Parallel segments only meet if they are collinear.
+    let distance = cross(offset, da).abs() / dot(da, da).sqrt();
+    if distance > 0.5 / GRID_SCALE {
+        return;
+    }
+    for point in [b.start, b.end] {
+        let t = dot(sub(point, a.start), da) / dot(da, da);
+        if inner(t) {
+            split(Some(t), None, point);
+        }
+    }
+    for point in [a.start, a.end] {
+        let u = dot(sub(point, b.start), db) / dot(db, db);
+        if inner(u) {
+            split(None, Some(u), point);
+        }
+    }
+}
+
+/// Returns edges with inside of the result on one side only, directed so that
+/// outer contours have positive area, as rectangles added by `PathBuilder`.
+fn boundary_edges(
+    vertices: &[Vertex],
+    edges: &[Edge],
+    inside: impl Fn(&[i32; 2]) -> bool,
+) -> Vec<(usize, usize)> {
+    let mut directed = Vec::new();
+    for edge in edges {
+        let start = vertices[edge.start];
+        let end = vertices[edge.end];
+        let mid = lerp(start, end, 0.5);
+        #[allow(clippy::float_cmp)]
+        let horizontal = start[1] == end[1];
+
+        // Winding numbers on the left of edge, or above it if edge is horizontal,
+        // by casting a ray to the left or upwards.
+        let mut winding = [0, 0];
+        for other in edges {
+            if std::ptr::eq(other, edge) {
+                continue;
+            }
+            let p0 = vertices[other.start];
+            let p1 = vertices[other.end];
+            let (axis, cross_axis) = if horizontal { (0, 1) } else { (1, 0) };
+            if (p0[axis] <= mid[axis]) == (p1[axis] <= mid[axis]) {
+                continue;
+            }
+            let t = (mid[axis] - p0[axis]) / (p1[axis] - p0[axis]);
+            let hit = (p1[cross_axis] - p0[cross_axis]).mul_add(t, p0[cross_axis]);
+            if hit < mid[cross_axis] {
+                let sign = direction_sign(p0, p1, horizontal);
+                for (value, delta) in winding.iter_mut().zip(other.deltas) {
+                    *value += sign * delta;
+                }
+            }
+        }
+
+        let sign = direction_sign(start, end, horizontal);
+        let mut other_side = winding;
+        for (value, delta) in other_side.iter_mut().zip(edge.deltas) {
+            *value += sign * delta;
+        }
+        let before = inside(&winding);
+        let after = inside(&other_side);
+        if before == after {
+            continue;
+        }
+
+        // Inside on the left goes down, inside above goes to the left.
+        let forward = if horizontal {
+            after == (end[0] > start[0])
+        } else {
+            before == (end[1] > start[1])
+        };
+        if forward {
+            directed.push((edge.start, edge.end));
+        } else {
+            directed.push((edge.end, edge.start));
+        }
+    }
+    directed
+}
+
+/// Returns change of winding number when crossing the edge from `p0` to `p1`
+/// along the ray, from left to right, or from top to bottom if `horizontal`.
+fn direction_sign(p0: Vertex, p1: Vertex, horizontal: bool) -> i32 {
+    if horizontal {
+        if p1[0] > p0[0] {
+            -1
+        } else {
+            1
+        }
+    } else if p1[1] > p0[1] {
+        1
+    } else {
+        -1
+    }
+}
+
+/// Links directed edges into closed contours, removing collinear vertices.
+fn link_edges(vertices: &[Vertex], directed: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut outgoing: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (index, &(start, _end)) in directed.iter().enumerate() {
+        outgoing.entry(start).or_default().push(index);
+    }
+    let mut used = vec![false; directed.len()];
+    let mut contours = Vec::new();
+
+    for first in 0..directed.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let (start, mut current) = directed[first];
+        let mut incoming = sub(vertices[current], vertices[start]);
+        let mut contour = vec![start];
+        while current != start {
+            contour.push(current);
+            // At vertices shared by several contours, take the sharpest turn
+            // so that contours touching at a vertex stay apart.
+            let next = outgoing.get(&current).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .copied()
+                    .filter(|&index| !used[index])
+                    .max_by(|&a, &b| {
+                        let turn = |index: usize| {
+                            let (from, to) = directed[index];
+                            let out = sub(vertices[to], vertices[from]);
+                            cross(incoming, out).atan2(dot(incoming, out))
+                        };
+                        turn(a).total_cmp(&turn(b))
+                    })
+            });
+            let Some(next) = next else {
+                break;
+            };
+            used[next] = true;
+            let (from, to) = directed[next];
+            incoming = sub(vertices[to], vertices[from]);
+            current = to;
+        }
+
+        let contour = remove_collinear(vertices, contour);
+        if contour.len() >= 3 {
+            contours.push(contour);
+        }
+    }
+    contours
+}
+
+/// Removes vertices of closed `contour` which are on the line between their neighbors.
+fn remove_collinear(vertices: &[Vertex], mut contour: Vec<usize>) -> Vec<usize> {
+    let mut index = 0;
+    while contour.len() >= 3 && index < contour.len() {
+        let count = contour.len();
+        let prev = vertices[contour[(index + count - 1) % count]];
+        let point = vertices[contour[index]];
+        let next = vertices[contour[(index + 1) % count]];
+        let incoming = sub(point, prev);
+        let outgoing = sub(next, point);
+        #[allow(clippy::float_cmp)]
+        if cross(incoming, outgoing) == 0.0 && dot(incoming, outgoing) > 0.0 {
+            contour.remove(index);
+            index = index.saturating_sub(1);
+        } else {
+            index += 1;
+        }
+    }
+    contour
+}
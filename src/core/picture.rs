@@ -0,0 +1,171 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::canvas::Canvas;
+use crate::core::clip_op::ClipOp;
+use crate::core::color::Color4f;
+use crate::core::data::Data;
+use crate::core::paint::Paint;
+use crate::core::path::Path;
+use crate::core::picture_priv::{read_picture, write_picture};
+use crate::core::rect::Rect;
+
+/// A drawing command recorded into Picture.
+#[derive(Debug, Clone)]
+pub(crate) enum Record {
+    Save,
+    SaveLayer(Option<Rect>, Option<Paint>),
+    Restore,
+    ClipPath(Path, ClipOp, bool),
+    Erase(Color4f),
+    DrawPath(Path, Paint),
+}
+
+/// Picture records drawing commands made to Canvas to be played back at a later time.
+///
+/// Picture is immutable once created, and may be played back any number of times
+/// into canvases of any backend.
+///
+/// Picture is created by `PictureRecorder`.
+#[derive(Debug, Clone)]
+pub struct Picture {
+    cull_rect: Rect,
+    records: Vec<Record>,
+}
+
+impl Picture {
+    #[must_use]
+    pub(crate) const fn new(cull_rect: Rect, records: Vec<Record>) -> Self {
+        Self { cull_rect, records }
+    }
+
+    /// Replays the drawing commands on the specified canvas.
+    ///
+    /// # Parameters
+    /// - `canvas` - receiver of drawing commands
+    pub fn playback(&self, canvas: &mut Canvas) {
+        // Unbalanced saves in records shall not leak into canvas.
+        let save_count = canvas.get_save_count();
+        for record in &self.records {
+            match record {
+                Record::Save => {
+                    canvas.save();
+                }
+                Record::SaveLayer(bounds, paint) => {
+                    canvas.save_layer(bounds.as_ref(), paint.as_ref());
+                }
+                Record::Restore => canvas.restore(),
+                Record::ClipPath(path, op, anti_alias) => canvas.clip_path(path, *op, *anti_alias),
+                Record::Erase(color) => canvas.clear_color4f(color),
+                Record::DrawPath(path, paint) => canvas.draw_path(path, paint),
+            }
+        }
+        canvas.restore_to_count(save_count);
+    }
+
+    /// Returns cull Rect for this picture, passed in when Picture was created.
+    ///
+    /// Returned Rect does not specify clipping Rect for Picture; cull is hint
+    /// of Picture bounds.
+    #[must_use]
+    #[inline]
+    pub const fn cull_rect(&self) -> &Rect {
+        &self.cull_rect
+    }
+
+    /// Returns the number of drawing commands in this picture.
+    #[must_use]
+    #[inline]
+    pub fn approximate_op_count(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns a Data containing a serialized representation of this picture.
+    ///
+    /// Data may be sent to another process or machine, and replayed there
+    /// after calling `deserialize()`.
+    ///
+    /// Returns None if a paint used in this picture has shader, color filter,
+    /// mask filter, image filter or path effect, which can not be serialized yet.
+    // TODO(Shaohua): Serialize shaders, filters and path effects of paints.
+    #[must_use]
+    pub fn serialize(&self) -> Option<Data> {
+        write_picture(&self.cull_rect, &self.records).map(|bytes| Data::from(&bytes))
+    }
+
+    /// Recreates Picture that was serialized into a buffer.
+    ///
+    /// Returns None if `data` is not a valid serialized picture.
+    ///
+    /// # Parameters
+    /// - `data` - bytes returned by `serialize()`
+    #[must_use]
+    pub fn deserialize(data: &[u8]) -> Option<Self> {
+        let (cull_rect, records) = read_picture(data)?;
+        Some(Self::new(cull_rect, records))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::color::Color;
+    use crate::core::paint_types::PaintStyle;
+    use crate::core::path_builder::PathBuilder;
+    use crate::core::picture_recorder::PictureRecorder;
+    use crate::core::scalar::Scalar;
+
+    fn record() -> Picture {
+        let mut recorder = PictureRecorder::new();
+        {
+            let mut canvas = recorder.begin_recording(&Rect::from_wh(50.0, 50.0));
+            let mut paint = Paint::new();
+            paint.set_color(Color::from_argb(255, 0, 255, 0));
+            canvas.clear(Color::from_argb(255, 255, 255, 255));
+            canvas.draw_rect(&Rect::from_xywh(10.0, 10.0, 20.0, 20.0), &paint);
+
+            let mut builder = PathBuilder::new();
+            builder.move_to(1.0, 1.0);
+            builder.conic_to(40.0, 1.0, 40.0, 40.0, 0.7);
+            builder.cubic_to(30.0, 45.0, 10.0, 45.0, 1.0, 40.0);
+            builder.close();
+            paint.set_style(PaintStyle::Stroke);
+            paint.set_stroke_width(3.0);
+            canvas.draw_path(&builder.finish().unwrap(), &paint);
+        }
+        recorder.finish_recording_as_picture().unwrap()
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let picture = record();
+        let data = picture.serialize().unwrap();
+        let copy = Picture::deserialize(data.bytes()).unwrap();
+        assert_eq!(copy.cull_rect(), picture.cull_rect());
+        assert_eq!(copy.approximate_op_count(), picture.approximate_op_count());
+        assert_eq!(copy.serialize().unwrap(), data);
+    }
+
+    #[test]
+    fn test_deserialize_truncated() {
+        let data = record().serialize().unwrap();
+        let bytes = data.bytes();
+        for len in 0..bytes.len() {
+            assert!(Picture::deserialize(&bytes[..len]).is_none());
+        }
+    }
+
+    #[test]
+    fn test_deserialize_invalid_scalars() {
+        let data = record().serialize().unwrap();
+        for value in [-1.0_f32, Scalar::NAN, Scalar::INFINITY] {
+            for offset in 0..data.bytes().len() - 4 {
+                let mut bytes = data.bytes().to_vec();
+                bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+                // Must not panic.
+                let _picture = Picture::deserialize(&bytes);
+            }
+        }
+    }
+}
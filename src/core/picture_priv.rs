@@ -0,0 +1,436 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Binary encoding of Picture records.
+//!
+//! All numbers are stored in little endian. Layout of the stream:
+//! - magic `b"jpic"` and version as u32
+//! - cull rect as 4 scalars, left, top, right and bottom
+//! - number of records as u32, followed by the records
+//!
+//! Each record starts with a tag byte:
+//! - `Save` and `Restore`: no data
+//! - `SaveLayer`: flags byte telling if bounds and paint present, then bounds
+//!   as 4 scalars and paint, if present
+//! - `ClipPath`: path, then clip op and anti-alias as bytes
+//! - `Erase`: color as 4 scalars
+//! - `DrawPath`: path, then paint
+//!
+//! Path is stored as fill type byte, verbs, points and conic weights, each
+//! array prefixed with its length as u32.
+//!
+//! Paint is stored as color, stroke width and miter limit as scalars, followed
+//! by style, cap, join, blend mode and flags as bytes.
+
+use crate::core::blend_mode::BlendMode;
+use crate::core::clip_op::ClipOp;
+use crate::core::color::Color4f;
+use crate::core::paint::Paint;
+use crate::core::paint_types::{PaintStyle, StrokeCap, StrokeJoin};
+use crate::core::path::Path;
+use crate::core::path_types::{PathFillType, PathVerb};
+use crate::core::picture::Record;
+use crate::core::point::Point;
+use crate::core::rect::Rect;
+use crate::core::scalar::Scalar;
+
+const MAGIC: &[u8; 4] = b"jpic";
+const VERSION: u32 = 1;
+
+const TAG_ERASE: u8 = 0;
+const TAG_DRAW_PATH: u8 = 1;
+const TAG_SAVE: u8 = 2;
+const TAG_RESTORE: u8 = 3;
+const TAG_CLIP_PATH: u8 = 4;
+const TAG_SAVE_LAYER: u8 = 5;
+
+const HAS_BOUNDS: u8 = 1 << 0;
+const HAS_PAINT: u8 = 1 << 1;
+
+const FLAG_ANTI_ALIAS: u8 = 1 << 0;
+const FLAG_DITHER: u8 = 1 << 1;
+
+const BLEND_MODES: [BlendMode; 29] = [
+    BlendMode::Clear,
+    BlendMode::Src,
+    BlendMode::Dst,
+    BlendMode::SrcOver,
+    BlendMode::DstOver,
+    BlendMode::SrcIn,
+    BlendMode::DstIn,
+    BlendMode::SrcOut,
+    BlendMode::DstOut,
+    BlendMode::SrcATop,
+    BlendMode::DstATop,
+    BlendMode::Xor,
+    BlendMode::Plus,
+    BlendMode::Modulate,
+    BlendMode::Screen,
+    BlendMode::Overlay,
+    BlendMode::Darken,
+    BlendMode::Lighten,
+    BlendMode::ColorDodge,
+    BlendMode::ColorBurn,
+    BlendMode::HardLight,
+    BlendMode::SoftLight,
+    BlendMode::Difference,
+    BlendMode::Exclusion,
+    BlendMode::Multiply,
+    BlendMode::Hue,
+    BlendMode::Saturation,
+    BlendMode::Color,
+    BlendMode::Luminosity,
+];
+
+/// Encodes `cull_rect` and `records` into bytes.
+///
+/// Returns None if a paint has shader, filters or path effect, which can not be
+/// encoded yet.
+pub fn write_picture(cull_rect: &Rect, records: &[Record]) -> Option<Vec<u8>> {
+    let mut writer = Writer::default();
+    writer.bytes.extend_from_slice(MAGIC);
+    writer.write_u32(VERSION);
+    writer.write_rect(cull_rect);
+    writer.write_len(records.len());
+    for record in records {
+        match record {
+            Record::Save => writer.write_u8(TAG_SAVE),
+            Record::SaveLayer(bounds, paint) => {
+                writer.write_u8(TAG_SAVE_LAYER);
+                let mut flags = 0;
+                if bounds.is_some() {
+                    flags |= HAS_BOUNDS;
+                }
+                if paint.is_some() {
+                    flags |= HAS_PAINT;
+                }
+                writer.write_u8(flags);
+                if let Some(bounds) = bounds {
+                    writer.write_rect(bounds);
+                }
+                if let Some(paint) = paint {
+                    writer.write_paint(paint)?;
+                }
+            }
+            Record::Restore => writer.write_u8(TAG_RESTORE),
+            Record::ClipPath(path, op, anti_alias) => {
+                writer.write_u8(TAG_CLIP_PATH);
+                writer.write_path(path);
+                writer.write_u8(*op as u8);
+                writer.write_u8(u8::from(*anti_alias));
+            }
+            Record::Erase(color) => {
+                writer.write_u8(TAG_ERASE);
+                writer.write_color(color);
+            }
+            Record::DrawPath(path, paint) => {
+                writer.write_u8(TAG_DRAW_PATH);
+                writer.write_path(path);
+                writer.write_paint(paint)?;
+            }
+        }
+    }
+    Some(writer.bytes)
+}
+
+/// Decodes cull rect and records from `bytes`.
+///
+/// Returns None if `bytes` is truncated or malformed.
+pub fn read_picture(bytes: &[u8]) -> Option<(Rect, Vec<Record>)> {
+    let mut reader = Reader { bytes };
+    if reader.read_bytes(MAGIC.len())? != MAGIC || reader.read_u32()? != VERSION {
+        return None;
+    }
+    let cull_rect = reader.read_rect()?;
+    let count = reader.read_len()?;
+    // Each record takes at least one byte, do not trust count for allocation.
+    let mut records = Vec::with_capacity(count.min(reader.bytes.len()));
+    for _ in 0..count {
+        let record = match reader.read_u8()? {
+            TAG_SAVE => Record::Save,
+            TAG_SAVE_LAYER => {
+                let flags = reader.read_u8()?;
+                if flags & !(HAS_BOUNDS | HAS_PAINT) != 0 {
+                    return None;
+                }
+                let bounds = if flags & HAS_BOUNDS == 0 {
+                    None
+                } else {
+                    Some(reader.read_rect()?)
+                };
+                let paint = if flags & HAS_PAINT == 0 {
+                    None
+                } else {
+                    Some(reader.read_paint()?)
+                };
+                Record::SaveLayer(bounds, paint)
+            }
+            TAG_RESTORE => Record::Restore,
+            TAG_CLIP_PATH => {
+                let path = reader.read_path()?;
+                let op = match reader.read_u8()? {
+                    0 => ClipOp::Difference,
+                    1 => ClipOp::Intersect,
+                    _ => return None,
+                };
+                let anti_alias = match reader.read_u8()? {
+                    0 => false,
+                    1 => true,
+                    _ => return None,
+                };
+                Record::ClipPath(path, op, anti_alias)
+            }
+            TAG_ERASE => Record::Erase(reader.read_color()?),
+            TAG_DRAW_PATH => {
+                let path = reader.read_path()?;
+                let paint = reader.read_paint()?;
+                Record::DrawPath(path, paint)
+            }
+            _ => return None,
+        };
+        records.push(record);
+    }
+    if reader.bytes.is_empty() {
+        Some((cull_rect, records))
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn write_len(&mut self, len: usize) {
+        self.write_u32(len as u32);
+    }
+
+    fn write_scalar(&mut self, value: Scalar) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_rect(&mut self, rect: &Rect) {
+        self.write_scalar(rect.left());
+        self.write_scalar(rect.top());
+        self.write_scalar(rect.right());
+        self.write_scalar(rect.bottom());
+    }
+
+    fn write_color(&mut self, color: &Color4f) {
+        self.write_scalar(color.red());
+        self.write_scalar(color.green());
+        self.write_scalar(color.blue());
+        self.write_scalar(color.alpha());
+    }
+
+    fn write_path(&mut self, path: &Path) {
+        self.write_u8(path.fill_type() as u8);
+        self.write_len(path.verbs().len());
+        for verb in path.verbs() {
+            self.write_u8(*verb as u8);
+        }
+        self.write_len(path.points().len());
+        for point in path.points() {
+            self.write_scalar(point.x());
+            self.write_scalar(point.y());
+        }
+        self.write_len(path.conic_weights().len());
+        for weight in path.conic_weights() {
+            self.write_scalar(*weight);
+        }
+    }
+
+    /// Returns None if `paint` has shader, filters or path effect.
+    // TODO(Shaohua): Write shader, filters and path effect.
+    fn write_paint(&mut self, paint: &Paint) -> Option<()> {
+        if paint.get_shader().is_some()
+            || paint.get_color_filter().is_some()
+            || paint.get_mask_filter().is_some()
+            || paint.get_image_filter().is_some()
+            || paint.get_path_effect().is_some()
+        {
+            return None;
+        }
+        self.write_color(paint.get_color4f());
+        self.write_scalar(paint.get_stroke_width());
+        self.write_scalar(paint.get_stroke_miter());
+        self.write_u8(paint.get_style() as u8);
+        self.write_u8(paint.get_stroke_cap() as u8);
+        self.write_u8(paint.get_stroke_join() as u8);
+        self.write_u8(paint.get_blend_mode() as u8);
+        let mut flags = 0;
+        if paint.is_anti_alias() {
+            flags |= FLAG_ANTI_ALIAS;
+        }
+        if paint.is_dither() {
+            flags |= FLAG_DITHER;
+        }
+        self.write_u8(flags);
+        Some(())
+    }
+}
+
+#[derive(Debug)]
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Some(head)
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        self.read_bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        let bytes = self.read_bytes(4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn read_len(&mut self) -> Option<usize> {
+        self.read_u32().and_then(|len| usize::try_from(len).ok())
+    }
+
+    fn read_scalar(&mut self) -> Option<Scalar> {
+        let bytes = self.read_bytes(4)?;
+        Some(Scalar::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn read_rect(&mut self) -> Option<Rect> {
+        let left = self.read_scalar()?;
+        let top = self.read_scalar()?;
+        let right = self.read_scalar()?;
+        let bottom = self.read_scalar()?;
+        Some(Rect::from_ltrb(left, top, right, bottom))
+    }
+
+    fn read_color(&mut self) -> Option<Color4f> {
+        let red = self.read_scalar()?;
+        let green = self.read_scalar()?;
+        let blue = self.read_scalar()?;
+        let alpha = self.read_scalar()?;
+        Some(Color4f::from_rgba(red, green, blue, alpha))
+    }
+
+    fn read_path(&mut self) -> Option<Path> {
+        let fill_type = match self.read_u8()? {
+            0 => PathFillType::Winding,
+            1 => PathFillType::EvenOdd,
+            2 => PathFillType::InverseWinding,
+            3 => PathFillType::InverseEvenOdd,
+            _ => return None,
+        };
+
+        let verb_count = self.read_len()?;
+        let mut verbs = Vec::with_capacity(verb_count.min(self.bytes.len()));
+        let mut point_count = 0;
+        let mut weight_count = 0;
+        for _ in 0..verb_count {
+            let verb = match self.read_u8()? {
+                0 => PathVerb::Move,
+                1 => PathVerb::Line,
+                2 => PathVerb::Quad,
+                3 => PathVerb::Conic,
+                4 => PathVerb::Cubic,
+                5 => PathVerb::Close,
+                _ => return None,
+            };
+            // Points of a segment do not include its start point.
+            point_count += match verb {
+                PathVerb::Move | PathVerb::Line => 1,
+                PathVerb::Quad | PathVerb::Conic => 2,
+                PathVerb::Cubic => 3,
+                PathVerb::Close => 0,
+            };
+            if verb == PathVerb::Conic {
+                weight_count += 1;
+            }
+            verbs.push(verb);
+        }
+
+        // Counts must match verbs, as drawing indexes points and weights by verbs.
+        if self.read_len()? != point_count {
+            return None;
+        }
+        let mut points = Vec::with_capacity(point_count.min(self.bytes.len()));
+        for _ in 0..point_count {
+            let x = self.read_scalar()?;
+            let y = self.read_scalar()?;
+            points.push(Point::from_xy(x, y));
+        }
+        if self.read_len()? != weight_count {
+            return None;
+        }
+        let mut conic_weights = Vec::with_capacity(weight_count.min(self.bytes.len()));
+        for _ in 0..weight_count {
+            conic_weights.push(self.read_scalar()?);
+        }
+
+        let bounds = Rect::from_points(&points);
+        Some(Path::new(points, verbs, conic_weights, bounds, fill_type))
+    }
+
+    fn read_paint(&mut self) -> Option<Paint> {
+        let color = self.read_color()?;
+        let stroke_width = self.read_scalar()?;
+        let miter_limit = self.read_scalar()?;
+        if !is_valid_stroke_param(stroke_width) || !is_valid_stroke_param(miter_limit) {
+            return None;
+        }
+        let style = match self.read_u8()? {
+            0 => PaintStyle::Fill,
+            1 => PaintStyle::Stroke,
+            2 => PaintStyle::StrokeAndFill,
+            _ => return None,
+        };
+        let cap = match self.read_u8()? {
+            0 => StrokeCap::Butt,
+            1 => StrokeCap::Round,
+            2 => StrokeCap::Square,
+            _ => return None,
+        };
+        let join = match self.read_u8()? {
+            0 => StrokeJoin::Miter,
+            1 => StrokeJoin::Round,
+            2 => StrokeJoin::Bevel,
+            _ => return None,
+        };
+        let blend_mode = *BLEND_MODES.get(usize::from(self.read_u8()?))?;
+        let flags = self.read_u8()?;
+
+        let mut paint = Paint::from_color(&color);
+        paint.set_stroke_width(stroke_width);
+        paint.set_stroke_miter(miter_limit);
+        paint.set_style(style);
+        paint.set_stroke_cap(cap);
+        paint.set_stroke_join(join);
+        paint.set_blend_mode(blend_mode);
+        paint.set_anti_alias(flags & FLAG_ANTI_ALIAS != 0);
+        paint.set_dither(flags & FLAG_DITHER != 0);
+        Some(paint)
+    }
+}
+
+/// Returns true if `value` can be used as stroke width or miter limit of Paint.
+fn is_valid_stroke_param(value: Scalar) -> bool {
+    value.is_finite() && value >= 0.0
+}
@@ -0,0 +1,133 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::core::canvas::Canvas;
+use crate::core::clip_op::ClipOp;
+use crate::core::color::Color4f;
+use crate::core::device::Device;
+use crate::core::image_info::ImageInfo;
+use crate::core::paint::Paint;
+use crate::core::path::Path;
+use crate::core::picture::{Picture, Record};
+use crate::core::rect::Rect;
+use crate::core::surface_props::SurfaceProps;
+
+/// Records drawing commands made to Canvas into Picture.
+#[derive(Debug, Default)]
+pub struct PictureRecorder {
+    cull_rect: Rect,
+    records: Option<Rc<RefCell<Vec<Record>>>>,
+}
+
+impl PictureRecorder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the canvas that records the drawing commands.
+    ///
+    /// Recording started earlier is discarded.
+    ///
+    /// # Parameters
+    /// - `bounds` - the cull rect used when recording this picture,
+    ///   any drawing that falls outside of this rect is undefined, and may be drawn or it may not.
+    pub fn begin_recording(&mut self, bounds: &Rect) -> Canvas {
+        let records = Rc::new(RefCell::new(Vec::new()));
+        self.cull_rect = bounds.clone();
+        self.records = Some(records.clone());
+        let device = RecordingDevice::new(bounds, records);
+        Canvas::with_device(Box::new(device))
+    }
+
+    /// Returns true if recording is in progress.
+    #[must_use]
+    #[inline]
+    pub const fn is_recording(&self) -> bool {
+        self.records.is_some()
+    }
+
+    /// Signals that the caller is done recording.
+    ///
+    /// Canvas returned by `begin_recording()` may still be alive, but its drawing
+    /// after this call is not recorded.
+    ///
+    /// Returns None if recording is not in progress.
+    #[must_use]
+    pub fn finish_recording_as_picture(&mut self) -> Option<Picture> {
+        let records = self.records.take()?;
+        let records =
+            Rc::try_unwrap(records).map_or_else(|records| records.take(), RefCell::into_inner);
+        Some(Picture::new(self.cull_rect.clone(), records))
+    }
+}
+
+/// Device which appends drawing commands to records of `PictureRecorder`.
+// TODO(Shaohua): Record local to device matrix, so that shaders and filters are
+// mapped by it on playback.
+#[derive(Debug)]
+struct RecordingDevice {
+    info: ImageInfo,
+    props: SurfaceProps,
+    records: Rc<RefCell<Vec<Record>>>,
+}
+
+impl RecordingDevice {
+    #[allow(clippy::cast_possible_truncation)]
+    fn new(bounds: &Rect, records: Rc<RefCell<Vec<Record>>>) -> Self {
+        let width = bounds.right().ceil() as i32;
+        let height = bounds.bottom().ceil() as i32;
+        Self {
+            info: ImageInfo::new_unknown(width.max(0), height.max(0)),
+            props: SurfaceProps::default(),
+            records,
+        }
+    }
+}
+
+impl Device for RecordingDevice {
+    fn image_info(&self) -> &ImageInfo {
+        &self.info
+    }
+
+    fn surface_props(&self) -> &SurfaceProps {
+        &self.props
+    }
+
+    fn save(&mut self) {
+        self.records.borrow_mut().push(Record::Save);
+    }
+
+    fn save_layer(&mut self, bounds: Option<&Rect>, paint: Option<&Paint>) {
+        self.records
+            .borrow_mut()
+            .push(Record::SaveLayer(bounds.cloned(), paint.cloned()));
+    }
+
+    fn restore(&mut self) {
+        self.records.borrow_mut().push(Record::Restore);
+    }
+
+    fn clip_path(&mut self, path: &Path, op: ClipOp, anti_alias: bool) {
+        self.records
+            .borrow_mut()
+            .push(Record::ClipPath(path.clone(), op, anti_alias));
+    }
+
+    fn erase(&mut self, color: &Color4f) {
+        self.records.borrow_mut().push(Record::Erase(color.clone()));
+    }
+
+    fn draw_path(&mut self, path: &Path, paint: &Paint) {
+        if paint.nothing_to_draw() {
+            return;
+        }
+        self.records
+            .borrow_mut()
+            .push(Record::DrawPath(path.clone(), paint.clone()));
+    }
+}
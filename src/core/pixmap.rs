@@ -71,6 +71,18 @@ impl Pixmap {
         }
     }
 
+    /// Creates Pixmap taking ownership of `pixels`, without copying.
+    ///
+    /// Like `from()`, no parameter checking is performed.
+    #[must_use]
+    pub const fn from_vec(info: ImageInfo, row_bytes: usize, pixels: Vec<u8>) -> Self {
+        Self {
+            row_bytes,
+            info,
+            pixels,
+        }
+    }
+
     /// Sets width, height, row bytes to zero; pixel to empty; `ColorType` to
     /// `ColorType::Unknown`; and `AlphaType` to `AlphaType::Unknown`.
     ///
@@ -259,8 +271,8 @@ impl Pixmap {
     ///
     /// Returns pixel converted to unpremultiplied color
     #[must_use]
-    pub fn get_color(&self, _x: i32, _y: i32) -> Option<Color> {
-        unimplemented!()
+    pub fn get_color(&self, x: i32, y: i32) -> Option<Color> {
+        self.get_color4f(x, y).map(Color::from)
     }
 
     /// Returns pixel at (x, y) as unpremultiplied color as an `Color4f`.
@@ -280,8 +292,12 @@ impl Pixmap {
     ///
     /// Returns pixel converted to unpremultiplied float color
     #[must_use]
-    pub fn get_color4f(&self, _x: i32, _y: i32) -> Option<Color4f> {
-        unimplemented!()
+    pub fn get_color4f(&self, x: i32, y: i32) -> Option<Color4f> {
+        debug_assert!(x >= 0 && x < self.width());
+        debug_assert!(y >= 0 && y < self.height());
+        let offset = self.info.compute_offset(x, y, self.row_bytes);
+        let pixel = self.pixels.get(offset..)?;
+        unpack_color4f(self.color_type(), self.alpha_type(), pixel)
     }
 
     /// Look up the pixel at (x,y) and return its alpha component, normalized to [0..1].
@@ -295,8 +311,13 @@ impl Pixmap {
     ///
     /// Returns alpha converted to normalized float
     #[must_use]
-    pub const fn get_alphaf(&self, _x: i32, _y: i32) -> f32 {
-        unimplemented!()
+    pub fn get_alphaf(&self, x: i32, y: i32) -> f32 {
+        debug_assert!(x >= 0 && x < self.width());
+        debug_assert!(y >= 0 && y < self.height());
+        let offset = self.info.compute_offset(x, y, self.row_bytes);
+        self.pixels
+            .get(offset..)
+            .map_or(0.0, |pixel| pixel_alphaf(self.color_type(), pixel))
     }
 
     /// Returns readable pixel address at (x, y).
@@ -768,15 +789,68 @@ impl Pixmap {
     /// - `src_y` - row index whose absolute value is less than `height()`
     ///
     /// Returns true if pixels are copied to `dst_pixels`.
+    #[allow(clippy::cast_sign_loss)]
     pub fn read_pixels_with_info_at(
         &self,
-        _dst_info: &ImageInfo,
-        _dst_pixels: &mut [u8],
-        _dst_row_bytes: usize,
-        _src_x: i32,
-        _src_y: i32,
+        dst_info: &ImageInfo,
+        dst_pixels: &mut [u8],
+        dst_row_bytes: usize,
+        src_x: i32,
+        src_y: i32,
     ) -> bool {
-        unimplemented!()
+        let src_color_type = self.color_type();
+        let dst_color_type = dst_info.color_type();
+        if self.info.is_empty()
+            || dst_info.is_empty()
+            || dst_color_type == ColorType::Unknown
+            || dst_row_bytes < dst_info.min_row_bytes()
+            || dst_pixels.len() < dst_info.compute_byte_size(dst_row_bytes)
+        {
+            return false;
+        }
+        if src_x.abs() >= self.width() || src_y.abs() >= self.height() {
+            return false;
+        }
+        if matches!(src_color_type, ColorType::Gray8 | ColorType::Alpha8)
+            && dst_color_type != src_color_type
+        {
+            return false;
+        }
+        if self.alpha_type() == AlphaType::Opaque && dst_info.alpha_type() != AlphaType::Opaque {
+            return false;
+        }
+
+        // Source pixels to copy.
+        let mut area = IRect::from_xywh(src_x, src_y, dst_info.width(), dst_info.height());
+        if !area.intersect(&self.bounds()) {
+            return false;
+        }
+        let src_bytes_per_pixel = self.info.bytes_per_pixel() as usize;
+        let dst_bytes_per_pixel = dst_info.bytes_per_pixel() as usize;
+        let same_format =
+            src_color_type == dst_color_type && self.alpha_type() == dst_info.alpha_type();
+        for y in area.top()..area.bottom() {
+            let src_start = self.info.compute_offset(area.left(), y, self.row_bytes);
+            let dst_start = dst_info.compute_offset(area.left() - src_x, y - src_y, dst_row_bytes);
+            let src_row = &self.pixels[src_start..];
+            let dst_row = &mut dst_pixels[dst_start..];
+            if same_format {
+                let row_len = area.width() as usize * src_bytes_per_pixel;
+                dst_row[..row_len].copy_from_slice(&src_row[..row_len]);
+                continue;
+            }
+            let src_pixels = src_row.chunks(src_bytes_per_pixel);
+            let dst_pixels = dst_row.chunks_mut(dst_bytes_per_pixel);
+            for (src, dst) in src_pixels.zip(dst_pixels).take(area.width() as usize) {
+                let Some(pixel) = unpack_color4f(src_color_type, self.alpha_type(), src)
+                    .and_then(|color| pack_color4f(dst_color_type, dst_info.alpha_type(), &color))
+                else {
+                    return false;
+                };
+                dst.copy_from_slice(&pixel[..dst_bytes_per_pixel]);
+            }
+        }
+        true
     }
 
     /// Copies a Rect of pixels to dst.
@@ -871,8 +945,8 @@ impl Pixmap {
     /// - `subset` - bounding integer Rect of written pixels
     ///
     /// Returns true if pixels are changed.
-    pub fn erase_with_subset(&mut self, _color: Color, _subset: &IRect) -> bool {
-        unimplemented!()
+    pub fn erase_with_subset(&mut self, color: Color, subset: &IRect) -> bool {
+        self.erase_with_color4f(&color.into(), Some(subset))
     }
 
     /// Writes color to pixels inside `bounds()`; returns true on success.
@@ -898,7 +972,251 @@ impl Pixmap {
     /// - `subset` - bounding integer Rect of pixels to write; may be nullptr
     ///
     /// Returns true if pixels are changed.
-    pub fn erase_with_color4f(&mut self, _color: &Color4f, _subset: Option<&IRect>) -> bool {
-        unimplemented!()
+    #[allow(clippy::cast_sign_loss)]
+    pub fn erase_with_color4f(&mut self, color: &Color4f, subset: Option<&IRect>) -> bool {
+        let mut area = self.bounds();
+        if let Some(subset) = subset {
+            if !area.intersect(subset) {
+                return false;
+            }
+        } else if area.is_empty() {
+            return false;
+        }
+        let Some(pixel) = pack_color4f(self.color_type(), self.alpha_type(), color) else {
+            return false;
+        };
+
+        let bytes_per_pixel = self.info.bytes_per_pixel() as usize;
+        let row_len = area.width() as usize * bytes_per_pixel;
+        for y in area.top()..area.bottom() {
+            let start = self.info.compute_offset(area.left(), y, self.row_bytes);
+            let Some(row) = self.pixels.get_mut(start..start + row_len) else {
+                return false;
+            };
+            for dst in row.chunks_exact_mut(bytes_per_pixel) {
+                dst.copy_from_slice(&pixel[..bytes_per_pixel]);
+            }
+        }
+        true
     }
 }
+
+/// Returns alpha component of the pixel at the start of `pixel`, normalized to [0..1].
+///
+/// Returns 1.0 for color types without alpha, and 0.0 for `ColorType::Unknown`.
+#[must_use]
+pub(crate) fn pixel_alphaf(color_type: ColorType, pixel: &[u8]) -> f32 {
+    let u16_at = |index: usize| u16::from_ne_bytes([pixel[index * 2], pixel[index * 2 + 1]]);
+    let u32_at = || u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+    let u64_at = || {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&pixel[..8]);
+        u64::from_ne_bytes(bytes)
+    };
+    match color_type {
+        ColorType::Unknown => 0.0,
+        ColorType::Gray8
+        | ColorType::R8G8Unorm
+        | ColorType::R16G16Unorm
+        | ColorType::R16G16Float
+        | ColorType::Rgb565
+        | ColorType::Rgb888x
+        | ColorType::Rgb101010x
+        | ColorType::Bgr101010x
+        | ColorType::Bgr101010xXr
+        | ColorType::R8Unorm => 1.0,
+        ColorType::Alpha8 => f32::from(pixel[0]) * (1.0 / 255.0),
+        ColorType::A16Unorm => f32::from(u16_at(0)) * (1.0 / 65535.0),
+        ColorType::A16Float => half_to_f32(u16_at(0)),
+        ColorType::Argb4444 => f32::from(u16_at(0) & 0xF) * (1.0 / 15.0),
+        ColorType::Rgba8888 | ColorType::Bgra8888 | ColorType::Srgba8888 => {
+            f32::from(pixel[3]) * (1.0 / 255.0)
+        }
+        ColorType::Rgba1010102 | ColorType::Bgra1010102 => {
+            #[allow(clippy::cast_precision_loss)]
+            let alpha = (u32_at() >> 30) as f32;
+            alpha * (1.0 / 3.0)
+        }
+        ColorType::Bgra10101010Xr => {
+            #[allow(clippy::cast_precision_loss)]
+            let alpha = ((u64_at() >> 54) & 0x3FF) as f32;
+            (alpha - 384.0) / 510.0
+        }
+        ColorType::Rgba10x6 => {
+            #[allow(clippy::cast_precision_loss)]
+            let alpha = (u64_at() >> 54) as f32;
+            alpha * (1.0 / 1023.0)
+        }
+        ColorType::R16G16B16A16Unorm => f32::from(u16_at(3)) * (1.0 / 65535.0),
+        ColorType::RgbaF16Norm | ColorType::RgbaF16 => half_to_f32(u16_at(3)),
+        ColorType::RgbaF32 => f32::from_ne_bytes([pixel[12], pixel[13], pixel[14], pixel[15]]),
+    }
+}
+
+/// Encodes `color` as one pixel of `color_type`, premultiplied if `alpha_type` is Premul.
+///
+/// Returns None if `color_type` is not supported yet.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+pub(crate) fn pack_color4f(
+    color_type: ColorType,
+    alpha_type: AlphaType,
+    color: &Color4f,
+) -> Option<[u8; 16]> {
+    let alpha = color.alpha().clamp(0.0, 1.0);
+    let scale = if alpha_type == AlphaType::Premul {
+        alpha
+    } else {
+        1.0
+    };
+    let red = color.red().clamp(0.0, 1.0) * scale;
+    let green = color.green().clamp(0.0, 1.0) * scale;
+    let blue = color.blue().clamp(0.0, 1.0) * scale;
+    let unorm = |value: f32, max: f32| (value * max).round() as u16;
+    let byte = |value: f32| unorm(value, 255.0) as u8;
+
+    let mut pixel = [0_u8; 16];
+    match color_type {
+        ColorType::Alpha8 => pixel[0] = byte(alpha),
+        ColorType::Gray8 => {
+            // Rec. 709 luma coefficients, same as Skia.
+            let luma = 0.0722_f32.mul_add(blue, 0.2126_f32.mul_add(red, 0.7152 * green));
+            pixel[0] = byte(luma);
+        }
+        ColorType::Rgb565 => {
+            let value = (unorm(red, 31.0) << 11) | (unorm(green, 63.0) << 5) | unorm(blue, 31.0);
+            pixel[..2].copy_from_slice(&value.to_ne_bytes());
+        }
+        ColorType::Rgba8888 => {
+            pixel[..4].copy_from_slice(&[byte(red), byte(green), byte(blue), byte(alpha)]);
+        }
+        ColorType::Rgb888x => {
+            pixel[..4].copy_from_slice(&[byte(red), byte(green), byte(blue), 0xFF]);
+        }
+        ColorType::Bgra8888 => {
+            pixel[..4].copy_from_slice(&[byte(blue), byte(green), byte(red), byte(alpha)]);
+        }
+        ColorType::R16G16B16A16Unorm => {
+            for (index, value) in [red, green, blue, alpha].into_iter().enumerate() {
+                pixel[index * 2..index * 2 + 2]
+                    .copy_from_slice(&unorm(value, 65535.0).to_ne_bytes());
+            }
+        }
+        ColorType::RgbaF16 | ColorType::RgbaF16Norm => {
+            for (index, value) in [red, green, blue, alpha].into_iter().enumerate() {
+                pixel[index * 2..index * 2 + 2].copy_from_slice(&f32_to_half(value).to_ne_bytes());
+            }
+        }
+        ColorType::RgbaF32 => {
+            for (index, value) in [red, green, blue, alpha].into_iter().enumerate() {
+                pixel[index * 4..index * 4 + 4].copy_from_slice(&value.to_ne_bytes());
+            }
+        }
+        _ => return None,
+    }
+    Some(pixel)
+}
+
+/// Decodes the pixel at the start of `pixel` as unpremultiplied color, the reverse
+/// of `pack_color4f()`.
+///
+/// Color of `ColorType::Alpha8` is black.
+///
+/// Returns None if `color_type` is not supported yet.
+#[must_use]
+pub(crate) fn unpack_color4f(
+    color_type: ColorType,
+    alpha_type: AlphaType,
+    pixel: &[u8],
+) -> Option<Color4f> {
+    let byte = |index: usize| f32::from(pixel[index]) * (1.0 / 255.0);
+    let u16_at = |index: usize| u16::from_ne_bytes([pixel[index * 2], pixel[index * 2 + 1]]);
+    let f32_at = |index: usize| {
+        f32::from_ne_bytes([
+            pixel[index * 4],
+            pixel[index * 4 + 1],
+            pixel[index * 4 + 2],
+            pixel[index * 4 + 3],
+        ])
+    };
+
+    let [red, green, blue, alpha] = match color_type {
+        ColorType::Alpha8 => [0.0, 0.0, 0.0, byte(0)],
+        ColorType::Gray8 => [byte(0), byte(0), byte(0), 1.0],
+        ColorType::Rgb565 => {
+            let value = u16_at(0);
+            [
+                f32::from(value >> 11) * (1.0 / 31.0),
+                f32::from((value >> 5) & 0x3F) * (1.0 / 63.0),
+                f32::from(value & 0x1F) * (1.0 / 31.0),
+                1.0,
+            ]
+        }
+        ColorType::Rgba8888 => [byte(0), byte(1), byte(2), byte(3)],
+        ColorType::Rgb888x => [byte(0), byte(1), byte(2), 1.0],
+        ColorType::Bgra8888 => [byte(2), byte(1), byte(0), byte(3)],
+        ColorType::R16G16B16A16Unorm => {
+            let unorm = |index: usize| f32::from(u16_at(index)) * (1.0 / 65535.0);
+            [unorm(0), unorm(1), unorm(2), unorm(3)]
+        }
+        ColorType::RgbaF16 | ColorType::RgbaF16Norm => [
+            half_to_f32(u16_at(0)),
+            half_to_f32(u16_at(1)),
+            half_to_f32(u16_at(2)),
+            half_to_f32(u16_at(3)),
+        ],
+        ColorType::RgbaF32 => [f32_at(0), f32_at(1), f32_at(2), f32_at(3)],
+        _ => return None,
+    };
+
+    if alpha_type == AlphaType::Premul && color_type != ColorType::Alpha8 {
+        if alpha <= 0.0 {
+            return Some(Color4f::from_rgba(0.0, 0.0, 0.0, 0.0));
+        }
+        let scale = 1.0 / alpha;
+        return Some(Color4f::from_rgba(
+            red * scale,
+            green * scale,
+            blue * scale,
+            alpha,
+        ));
+    }
+    Some(Color4f::from_rgba(red, green, blue, alpha))
+}
+
+/// Converts f32 in range [0, 1] to half precision floating point value.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn f32_to_half(value: f32) -> u16 {
+    if value < 6.103_515_6e-5 {
+        // Subnormal or zero, with step of 2^-24.
+        return (value.max(0.0) * 16_777_216.0).round() as u16;
+    }
+    let bits = value.to_bits();
+    let exponent = (bits >> 23) & 0xFF;
+    let mantissa = bits & 0x7F_FFFF;
+    // Round to nearest, a carry into exponent is correct too.
+    let half = ((exponent - 112) << 10) + ((mantissa + 0x1000) >> 13);
+    half as u16
+}
+
+/// Converts half precision floating point value to f32.
+#[must_use]
+fn half_to_f32(half: u16) -> f32 {
+    let sign = u32::from(half >> 15) << 31;
+    let exponent = u32::from((half >> 10) & 0x1F);
+    let mantissa = u32::from(half & 0x3FF);
+    let bits = match exponent {
+        0 => {
+            // Zero or subnormal.
+            #[allow(clippy::cast_precision_loss)]
+            let value = mantissa as f32 * (1.0 / 16_777_216.0);
+            return if sign == 0 { value } else { -value };
+        }
+        0x1F => sign | 0x7F80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
@@ -0,0 +1,424 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Anti-aliased scan converter of path fills.
+//!
+//! Paths are flattened into lines, and each line adds the signed area it covers
+//! to an accumulation buffer. The running sum along a row is then the winding
+//! number of each pixel weighted by its covered area, so that both fill rules and
+//! partial coverage are computed without sorting edges.
+
+use crate::core::geometry::{eval_cubic_at, eval_quad_at, Conic};
+use crate::core::path::Path;
+use crate::core::path_types::{PathFillType, PathVerb};
+use crate::core::point::Point;
+use crate::core::scalar::Scalar;
+
+/// Maximum distance in pixels between a curve and the lines approximating it.
+pub const FLATTEN_TOLERANCE: Scalar = 0.1;
+
+/// Upper bound of lines generated for a single curve.
+const MAX_CURVE_LINES: usize = 256;
+
+/// Approximates `path` with lines, and calls `add_line` for each of them.
+///
+/// Contours are closed implicitly, as required by fills.
+pub fn flatten_path(path: &Path, tolerance: Scalar, mut add_line: impl FnMut(Point, Point)) {
+    flatten_contours(path, tolerance, |points, _closed| {
+        for pair in points.windows(2) {
+            add_line(pair[0], pair[1]);
+        }
+        if let (Some(&first), Some(&last)) = (points.first(), points.last()) {
+            if first != last {
+                add_line(last, first);
+            }
+        }
+    });
+}
+
+/// Approximates each contour of `path` with a polyline, and calls `visit` with its
+/// points and whether the contour is closed by `PathVerb::Close`.
+///
+/// Contours with only a move verb are skipped, while zero length contours have
+/// at least two points.
+pub fn flatten_contours(path: &Path, tolerance: Scalar, mut visit: impl FnMut(&[Point], bool)) {
+    let points = path.points();
+    let weights = path.conic_weights();
+    let mut point_index = 0;
+    let mut weight_index = 0;
+    let mut contour: Vec<Point> = Vec::new();
+    let mut last = Point::new();
+
+    for verb in path.verbs() {
+        match verb {
+            PathVerb::Move => {
+                if contour.len() > 1 {
+                    visit(&contour, false);
+                }
+                contour.clear();
+                last = points[point_index];
+                contour.push(last);
+                point_index += 1;
+            }
+            PathVerb::Line => {
+                last = points[point_index];
+                contour.push(last);
+                point_index += 1;
+            }
+            PathVerb::Quad => {
+                let pts = [last, points[point_index], points[point_index + 1]];
+                let count = quad_line_count(&pts, tolerance);
+                add_curve(&mut contour, pts[2], count, |t| eval_quad_at(&pts, t));
+                last = pts[2];
+                point_index += 2;
+            }
+            PathVerb::Conic => {
+                let weight = weights[weight_index];
+                let conic = Conic::new(last, points[point_index], points[point_index + 1], weight);
+                // Conics with large weight are sharper than the quad with same control points.
+                let count = quad_line_count(&conic.pts, tolerance * weight.min(1.0));
+                add_curve(&mut contour, conic.pts[2], count, |t| conic.eval_at(t));
+                last = conic.pts[2];
+                point_index += 2;
+                weight_index += 1;
+            }
+            PathVerb::Cubic => {
+                let pts = [
+                    last,
+                    points[point_index],
+                    points[point_index + 1],
+                    points[point_index + 2],
+                ];
+                let count = cubic_line_count(&pts, tolerance);
+                add_curve(&mut contour, pts[3], count, |t| eval_cubic_at(&pts, t));
+                last = pts[3];
+                point_index += 3;
+            }
+            PathVerb::Close => {
+                if let Some(&start) = contour.first() {
+                    if contour.len() > 1 {
+                        visit(&contour, true);
+                    }
+                    contour.clear();
+                    // Verbs after close without a move start at the same point.
+                    last = start;
+                    contour.push(start);
+                }
+            }
+        }
+    }
+    if contour.len() > 1 {
+        visit(&contour, false);
+    }
+}
+
+/// Returns number of lines needed to approximate quad `pts` within `tolerance`.
+///
+/// The distance between a quad and its chord is at most |p0 - 2p1 + p2| / 4,
+/// and it shrinks with the square of number of pieces.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn quad_line_count(pts: &[Point; 3], tolerance: Scalar) -> usize {
+    let dev = (pts[0] - pts[1] * 2.0 + pts[2]).length();
+    let count = (dev / (4.0 * tolerance)).sqrt().ceil();
+    if count.is_finite() {
+        (count as usize).clamp(1, MAX_CURVE_LINES)
+    } else {
+        1
+    }
+}
+
+/// Returns number of lines needed to approximate cubic `pts` within `tolerance`.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn cubic_line_count(pts: &[Point; 4], tolerance: Scalar) -> usize {
+    let dev = (pts[0] - pts[1] * 2.0 + pts[2])
+        .length()
+        .max((pts[1] - pts[2] * 2.0 + pts[3]).length());
+    let count = (3.0 * dev / (4.0 * tolerance)).sqrt().ceil();
+    if count.is_finite() {
+        (count as usize).clamp(1, MAX_CURVE_LINES)
+    } else {
+        1
+    }
+}
+
+/// Appends `count` points of `eval` at uniform steps of t to `contour`, the last
+/// one being `end`.
+#[allow(clippy::cast_precision_loss)]
+fn add_curve(contour: &mut Vec<Point>, end: Point, count: usize, eval: impl Fn(Scalar) -> Point) {
+    let step = 1.0 / count as Scalar;
+    for i in 1..count {
+        contour.push(eval(i as Scalar * step));
+    }
+    contour.push(end);
+}
+
+/// Accumulates coverage of lines in a `width` by `height` pixel area.
+#[derive(Debug, Clone)]
+pub struct Rasterizer {
+    width: usize,
+    height: usize,
+
+    /// Signed area added to each cell, row by row.
+    ///
+    /// Each row has two extra cells on the right, for lines on the right border.
+    accumulation: Vec<f32>,
+
+    /// Rows touched by lines, from `top` to `bottom` exclusive.
+    top: usize,
+    bottom: usize,
+
+    /// Position of the top left corner of the area.
+    origin: Point,
+}
+
+impl Rasterizer {
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            accumulation: vec![0.0; (width + 2) * height],
+            top: height,
+            bottom: 0,
+            origin: Point::new(),
+        }
+    }
+
+    /// Creates rasterizer whose area has its top left corner at `origin`,
+    /// so that row and column indices are relative to `origin`.
+    #[must_use]
+    pub fn with_origin(width: usize, height: usize, origin: Point) -> Self {
+        Self {
+            origin,
+            ..Self::new(width, height)
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    const fn stride(&self) -> usize {
+        self.width + 2
+    }
+
+    /// Adds all contours of `path`, closing them if needed.
+    pub fn add_path(&mut self, path: &Path) {
+        flatten_path(path, FLATTEN_TOLERANCE, |p0, p1| self.add_line(p0, p1));
+    }
+
+    /// Adds the line from `p0` to `p1`.
+    ///
+    /// Parts on the left or the right of the area are moved onto its border, so that
+    /// they still change winding of pixels on their right.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn add_line(&mut self, p0: Point, p1: Point) {
+        if !(p0.is_finite() && p1.is_finite()) {
+            return;
+        }
+        let (p0, p1) = (p0 - self.origin, p1 - self.origin);
+        let width = self.width as f32;
+        let mut splits = [0.0; 4];
+        let mut count = 1;
+        for border in [0.0, width] {
+            if (p0.x() - border) * (p1.x() - border) < 0.0 {
+                splits[count] = (border - p0.x()) / (p1.x() - p0.x());
+                count += 1;
+            }
+        }
+        splits[count] = 1.0;
+        let splits = &mut splits[..=count];
+        splits.sort_by(f32::total_cmp);
+
+        let point_at = |t: Scalar| {
+            let x = (p1.x() - p0.x()).mul_add(t, p0.x());
+            let y = (p1.y() - p0.y()).mul_add(t, p0.y());
+            Point::from_xy(x.clamp(0.0, width), y)
+        };
+        for pair in splits.windows(2) {
+            self.accumulate_line(point_at(pair[0]), point_at(pair[1]));
+        }
+    }
+
+    /// Adds signed area of line from `p0` to `p1`, whose x is inside of [0, width].
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_sign_loss)]
+    #[allow(clippy::float_cmp)]
+    fn accumulate_line(&mut self, p0: Point, p1: Point) {
+        if p0.y() == p1.y() {
+            return;
+        }
+        let (direction, p0, p1) = if p0.y() < p1.y() {
+            (1.0, p0, p1)
+        } else {
+            (-1.0, p1, p0)
+        };
+        let height = self.height as f32;
+        if p1.y() <= 0.0 || p0.y() >= height {
+            return;
+        }
+
+        let width = self.width as f32;
+        let dxdy = (p1.x() - p0.x()) / (p1.y() - p0.y());
+        let y_start = p0.y().max(0.0);
+        let y_end = p1.y().min(height);
+        let mut x = dxdy.mul_add(y_start - p0.y(), p0.x());
+        let row_start = y_start as usize;
+        let row_end = (y_end.ceil() as usize).min(self.height);
+        self.top = self.top.min(row_start);
+        self.bottom = self.bottom.max(row_end);
+
+        let stride = self.stride();
+        for row in row_start..row_end {
+            let line_start = row * stride;
+            let row_y = row as f32;
+            let dy = (row_y + 1.0).min(y_end) - row_y.max(y_start);
+            let x_next = dxdy.mul_add(dy, x).clamp(0.0, width);
+            let area = dy * direction;
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let x0_floor = x0.floor();
+            let x0_index = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1_index = x1_ceil as usize;
+
+            if x1_index <= x0_index + 1 {
+                // Line is inside of a single cell.
+                let x_mid = (x + x_next).mul_add(0.5, -x0_floor);
+                self.accumulation[line_start + x0_index] += x_mid.mul_add(-area, area);
+                self.accumulation[line_start + x0_index + 1] += x_mid * area;
+            } else {
+                let s = (x1 - x0).recip();
+                let x0f = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - x0f) * (1.0 - x0f);
+                let x1f = x1 - x1_ceil + 1.0;
+                let am = 0.5 * s * x1f * x1f;
+                self.accumulation[line_start + x0_index] += area * a0;
+                if x1_index == x0_index + 2 {
+                    self.accumulation[line_start + x0_index + 1] += area * (1.0 - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - x0f);
+                    self.accumulation[line_start + x0_index + 1] += area * (a1 - a0);
+                    for index in x0_index + 2..x1_index - 1 {
+                        self.accumulation[line_start + index] += area * s;
+                    }
+                    let a2 = ((x1_index - x0_index - 3) as f32).mul_add(s, a1);
+                    self.accumulation[line_start + x1_index - 1] += area * (1.0 - a2 - am);
+                }
+                self.accumulation[line_start + x1_index] += area * am;
+            }
+            x = x_next;
+        }
+    }
+
+    /// Calls `blit_row` with row index and coverage in [0, 1] of all pixels in the row,
+    /// for each row which may be covered.
+    ///
+    /// If `anti_alias` is false, pixels are either fully covered or not covered.
+    pub fn fill(
+        &self,
+        fill_type: PathFillType,
+        anti_alias: bool,
+        mut blit_row: impl FnMut(usize, &[f32]),
+    ) {
+        let (top, bottom) = if fill_type.is_inverse() {
+            (0, self.height)
+        } else {
+            (self.top, self.bottom)
+        };
+        let stride = self.stride();
+        let mut coverage = vec![0.0; self.width];
+        for row in top..bottom {
+            let cells = &self.accumulation[row * stride..row * stride + self.width];
+            let mut winding = 0.0;
+            for (value, cell) in coverage.iter_mut().zip(cells) {
+                winding += cell;
+                let mut alpha = match fill_type {
+                    PathFillType::Winding | PathFillType::InverseWinding => winding.abs().min(1.0),
+                    PathFillType::EvenOdd | PathFillType::InverseEvenOdd => {
+                        let alpha = winding.abs() % 2.0;
+                        if alpha > 1.0 {
+                            2.0 - alpha
+                        } else {
+                            alpha
+                        }
+                    }
+                };
+                if fill_type.is_inverse() {
+                    alpha = 1.0 - alpha;
+                }
+                if !anti_alias {
+                    alpha = if alpha >= 0.5 { 1.0 } else { 0.0 };
+                }
+                *value = alpha;
+            }
+            blit_row(row, &coverage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::path_builder::PathBuilder;
+    use crate::core::rect::Rect;
+
+    /// Returns coverage of each pixel, row by row.
+    fn coverage(path: &Path, fill_type: PathFillType, size: usize) -> Vec<f32> {
+        let mut rasterizer = Rasterizer::new(size, size);
+        rasterizer.add_path(path);
+        let mut pixels = vec![0.0; size * size];
+        rasterizer.fill(fill_type, true, |row, coverage| {
+            pixels[row * size..(row + 1) * size].copy_from_slice(coverage);
+        });
+        pixels
+    }
+
+    fn is_coverage(value: f32, expected: f32) -> bool {
+        (value - expected).abs() < 1e-4
+    }
+
+    /// Two nested squares with the same direction.
+    fn nested_squares() -> Path {
+        let mut builder = PathBuilder::new();
+        builder.add_rect(&Rect::from_ltrb(2.0, 2.0, 18.0, 18.0));
+        builder.add_rect(&Rect::from_ltrb(6.0, 6.0, 14.0, 14.0));
+        builder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_winding_fills_inner_square() {
+        let pixels = coverage(&nested_squares(), PathFillType::Winding, 20);
+        assert!(is_coverage(pixels[10 * 20 + 10], 1.0));
+        assert!(is_coverage(pixels[4 * 20 + 4], 1.0));
+        assert!(is_coverage(pixels[20 + 1], 0.0));
+    }
+
+    #[test]
+    fn test_even_odd_leaves_hole() {
+        let pixels = coverage(&nested_squares(), PathFillType::EvenOdd, 20);
+        assert!(is_coverage(pixels[10 * 20 + 10], 0.0));
+        assert!(is_coverage(pixels[4 * 20 + 4], 1.0));
+        assert!(is_coverage(pixels[20 + 1], 0.0));
+    }
+
+    #[test]
+    fn test_inverse_even_odd() {
+        let pixels = coverage(&nested_squares(), PathFillType::InverseEvenOdd, 20);
+        assert!(is_coverage(pixels[10 * 20 + 10], 1.0));
+        assert!(is_coverage(pixels[4 * 20 + 4], 0.0));
+        assert!(is_coverage(pixels[20 + 1], 1.0));
+    }
+
+    #[test]
+    fn test_partial_coverage() {
+        let mut builder = PathBuilder::new();
+        builder.add_rect(&Rect::from_ltrb(2.0, 2.0, 4.5, 4.0));
+        let path = builder.finish().unwrap();
+        let pixels = coverage(&path, PathFillType::Winding, 8);
+        assert!(is_coverage(pixels[3 * 8 + 3], 1.0));
+        assert!(is_coverage(pixels[3 * 8 + 4], 0.5));
+        assert!(is_coverage(pixels[3 * 8 + 5], 0.0));
+    }
+}
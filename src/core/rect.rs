@@ -156,7 +156,7 @@ impl Rect {
     pub fn is_empty(&self) -> bool {
         // We write it as the NOT of a non-empty rect, so we will return true if any values
         // are NaN.
-        self.left < self.right && self.top < self.bottom
+        !(self.left < self.right && self.top < self.bottom)
     }
 
     /// Returns true if left is equal to or less than right,
@@ -334,15 +334,38 @@ impl Rect {
     ///
     /// Result is either empty or sorted: left is less than or equal to right, and
     /// top is less than or equal to bottom.
-    pub fn set_bounds_check(&mut self, _points: &[Point]) {
-        unimplemented!()
+    pub fn set_bounds_check(&mut self, points: &[Point]) {
+        let is_finite = points
+            .iter()
+            .all(|p| p.x().is_finite() && p.y().is_finite());
+        if points.is_empty() || !is_finite {
+            self.set_ltrb(0.0, 0.0, 0.0, 0.0);
+            return;
+        }
+        self.set_bounds_no_check(points);
     }
 
     /// Sets to bounds of Point pts array with count entries.
     ///
     /// If any Point in pts contains infinity or NaN, all Rect dimensions are set to NaN.
-    pub fn set_bounds_no_check(&mut self, _points: &[Point]) {
-        unimplemented!()
+    pub fn set_bounds_no_check(&mut self, points: &[Point]) {
+        let Some((first, rest)) = points.split_first() else {
+            self.set_ltrb(0.0, 0.0, 0.0, 0.0);
+            return;
+        };
+        self.set_ltrb(first.x(), first.y(), first.x(), first.y());
+        for point in rest {
+            self.left = self.left.min(point.x());
+            self.top = self.top.min(point.y());
+            self.right = self.right.max(point.x());
+            self.bottom = self.bottom.max(point.y());
+        }
+        let is_finite = points
+            .iter()
+            .all(|p| p.x().is_finite() && p.y().is_finite());
+        if !is_finite {
+            self.set_ltrb(Scalar::NAN, Scalar::NAN, Scalar::NAN, Scalar::NAN);
+        }
     }
 
     /// Sets bounds to the smallest Rect enclosing Point p0 and p1.
@@ -653,25 +676,25 @@ impl Rect {
 
     /// Sets `IRect` by discarding the fractional portion of left and top;
     /// and rounding up right and bottom, using
-    /// `(left.floor_to_int(), top.floor_to_int(), right.floor_to_int(), bottom.floor_to_int())`.
+    /// `(left.floor_to_int(), top.floor_to_int(), right.ceil_to_int(), bottom.ceil_to_int())`.
     pub fn round_out_irect(&self, dst: &mut IRect) {
         dst.set_ltrb(
             self.left.floor_to_int(),
             self.top.floor_to_int(),
-            self.right.floor_to_int(),
-            self.bottom.floor_to_int(),
+            self.right.ceil_to_int(),
+            self.bottom.ceil_to_int(),
         );
     }
 
     /// Sets Rect by discarding the fractional portion of left and top; and
     /// rounding up right and bottom, using
-    /// `(left.floor(), top.floor(), right.floor(), bottom.floor())`.
+    /// `(left.floor(), top.floor(), right.ceil(), bottom.ceil())`.
     pub fn round_out(&self, dst: &mut Self) {
         dst.set_ltrb(
             self.left.floor(),
             self.top.floor(),
-            self.right.floor(),
-            self.bottom.floor(),
+            self.right.ceil(),
+            self.bottom.ceil(),
         );
     }
 
@@ -698,7 +721,7 @@ impl Rect {
 
     /// Sets `IRect` by discarding the fractional portion of left and top; and rounding
     /// up right and bottom, using
-    /// `(left.floor_to_int(), top.floor_to_int(), right.floor_to_int(), bottom.floor_to_int())`.
+    /// `(left.floor_to_int(), top.floor_to_int(), right.ceil_to_int(), bottom.ceil_to_int())`.
     #[must_use]
     pub fn round_out_to_irect(&self) -> IRect {
         let mut ir = IRect::default();
@@ -142,4 +142,22 @@ impl SamplingOptions {
     pub const fn is_aniso(&self) -> bool {
         self.max_aniso != 0
     }
+
+    #[must_use]
+    #[inline]
+    pub const fn filter(&self) -> FilterMode {
+        self.filter
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn mipmap(&self) -> MipmapMode {
+        self.mipmap
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn use_cubic(&self) -> bool {
+        self.use_cubic
+    }
 }
@@ -0,0 +1,35 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::fmt;
+
+use crate::core::color::Color4f;
+use crate::core::point::Point;
+
+/// Shaders specify the source color(s) for what is being drawn.
+///
+/// If a paint has no shader, then the paint's color is used. If the paint has a
+/// shader, then the shader's color(s) are use instead, but they are modulated by
+/// the paint's alpha. This makes it easy to create a shader once (e.g. bitmap tiling
+/// or gradient) and then change its transparency w/o having to modify the original
+/// shader... only the paint's alpha needs to be modified.
+pub trait Shader: fmt::Debug {
+    /// Returns true if the shader is guaranteed to produce only opaque colors,
+    /// subject to the `Paint` using the shader to apply an opaque alpha value.
+    ///
+    /// Subclasses should override this to allow some optimizations.
+    fn is_opaque(&self) -> bool {
+        false
+    }
+
+    /// Returns the color if this shader draws a single constant color.
+    fn as_a_color(&self) -> Option<Color4f> {
+        None
+    }
+
+    /// Returns unpremultiplied color at `point`, which is in local coordinates of shader.
+    ///
+    /// Raster devices call this at pixel centers.
+    fn color_at(&self, point: &Point) -> Color4f;
+}
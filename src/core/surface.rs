@@ -0,0 +1,153 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Surface is responsible for managing the pixels that a canvas draws into.
+//!
+//! The pixels can be allocated either in CPU memory (a raster surface) or on the GPU.
+//! Surface takes care of allocating a Canvas that will draw into the surface.
+//! Call `canvas()` to use that canvas (but don't delete it, it is owned by the surface).
+//! Surface always has non-zero dimensions. If there is a request for a new surface,
+//! and either of the requested dimensions are zero, then None will be returned.
+
+use crate::core::bitmap_device::BitmapDevice;
+use crate::core::canvas::Canvas;
+use crate::core::image::Image;
+use crate::core::image_info::ImageInfo;
+use crate::core::irect::IRect;
+use crate::core::pixmap::Pixmap;
+use crate::core::surface_props::SurfaceProps;
+
+pub struct Surface {
+    canvas: Canvas,
+}
+
+impl Surface {
+    /// Allocates raster Surface.
+    ///
+    /// Canvas returned by Surface draws directly into pixels, which are zero initialized.
+    ///
+    /// Surface is returned if all parameters are valid.
+    /// Valid parameters include:
+    /// - info dimensions are greater than zero;
+    /// - info contains `ColorType` and `AlphaType` supported by raster surface.
+    ///
+    /// # Parameters
+    /// - `info` - width, height, `ColorType`, `AlphaType`, `ColorSpace`,
+    ///   of raster surface; width and height must be greater than zero
+    ///
+    /// Returns Surface if all parameters are valid; otherwise, None
+    #[must_use]
+    pub fn new_raster(info: &ImageInfo) -> Option<Self> {
+        Self::new_raster_with_props(info, 0, None)
+    }
+
+    /// Allocates raster Surface, see `new_raster()`.
+    ///
+    /// # Parameters
+    /// - `info` - width, height, `ColorType`, `AlphaType`, `ColorSpace`,
+    ///   of raster surface; width and height must be greater than zero
+    /// - `row_bytes` - interval from one Surface row to the next; may be zero
+    /// - `props` - LCD striping orientation and setting for device independent fonts;
+    ///   may be None
+    ///
+    /// Returns Surface if all parameters are valid; otherwise, None
+    #[must_use]
+    pub fn new_raster_with_props(
+        info: &ImageInfo,
+        row_bytes: usize,
+        props: Option<&SurfaceProps>,
+    ) -> Option<Self> {
+        let props = props.cloned().unwrap_or_default();
+        let device = BitmapDevice::alloc(info, row_bytes, props)?;
+        Some(Self {
+            canvas: Canvas::with_device(Box::new(device)),
+        })
+    }
+
+    /// Allocates raster Surface with `ColorType::N32` and `AlphaType::Premul`.
+    ///
+    /// Returns Surface if width and height are greater than zero; otherwise, None
+    #[must_use]
+    pub fn new_raster_n32_premul(width: i32, height: i32) -> Option<Self> {
+        Self::new_raster(&ImageInfo::new_n32_premul(width, height, None))
+    }
+
+    /// Returns pixel count in each row; may be zero or greater.
+    #[must_use]
+    pub fn width(&self) -> i32 {
+        self.canvas.device().width()
+    }
+
+    /// Returns pixel row count; may be zero or greater.
+    #[must_use]
+    pub fn height(&self) -> i32 {
+        self.canvas.device().height()
+    }
+
+    /// Returns an `ImageInfo` describing the surface.
+    #[must_use]
+    pub fn image_info(&self) -> &ImageInfo {
+        self.canvas.device().image_info()
+    }
+
+    /// Returns LCD striping orientation and setting for device independent fonts.
+    #[must_use]
+    pub fn props(&self) -> &SurfaceProps {
+        self.canvas.device().surface_props()
+    }
+
+    /// Returns Canvas that draws into Surface.
+    ///
+    /// Subsequent calls return the same Canvas.
+    pub fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    /// Returns compatible Surface, with the same raster or GPU properties
+    /// and `SurfaceProps` as the original.
+    ///
+    /// # Parameters
+    /// - `info` - width, height, `ColorType`, `AlphaType`, `ColorSpace`, of Surface;
+    ///   width and height must be greater than zero
+    ///
+    /// Returns compatible Surface or None
+    #[must_use]
+    pub fn make_surface(&self, info: &ImageInfo) -> Option<Self> {
+        Self::new_raster_with_props(info, 0, Some(self.props()))
+    }
+
+    /// Returns pixels if they can be read directly.
+    #[must_use]
+    pub fn peek_pixels(&self) -> Option<&Pixmap> {
+        self.canvas.device().peek_pixels()
+    }
+
+    /// Returns Image capturing Surface contents.
+    ///
+    /// Subsequent drawing to Surface contents are not captured.
+    ///
+    /// Returns None if pixels of Surface can not be read.
+    #[must_use]
+    pub fn image_snapshot(&self) -> Option<Image<'static>> {
+        Image::from_pixmap(self.peek_pixels()?)
+    }
+
+    /// Like the no-parameter version, this returns an image of the current surface contents.
+    ///
+    /// This variant takes a rectangle specifying the subset of the surface that is of interest.
+    /// These bounds will be sanitized before being used.
+    /// - If bounds extends beyond the surface, it will be trimmed to just the intersection
+    ///   of it and the surface.
+    /// - If bounds does not intersect the surface, then this returns None.
+    #[must_use]
+    pub fn image_snapshot_subset(&self, bounds: &IRect) -> Option<Image<'static>> {
+        let pixmap = self.peek_pixels()?;
+        let image = Image::from_raw(pixmap.pixels(), pixmap.info().clone(), pixmap.row_bytes())?;
+        let mut subset = image.bounds();
+        if !subset.intersect(bounds) {
+            return None;
+        }
+        image.subset(&subset).map(Image::into_owned)
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Calendar date and wall clock time, used to stamp documents.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct DateTime {
+    /// The number of minutes that `get_date_time()` is ahead of or behind UTC.
+    pub time_zone_minutes: i16,
+
+    /// e.g. 2005
+    pub year: u16,
+
+    /// 1..12
+    pub month: u8,
+
+    /// 0..6, 0 == Sunday
+    pub day_of_week: u8,
+
+    /// 1..31
+    pub day: u8,
+
+    /// 0..23
+    pub hour: u8,
+
+    /// 0..59
+    pub minute: u8,
+
+    /// 0..59
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Returns current date and time in UTC.
+    #[must_use]
+    pub fn now() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |n| n.as_secs());
+        Self::from_unix_secs(secs)
+    }
+
+    /// Converts seconds since the unix epoch to UTC date and time.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_possible_wrap)]
+    #[allow(clippy::cast_sign_loss)]
+    pub const fn from_unix_secs(secs: u64) -> Self {
+        let days = (secs / SECS_PER_DAY) as i64;
+        let secs_of_day = secs % SECS_PER_DAY;
+
+        // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            time_zone_minutes: 0,
+            year: year as u16,
+            month: month as u8,
+            // 1970-01-01 is Thursday.
+            day_of_week: ((days + 4) % 7) as u8,
+            day: day as u8,
+            hour: (secs_of_day / 3600) as u8,
+            minute: (secs_of_day / 60 % 60) as u8,
+            second: (secs_of_day % 60) as u8,
+        }
+    }
+
+    /// Returns true if this is a zero initialized value, which represents an unknown time.
+    #[must_use]
+    #[inline]
+    pub const fn is_unset(&self) -> bool {
+        self.year == 0 && self.month == 0 && self.day == 0
+    }
+
+    /// Formats date time as `YYYY-MM-DDThh:mm:ss+hh:mm`.
+    #[must_use]
+    pub fn to_iso8601(&self) -> String {
+        let (sign, hours, minutes) = self.time_zone_parts();
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{sign}{hours:02}:{minutes:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+
+    /// Returns sign, hours and minutes of time zone offset.
+    #[must_use]
+    pub(crate) const fn time_zone_parts(&self) -> (char, u16, u16) {
+        let sign = if self.time_zone_minutes >= 0 {
+            '+'
+        } else {
+            '-'
+        };
+        let minutes = self.time_zone_minutes.unsigned_abs();
+        (sign, minutes / 60, minutes % 60)
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_iso8601())
+    }
+}
+
+/// Returns current date and time.
+#[must_use]
+#[inline]
+pub fn get_date_time() -> DateTime {
+    DateTime::now()
+}
@@ -0,0 +1,271 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::rc::Rc;
+
+use crate::core::blur_priv::{blur_plane, blur_radius, gaussian_kernel};
+use crate::core::image_filter::{FilterImage, ImageFilter, MapDirection};
+use crate::core::irect::IRect;
+use crate::core::matrix::Matrix;
+use crate::core::rect::Rect;
+use crate::core::scalar::Scalar;
+use crate::core::tile_mode::TileMode;
+use crate::shaders::image_shader::tile;
+
+/// Blurs its input with a separable Gaussian.
+#[derive(Debug, Clone)]
+pub struct BlurImageFilter {
+    sigma_x: Scalar,
+    sigma_y: Scalar,
+    tile_mode: TileMode,
+    input: Option<Rc<dyn ImageFilter>>,
+    crop_rect: Option<Rect>,
+}
+
+impl BlurImageFilter {
+    /// Create a filter that blurs its input by the separate X and Y sigmas.
+    ///
+    /// # Parameters
+    /// - `sigma_x` - the Gaussian sigma value for blurring along the X axis
+    /// - `sigma_y` - the Gaussian sigma value for blurring along the Y axis
+    /// - `tile_mode` - the tile mode applied at edges of input, `TileMode::Decal`
+    ///   blurs input into transparent pixels around it, while other modes keep
+    ///   bounds of input
+    /// - `input` - the input filter that is blurred, uses source image if this is None
+    /// - `crop_rect` - optional rectangle that crops the input and output
+    ///
+    /// Returns None if either sigma is negative or not finite.
+    #[must_use]
+    pub fn new(
+        sigma_x: Scalar,
+        sigma_y: Scalar,
+        tile_mode: TileMode,
+        input: Option<Rc<dyn ImageFilter>>,
+        crop_rect: Option<&Rect>,
+    ) -> Option<Self> {
+        if !(sigma_x >= 0.0 && sigma_x.is_finite() && sigma_y >= 0.0 && sigma_y.is_finite()) {
+            return None;
+        }
+        Some(Self {
+            sigma_x,
+            sigma_y,
+            tile_mode,
+            input,
+            crop_rect: crop_rect.cloned(),
+        })
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn sigma_x(&self) -> Scalar {
+        self.sigma_x
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn sigma_y(&self) -> Scalar {
+        self.sigma_y
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn tile_mode(&self) -> TileMode {
+        self.tile_mode
+    }
+}
+
+/// Returns sigmas mapped to device space by `ctm`.
+///
+/// Sigmas are mapped as a vector, which is exact for scales, translations and
+/// rotations by multiples of 90 degrees.
+#[must_use]
+pub(crate) fn map_sigma(sigma_x: Scalar, sigma_y: Scalar, ctm: &Matrix) -> (Scalar, Scalar) {
+    let sigma = ctm.map_vector(sigma_x, sigma_y);
+    (sigma.x().abs(), sigma.y().abs())
+}
+
+/// Blurs each channel of `image` with `sigma_x` and `sigma_y`.
+///
+/// Pixels outside of `image` are read with `tile_mode`.
+/// The result is outset by blur radius with `TileMode::Decal`, and has the same
+/// bounds as `image` otherwise.
+#[must_use]
+#[allow(clippy::cast_sign_loss)]
+pub(crate) fn blur_image(
+    image: &FilterImage,
+    sigma_x: Scalar,
+    sigma_y: Scalar,
+    tile_mode: TileMode,
+) -> FilterImage {
+    if image.is_empty() {
+        return image.clone();
+    }
+    let radius_x = blur_radius(sigma_x);
+    let radius_y = blur_radius(sigma_y);
+    let src_bounds = image.bounds();
+    let mut padded_bounds = src_bounds.clone();
+    padded_bounds.outset(radius_x, radius_y);
+    let width = padded_bounds.width() as usize;
+    let height = padded_bounds.height() as usize;
+
+    // Planes of each channel, padded by blur radius.
+    let mut planes = vec![vec![0.0; width * height]; 4];
+    for y in padded_bounds.top()..padded_bounds.bottom() {
+        let Some(src_y) = tile(y - src_bounds.top(), src_bounds.height(), tile_mode) else {
+            continue;
+        };
+        let row = (y - padded_bounds.top()) as usize;
+        for x in padded_bounds.left()..padded_bounds.right() {
+            let Some(src_x) = tile(x - src_bounds.left(), src_bounds.width(), tile_mode) else {
+                continue;
+            };
+            let index = row * width + (x - padded_bounds.left()) as usize;
+            let pixel = image.pixel_at(src_bounds.left() + src_x, src_bounds.top() + src_y);
+            for (plane, value) in planes.iter_mut().zip(pixel) {
+                plane[index] = value;
+            }
+        }
+    }
+
+    let kernel_x = gaussian_kernel(sigma_x);
+    let kernel_y = gaussian_kernel(sigma_y);
+    let planes: Vec<Vec<Scalar>> = planes
+        .iter()
+        .map(|plane| blur_plane(plane, width, height, &kernel_x, &kernel_y))
+        .collect();
+
+    let bounds = if tile_mode == TileMode::Decal {
+        padded_bounds.clone()
+    } else {
+        src_bounds.clone()
+    };
+    let mut blurred = FilterImage::new(bounds.clone());
+    for y in bounds.top()..bounds.bottom() {
+        let row = (y - padded_bounds.top()) as usize;
+        for x in bounds.left()..bounds.right() {
+            let index = row * width + (x - padded_bounds.left()) as usize;
+            let pixel = [
+                planes[0][index],
+                planes[1][index],
+                planes[2][index],
+                planes[3][index],
+            ];
+            blurred.set_pixel(x, y, pixel);
+        }
+    }
+    blurred
+}
+
+impl ImageFilter for BlurImageFilter {
+    fn count_inputs(&self) -> usize {
+        1
+    }
+
+    fn get_input(&self, index: usize) -> Option<&Rc<dyn ImageFilter>> {
+        if index == 0 {
+            self.input.as_ref()
+        } else {
+            None
+        }
+    }
+
+    fn crop_rect(&self) -> Option<&Rect> {
+        self.crop_rect.as_ref()
+    }
+
+    fn on_filter_image(&self, source: &FilterImage, ctm: &Matrix) -> FilterImage {
+        let mut input = self.filter_input(0, source, ctm);
+        if let Some(crop_rect) = self.device_crop_rect(ctm) {
+            input = input.crop(&crop_rect);
+        }
+        let (sigma_x, sigma_y) = map_sigma(self.sigma_x, self.sigma_y, ctm);
+        blur_image(&input, sigma_x, sigma_y, self.tile_mode)
+    }
+
+    fn on_filter_node_bounds(&self, src: &IRect, ctm: &Matrix, _dir: MapDirection) -> IRect {
+        let mut bounds = src.clone();
+        if !bounds.is_empty() {
+            let (sigma_x, sigma_y) = map_sigma(self.sigma_x, self.sigma_y, ctm);
+            bounds.outset(blur_radius(sigma_x), blur_radius(sigma_y));
+        }
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a white opaque square of 4x4 at (10, 10).
+    fn square() -> FilterImage {
+        let bounds = IRect::from_xywh(10, 10, 4, 4);
+        let mut image = FilterImage::new(bounds.clone());
+        for y in bounds.top()..bounds.bottom() {
+            for x in bounds.left()..bounds.right() {
+                image.set_pixel(x, y, [1.0; 4]);
+            }
+        }
+        image
+    }
+
+    fn total_alpha(image: &FilterImage) -> f32 {
+        image.pixels().iter().map(|pixel| pixel[3]).sum()
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(BlurImageFilter::new(-1.0, 1.0, TileMode::Decal, None, None).is_none());
+        assert!(BlurImageFilter::new(1.0, f32::INFINITY, TileMode::Decal, None, None).is_none());
+        assert!(BlurImageFilter::new(0.0, 0.0, TileMode::Decal, None, None).is_some());
+    }
+
+    #[test]
+    fn test_decal() {
+        let filter = BlurImageFilter::new(1.0, 2.0, TileMode::Decal, None, None).unwrap();
+        let ctm = Matrix::identity();
+        let image = filter.filter_image(&square(), &ctm);
+        assert_eq!(*image.bounds(), IRect::from_ltrb(7, 4, 17, 20));
+        assert!((total_alpha(&image) - 16.0).abs() < 0.01);
+        assert!(image.pixel_at(7, 12)[3] > 0.0);
+        assert!(image.pixel_at(11, 11)[3] < 1.0);
+
+        let src = IRect::from_xywh(10, 10, 4, 4);
+        assert_eq!(
+            filter.filter_bounds(&src, &ctm, MapDirection::Forward),
+            IRect::from_ltrb(7, 4, 17, 20)
+        );
+    }
+
+    #[test]
+    fn test_clamp() {
+        // Blurring a solid image with clamped edges keeps it solid.
+        let filter = BlurImageFilter::new(2.0, 2.0, TileMode::Clamp, None, None).unwrap();
+        let image = filter.filter_image(&square(), &Matrix::identity());
+        assert_eq!(*image.bounds(), IRect::from_xywh(10, 10, 4, 4));
+        for pixel in image.pixels() {
+            assert!(pixel.iter().all(|value| (value - 1.0).abs() < 1e-4));
+        }
+    }
+
+    #[test]
+    fn test_sigma_follows_ctm() {
+        let filter = BlurImageFilter::new(1.0, 1.0, TileMode::Decal, None, None).unwrap();
+        let ctm = Matrix::from_scale(2.0, 3.0);
+        assert_eq!(map_sigma(1.0, 1.0, &ctm), (2.0, 3.0));
+        let image = filter.filter_image(&square(), &ctm);
+        assert_eq!(*image.bounds(), IRect::from_ltrb(4, 1, 20, 23));
+    }
+
+    #[test]
+    fn test_crop_rect() {
+        let crop_rect = Rect::from_xywh(0.0, 0.0, 12.0, 100.0);
+        let filter =
+            BlurImageFilter::new(1.0, 1.0, TileMode::Decal, None, Some(&crop_rect)).unwrap();
+        let image = filter.filter_image(&square(), &Matrix::identity());
+        assert_eq!(*image.bounds(), IRect::from_ltrb(7, 7, 12, 17));
+        // Only the left half of the square is blurred, and the result is cropped again.
+        let total = total_alpha(&image);
+        assert!(total > 4.0 && total < 8.0);
+    }
+}
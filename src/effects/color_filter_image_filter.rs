@@ -0,0 +1,107 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::rc::Rc;
+
+use crate::core::color::Color4f;
+use crate::core::color_filter::ColorFilter;
+use crate::core::color_space::ColorSpace;
+use crate::core::image_filter::{FilterImage, ImageFilter};
+use crate::core::matrix::Matrix;
+use crate::core::rect::Rect;
+
+/// Applies a color filter to each pixel of its input.
+#[derive(Debug, Clone)]
+pub struct ColorFilterImageFilter {
+    color_filter: Rc<ColorFilter>,
+    input: Option<Rc<dyn ImageFilter>>,
+    crop_rect: Option<Rect>,
+}
+
+impl ColorFilterImageFilter {
+    /// Create a filter that applies the color filter to the input filter results.
+    ///
+    /// # Parameters
+    /// - `color_filter` - the color filter that transforms the input image
+    /// - `input` - the input image filter, if None the source image is used
+    /// - `crop_rect` - optional rectangle that crops the input and output
+    // TODO(Shaohua): Fill area outside of input if color filter affects transparent black.
+    #[must_use]
+    pub fn new(
+        color_filter: Rc<ColorFilter>,
+        input: Option<Rc<dyn ImageFilter>>,
+        crop_rect: Option<&Rect>,
+    ) -> Self {
+        Self {
+            color_filter,
+            input,
+            crop_rect: crop_rect.cloned(),
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn color_filter(&self) -> &Rc<ColorFilter> {
+        &self.color_filter
+    }
+}
+
+impl ImageFilter for ColorFilterImageFilter {
+    fn count_inputs(&self) -> usize {
+        1
+    }
+
+    fn get_input(&self, index: usize) -> Option<&Rc<dyn ImageFilter>> {
+        if index == 0 {
+            self.input.as_ref()
+        } else {
+            None
+        }
+    }
+
+    fn crop_rect(&self) -> Option<&Rect> {
+        self.crop_rect.as_ref()
+    }
+
+    fn as_a_color_filter(&self) -> Option<Rc<ColorFilter>> {
+        if self.input.is_none() && self.crop_rect.is_none() {
+            Some(self.color_filter.clone())
+        } else {
+            None
+        }
+    }
+
+    fn on_filter_image(&self, source: &FilterImage, ctm: &Matrix) -> FilterImage {
+        let mut image = self.filter_input(0, source, ctm);
+        let src_cs = ColorSpace::default();
+        let mut dst_cs = ColorSpace::default();
+        let bounds = image.bounds().clone();
+        for y in bounds.top()..bounds.bottom() {
+            for x in bounds.left()..bounds.right() {
+                let pixel = image.pixel_at(x, y);
+                let alpha = pixel[3];
+                let color = if alpha > 0.0 {
+                    Color4f::from_rgba(pixel[0] / alpha, pixel[1] / alpha, pixel[2] / alpha, alpha)
+                } else {
+                    Color4f::from_rgba(0.0, 0.0, 0.0, 0.0)
+                };
+                let color = self
+                    .color_filter
+                    .filter_color4f(&color, &src_cs, &mut dst_cs);
+                let alpha = color.alpha().clamp(0.0, 1.0);
+                image.set_pixel(
+                    x,
+                    y,
+                    [
+                        color.red().clamp(0.0, 1.0) * alpha,
+                        color.green().clamp(0.0, 1.0) * alpha,
+                        color.blue().clamp(0.0, 1.0) * alpha,
+                        alpha,
+                    ],
+                );
+            }
+        }
+        image
+    }
+}
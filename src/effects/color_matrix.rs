@@ -71,36 +71,74 @@ impl ColorMatrix {
         self.set_scale_with_alpha(red_scale, green_scale, blue_scale, 1.0);
     }
 
+    #[rustfmt::skip]
     pub fn set_scale_with_alpha(
         &mut self,
-        _red_scale: f32,
-        _green_scale: f32,
-        _blue_scale: f32,
-        _alpha_scale: f32,
+        red_scale: f32,
+        green_scale: f32,
+        blue_scale: f32,
+        alpha_scale: f32,
     ) {
-        unimplemented!();
+        self.mat = [
+            red_scale, 0., 0., 0., 0.,
+            0., green_scale, 0., 0., 0.,
+            0., 0., blue_scale, 0., 0.,
+            0., 0., 0., alpha_scale, 0.,
+        ];
     }
 
-    pub fn post_translate(&mut self, _dr: f32, _dg: f32, _db: f32, _da: f32) {
-        unimplemented!();
+    /// Adds translation to each channel, in [0, 1] units.
+    pub fn post_translate(&mut self, dr: f32, dg: f32, db: f32, da: f32) {
+        self.mat[4] += dr;
+        self.mat[9] += dg;
+        self.mat[14] += db;
+        self.mat[19] += da;
     }
 
-    pub fn set_concat(&mut self, _mat_a: &Self, _mat_b: &Self) {
-        unimplemented!();
+    /// Sets matrix to `mat_a` * `mat_b`, which applies `mat_b` first.
+    pub fn set_concat(&mut self, mat_a: &Self, mat_b: &Self) {
+        let a = &mat_a.mat;
+        let b = &mat_b.mat;
+        let mut result = [0.0; 20];
+        for row in 0..4 {
+            for column in 0..5 {
+                let mut value = if column == 4 { a[row * 5 + 4] } else { 0.0 };
+                for k in 0..4 {
+                    value = a[row * 5 + k].mul_add(b[k * 5 + column], value);
+                }
+                result[row * 5 + column] = value;
+            }
+        }
+        self.mat = result;
     }
 
-    pub fn pre_concat(&mut self, _mat: &Self) {
-        unimplemented!();
-        //this->setConcat(*this, mat)
+    /// Sets matrix to self * `mat`, which applies `mat` first.
+    pub fn pre_concat(&mut self, mat: &Self) {
+        let this = self.clone();
+        self.set_concat(&this, mat);
     }
 
-    pub fn post_concat(&mut self, _mat: &Self) {
-        unimplemented!()
-        //this->setConcat(mat, *this);
+    /// Sets matrix to `mat` * self, which applies `mat` last.
+    pub fn post_concat(&mut self, mat: &Self) {
+        let this = self.clone();
+        self.set_concat(mat, &this);
     }
 
-    pub fn set_saturation(&mut self, _sat: f32) {
-        unimplemented!()
+    /// Sets matrix to change saturation of colors, where 0 makes gray and 1 keeps colors.
+    #[rustfmt::skip]
+    pub fn set_saturation(&mut self, sat: f32) {
+        const HUE_R: f32 = 0.213;
+        const HUE_G: f32 = 0.715;
+        const HUE_B: f32 = 0.072;
+        let red = HUE_R * (1.0 - sat);
+        let green = HUE_G * (1.0 - sat);
+        let blue = HUE_B * (1.0 - sat);
+        self.mat = [
+            red + sat, green, blue, 0., 0.,
+            red, green + sat, blue, 0., 0.,
+            red, green, blue + sat, 0., 0.,
+            0., 0., 0., 1., 0.,
+        ];
     }
 
     pub fn set_row_major(&mut self, src: &[f32; 20]) {
@@ -0,0 +1,123 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::rc::Rc;
+
+use crate::core::image_filter::{FilterImage, ImageFilter, MapDirection};
+use crate::core::irect::IRect;
+use crate::core::matrix::Matrix;
+
+/// Applies the outer filter to result of the inner filter.
+#[derive(Debug, Clone)]
+pub struct ComposeImageFilter {
+    outer: Rc<dyn ImageFilter>,
+    inner: Rc<dyn ImageFilter>,
+}
+
+impl ComposeImageFilter {
+    /// Create a filter that composes `inner` with `outer`, such that the results
+    /// of `inner` are treated as the source image passed to `outer`, i.e.
+    /// result = outer(inner(source)).
+    #[must_use]
+    pub fn new(outer: Rc<dyn ImageFilter>, inner: Rc<dyn ImageFilter>) -> Self {
+        Self { outer, inner }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn outer(&self) -> &Rc<dyn ImageFilter> {
+        &self.outer
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn inner(&self) -> &Rc<dyn ImageFilter> {
+        &self.inner
+    }
+}
+
+impl ImageFilter for ComposeImageFilter {
+    fn count_inputs(&self) -> usize {
+        2
+    }
+
+    fn get_input(&self, index: usize) -> Option<&Rc<dyn ImageFilter>> {
+        match index {
+            0 => Some(&self.outer),
+            1 => Some(&self.inner),
+            _ => None,
+        }
+    }
+
+    fn on_filter_image(&self, source: &FilterImage, ctm: &Matrix) -> FilterImage {
+        self.outer
+            .filter_image(&self.inner.filter_image(source, ctm), ctm)
+    }
+
+    fn filter_bounds(&self, src: &IRect, ctm: &Matrix, dir: MapDirection) -> IRect {
+        match dir {
+            MapDirection::Forward => {
+                self.outer
+                    .filter_bounds(&self.inner.filter_bounds(src, ctm, dir), ctm, dir)
+            }
+            MapDirection::Reverse => {
+                self.inner
+                    .filter_bounds(&self.outer.filter_bounds(src, ctm, dir), ctm, dir)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::merge_image_filter::MergeImageFilter;
+    use crate::effects::offset_image_filter::OffsetImageFilter;
+
+    fn offset(dx: f32, dy: f32) -> Rc<dyn ImageFilter> {
+        Rc::new(OffsetImageFilter::new(dx, dy, None, None).unwrap())
+    }
+
+    fn source() -> FilterImage {
+        let mut image = FilterImage::new(IRect::from_xywh(0, 0, 1, 1));
+        image.set_pixel(0, 0, [1.0; 4]);
+        image
+    }
+
+    #[test]
+    fn test_compose() {
+        let ctm = Matrix::identity();
+        let filter = ComposeImageFilter::new(offset(1.0, 0.0), offset(0.0, 2.0));
+        assert_eq!(filter.count_inputs(), 2);
+        let image = filter.filter_image(&source(), &ctm);
+        assert_eq!(*image.bounds(), IRect::from_xywh(1, 2, 1, 1));
+
+        let src = IRect::from_xywh(0, 0, 1, 1);
+        assert_eq!(
+            filter.filter_bounds(&src, &ctm, MapDirection::Forward),
+            IRect::from_xywh(1, 2, 1, 1)
+        );
+        assert_eq!(
+            filter.filter_bounds(&src, &ctm, MapDirection::Reverse),
+            IRect::from_xywh(-1, -2, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let ctm = Matrix::identity();
+        let filter = MergeImageFilter::new(vec![Some(offset(3.0, 0.0)), None], None);
+        let image = filter.filter_image(&source(), &ctm);
+        assert_eq!(*image.bounds(), IRect::from_xywh(0, 0, 4, 1));
+        assert!((image.pixel_at(0, 0)[3] - 1.0).abs() < 1e-6);
+        assert!(image.pixel_at(1, 0)[3].abs() < 1e-6);
+        assert!((image.pixel_at(3, 0)[3] - 1.0).abs() < 1e-6);
+
+        let src = IRect::from_xywh(0, 0, 1, 1);
+        assert_eq!(
+            filter.filter_bounds(&src, &ctm, MapDirection::Forward),
+            IRect::from_xywh(0, 0, 4, 1)
+        );
+    }
+}
@@ -32,6 +32,7 @@ pub fn make(_path: &Path, _advance: Scalar, _phase: Scalar, _style: Style) -> Bo
     unimplemented!()
 }
 
+#[derive(Debug)]
 pub struct D1PathEffect {}
 
 impl PathEffect for D1PathEffect {
@@ -0,0 +1,261 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::rc::Rc;
+
+use crate::core::blur_priv::blur_radius;
+use crate::core::color::Color4f;
+use crate::core::image_filter::{FilterImage, ImageFilter, MapDirection};
+use crate::core::irect::IRect;
+use crate::core::matrix::Matrix;
+use crate::core::rect::Rect;
+use crate::core::scalar::Scalar;
+use crate::core::tile_mode::TileMode;
+use crate::effects::blur_image_filter::{blur_image, map_sigma};
+
+/// Draws a blurred and offset copy of alpha of its input, filled with a color,
+/// below the input.
+#[derive(Debug, Clone)]
+pub struct DropShadowImageFilter {
+    dx: Scalar,
+    dy: Scalar,
+    sigma_x: Scalar,
+    sigma_y: Scalar,
+    color: Color4f,
+    shadow_only: bool,
+    input: Option<Rc<dyn ImageFilter>>,
+    crop_rect: Option<Rect>,
+}
+
+impl DropShadowImageFilter {
+    /// Create a filter that draws a drop shadow under the input content.
+    ///
+    /// This filter produces an image that includes the inputs' content.
+    ///
+    /// # Parameters
+    /// - `dx` - the X offset of the shadow, rounded to whole pixels
+    /// - `dy` - the Y offset of the shadow, rounded to whole pixels
+    /// - `sigma_x` - the blur radius for the shadow, along the X axis
+    /// - `sigma_y` - the blur radius for the shadow, along the Y axis
+    /// - `color` - the color of the drop shadow
+    /// - `input` - the input filter, or will use the source image if this is None
+    /// - `crop_rect` - optional rectangle that crops the input and output
+    ///
+    /// Returns None if an offset is not finite, or a sigma is negative or not finite.
+    #[must_use]
+    pub fn new(
+        dx: Scalar,
+        dy: Scalar,
+        sigma_x: Scalar,
+        sigma_y: Scalar,
+        color: Color4f,
+        input: Option<Rc<dyn ImageFilter>>,
+        crop_rect: Option<&Rect>,
+    ) -> Option<Self> {
+        if !(dx.is_finite()
+            && dy.is_finite()
+            && sigma_x >= 0.0
+            && sigma_x.is_finite()
+            && sigma_y >= 0.0
+            && sigma_y.is_finite())
+        {
+            return None;
+        }
+        Some(Self {
+            dx,
+            dy,
+            sigma_x,
+            sigma_y,
+            color,
+            shadow_only: false,
+            input,
+            crop_rect: crop_rect.cloned(),
+        })
+    }
+
+    /// Create a filter that renders a drop shadow, in exactly the same manner as
+    /// `new()`, except that the resulting image does not include the input content.
+    ///
+    /// This allows the shadow and input to be composed by a filter DAG in a more
+    /// flexible manner.
+    #[must_use]
+    pub fn new_shadow_only(
+        dx: Scalar,
+        dy: Scalar,
+        sigma_x: Scalar,
+        sigma_y: Scalar,
+        color: Color4f,
+        input: Option<Rc<dyn ImageFilter>>,
+        crop_rect: Option<&Rect>,
+    ) -> Option<Self> {
+        let mut filter = Self::new(dx, dy, sigma_x, sigma_y, color, input, crop_rect)?;
+        filter.shadow_only = true;
+        Some(filter)
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn color(&self) -> &Color4f {
+        &self.color
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn shadow_only(&self) -> bool {
+        self.shadow_only
+    }
+
+    /// Returns offset mapped to device space by `ctm`, rounded to whole pixels.
+    #[allow(clippy::cast_possible_truncation)]
+    fn offset(&self, ctm: &Matrix) -> (i32, i32) {
+        let offset = ctm.map_vector(self.dx, self.dy);
+        (offset.x().round() as i32, offset.y().round() as i32)
+    }
+}
+
+impl ImageFilter for DropShadowImageFilter {
+    fn count_inputs(&self) -> usize {
+        1
+    }
+
+    fn get_input(&self, index: usize) -> Option<&Rc<dyn ImageFilter>> {
+        if index == 0 {
+            self.input.as_ref()
+        } else {
+            None
+        }
+    }
+
+    fn crop_rect(&self) -> Option<&Rect> {
+        self.crop_rect.as_ref()
+    }
+
+    fn on_filter_image(&self, source: &FilterImage, ctm: &Matrix) -> FilterImage {
+        let mut input = self.filter_input(0, source, ctm);
+        if let Some(crop_rect) = self.device_crop_rect(ctm) {
+            input = input.crop(&crop_rect);
+        }
+        let (sigma_x, sigma_y) = map_sigma(self.sigma_x, self.sigma_y, ctm);
+        let mut shadow = blur_image(&input, sigma_x, sigma_y, TileMode::Decal);
+        let alpha = self.color.alpha().clamp(0.0, 1.0);
+        let color = [
+            self.color.red().clamp(0.0, 1.0) * alpha,
+            self.color.green().clamp(0.0, 1.0) * alpha,
+            self.color.blue().clamp(0.0, 1.0) * alpha,
+            alpha,
+        ];
+        let bounds = shadow.bounds().clone();
+        for y in bounds.top()..bounds.bottom() {
+            for x in bounds.left()..bounds.right() {
+                let coverage = shadow.pixel_at(x, y)[3];
+                shadow.set_pixel(x, y, color.map(|value| value * coverage));
+            }
+        }
+        let (dx, dy) = self.offset(ctm);
+        shadow.offset(dx, dy);
+
+        if self.shadow_only {
+            shadow
+        } else {
+            FilterImage::merge(&[shadow, input])
+        }
+    }
+
+    fn on_filter_node_bounds(&self, src: &IRect, ctm: &Matrix, dir: MapDirection) -> IRect {
+        if src.is_empty() {
+            return src.clone();
+        }
+        let (dx, dy) = self.offset(ctm);
+        let (sigma_x, sigma_y) = map_sigma(self.sigma_x, self.sigma_y, ctm);
+        let mut bounds = src.clone();
+        if dir == MapDirection::Forward {
+            bounds.offset(dx, dy);
+        } else {
+            bounds.offset(-dx, -dy);
+        }
+        bounds.outset(blur_radius(sigma_x), blur_radius(sigma_y));
+        if !self.shadow_only {
+            bounds.join(src);
+        }
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a white opaque square of 4x4 at (10, 10).
+    fn square() -> FilterImage {
+        let bounds = IRect::from_xywh(10, 10, 4, 4);
+        let mut image = FilterImage::new(bounds.clone());
+        for y in bounds.top()..bounds.bottom() {
+            for x in bounds.left()..bounds.right() {
+                image.set_pixel(x, y, [1.0; 4]);
+            }
+        }
+        image
+    }
+
+    fn is_near(pixel: [f32; 4], expected: [f32; 4]) -> bool {
+        pixel
+            .iter()
+            .zip(expected)
+            .all(|(value, expected)| (value - expected).abs() < 1e-4)
+    }
+
+    fn black() -> Color4f {
+        Color4f::from_rgba(0.0, 0.0, 0.0, 1.0)
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(DropShadowImageFilter::new(f32::NAN, 0.0, 1.0, 1.0, black(), None, None).is_none());
+        assert!(DropShadowImageFilter::new(0.0, 0.0, -1.0, 1.0, black(), None, None).is_none());
+        let filter =
+            DropShadowImageFilter::new_shadow_only(1.0, 1.0, 0.0, 0.0, black(), None, None);
+        assert!(filter.unwrap().shadow_only());
+    }
+
+    #[test]
+    fn test_shadow() {
+        let filter = DropShadowImageFilter::new(3.0, 0.0, 0.0, 0.0, black(), None, None).unwrap();
+        let image = filter.filter_image(&square(), &Matrix::identity());
+        assert_eq!(*image.bounds(), IRect::from_ltrb(10, 10, 17, 14));
+        // Content is drawn over its shadow.
+        assert!(is_near(image.pixel_at(12, 12), [1.0; 4]));
+        assert!(is_near(image.pixel_at(15, 12), [0.0, 0.0, 0.0, 1.0]));
+        assert!(is_near(image.pixel_at(10, 10), [1.0; 4]));
+    }
+
+    #[test]
+    fn test_shadow_only() {
+        let color = Color4f::from_rgba(1.0, 0.0, 0.0, 0.5);
+        let filter =
+            DropShadowImageFilter::new_shadow_only(0.0, 2.0, 0.0, 0.0, color, None, None).unwrap();
+        let ctm = Matrix::from_scale(2.0, 2.0);
+        let image = filter.filter_image(&square(), &ctm);
+        assert_eq!(*image.bounds(), IRect::from_xywh(10, 14, 4, 4));
+        assert!(is_near(image.pixel_at(10, 14), [0.5, 0.0, 0.0, 0.5]));
+
+        let src = IRect::from_xywh(10, 10, 4, 4);
+        assert_eq!(
+            filter.filter_bounds(&src, &ctm, MapDirection::Forward),
+            IRect::from_xywh(10, 14, 4, 4)
+        );
+        assert_eq!(
+            filter.filter_bounds(&src, &ctm, MapDirection::Reverse),
+            IRect::from_xywh(10, 6, 4, 4)
+        );
+    }
+
+    #[test]
+    fn test_blurred_shadow() {
+        let filter = DropShadowImageFilter::new(0.0, 0.0, 1.0, 1.0, black(), None, None).unwrap();
+        let image = filter.filter_image(&square(), &Matrix::identity());
+        assert_eq!(*image.bounds(), IRect::from_ltrb(7, 7, 17, 17));
+        assert!(image.pixel_at(8, 12)[3] > 0.0);
+        assert!(image.pixel_at(8, 12)[0].abs() < 1e-6);
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::rc::Rc;
+
+use crate::core::image_filter::{FilterImage, ImageFilter};
+use crate::core::matrix::Matrix;
+use crate::core::rect::Rect;
+
+/// Draws its inputs one over another with source-over blending.
+#[derive(Debug, Clone)]
+pub struct MergeImageFilter {
+    inputs: Vec<Option<Rc<dyn ImageFilter>>>,
+    crop_rect: Option<Rect>,
+}
+
+impl MergeImageFilter {
+    /// Create a filter that merges the filters together by drawing their results
+    /// in order with src-over blending.
+    ///
+    /// # Parameters
+    /// - `filters` - the input filter array to merge, where None entries use the
+    ///   source image
+    /// - `crop_rect` - optional rectangle to crop all filters' outputs
+    #[must_use]
+    pub fn new(filters: Vec<Option<Rc<dyn ImageFilter>>>, crop_rect: Option<&Rect>) -> Self {
+        Self {
+            inputs: filters,
+            crop_rect: crop_rect.cloned(),
+        }
+    }
+}
+
+impl ImageFilter for MergeImageFilter {
+    fn count_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    fn get_input(&self, index: usize) -> Option<&Rc<dyn ImageFilter>> {
+        self.inputs.get(index).and_then(Option::as_ref)
+    }
+
+    fn crop_rect(&self) -> Option<&Rect> {
+        self.crop_rect.as_ref()
+    }
+
+    fn on_filter_image(&self, source: &FilterImage, ctm: &Matrix) -> FilterImage {
+        let images: Vec<FilterImage> = (0..self.inputs.len())
+            .map(|index| self.filter_input(index, source, ctm))
+            .collect();
+        FilterImage::merge(&images)
+    }
+}
@@ -2,6 +2,12 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+pub mod blur_image_filter;
+pub mod color_filter_image_filter;
 pub mod color_matrix;
+pub mod compose_image_filter;
 pub mod d1_path_effect;
+pub mod drop_shadow_image_filter;
+pub mod merge_image_filter;
+pub mod offset_image_filter;
 pub mod stroke_and_fill_path_effect;
@@ -0,0 +1,105 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::rc::Rc;
+
+use crate::core::image_filter::{FilterImage, ImageFilter, MapDirection};
+use crate::core::irect::IRect;
+use crate::core::matrix::Matrix;
+use crate::core::rect::Rect;
+use crate::core::scalar::Scalar;
+
+/// Moves its input by an offset.
+#[derive(Debug, Clone)]
+pub struct OffsetImageFilter {
+    dx: Scalar,
+    dy: Scalar,
+    input: Option<Rc<dyn ImageFilter>>,
+    crop_rect: Option<Rect>,
+}
+
+impl OffsetImageFilter {
+    /// Create a filter that offsets the input filter by the given vector.
+    ///
+    /// # Parameters
+    /// - `dx` - the X offset, rounded to whole pixels
+    /// - `dy` - the Y offset, rounded to whole pixels
+    /// - `input` - the input that will be moved, if None the source image is used
+    /// - `crop_rect` - optional rectangle to crop the input and output
+    ///
+    /// Returns None if an offset is not finite.
+    // TODO(Shaohua): Resample input for fractional offsets.
+    #[must_use]
+    pub fn new(
+        dx: Scalar,
+        dy: Scalar,
+        input: Option<Rc<dyn ImageFilter>>,
+        crop_rect: Option<&Rect>,
+    ) -> Option<Self> {
+        if !(dx.is_finite() && dy.is_finite()) {
+            return None;
+        }
+        Some(Self {
+            dx,
+            dy,
+            input,
+            crop_rect: crop_rect.cloned(),
+        })
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn dx(&self) -> Scalar {
+        self.dx
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn dy(&self) -> Scalar {
+        self.dy
+    }
+
+    /// Returns offset mapped to device space by `ctm`, rounded to whole pixels.
+    #[allow(clippy::cast_possible_truncation)]
+    fn offset(&self, ctm: &Matrix) -> (i32, i32) {
+        let offset = ctm.map_vector(self.dx, self.dy);
+        (offset.x().round() as i32, offset.y().round() as i32)
+    }
+}
+
+impl ImageFilter for OffsetImageFilter {
+    fn count_inputs(&self) -> usize {
+        1
+    }
+
+    fn get_input(&self, index: usize) -> Option<&Rc<dyn ImageFilter>> {
+        if index == 0 {
+            self.input.as_ref()
+        } else {
+            None
+        }
+    }
+
+    fn crop_rect(&self) -> Option<&Rect> {
+        self.crop_rect.as_ref()
+    }
+
+    fn on_filter_image(&self, source: &FilterImage, ctm: &Matrix) -> FilterImage {
+        let mut image = self.filter_input(0, source, ctm);
+        let (dx, dy) = self.offset(ctm);
+        image.offset(dx, dy);
+        image
+    }
+
+    fn on_filter_node_bounds(&self, src: &IRect, ctm: &Matrix, dir: MapDirection) -> IRect {
+        let (dx, dy) = self.offset(ctm);
+        let mut bounds = src.clone();
+        if dir == MapDirection::Forward {
+            bounds.offset(dx, dy);
+        } else {
+            bounds.offset(-dx, -dy);
+        }
+        bounds
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Captures successive frames of an animation into a video.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::core::pixmap::Pixmap;
+use crate::core::scalar::Scalar;
+use crate::core::surface::Surface;
+use crate::encode::png_encoder;
+
+/// Destination of frames captured by `FrameRecorder`.
+pub enum FrameOutput {
+    /// Each frame is written as a PNG file in the directory, named as `frame_00000.png`.
+    PngSequence(PathBuf),
+
+    /// Frames are written as a YUV4MPEG2 stream, with 4:2:0 chroma subsampling
+    /// in full range.
+    Y4m(Box<dyn Write>),
+}
+
+impl fmt::Debug for FrameOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PngSequence(dir) => f.debug_tuple("PngSequence").field(dir).finish(),
+            Self::Y4m(_) => f.write_str("Y4m(..)"),
+        }
+    }
+}
+
+/// Captures successive frames at a fixed frame rate.
+///
+/// All frames shall have the same dimensions as the first one.
+/// Pixels are composited over black, as videos have no alpha channel.
+// TODO(Shaohua): Encode mp4.
+#[derive(Debug)]
+pub struct FrameRecorder {
+    output: FrameOutput,
+    fps: u32,
+    frame_count: usize,
+
+    /// Dimensions of the first frame.
+    dimensions: Option<(i32, i32)>,
+    png_options: png_encoder::Options,
+}
+
+impl FrameRecorder {
+    /// Creates recorder writing frames as PNG files into directory `dir`,
+    /// which is created if not exists.
+    ///
+    /// # Errors
+    /// Returns error if failed to create `dir`.
+    pub fn png_sequence<P: Into<PathBuf>>(dir: P, fps: u32) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self::new(FrameOutput::PngSequence(dir), fps))
+    }
+
+    /// Creates recorder writing frames as a YUV4MPEG2 stream.
+    #[must_use]
+    pub fn y4m(stream: Box<dyn Write>, fps: u32) -> Self {
+        Self::new(FrameOutput::Y4m(stream), fps)
+    }
+
+    /// Creates recorder writing frames to `output`, at `fps` frames per second.
+    ///
+    /// `fps` of zero is treated as one.
+    #[must_use]
+    pub fn new(output: FrameOutput, fps: u32) -> Self {
+        Self {
+            output,
+            fps: fps.max(1),
+            frame_count: 0,
+            dimensions: None,
+            png_options: png_encoder::Options::default(),
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    /// Returns number of frames captured.
+    #[must_use]
+    #[inline]
+    pub const fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    /// Returns time of the next frame since start of the video, to draw the
+    /// animation at.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn next_frame_time(&self) -> Duration {
+        let nanos = self.frame_count as u128 * 1_000_000_000 / u128::from(self.fps);
+        Duration::from_nanos(nanos as u64)
+    }
+
+    /// Sets options used to encode PNG files.
+    pub fn set_png_options(&mut self, options: png_encoder::Options) {
+        self.png_options = options;
+    }
+
+    /// Captures pixels of `surface` as the next frame.
+    ///
+    /// # Errors
+    /// Returns error if `surface` has no pixels in memory, or if failed to
+    /// write the frame.
+    pub fn add_surface(&mut self, surface: &Surface) -> io::Result<()> {
+        let pixmap = surface
+            .peek_pixels()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "surface has no pixels"))?;
+        self.add_frame(pixmap)
+    }
+
+    /// Captures `pixmap` as the next frame.
+    ///
+    /// # Errors
+    /// Returns error if `pixmap` is empty, if dimensions of `pixmap` differ
+    /// from the first frame, if color type of `pixmap` is not supported, or if
+    /// failed to write the frame.
+    pub fn add_frame(&mut self, pixmap: &Pixmap) -> io::Result<()> {
+        let dimensions = (pixmap.width(), pixmap.height());
+        if pixmap.info().is_empty() || self.dimensions.is_some_and(|d| d != dimensions) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame is empty or dimensions of frames differ",
+            ));
+        }
+        let is_first = self.dimensions.is_none();
+        self.dimensions = Some(dimensions);
+
+        match &mut self.output {
+            FrameOutput::PngSequence(dir) => {
+                let data = png_encoder::encode(pixmap, &self.png_options)?;
+                let path = dir.join(format!("frame_{:05}.png", self.frame_count));
+                fs::write(path, data)?;
+            }
+            FrameOutput::Y4m(stream) => {
+                if is_first {
+                    writeln!(
+                        stream,
+                        "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C420jpeg XCOLORRANGE=FULL",
+                        dimensions.0, dimensions.1, self.fps
+                    )?;
+                }
+                let planes = yuv420_planes(pixmap).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "failed to read frame pixels")
+                })?;
+                stream.write_all(b"FRAME\n")?;
+                stream.write_all(&planes)?;
+            }
+        }
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    /// Flushes frames written to stream.
+    ///
+    /// # Errors
+    /// Returns error if failed to flush stream.
+    pub fn finish(mut self) -> io::Result<()> {
+        match &mut self.output {
+            FrameOutput::PngSequence(_) => Ok(()),
+            FrameOutput::Y4m(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Returns Y, Cb and Cr planes of `pixmap`, composited over black, with chroma
+/// planes subsampled by 2x2 blocks.
+///
+/// Returns None if pixels can not be read.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+#[allow(clippy::cast_precision_loss)]
+fn yuv420_planes(pixmap: &Pixmap) -> Option<Vec<u8>> {
+    let width = pixmap.width();
+    let height = pixmap.height();
+    let chroma_width = (width + 1) / 2;
+    let chroma_height = (height + 1) / 2;
+    let luma_size = (width * height) as usize;
+    let chroma_size = (chroma_width * chroma_height) as usize;
+    let mut planes = vec![0; luma_size + chroma_size * 2];
+    let (luma, chroma) = planes.split_at_mut(luma_size);
+    let (blue_plane, red_plane) = chroma.split_at_mut(chroma_size);
+    let to_byte = |value: Scalar| (value * 255.0).round().clamp(0.0, 255.0) as u8;
+
+    for chroma_y in 0..chroma_height {
+        for chroma_x in 0..chroma_width {
+            let mut blue_diff = 0.0;
+            let mut red_diff = 0.0;
+            let mut count = 0.0;
+            for y in chroma_y * 2..(chroma_y * 2 + 2).min(height) {
+                for x in chroma_x * 2..(chroma_x * 2 + 2).min(width) {
+                    let color = pixmap.get_color4f(x, y)?;
+                    let alpha = color.alpha();
+                    let red = color.red() * alpha;
+                    let green = color.green() * alpha;
+                    let blue = color.blue() * alpha;
+                    // BT.601 in full range, as in JPEG.
+                    let y_value = 0.114f32.mul_add(blue, 0.299f32.mul_add(red, 0.587 * green));
+                    luma[(y * width + x) as usize] = to_byte(y_value);
+                    blue_diff +=
+                        0.5f32.mul_add(blue, (-0.168_736f32).mul_add(red, -0.331_264 * green));
+                    red_diff +=
+                        0.5f32.mul_add(red, (-0.418_688f32).mul_add(green, -0.081_312 * blue));
+                    count += 1.0;
+                }
+            }
+            let index = (chroma_y * chroma_width + chroma_x) as usize;
+            blue_plane[index] = to_byte(blue_diff / count + 128.0 / 255.0);
+            red_plane[index] = to_byte(red_diff / count + 128.0 / 255.0);
+        }
+    }
+    Some(planes)
+}
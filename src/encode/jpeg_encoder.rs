@@ -2,6 +2,19 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+use jpeg_encoder::{ColorType as JpegColorType, Encoder, SamplingFactor};
+
+use crate::core::alpha_type::AlphaType;
+use crate::core::color_type::ColorType;
+use crate::core::pixmap::Pixmap;
+use crate::encode::transform_scanline::{self, BGRA_ORDER, RGBA_ORDER};
+
+/// Signature of Exif metadata in APP1 marker.
+const EXIF_SIGNATURE: &[u8] = b"Exif\0\0";
+
+/// Signature of XMP packet in APP1 marker.
+const XMP_SIGNATURE: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 pub enum AlphaOption {
     Ignore,
@@ -40,15 +53,27 @@ pub struct Options {
     /// In the second case, the encoder supports linear or legacy blending.
     pub alpha_option: AlphaOption,
 
+    /// Encode as progressive JPEG, with spectral selection scans.
+    ///
+    /// Progressive images are usually smaller, and can be displayed before they are
+    /// fully downloaded.
+    pub progressive: bool,
+
+    /// Compute optimal Huffman tables for the image instead of using the standard ones.
+    ///
+    /// This reduces file size at the cost of an extra pass over the image.
+    pub optimize_huffman: bool,
+
     /// Optional XMP metadata.
     pub xmp_metadata: Vec<u8>,
 
-    ///  An optional ICC profile to override the default behavior.
+    /// Optional Exif metadata starting at TIFF header.
+    pub exif_metadata: Vec<u8>,
+
+    /// Optional ICC profile, split into APP2 markers if it is larger than one marker.
     ///
-    /// The default behavior is to generate an ICC profile using a primary matrix and
-    /// analytic transfer function. If the color space of src cannot be represented
-    /// in this way (e.g, it is HLG or PQ), then no profile will be embedded.
-    //icc_profile: Option<IccProfile>,
+    /// No profile is embedded if it is empty.
+    pub icc_profile: Vec<u8>,
     icc_profile_description: String,
 }
 
@@ -58,9 +83,239 @@ impl Default for Options {
             quality: 100,
             downsample: Downsample::K420,
             alpha_option: AlphaOption::Ignore,
+            progressive: false,
+            optimize_huffman: false,
             xmp_metadata: Vec::new(),
-            //icc_profile: None,
+            exif_metadata: Vec::new(),
+            icc_profile: Vec::new(),
             icc_profile_description: String::new(),
         }
     }
 }
+
+impl Options {
+    /// Returns true if quality is in `[0, 100]`.
+    #[must_use]
+    #[inline]
+    pub const fn is_valid(&self) -> bool {
+        self.quality >= 0 && self.quality <= 100
+    }
+
+    /// Returns quality clamped to `[1, 100]`, quality 0 is treated as 1.
+    #[must_use]
+    #[inline]
+    #[allow(clippy::cast_sign_loss)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn clamped_quality(&self) -> u8 {
+        if self.quality < 1 {
+            1
+        } else if self.quality > 100 {
+            100
+        } else {
+            self.quality as u8
+        }
+    }
+}
+
+/// Encodes `src` as JPEG.
+///
+/// Supported color types are `Gray8`, `Rgb888x`, `Rgba8888` and `Bgra8888`.
+/// Alpha channel is handled as specified by `options.alpha_option`.
+///
+/// Returns None if color type of `src` is not supported, if `options` are invalid,
+/// or if `src` is empty or larger than 65535 pixels in either dimension.
+#[must_use]
+#[allow(clippy::cast_sign_loss)]
+pub fn encode(src: &Pixmap, options: &Options) -> Option<Vec<u8>> {
+    if !options.is_valid() || src.info().is_empty() {
+        return None;
+    }
+    let width = u16::try_from(src.width()).ok()?;
+    let height = u16::try_from(src.height()).ok()?;
+
+    let alpha_type = src.alpha_type();
+    let bytes_per_pixel = src.info().bytes_per_pixel() as usize;
+    let (color_type, order) = match src.color_type() {
+        ColorType::Gray8 => (JpegColorType::Luma, RGBA_ORDER),
+        ColorType::Rgb888x | ColorType::Rgba8888 => (JpegColorType::Rgb, RGBA_ORDER),
+        ColorType::Bgra8888 => (JpegColorType::Rgb, BGRA_ORDER),
+        _ => return None,
+    };
+    let mut data = Vec::new();
+    for y in 0..usize::from(height) {
+        let row = &src.pixels()[y * src.row_bytes()..][..usize::from(width) * bytes_per_pixel];
+        if color_type == JpegColorType::Luma {
+            data.extend_from_slice(row);
+        } else if src.color_type() == ColorType::Rgb888x || src.is_opaque() {
+            transform_scanline::to_rgb(&mut data, row, order, AlphaType::Opaque);
+        } else if options.alpha_option == AlphaOption::BlendOnBlack {
+            transform_scanline::to_rgb_on_black(&mut data, row, order, alpha_type);
+        } else {
+            transform_scanline::to_rgb(&mut data, row, order, alpha_type);
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out, options.clamped_quality());
+    encoder.set_sampling_factor(match options.downsample {
+        Downsample::K420 => SamplingFactor::R_4_2_0,
+        Downsample::K422 => SamplingFactor::R_4_2_2,
+        Downsample::K444 => SamplingFactor::R_4_4_4,
+    });
+    encoder.set_progressive(options.progressive);
+    encoder.set_optimized_huffman_tables(options.optimize_huffman);
+    if !options.exif_metadata.is_empty() {
+        let mut segment = EXIF_SIGNATURE.to_vec();
+        segment.extend_from_slice(&options.exif_metadata);
+        encoder.add_app_segment(1, &segment).ok()?;
+    }
+    if !options.xmp_metadata.is_empty() {
+        let mut segment = XMP_SIGNATURE.to_vec();
+        segment.extend_from_slice(&options.xmp_metadata);
+        encoder.add_app_segment(1, &segment).ok()?;
+    }
+    if !options.icc_profile.is_empty() {
+        encoder.add_icc_profile(&options.icc_profile).ok()?;
+    }
+    encoder.encode(&data, width, height, color_type).ok()?;
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::jpeg_decoder;
+    use crate::core::image_info::ImageInfo;
+
+    const SIZE: i32 = 16;
+
+    /// Returns a gradient image with `color_type` of 4 bytes per pixel.
+    fn gradient(color_type: ColorType, alpha_type: AlphaType) -> Pixmap {
+        let info = ImageInfo::from(SIZE, SIZE, color_type, alpha_type, None);
+        let mut pixels = Vec::new();
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let value = u8::try_from(x * 16).unwrap();
+                let other = u8::try_from(y * 16).unwrap();
+                pixels.extend_from_slice(&[value, other, 128, 255]);
+            }
+        }
+        let row_bytes = usize::try_from(SIZE * 4).unwrap();
+        Pixmap::from_vec(info, row_bytes, pixels)
+    }
+
+    fn contains(data: &[u8], needle: &[u8]) -> bool {
+        data.windows(needle.len()).any(|window| window == needle)
+    }
+
+    #[test]
+    fn test_options() {
+        let mut options = Options::default();
+        assert!(options.is_valid());
+        assert_eq!(options.clamped_quality(), 100);
+        options.quality = 0;
+        assert!(options.is_valid());
+        assert_eq!(options.clamped_quality(), 1);
+        options.quality = 101;
+        assert!(!options.is_valid());
+        options.quality = -1;
+        assert!(!options.is_valid());
+    }
+
+    #[test]
+    fn test_encode() {
+        let src = gradient(ColorType::Rgba8888, AlphaType::Opaque);
+        let data = encode(&src, &Options::default()).unwrap();
+        assert!(jpeg_decoder::is_jpeg(&data));
+        let decoded = jpeg_decoder::decode(&data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (SIZE, SIZE));
+        let color = decoded.get_color(8, 4).unwrap();
+        assert!(color.red().abs_diff(128) < 8);
+        assert!(color.green().abs_diff(64) < 8);
+        assert!(color.blue().abs_diff(128) < 8);
+
+        // Red and blue are swapped in BGRA.
+        let src = gradient(ColorType::Bgra8888, AlphaType::Opaque);
+        let decoded = jpeg_decoder::decode(&encode(&src, &Options::default()).unwrap()).unwrap();
+        let color = decoded.get_color(8, 4).unwrap();
+        assert!(color.red().abs_diff(128) < 8);
+        assert!(color.blue().abs_diff(128) < 8);
+    }
+
+    #[test]
+    fn test_quality() {
+        let src = gradient(ColorType::Rgba8888, AlphaType::Opaque);
+        let high = encode(&src, &Options::default()).unwrap();
+        let options = Options {
+            quality: 10,
+            ..Options::default()
+        };
+        let low = encode(&src, &options).unwrap();
+        assert!(low.len() < high.len());
+
+        let options = Options {
+            quality: 101,
+            ..Options::default()
+        };
+        assert!(encode(&src, &options).is_none());
+    }
+
+    #[test]
+    fn test_progressive_and_huffman() {
+        let src = gradient(ColorType::Rgba8888, AlphaType::Opaque);
+        let baseline = encode(&src, &Options::default()).unwrap();
+        // Start of frame markers of baseline and progressive DCT.
+        assert!(contains(&baseline, &[0xff, 0xc0]));
+        assert!(!contains(&baseline, &[0xff, 0xc2]));
+
+        let options = Options {
+            progressive: true,
+            ..Options::default()
+        };
+        let progressive = encode(&src, &options).unwrap();
+        assert!(contains(&progressive, &[0xff, 0xc2]));
+        assert!(jpeg_decoder::decode(&progressive).is_some());
+
+        let options = Options {
+            optimize_huffman: true,
+            ..Options::default()
+        };
+        let optimized = encode(&src, &options).unwrap();
+        assert!(optimized.len() < baseline.len());
+    }
+
+    #[test]
+    fn test_alpha_option() {
+        let info = ImageInfo::from(1, 1, ColorType::Rgba8888, AlphaType::Unpremul, None);
+        let src = Pixmap::from_vec(info, 4, vec![255, 255, 255, 0]);
+        let options = Options {
+            alpha_option: AlphaOption::BlendOnBlack,
+            ..Options::default()
+        };
+        let decoded = jpeg_decoder::decode(&encode(&src, &options).unwrap()).unwrap();
+        assert!(decoded.get_color(0, 0).unwrap().red() < 8);
+        let decoded = jpeg_decoder::decode(&encode(&src, &Options::default()).unwrap()).unwrap();
+        assert!(decoded.get_color(0, 0).unwrap().red() > 247);
+    }
+
+    #[test]
+    fn test_xmp_and_gray() {
+        let info = ImageInfo::from(2, 2, ColorType::Gray8, AlphaType::Opaque, None);
+        let src = Pixmap::from_vec(info, 2, vec![0, 64, 128, 255]);
+        let options = Options {
+            xmp_metadata: b"<x:xmpmeta/>".to_vec(),
+            ..Options::default()
+        };
+        let data = encode(&src, &options).unwrap();
+        assert!(contains(
+            &data,
+            b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta/>"
+        ));
+        let decoded = jpeg_decoder::decode(&data).unwrap();
+        assert_eq!(decoded.color_type(), ColorType::Gray8);
+
+        let info = ImageInfo::from(2, 2, ColorType::Rgb565, AlphaType::Opaque, None);
+        let src = Pixmap::from_vec(info, 4, vec![0; 8]);
+        assert!(encode(&src, &Options::default()).is_none());
+    }
+}
@@ -2,4 +2,8 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+pub mod frame_recorder;
 pub mod jpeg_encoder;
+pub mod png_encoder;
+pub mod transcode;
+mod transform_scanline;
@@ -0,0 +1,395 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::io;
+
+use bitflags::bitflags;
+use png::text_metadata::{ITXtChunk, TEXtChunk};
+use png::{BitDepth, ColorType as PngColorType, DeflateCompression, Encoder, Filter, Info};
+
+use crate::codec::png_decoder::XMP_KEYWORD;
+use crate::core::alpha_type::AlphaType;
+use crate::core::color_type::ColorType;
+use crate::core::document_metadata::DocumentMetadata;
+use crate::core::pixmap::Pixmap;
+use crate::encode::transform_scanline::{self, BGRA_ORDER, RGBA_ORDER};
+
+bitflags! {
+    /// Row filters which the encoder may choose from.
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    pub struct FilterFlag : u8 {
+        const None = 0x08;
+        const Sub = 0x10;
+        const Up = 0x20;
+        const Avg = 0x40;
+        const Paeth = 0x80;
+        const All = Self::None.bits() | Self::Sub.bits() | Self::Up.bits()
+            | Self::Avg.bits() | Self::Paeth.bits();
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// Selects which filtering strategies to use.
+    ///
+    /// If a single filter is chosen, png will use that filter for every row.
+    ///
+    /// If multiple filters are chosen, png will use a heuristic to guess which filter
+    /// will encode smallest, then apply that filter. This happens on a per row basis,
+    /// different rows can use different filters.
+    ///
+    /// Using a single filter (or less filters) is typically faster. Trying all of the
+    /// filters may help minimize the output file size.
+    pub filter_flags: FilterFlag,
+
+    /// Must be in [0, 9] where 9 corresponds to maximal compression.
+    ///
+    /// This value is passed directly to zlib. 0 is a special case to skip zlib entirely,
+    /// creating dramatically larger pngs.
+    pub zlib_level: i32,
+
+    /// Represents comments in the tEXt ancillary chunk of the png.
+    ///
+    /// Each item is a keyword-text pair. Keywords shall be 1 to 79 Latin-1
+    /// characters. Texts which are not Latin-1 are written in the iTXt chunk.
+    pub comments: Vec<(String, String)>,
+
+    /// Language of `comments`, as a RFC 3066 tag like `en-US`.
+    ///
+    /// If not empty, comments are written in the iTXt chunk with this language tag.
+    pub language: String,
+
+    /// Optional ICC profile, written uncompressed to iCCP chunk.
+    pub icc_profile: Vec<u8>,
+
+    /// Optional Exif metadata starting at TIFF header, written to eXIf chunk.
+    pub exif_metadata: Vec<u8>,
+
+    /// Optional XMP packet, written to iTXt chunk. It shall be UTF-8 encoded.
+    pub xmp_metadata: Vec<u8>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            filter_flags: FilterFlag::All,
+            zlib_level: 6,
+            comments: Vec::new(),
+            language: String::new(),
+            icc_profile: Vec::new(),
+            exif_metadata: Vec::new(),
+            xmp_metadata: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    /// Appends document metadata to `comments`, using predefined keywords of
+    /// the PNG specification.
+    pub fn add_metadata(&mut self, metadata: &DocumentMetadata) {
+        let texts = [
+            ("Title", metadata.title.clone()),
+            ("Author", metadata.author.clone()),
+            ("Description", metadata.subject.clone()),
+            ("Keywords", metadata.joined_keywords()),
+            ("Software", metadata.producer_or_default()),
+            ("Source", metadata.creator.clone()),
+        ];
+        for (keyword, text) in texts {
+            if !text.is_empty() {
+                self.comments.push((keyword.to_owned(), text));
+            }
+        }
+        if let Some(creation) = &metadata.creation {
+            self.comments
+                .push(("Creation Time".to_owned(), creation.to_iso8601()));
+        }
+        if !metadata.language.is_empty() {
+            self.language.clone_from(&metadata.language);
+        }
+    }
+
+    /// Returns filter used by png crate, or None if no filter is selected.
+    fn filter(&self) -> Option<Filter> {
+        let flags = self.filter_flags;
+        let filter = if flags == FilterFlag::None {
+            Filter::NoFilter
+        } else if flags == FilterFlag::Sub {
+            Filter::Sub
+        } else if flags == FilterFlag::Up {
+            Filter::Up
+        } else if flags == FilterFlag::Avg {
+            Filter::Avg
+        } else if flags == FilterFlag::Paeth {
+            Filter::Paeth
+        } else if flags.is_empty() {
+            return None;
+        } else {
+            Filter::Adaptive
+        };
+        Some(filter)
+    }
+}
+
+/// Encodes `src` as PNG.
+///
+/// Supported color types are `Alpha8`, `Gray8`, `Rgb888x`, `Rgba8888`, `Bgra8888`
+/// and `R16G16B16A16Unorm`. Premultiplied colors are unpremultiplied, and alpha
+/// channel is dropped if `src` is opaque.
+///
+/// # Errors
+/// Returns error if color type of `src` is not supported, if `options` are invalid,
+/// if a keyword of comments is not 1 to 79 Latin-1 characters, if XMP metadata is not
+/// UTF-8 encoded, or if `src` is empty.
+#[allow(clippy::cast_sign_loss)]
+#[allow(clippy::cast_possible_truncation)]
+pub fn encode(src: &Pixmap, options: &Options) -> io::Result<Vec<u8>> {
+    let invalid_input = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    if !(0..=9).contains(&options.zlib_level) || src.info().is_empty() {
+        return Err(invalid_input("invalid zlib level or empty pixmap"));
+    }
+    if !options.language.is_ascii() {
+        return Err(invalid_input("language tag is not ASCII"));
+    }
+    if let Some((keyword, _text)) = options
+        .comments
+        .iter()
+        .find(|(keyword, _text)| !is_valid_keyword(keyword))
+    {
+        return Err(invalid_input(&format!("invalid keyword: {keyword:?}")));
+    }
+    let xmp = String::from_utf8(options.xmp_metadata.clone())
+        .map_err(|_err| invalid_input("XMP metadata is not UTF-8"))?;
+    let filter = options
+        .filter()
+        .ok_or_else(|| invalid_input("no filter selected"))?;
+    let (color_type, bit_depth, data) =
+        encode_pixels(src).ok_or_else(|| invalid_input("unsupported color type"))?;
+
+    let mut out = Vec::new();
+    let mut info = Info::with_size(src.width() as u32, src.height() as u32);
+    if !options.icc_profile.is_empty() {
+        info.icc_profile = Some(options.icc_profile.as_slice().into());
+    }
+    if !options.exif_metadata.is_empty() {
+        info.exif_metadata = Some(options.exif_metadata.as_slice().into());
+    }
+    let mut encoder = Encoder::with_info(&mut out, info)?;
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+    encoder.set_filter(filter);
+    encoder.set_deflate_compression(if options.zlib_level == 0 {
+        DeflateCompression::NoCompression
+    } else {
+        DeflateCompression::Level(options.zlib_level as u8)
+    });
+    let mut writer = encoder.write_header()?;
+    for (keyword, text) in &options.comments {
+        if options.language.is_empty() && is_latin1(text) {
+            writer.write_text_chunk(&TEXtChunk::new(keyword.as_str(), text.as_str()))?;
+        } else {
+            let mut chunk = ITXtChunk::new(keyword.as_str(), text.as_str());
+            chunk.language_tag.clone_from(&options.language);
+            writer.write_text_chunk(&chunk)?;
+        }
+    }
+    if !xmp.is_empty() {
+        writer.write_text_chunk(&ITXtChunk::new(XMP_KEYWORD, xmp))?;
+    }
+    writer.write_image_data(&data)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+fn is_latin1(text: &str) -> bool {
+    text.chars().all(|c| u32::from(c) <= 0xff)
+}
+
+/// Keyword of text chunks shall be 1 to 79 Latin-1 characters.
+fn is_valid_keyword(keyword: &str) -> bool {
+    is_latin1(keyword) && (1..=79).contains(&keyword.chars().count())
+}
+
+/// Converts pixels of `src` to PNG samples, rows are not padded.
+#[allow(clippy::cast_sign_loss)]
+fn encode_pixels(src: &Pixmap) -> Option<(PngColorType, BitDepth, Vec<u8>)> {
+    let alpha_type = src.alpha_type();
+    let is_opaque = alpha_type == AlphaType::Opaque;
+    let (color_type, bit_depth) = match src.color_type() {
+        ColorType::Alpha8 => (PngColorType::GrayscaleAlpha, BitDepth::Eight),
+        ColorType::Gray8 => (PngColorType::Grayscale, BitDepth::Eight),
+        ColorType::Rgb888x => (PngColorType::Rgb, BitDepth::Eight),
+        ColorType::Rgba8888 | ColorType::Bgra8888 if is_opaque => {
+            (PngColorType::Rgb, BitDepth::Eight)
+        }
+        ColorType::Rgba8888 | ColorType::Bgra8888 => (PngColorType::Rgba, BitDepth::Eight),
+        ColorType::R16G16B16A16Unorm if is_opaque => (PngColorType::Rgb, BitDepth::Sixteen),
+        ColorType::R16G16B16A16Unorm => (PngColorType::Rgba, BitDepth::Sixteen),
+        _ => return None,
+    };
+
+    let width = src.width() as usize;
+    let bytes_per_pixel = src.info().bytes_per_pixel() as usize;
+    let row_size = width * color_type.samples() * (bit_depth as usize).div_ceil(8);
+    let mut data = Vec::with_capacity(row_size * src.height() as usize);
+    for y in 0..src.height() as usize {
+        let row = &src.pixels()[y * src.row_bytes()..][..width * bytes_per_pixel];
+        match (src.color_type(), color_type) {
+            (ColorType::Alpha8, _) => transform_scanline::a8_to_gray_alpha(&mut data, row),
+            (ColorType::Gray8, _) => data.extend_from_slice(row),
+            (ColorType::Rgb888x, _) => {
+                transform_scanline::to_rgb(&mut data, row, RGBA_ORDER, AlphaType::Opaque);
+            }
+            (ColorType::Rgba8888, PngColorType::Rgb) => {
+                transform_scanline::to_rgb(&mut data, row, RGBA_ORDER, alpha_type);
+            }
+            (ColorType::Rgba8888, _) => {
+                transform_scanline::to_rgba(&mut data, row, RGBA_ORDER, alpha_type);
+            }
+            (ColorType::Bgra8888, PngColorType::Rgb) => {
+                transform_scanline::to_rgb(&mut data, row, BGRA_ORDER, alpha_type);
+            }
+            (ColorType::Bgra8888, _) => {
+                transform_scanline::to_rgba(&mut data, row, BGRA_ORDER, alpha_type);
+            }
+            _ => transform_scanline::rgba16_to_big_endian(&mut data, row, alpha_type, !is_opaque),
+        }
+    }
+    Some((color_type, bit_depth, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::png_decoder;
+    use crate::core::image_info::ImageInfo;
+
+    fn pixmap(color_type: ColorType, alpha_type: AlphaType, pixels: Vec<u8>) -> Pixmap {
+        let info = ImageInfo::from(2, 1, color_type, alpha_type, None);
+        let row_bytes = pixels.len();
+        Pixmap::from_vec(info, row_bytes, pixels)
+    }
+
+    fn decode_rgba(data: &[u8]) -> Vec<(u8, u8, u8, u8)> {
+        let decoded = png_decoder::decode(data).unwrap();
+        (0..decoded.width())
+            .map(|x| {
+                let color = decoded.get_color(x, 0).unwrap();
+                (color.red(), color.green(), color.blue(), color.alpha())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_premul() {
+        let src = pixmap(
+            ColorType::Rgba8888,
+            AlphaType::Premul,
+            vec![255, 0, 0, 255, 0, 64, 0, 128],
+        );
+        let data = encode(&src, &Options::default()).unwrap();
+        assert!(png_decoder::is_png(&data));
+        assert_eq!(decode_rgba(&data), [(255, 0, 0, 255), (0, 128, 0, 128)]);
+    }
+
+    #[test]
+    fn test_encode_color_types() {
+        let bgra = pixmap(
+            ColorType::Bgra8888,
+            AlphaType::Opaque,
+            vec![255, 0, 0, 255, 0, 0, 255, 255],
+        );
+        let data = encode(&bgra, &Options::default()).unwrap();
+        assert_eq!(decode_rgba(&data), [(0, 0, 255, 255), (255, 0, 0, 255)]);
+
+        let alpha = pixmap(ColorType::Alpha8, AlphaType::Premul, vec![0, 200]);
+        let data = encode(&alpha, &Options::default()).unwrap();
+        assert_eq!(decode_rgba(&data), [(0, 0, 0, 0), (0, 0, 0, 200)]);
+
+        let gray = pixmap(ColorType::Gray8, AlphaType::Opaque, vec![10, 250]);
+        let options = Options {
+            filter_flags: FilterFlag::Paeth,
+            zlib_level: 0,
+            ..Options::default()
+        };
+        let data = encode(&gray, &options).unwrap();
+        assert_eq!(
+            decode_rgba(&data),
+            [(10, 10, 10, 255), (250, 250, 250, 255)]
+        );
+
+        let unsupported = pixmap(ColorType::Rgb565, AlphaType::Opaque, vec![0; 4]);
+        assert!(encode(&unsupported, &Options::default()).is_err());
+    }
+
+    #[test]
+    fn test_comments() {
+        let src = pixmap(ColorType::Gray8, AlphaType::Opaque, vec![0, 0]);
+        let mut options = Options::default();
+        options
+            .comments
+            .push(("Title".to_owned(), "Café".to_owned()));
+        options
+            .comments
+            .push(("Author".to_owned(), "李".to_owned()));
+        let data = encode(&src, &options).unwrap();
+        let contains = |needle: &[u8]| data.windows(needle.len()).any(|window| window == needle);
+        assert!(contains(b"tEXtTitle\0Caf\xe9"));
+        assert!(contains(b"iTXtAuthor\0"));
+
+        options.language = "zh".to_owned();
+        let data = encode(&src, &options).unwrap();
+        let contains = |needle: &[u8]| data.windows(needle.len()).any(|window| window == needle);
+        assert!(contains(b"iTXtTitle\0\0\0zh\0"));
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let src = pixmap(ColorType::Gray8, AlphaType::Opaque, vec![0, 0]);
+        let options = Options {
+            zlib_level: 10,
+            ..Options::default()
+        };
+        assert!(encode(&src, &options).is_err());
+        let options = Options {
+            filter_flags: FilterFlag::empty(),
+            ..Options::default()
+        };
+        assert!(encode(&src, &options).is_err());
+        let mut options = Options::default();
+        options.comments.push((String::new(), "text".to_owned()));
+        assert!(encode(&src, &options).is_err());
+        let options = Options {
+            language: "中文".to_owned(),
+            ..Options::default()
+        };
+        assert!(encode(&src, &options).is_err());
+        assert!(encode(&Pixmap::new(), &Options::default()).is_err());
+    }
+
+    #[test]
+    fn test_add_metadata() {
+        let metadata = DocumentMetadata {
+            title: "Chart".to_owned(),
+            subject: "Sales".to_owned(),
+            language: "en".to_owned(),
+            ..DocumentMetadata::default()
+        };
+        let mut options = Options::default();
+        options.add_metadata(&metadata);
+        assert_eq!(
+            options.comments[0],
+            ("Title".to_owned(), "Chart".to_owned())
+        );
+        assert_eq!(
+            options.comments[1],
+            ("Description".to_owned(), "Sales".to_owned())
+        );
+        assert!(options
+            .comments
+            .iter()
+            .any(|(keyword, _)| keyword == "Software"));
+        assert_eq!(options.language, "en");
+    }
+}
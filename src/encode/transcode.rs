@@ -0,0 +1,452 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Decodes an image, optionally resizes and converts its pixels, and re-encodes it.
+//!
+//! Exif, XMP and ICC metadata blocks of source image can be copied to the transcoded
+//! image, so that uploaded images can be normalized without losing them.
+
+use bitflags::bitflags;
+
+use crate::codec::bmp_decoder;
+use crate::codec::encoded_origin::EncodedOrigin;
+use crate::codec::exif::ExifMetadata;
+use crate::codec::jpeg_decoder::{self, JpegCodec};
+use crate::codec::png_decoder::{self, PngCodec};
+use crate::core::color::Color4f;
+use crate::core::color_type::ColorType;
+use crate::core::data::Data;
+use crate::core::pixmap::{pack_color4f, unpack_color4f, Pixmap};
+use crate::core::size::ISize;
+use crate::encode::{jpeg_encoder, png_encoder};
+
+/// Format of transcoded image.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Format {
+    Png,
+    Jpeg,
+}
+
+bitflags! {
+    /// Metadata blocks which are copied from source image.
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    pub struct MetadataFlag : u8 {
+        const Exif = 0x01;
+        const Xmp = 0x02;
+        const Icc = 0x04;
+        const All = Self::Exif.bits() | Self::Xmp.bits() | Self::Icc.bits();
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// Format of transcoded image.
+    pub format: Format,
+
+    /// Size of transcoded image, or None to keep size of source image.
+    ///
+    /// Size is applied after orientation, see `metadata`.
+    pub dimensions: Option<ISize>,
+
+    /// Color type of transcoded pixels, or None to keep color type of decoded pixels.
+    ///
+    /// Gray images can not be converted to other color types.
+    pub color_type: Option<ColorType>,
+
+    /// Selects which metadata blocks are copied.
+    ///
+    /// If Exif is copied, pixels are stored in the order they are decoded and its
+    /// orientation tag is kept. Otherwise the orientation is applied to pixels.
+    pub metadata: MetadataFlag,
+
+    /// Options to encode PNG images, metadata fields are replaced by copied blocks.
+    pub png_options: png_encoder::Options,
+
+    /// Options to encode JPEG images, metadata fields are replaced by copied blocks.
+    pub jpeg_options: jpeg_encoder::Options,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            format: Format::Png,
+            dimensions: None,
+            color_type: None,
+            metadata: MetadataFlag::All,
+            png_options: png_encoder::Options::default(),
+            jpeg_options: jpeg_encoder::Options::default(),
+        }
+    }
+}
+
+/// Decoded pixels and metadata blocks of source image.
+struct Source {
+    pixmap: Pixmap,
+    icc_profile: Option<Vec<u8>>,
+    exif: Option<Vec<u8>>,
+    xmp: Option<Vec<u8>>,
+}
+
+/// Decodes `data`, transforms and re-encodes it as specified by `options`.
+///
+/// Supported source formats are BMP, PNG and JPEG.
+///
+/// Returns None if `data` is not a valid image of supported formats, if pixels can
+/// not be converted to `options.color_type`, or if the encoder rejects pixels or options.
+#[must_use]
+pub fn transcode(data: &[u8], options: &Options) -> Option<Vec<u8>> {
+    let source = decode(data)?;
+    let origin = source
+        .exif
+        .as_deref()
+        .map_or(EncodedOrigin::TopLeft, exif_origin);
+    let keep = |flag: MetadataFlag, block: Option<Vec<u8>>| {
+        block
+            .filter(|_block| options.metadata.contains(flag))
+            .unwrap_or_default()
+    };
+    let icc_profile = keep(MetadataFlag::Icc, source.icc_profile);
+    let xmp = keep(MetadataFlag::Xmp, source.xmp);
+    let exif = keep(MetadataFlag::Exif, source.exif);
+
+    let mut pixmap = source.pixmap;
+    if exif.is_empty() && origin != EncodedOrigin::TopLeft {
+        pixmap = origin.orient_pixmap(&pixmap);
+    }
+    if let Some(color_type) = options.color_type {
+        pixmap = convert(&pixmap, color_type)?;
+    }
+    if let Some(dimensions) = options.dimensions {
+        pixmap = resize(&pixmap, dimensions)?;
+    }
+
+    match options.format {
+        Format::Png => {
+            let mut png_options = options.png_options.clone();
+            png_options.icc_profile = icc_profile;
+            png_options.exif_metadata = exif;
+            png_options.xmp_metadata = xmp;
+            png_encoder::encode(&pixmap, &png_options).ok()
+        }
+        Format::Jpeg => {
+            let mut jpeg_options = options.jpeg_options.clone();
+            jpeg_options.icc_profile = icc_profile;
+            jpeg_options.exif_metadata = exif;
+            jpeg_options.xmp_metadata = xmp;
+            jpeg_encoder::encode(&pixmap, &jpeg_options)
+        }
+    }
+}
+
+/// Decodes pixels without applying orientation, and reads metadata blocks of `data`.
+fn decode(data: &[u8]) -> Option<Source> {
+    if png_decoder::is_png(data) {
+        let codec = PngCodec::new(data)?;
+        Some(Source {
+            pixmap: codec.decode()?,
+            icc_profile: codec.icc_profile().map(<[u8]>::to_vec),
+            exif: codec.exif_data().map(<[u8]>::to_vec),
+            xmp: codec.xmp_data().map(<[u8]>::to_vec),
+        })
+    } else if jpeg_decoder::is_jpeg(data) {
+        let codec = JpegCodec::new(data)?;
+        Some(Source {
+            pixmap: codec.decode()?,
+            icc_profile: codec.icc_profile().map(<[u8]>::to_vec),
+            exif: codec.exif_data().map(<[u8]>::to_vec),
+            xmp: codec.xmp_data().map(<[u8]>::to_vec),
+        })
+    } else if bmp_decoder::is_bmp(data) {
+        Some(Source {
+            pixmap: bmp_decoder::decode(data)?,
+            icc_profile: None,
+            exif: None,
+            xmp: None,
+        })
+    } else {
+        None
+    }
+}
+
+/// Returns orientation in Exif metadata, or `EncodedOrigin::TopLeft` if not specified.
+fn exif_origin(exif: &[u8]) -> EncodedOrigin {
+    let mut origin = EncodedOrigin::TopLeft;
+    if let Some(metadata) = ExifMetadata::from_data(Data::from(exif)) {
+        let _has_origin = metadata.get_origin(&mut origin);
+    }
+    origin
+}
+
+/// Converts pixels of `src` to `color_type`, alpha type is kept.
+fn convert(src: &Pixmap, color_type: ColorType) -> Option<Pixmap> {
+    if src.color_type() == color_type {
+        return Some(src.clone());
+    }
+    let info = src.info().from_color_type(color_type);
+    let row_bytes = info.min_row_bytes();
+    let mut pixels = vec![0; info.compute_byte_size(row_bytes)];
+    if !src.read_pixels_with_info(&info, &mut pixels, row_bytes) {
+        return None;
+    }
+    Some(Pixmap::from_vec(info, row_bytes, pixels))
+}
+
+/// Resizes `src` to `dimensions`.
+///
+/// Each destination pixel is the average of source pixels it covers, in premultiplied
+/// colors. When enlarging, source pixels are repeated.
+#[allow(clippy::cast_sign_loss)]
+#[allow(clippy::cast_precision_loss)]
+fn resize(src: &Pixmap, dimensions: ISize) -> Option<Pixmap> {
+    if dimensions.width() <= 0 || dimensions.height() <= 0 || src.info().is_empty() {
+        return None;
+    }
+    if dimensions == src.dimensions() {
+        return Some(src.clone());
+    }
+    let info = src.info().from_dimensions(dimensions);
+    let row_bytes = info.min_row_bytes();
+    let mut pixels = vec![0; info.compute_byte_size(row_bytes)];
+    let src_bytes_per_pixel = src.info().bytes_per_pixel() as usize;
+    let dst_bytes_per_pixel = info.bytes_per_pixel() as usize;
+    let color_type = src.color_type();
+    let alpha_type = src.alpha_type();
+
+    // Range of source pixels covered by each destination pixel, in one direction.
+    let spans = |src_size: i32, dst_size: i32| -> Vec<(usize, usize)> {
+        let (src_size, dst_size) = (src_size as usize, dst_size as usize);
+        (0..dst_size)
+            .map(|index| {
+                let start = index * src_size / dst_size;
+                let end = ((index + 1) * src_size).div_ceil(dst_size).max(start + 1);
+                (start, end)
+            })
+            .collect()
+    };
+    let columns = spans(src.width(), dimensions.width());
+    let rows = spans(src.height(), dimensions.height());
+
+    for (dst_y, &(top, bottom)) in rows.iter().enumerate() {
+        for (dst_x, &(left, right)) in columns.iter().enumerate() {
+            let mut sum = [0.0_f32; 4];
+            for y in top..bottom {
+                let row = &src.pixels()[y * src.row_bytes()..];
+                for x in left..right {
+                    let pixel = &row[x * src_bytes_per_pixel..];
+                    let color = unpack_color4f(color_type, alpha_type, pixel)?;
+                    let alpha = color.alpha();
+                    sum[0] = color.red().mul_add(alpha, sum[0]);
+                    sum[1] = color.green().mul_add(alpha, sum[1]);
+                    sum[2] = color.blue().mul_add(alpha, sum[2]);
+                    sum[3] += alpha;
+                }
+            }
+            let color = if sum[3] <= 0.0 {
+                Color4f::from_rgba(0.0, 0.0, 0.0, 0.0)
+            } else {
+                let count = ((bottom - top) * (right - left)) as f32;
+                Color4f::from_rgba(
+                    sum[0] / sum[3],
+                    sum[1] / sum[3],
+                    sum[2] / sum[3],
+                    sum[3] / count,
+                )
+            };
+            let pixel = pack_color4f(color_type, alpha_type, &color)?;
+            let offset = dst_y * row_bytes + dst_x * dst_bytes_per_pixel;
+            pixels[offset..offset + dst_bytes_per_pixel]
+                .copy_from_slice(&pixel[..dst_bytes_per_pixel]);
+        }
+    }
+    Some(Pixmap::from_vec(info, row_bytes, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::alpha_type::AlphaType;
+    use crate::core::image_info::ImageInfo;
+
+    const ICC_PROFILE: &[u8] = b"fake icc profile";
+    const XMP: &[u8] = b"<x:xmpmeta/>";
+
+    /// Big endian TIFF header with an IFD holding only the orientation tag.
+    fn exif(orientation: u8) -> Vec<u8> {
+        let mut data = b"MM\0\x2a\0\0\0\x08\0\x01".to_vec();
+        data.extend([0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, orientation, 0, 0]);
+        data.extend([0, 0, 0, 0]);
+        data
+    }
+
+    /// Returns an opaque PNG image whose left half is red and right half is blue,
+    /// with ICC, Exif and XMP metadata.
+    fn png_file(width: i32, height: i32, orientation: u8) -> Vec<u8> {
+        let info = ImageInfo::from(width, height, ColorType::Rgba8888, AlphaType::Opaque, None);
+        let mut pixels = Vec::new();
+        for _y in 0..height {
+            for x in 0..width {
+                if x < width / 2 {
+                    pixels.extend([255, 0, 0, 255]);
+                } else {
+                    pixels.extend([0, 0, 255, 255]);
+                }
+            }
+        }
+        let row_bytes = info.min_row_bytes();
+        let pixmap = Pixmap::from_vec(info, row_bytes, pixels);
+        let options = png_encoder::Options {
+            icc_profile: ICC_PROFILE.to_vec(),
+            exif_metadata: exif(orientation),
+            xmp_metadata: XMP.to_vec(),
+            ..png_encoder::Options::default()
+        };
+        png_encoder::encode(&pixmap, &options).unwrap()
+    }
+
+    fn color_at(pixmap: &Pixmap, x: i32, y: i32) -> [u8; 3] {
+        let pixel = pixmap.pixels_at(x, y).unwrap();
+        [pixel[0], pixel[1], pixel[2]]
+    }
+
+    #[test]
+    fn test_png_to_png() {
+        let data = transcode(&png_file(4, 2, 1), &Options::default()).unwrap();
+        let codec = PngCodec::new(&data).unwrap();
+        assert_eq!(codec.icc_profile(), Some(ICC_PROFILE));
+        assert_eq!(codec.exif_data(), Some(exif(1).as_slice()));
+        assert_eq!(codec.xmp_data(), Some(XMP));
+        let pixmap = codec.decode().unwrap();
+        assert_eq!(pixmap.dimensions(), ISize::from_wh(4, 2));
+        assert_eq!(color_at(&pixmap, 0, 0), [255, 0, 0]);
+        assert_eq!(color_at(&pixmap, 3, 1), [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_png_to_jpeg() {
+        let options = Options {
+            format: Format::Jpeg,
+            ..Options::default()
+        };
+        let data = transcode(&png_file(16, 16, 1), &options).unwrap();
+        let codec = JpegCodec::new(&data).unwrap();
+        assert_eq!(codec.icc_profile(), Some(ICC_PROFILE));
+        assert_eq!(codec.exif_data(), Some(exif(1).as_slice()));
+        assert_eq!(codec.xmp_data(), Some(XMP));
+        assert_eq!(codec.width(), 16);
+        assert_eq!(codec.height(), 16);
+    }
+
+    #[test]
+    fn test_selected_metadata() {
+        let options = Options {
+            metadata: MetadataFlag::Icc | MetadataFlag::Xmp,
+            ..Options::default()
+        };
+        let data = transcode(&png_file(4, 2, 1), &options).unwrap();
+        let codec = PngCodec::new(&data).unwrap();
+        assert_eq!(codec.icc_profile(), Some(ICC_PROFILE));
+        assert_eq!(codec.exif_data(), None);
+        assert_eq!(codec.xmp_data(), Some(XMP));
+
+        let options = Options {
+            metadata: MetadataFlag::empty(),
+            ..Options::default()
+        };
+        let data = transcode(&png_file(4, 2, 1), &options).unwrap();
+        let codec = PngCodec::new(&data).unwrap();
+        assert_eq!(codec.icc_profile(), None);
+        assert_eq!(codec.xmp_data(), None);
+    }
+
+    #[test]
+    fn test_orientation() {
+        // Orientation 6 rotates image 90 degrees clockwise.
+        let src = png_file(4, 2, 6);
+
+        // Exif is kept, so is its orientation.
+        let data = transcode(&src, &Options::default()).unwrap();
+        let codec = PngCodec::new(&data).unwrap();
+        assert_eq!(codec.exif_data(), Some(exif(6).as_slice()));
+        assert_eq!(codec.width(), 4);
+
+        // Exif is dropped, and orientation is applied to pixels.
+        let options = Options {
+            metadata: MetadataFlag::Icc,
+            ..Options::default()
+        };
+        let pixmap = png_decoder::decode(&transcode(&src, &options).unwrap()).unwrap();
+        assert_eq!(pixmap.dimensions(), ISize::from_wh(2, 4));
+        assert_eq!(color_at(&pixmap, 0, 0), [255, 0, 0]);
+        assert_eq!(color_at(&pixmap, 1, 3), [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_resize_and_convert() {
+        let options = Options {
+            dimensions: Some(ISize::from_wh(2, 1)),
+            color_type: Some(ColorType::Bgra8888),
+            ..Options::default()
+        };
+        let pixmap =
+            png_decoder::decode(&transcode(&png_file(8, 4, 1), &options).unwrap()).unwrap();
+        assert_eq!(pixmap.dimensions(), ISize::from_wh(2, 1));
+        assert_eq!(color_at(&pixmap, 0, 0), [255, 0, 0]);
+        assert_eq!(color_at(&pixmap, 1, 0), [0, 0, 255]);
+
+        // Pixels covering both halves are averaged.
+        let options = Options {
+            dimensions: Some(ISize::from_wh(1, 1)),
+            ..Options::default()
+        };
+        let pixmap =
+            png_decoder::decode(&transcode(&png_file(2, 1, 1), &options).unwrap()).unwrap();
+        assert_eq!(color_at(&pixmap, 0, 0), [128, 0, 128]);
+
+        // Enlarging repeats pixels.
+        let options = Options {
+            dimensions: Some(ISize::from_wh(4, 2)),
+            ..Options::default()
+        };
+        let pixmap =
+            png_decoder::decode(&transcode(&png_file(2, 1, 1), &options).unwrap()).unwrap();
+        assert_eq!(color_at(&pixmap, 1, 1), [255, 0, 0]);
+        assert_eq!(color_at(&pixmap, 2, 0), [0, 0, 255]);
+
+        let options = Options {
+            dimensions: Some(ISize::from_wh(0, 1)),
+            ..Options::default()
+        };
+        assert!(transcode(&png_file(2, 1, 1), &options).is_none());
+    }
+
+    #[test]
+    fn test_bmp_source() {
+        // 1x1 bitmap with a red pixel in BGR order, row is padded to 4 bytes.
+        let mut data = b"BM".to_vec();
+        data.extend(58_u32.to_le_bytes());
+        data.extend(0_u32.to_le_bytes());
+        data.extend(54_u32.to_le_bytes());
+        data.extend(40_u32.to_le_bytes());
+        data.extend(1_i32.to_le_bytes());
+        data.extend(1_i32.to_le_bytes());
+        data.extend(1_u16.to_le_bytes());
+        data.extend(24_u16.to_le_bytes());
+        data.extend([0; 24]);
+        data.extend([0, 0, 255, 0]);
+
+        let pixmap = png_decoder::decode(&transcode(&data, &Options::default()).unwrap()).unwrap();
+        assert_eq!(pixmap.dimensions(), ISize::from_wh(1, 1));
+        assert_eq!(color_at(&pixmap, 0, 0), [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(transcode(b"not an image", &Options::default()).is_none());
+        let options = Options {
+            color_type: Some(ColorType::Unknown),
+            ..Options::default()
+        };
+        assert!(transcode(&png_file(2, 1, 1), &options).is_none());
+    }
+}
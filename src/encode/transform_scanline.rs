@@ -0,0 +1,182 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Conversion of pixel rows to the layout expected by encoders.
+//!
+//! Each function reads all pixels of `src` and appends converted samples to `dst`.
+
+use crate::core::alpha_type::AlphaType;
+
+/// Byte indices of red, green and blue in 32 bit pixels stored as RGBA.
+pub const RGBA_ORDER: [usize; 3] = [0, 1, 2];
+
+/// Byte indices of red, green and blue in 32 bit pixels stored as BGRA.
+pub const BGRA_ORDER: [usize; 3] = [2, 1, 0];
+
+#[must_use]
+#[inline]
+#[allow(clippy::cast_possible_truncation)]
+const fn unpremultiply(value: u8, alpha: u8) -> u8 {
+    if alpha == 0 {
+        return 0;
+    }
+    let value = (value as u32 * 255 + alpha as u32 / 2) / alpha as u32;
+    if value > 255 {
+        255
+    } else {
+        value as u8
+    }
+}
+
+#[must_use]
+#[inline]
+#[allow(clippy::cast_possible_truncation)]
+const fn premultiply(value: u8, alpha: u8) -> u8 {
+    ((value as u32 * alpha as u32 + 127) / 255) as u8
+}
+
+#[must_use]
+#[inline]
+#[allow(clippy::cast_possible_truncation)]
+const fn unpremultiply16(value: u16, alpha: u16) -> u16 {
+    if alpha == 0 {
+        return 0;
+    }
+    let value = (value as u32 * 65535 + alpha as u32 / 2) / alpha as u32;
+    if value > 65535 {
+        65535
+    } else {
+        value as u16
+    }
+}
+
+/// Converts 32 bit pixels to 8 bit RGB, ignoring alpha.
+///
+/// Premultiplied colors are unpremultiplied first.
+pub fn to_rgb(dst: &mut Vec<u8>, src: &[u8], order: [usize; 3], alpha_type: AlphaType) {
+    for pixel in src.chunks_exact(4) {
+        let alpha = pixel[3];
+        for index in order {
+            if alpha_type == AlphaType::Premul {
+                dst.push(unpremultiply(pixel[index], alpha));
+            } else {
+                dst.push(pixel[index]);
+            }
+        }
+    }
+}
+
+/// Converts 32 bit pixels to 8 bit RGB, blended on black background.
+pub fn to_rgb_on_black(dst: &mut Vec<u8>, src: &[u8], order: [usize; 3], alpha_type: AlphaType) {
+    for pixel in src.chunks_exact(4) {
+        let alpha = pixel[3];
+        for index in order {
+            if alpha_type == AlphaType::Unpremul {
+                dst.push(premultiply(pixel[index], alpha));
+            } else {
+                dst.push(pixel[index]);
+            }
+        }
+    }
+}
+
+/// Converts 32 bit pixels to 8 bit unpremultiplied RGBA.
+pub fn to_rgba(dst: &mut Vec<u8>, src: &[u8], order: [usize; 3], alpha_type: AlphaType) {
+    for pixel in src.chunks_exact(4) {
+        to_rgb(dst, pixel, order, alpha_type);
+        dst.push(pixel[3]);
+    }
+}
+
+/// Converts 8 bit alpha pixels to black gray with alpha.
+pub fn a8_to_gray_alpha(dst: &mut Vec<u8>, src: &[u8]) {
+    for &alpha in src {
+        dst.extend_from_slice(&[0, alpha]);
+    }
+}
+
+/// Converts native endian 16 bit RGBA pixels to big endian 16 bit RGB or RGBA.
+///
+/// Premultiplied colors are unpremultiplied.
+pub fn rgba16_to_big_endian(
+    dst: &mut Vec<u8>,
+    src: &[u8],
+    alpha_type: AlphaType,
+    keep_alpha: bool,
+) {
+    for pixel in src.chunks_exact(8) {
+        let sample = |index: usize| u16::from_ne_bytes([pixel[index * 2], pixel[index * 2 + 1]]);
+        let alpha = sample(3);
+        for index in 0..3 {
+            let value = if alpha_type == AlphaType::Premul {
+                unpremultiply16(sample(index), alpha)
+            } else {
+                sample(index)
+            };
+            dst.extend_from_slice(&value.to_be_bytes());
+        }
+        if keep_alpha {
+            dst.extend_from_slice(&alpha.to_be_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_rgb() {
+        let src = [10, 20, 30, 255, 64, 32, 0, 128, 5, 6, 7, 0];
+        let mut dst = Vec::new();
+        to_rgb(&mut dst, &src, RGBA_ORDER, AlphaType::Premul);
+        assert_eq!(dst, [10, 20, 30, 128, 64, 0, 0, 0, 0]);
+
+        let mut dst = Vec::new();
+        to_rgb(&mut dst, &src, BGRA_ORDER, AlphaType::Unpremul);
+        assert_eq!(dst, [30, 20, 10, 0, 32, 64, 7, 6, 5]);
+    }
+
+    #[test]
+    fn test_to_rgb_on_black() {
+        let src = [200, 100, 0, 128, 10, 20, 30, 255];
+        let mut dst = Vec::new();
+        to_rgb_on_black(&mut dst, &src, RGBA_ORDER, AlphaType::Unpremul);
+        assert_eq!(dst, [100, 50, 0, 10, 20, 30]);
+
+        let mut dst = Vec::new();
+        to_rgb_on_black(&mut dst, &src, RGBA_ORDER, AlphaType::Premul);
+        assert_eq!(dst, [200, 100, 0, 10, 20, 30]);
+    }
+
+    #[test]
+    fn test_to_rgba() {
+        let src = [32, 64, 0, 128];
+        let mut dst = Vec::new();
+        to_rgba(&mut dst, &src, BGRA_ORDER, AlphaType::Premul);
+        assert_eq!(dst, [0, 128, 64, 128]);
+    }
+
+    #[test]
+    fn test_a8_to_gray_alpha() {
+        let mut dst = Vec::new();
+        a8_to_gray_alpha(&mut dst, &[0, 128, 255]);
+        assert_eq!(dst, [0, 0, 0, 128, 0, 255]);
+    }
+
+    #[test]
+    fn test_rgba16_to_big_endian() {
+        let src: Vec<u8> = [0x8000_u16, 0x4000, 0, 0x8000]
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        let mut dst = Vec::new();
+        rgba16_to_big_endian(&mut dst, &src, AlphaType::Premul, true);
+        assert_eq!(dst, [0xff, 0xff, 0x80, 0, 0, 0, 0x80, 0]);
+
+        let mut dst = Vec::new();
+        rgba16_to_big_endian(&mut dst, &src, AlphaType::Unpremul, false);
+        assert_eq!(dst, [0x80, 0, 0x40, 0, 0, 0]);
+    }
+}
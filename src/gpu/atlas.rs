@@ -0,0 +1,219 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::image::Image;
+use crate::core::image_info::ImageInfo;
+use crate::core::irect::IRect;
+use crate::core::rect::Rect;
+use crate::gpu::rectanizer::Rectanizer;
+use crate::gpu::rectanizer_skyline::RectanizerSkyline;
+
+/// Location of one image packed in atlas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasEntry {
+    /// Pixel bounds in atlas image.
+    pub rect: IRect,
+
+    /// Bounds in atlas image, normalized to [0..1].
+    pub uv: Rect,
+}
+
+/// An image with many small images packed into it, like a sprite sheet or
+/// a glyph cache texture.
+#[derive(Debug, Clone)]
+pub struct Atlas {
+    image: Image<'static>,
+    entries: Vec<AtlasEntry>,
+}
+
+impl Atlas {
+    /// Packs `images` into one atlas image of `width` x `height` pixels.
+    ///
+    /// `padding` transparent pixels are kept between images, to avoid bleeding
+    /// when sampling with filtering.
+    ///
+    /// Returns None if `images` is empty, if images have different color types or
+    /// alpha types, or if they do not fit in atlas.
+    #[must_use]
+    pub fn pack(images: &[Image<'_>], width: i32, height: i32, padding: i32) -> Option<Self> {
+        let first = images.first()?;
+        if padding < 0
+            || images.iter().any(|image| {
+                image.color_type() != first.color_type() || image.alpha_type() != first.alpha_type()
+            })
+        {
+            return None;
+        }
+
+        // Place tall images first, which packs tighter with skyline.
+        let mut order: Vec<usize> = (0..images.len()).collect();
+        order.sort_by_key(|&index| {
+            let image = &images[index];
+            (
+                std::cmp::Reverse(image.height()),
+                std::cmp::Reverse(image.width()),
+            )
+        });
+
+        let mut rectanizer = RectanizerSkyline::new(width, height);
+        let mut rects = vec![IRect::new(); images.len()];
+        for index in order {
+            let image = &images[index];
+            let loc = rectanizer.add_rect(image.width() + padding, image.height() + padding)?;
+            rects[index] = IRect::from_xywh(loc.x(), loc.y(), image.width(), image.height());
+        }
+
+        let info = ImageInfo::from(
+            width,
+            height,
+            first.color_type(),
+            first.alpha_type(),
+            first.color_space().clone(),
+        );
+        let row_bytes = info.min_row_bytes();
+        let mut pixels = vec![0; info.compute_byte_size(row_bytes)];
+        for (image, rect) in images.iter().zip(&rects) {
+            copy_pixels(image, rect, &info, row_bytes, &mut pixels);
+        }
+        let image = Image::from_vec(pixels, info, row_bytes)?;
+
+        #[allow(clippy::cast_precision_loss)]
+        let (atlas_width, atlas_height) = (width as f32, height as f32);
+        #[allow(clippy::cast_precision_loss)]
+        let entries = rects
+            .into_iter()
+            .map(|rect| AtlasEntry {
+                uv: Rect::from_ltrb(
+                    rect.left() as f32 / atlas_width,
+                    rect.top() as f32 / atlas_height,
+                    rect.right() as f32 / atlas_width,
+                    rect.bottom() as f32 / atlas_height,
+                ),
+                rect,
+            })
+            .collect();
+        Some(Self { image, entries })
+    }
+
+    /// Returns the atlas image.
+    #[must_use]
+    #[inline]
+    pub const fn image(&self) -> &Image<'static> {
+        &self.image
+    }
+
+    /// Returns location of packed images, in the same order as input images.
+    #[must_use]
+    #[inline]
+    pub fn entries(&self) -> &[AtlasEntry] {
+        &self.entries
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn copy_pixels(
+    image: &Image<'_>,
+    rect: &IRect,
+    dst_info: &ImageInfo,
+    dst_row_bytes: usize,
+    dst: &mut [u8],
+) {
+    let row_len = image.info().min_row_bytes();
+    for row in 0..image.height() {
+        let src_offset = row as usize * image.row_bytes();
+        let dst_offset = dst_info.compute_offset(rect.left(), rect.top() + row, dst_row_bytes);
+        dst[dst_offset..dst_offset + row_len]
+            .copy_from_slice(&image.pixels()[src_offset..src_offset + row_len]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::alpha_type::AlphaType;
+
+    /// Returns an opaque N32 image filled with `value`.
+    fn solid(width: i32, height: i32, value: u8) -> Image<'static> {
+        let info = ImageInfo::new_n32(width, height, AlphaType::Opaque, None);
+        let row_bytes = info.min_row_bytes();
+        Image::from_vec(
+            vec![value; info.compute_byte_size(row_bytes)],
+            info,
+            row_bytes,
+        )
+        .unwrap()
+    }
+
+    fn pixel_at<'a>(image: &'a Image<'_>, x: i32, y: i32) -> &'a [u8] {
+        let offset = image.info().compute_offset(x, y, image.row_bytes());
+        &image.pixels()[offset..offset + 4]
+    }
+
+    fn to_f32(value: i32) -> f32 {
+        f32::from(u8::try_from(value).unwrap())
+    }
+
+    #[test]
+    fn test_pack() {
+        let images = [solid(4, 2, 10), solid(3, 5, 20), solid(6, 6, 30)];
+        let atlas = Atlas::pack(&images, 16, 16, 1).unwrap();
+        assert_eq!(atlas.image().width(), 16);
+        assert_eq!(atlas.image().height(), 16);
+        assert_eq!(atlas.entries().len(), 3);
+
+        for (image, entry) in images.iter().zip(atlas.entries()) {
+            let rect = &entry.rect;
+            assert_eq!(
+                (rect.width(), rect.height()),
+                (image.width(), image.height())
+            );
+            let value = image.pixels()[0];
+            assert!(pixel_at(atlas.image(), rect.left(), rect.top())
+                .iter()
+                .all(|&byte| byte == value));
+            assert!(pixel_at(atlas.image(), rect.right() - 1, rect.bottom() - 1)
+                .iter()
+                .all(|&byte| byte == value));
+            assert!(entry.uv.left().mul_add(16.0, -to_f32(rect.left())).abs() < 1e-4);
+            assert!(
+                entry
+                    .uv
+                    .bottom()
+                    .mul_add(16.0, -to_f32(rect.bottom()))
+                    .abs()
+                    < 1e-4
+            );
+        }
+
+        // Images are padded from each other.
+        for (index, entry) in atlas.entries().iter().enumerate() {
+            let mut padded = entry.rect.clone();
+            padded.outset(1, 1);
+            for other in &atlas.entries()[index + 1..] {
+                assert!(!padded.clone().intersect(&other.rect));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pack_tall_images_first() {
+        let images = [solid(2, 2, 1), solid(2, 8, 2)];
+        let atlas = Atlas::pack(&images, 4, 8, 0).unwrap();
+        assert_eq!(atlas.entries()[1].rect, IRect::from_xywh(0, 0, 2, 8));
+        assert_eq!(atlas.entries()[0].rect, IRect::from_xywh(2, 0, 2, 2));
+    }
+
+    #[test]
+    fn test_pack_fails() {
+        assert!(Atlas::pack(&[], 16, 16, 0).is_none());
+        assert!(Atlas::pack(&[solid(4, 4, 0)], 16, 16, -1).is_none());
+        assert!(Atlas::pack(&[solid(4, 4, 0), solid(8, 8, 0)], 8, 8, 0).is_none());
+        // Padding does not fit.
+        assert!(Atlas::pack(&[solid(8, 8, 0)], 8, 8, 1).is_none());
+
+        let info = ImageInfo::new_a8(4, 4);
+        let alpha = Image::from_vec(vec![0; 16], info, 4).unwrap();
+        assert!(Atlas::pack(&[solid(4, 4, 0), alpha], 16, 16, 0).is_none());
+    }
+}
@@ -1,3 +1,7 @@
 // Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
+
+pub mod atlas;
+pub mod rectanizer;
+pub mod rectanizer_skyline;
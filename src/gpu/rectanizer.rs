@@ -0,0 +1,25 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::point::IPoint;
+
+/// Packs rectangles into a fixed size plot, used to build texture atlases.
+pub trait Rectanizer {
+    /// Width of the plot.
+    fn width(&self) -> i32;
+
+    /// Height of the plot.
+    fn height(&self) -> i32;
+
+    /// Removes all rectangles.
+    fn reset(&mut self);
+
+    /// Attempts to add a rect with `width` and `height`.
+    ///
+    /// Returns the location of its top-left corner, or None if there is no room.
+    fn add_rect(&mut self, width: i32, height: i32) -> Option<IPoint>;
+
+    /// Returns ratio of the area occupied by rectangles.
+    fn percent_full(&self) -> f32;
+}
@@ -0,0 +1,228 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::point::IPoint;
+use crate::gpu::rectanizer::Rectanizer;
+
+/// A top edge of the occupied area.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct SkylineSegment {
+    /// The left edge of the segment.
+    x: i32,
+
+    /// The height of the segment.
+    y: i32,
+
+    /// The width of the segment.
+    width: i32,
+}
+
+/// Pack rectangles and track the current silhouette.
+///
+/// Based in part on Jukka Jylänki's work at <http://clb.demon.fi>
+#[derive(Debug, Clone)]
+pub struct RectanizerSkyline {
+    width: i32,
+    height: i32,
+
+    skyline: Vec<SkylineSegment>,
+    area_so_far: i64,
+}
+
+impl RectanizerSkyline {
+    #[must_use]
+    pub fn new(width: i32, height: i32) -> Self {
+        let mut rectanizer = Self {
+            width,
+            height,
+            skyline: Vec::new(),
+            area_so_far: 0,
+        };
+        rectanizer.reset();
+        rectanizer
+    }
+
+    /// Returns true if the rectangle can fit at the left edge of segment `index`,
+    /// with the y position where it would be placed.
+    fn rectangle_fits(&self, index: usize, width: i32, height: i32) -> Option<i32> {
+        let x = self.skyline[index].x;
+        if x + width > self.width {
+            return None;
+        }
+
+        let mut width_left = width;
+        let mut y = self.skyline[index].y;
+        for segment in &self.skyline[index..] {
+            if width_left <= 0 {
+                break;
+            }
+            y = y.max(segment.y);
+            if y + height > self.height {
+                return None;
+            }
+            width_left -= segment.width;
+        }
+        Some(y)
+    }
+
+    /// Update the skyline structure to include a new rect.
+    fn add_skyline_level(&mut self, index: usize, x: i32, y: i32, width: i32, height: i32) {
+        let new_segment = SkylineSegment {
+            x,
+            y: y + height,
+            width,
+        };
+        debug_assert!(new_segment.x + new_segment.width <= self.width);
+        debug_assert!(new_segment.y <= self.height);
+        self.skyline.insert(index, new_segment);
+
+        // Delete width of the new skyline segment from following ones.
+        let i = index + 1;
+        while i < self.skyline.len() {
+            let prev = self.skyline[i - 1];
+            debug_assert!(prev.x <= self.skyline[i].x);
+            // The new segment subsumes all or part of skyline[i].
+            if self.skyline[i].x >= prev.x + prev.width {
+                break;
+            }
+            let shrink = prev.x + prev.width - self.skyline[i].x;
+            self.skyline[i].x += shrink;
+            self.skyline[i].width -= shrink;
+            if self.skyline[i].width > 0 {
+                // Only partially consumed.
+                break;
+            }
+            // Fully consumed.
+            self.skyline.remove(i);
+        }
+
+        // Merge skylines.
+        let mut i = 0;
+        while i + 1 < self.skyline.len() {
+            if self.skyline[i].y == self.skyline[i + 1].y {
+                self.skyline[i].width += self.skyline[i + 1].width;
+                self.skyline.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+impl Rectanizer for RectanizerSkyline {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn reset(&mut self) {
+        self.area_so_far = 0;
+        self.skyline.clear();
+        self.skyline.push(SkylineSegment {
+            x: 0,
+            y: 0,
+            width: self.width,
+        });
+    }
+
+    fn add_rect(&mut self, width: i32, height: i32) -> Option<IPoint> {
+        if width <= 0 || height <= 0 || width > self.width || height > self.height {
+            return None;
+        }
+
+        // Find position for new rectangle.
+        let mut best_width = self.width + 1;
+        let mut best_x = 0;
+        let mut best_y = self.height + 1;
+        let mut best_index = None;
+        for (index, segment) in self.skyline.iter().enumerate() {
+            if let Some(y) = self.rectangle_fits(index, width, height) {
+                // Minimize y position first, then width of skyline.
+                if y < best_y || (y == best_y && segment.width < best_width) {
+                    best_index = Some(index);
+                    best_width = segment.width;
+                    best_x = segment.x;
+                    best_y = y;
+                }
+            }
+        }
+
+        // Add rectangle to skyline.
+        let index = best_index?;
+        self.add_skyline_level(index, best_x, best_y, width, height);
+        self.area_so_far += i64::from(width) * i64::from(height);
+        Some(IPoint::from_xy(best_x, best_y))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn percent_full(&self) -> f32 {
+        let area = i64::from(self.width) * i64::from(self.height);
+        if area == 0 {
+            return 0.0;
+        }
+        self.area_so_far as f32 / area as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_rect() {
+        let mut rectanizer = RectanizerSkyline::new(10, 10);
+        assert_eq!(rectanizer.add_rect(4, 6), Some(IPoint::from_xy(0, 0)));
+        assert_eq!(rectanizer.add_rect(6, 3), Some(IPoint::from_xy(4, 0)));
+        // Lowest skyline is on the right.
+        assert_eq!(rectanizer.add_rect(6, 3), Some(IPoint::from_xy(4, 3)));
+        assert_eq!(rectanizer.add_rect(10, 4), Some(IPoint::from_xy(0, 6)));
+        assert_eq!(rectanizer.add_rect(1, 1), None);
+        assert!((rectanizer.percent_full() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_rect() {
+        let mut rectanizer = RectanizerSkyline::new(10, 10);
+        assert_eq!(rectanizer.add_rect(0, 5), None);
+        assert_eq!(rectanizer.add_rect(5, -1), None);
+        assert_eq!(rectanizer.add_rect(11, 5), None);
+        assert_eq!(rectanizer.add_rect(5, 11), None);
+        assert!(rectanizer.percent_full().abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut rectanizer = RectanizerSkyline::new(8, 4);
+        assert_eq!(rectanizer.add_rect(8, 4), Some(IPoint::from_xy(0, 0)));
+        assert_eq!(rectanizer.add_rect(2, 2), None);
+        rectanizer.reset();
+        assert!(rectanizer.percent_full().abs() < 1e-6);
+        assert_eq!(rectanizer.add_rect(2, 2), Some(IPoint::from_xy(0, 0)));
+        assert!((rectanizer.percent_full() - 0.125).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_no_overlap() {
+        let mut rectanizer = RectanizerSkyline::new(64, 64);
+        let mut placed: Vec<(i32, i32, i32, i32)> = Vec::new();
+        for index in 0..40 {
+            let (width, height) = (3 + index % 7, 2 + index % 5);
+            let Some(loc) = rectanizer.add_rect(width, height) else {
+                continue;
+            };
+            let rect = (loc.x(), loc.y(), loc.x() + width, loc.y() + height);
+            assert!(rect.2 <= 64 && rect.3 <= 64);
+            for other in &placed {
+                let overlaps =
+                    rect.0 < other.2 && other.0 < rect.2 && rect.1 < other.3 && other.1 < rect.3;
+                assert!(!overlaps);
+            }
+            placed.push(rect);
+        }
+        assert_eq!(placed.len(), 40);
+    }
+}
@@ -0,0 +1,588 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Streaming PDF writer.
+//!
+//! Every object is written to the output stream as soon as it is emitted, only its
+//! byte offset is kept for the cross-reference table. Page content is buffered
+//! until `end_page()`, so memory usage is bounded by the largest page instead of
+//! the whole document.
+
+use std::cell::{RefCell, RefMut};
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::core::canvas::Canvas;
+use crate::core::document_metadata::DocumentMetadata;
+use crate::core::scalar::Scalar;
+use crate::core::surface_props::SurfaceProps;
+use crate::pdf::metadata::{document_information, make_uuid, make_xmp, Uuid};
+use crate::pdf::pdf_device::PdfDevice;
+use crate::pdf::types::{Dict, IndirectReference, Object};
+
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Options {
+    /// Document information and XMP metadata.
+    pub metadata: DocumentMetadata,
+
+    /// If true, identify the document as PDF/A-2b in its XMP metadata.
+    ///
+    /// This only writes the identification. No output intent is written, so the
+    /// document is not PDF/A-2b conformant yet.
+    // TODO(Shaohua): Write an sRGB output intent.
+    pub pdfa: bool,
+}
+
+/// A page under construction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    width: Scalar,
+    height: Scalar,
+    content: Vec<u8>,
+    resources: Dict,
+
+    /// Dictionary and content stream of form `XObject`s used by this page.
+    forms: Vec<(Dict, Vec<u8>)>,
+}
+
+impl Page {
+    #[must_use]
+    #[inline]
+    pub const fn new(width: Scalar, height: Scalar) -> Self {
+        Self {
+            width,
+            height,
+            content: Vec::new(),
+            resources: Dict::new(),
+            forms: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn width(&self) -> Scalar {
+        self.width
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn height(&self) -> Scalar {
+        self.height
+    }
+
+    /// Returns the content stream of this page.
+    #[must_use]
+    #[inline]
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+
+    /// Returns the content stream of this page, to append operators to.
+    #[inline]
+    pub fn content_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.content
+    }
+
+    /// Registers an indirect object used by content stream in resource dictionary.
+    ///
+    /// `category` is one of resource types, like "`XObject`", "Font" or "`ExtGState`".
+    pub fn add_resource(&mut self, category: &str, name: &str, reference: IndirectReference) {
+        self.resources.dict_mut(category).insert(name, reference);
+    }
+
+    /// Returns the resource dictionary, to add direct resource objects to.
+    #[inline]
+    pub fn resources_mut(&mut self) -> &mut Dict {
+        &mut self.resources
+    }
+
+    /// Adds a form `XObject` with `dict` and `content` stream, which is written
+    /// along with this page.
+    ///
+    /// Form shares resources of this page, including forms added before it.
+    ///
+    /// Returns name of the form in resource dictionary.
+    pub fn add_form(&mut self, dict: Dict, content: Vec<u8>) -> String {
+        let name = form_name(self.forms.len());
+        self.forms.push((dict, content));
+        name
+    }
+}
+
+fn form_name(index: usize) -> String {
+    format!("Fm{index}")
+}
+
+/// Wraps the output stream and counts bytes written, to record object offsets.
+#[derive(Debug)]
+struct CountingWriter<W: Write> {
+    inner: W,
+    bytes_written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.bytes_written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[derive(Debug)]
+pub struct Document<W: Write> {
+    stream: CountingWriter<W>,
+    options: Options,
+
+    /// Byte offset of each object, indexed by object number - 1.
+    /// Zero means reserved but not emitted yet.
+    offsets: Vec<u64>,
+
+    pages_ref: IndirectReference,
+    pages: Vec<IndirectReference>,
+    current_page: Option<Rc<RefCell<Page>>>,
+}
+
+impl<W: Write> Document<W> {
+    /// Creates a new document and writes the file header to `stream`.
+    ///
+    /// # Errors
+    /// Returns error if failed to write to stream.
+    pub fn new(stream: W, options: Options) -> io::Result<Self> {
+        let mut document = Self {
+            stream: CountingWriter {
+                inner: stream,
+                bytes_written: 0,
+            },
+            options,
+            offsets: Vec::new(),
+            pages_ref: IndirectReference(0),
+            pages: Vec::new(),
+            current_page: None,
+        };
+        // Binary comment marks this file as binary for transfer programs.
+        document
+            .stream
+            .write_all(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n")?;
+        document.pages_ref = document.reserve_ref();
+        Ok(document)
+    }
+
+    /// Returns number of pages finished so far.
+    #[must_use]
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Returns number of bytes written to stream so far.
+    #[must_use]
+    #[inline]
+    pub const fn bytes_written(&self) -> u64 {
+        self.stream.bytes_written
+    }
+
+    /// Allocates an object number, the object can be emitted later with `emit()`.
+    ///
+    /// This allows objects to be referenced before they are written.
+    pub fn reserve_ref(&mut self) -> IndirectReference {
+        self.offsets.push(0);
+        #[allow(clippy::cast_possible_truncation)]
+        IndirectReference(self.offsets.len() as u32)
+    }
+
+    /// Writes `object` as indirect object with number `reference`.
+    ///
+    /// # Errors
+    /// Returns error if `reference` is not reserved by `reserve_ref()` or is
+    /// already emitted, or if failed to write to stream.
+    pub fn emit(&mut self, reference: IndirectReference, object: &Object) -> io::Result<()> {
+        self.begin_object(reference)?;
+        object.write_to(&mut self.stream)?;
+        self.end_object()
+    }
+
+    /// Writes a stream object with number `reference`.
+    ///
+    /// `/Length` entry of `dict` is set to length of `data`.
+    ///
+    /// # Errors
+    /// Returns error if `reference` is not reserved by `reserve_ref()` or is
+    /// already emitted, or if failed to write to stream.
+    pub fn emit_stream(
+        &mut self,
+        reference: IndirectReference,
+        mut dict: Dict,
+        data: &[u8],
+    ) -> io::Result<()> {
+        dict.insert("Length", data.len());
+        self.begin_object(reference)?;
+        dict.write_to(&mut self.stream)?;
+        self.stream.write_all(b" stream\n")?;
+        self.stream.write_all(data)?;
+        self.stream.write_all(b"\nendstream")?;
+        self.end_object()
+    }
+
+    /// Reserves and writes `object` in one step.
+    ///
+    /// # Errors
+    /// Returns error if failed to write to stream.
+    pub fn add(&mut self, object: &Object) -> io::Result<IndirectReference> {
+        let reference = self.reserve_ref();
+        self.emit(reference, object)?;
+        Ok(reference)
+    }
+
+    /// Reserves and writes a stream object in one step.
+    ///
+    /// # Errors
+    /// Returns error if failed to write to stream.
+    pub fn add_stream(&mut self, dict: Dict, data: &[u8]) -> io::Result<IndirectReference> {
+        let reference = self.reserve_ref();
+        self.emit_stream(reference, dict, data)?;
+        Ok(reference)
+    }
+
+    /// Starts a new page, finishing current page if any.
+    ///
+    /// Returns a canvas which draws into the new page, with origin at top-left
+    /// corner of page and one unit per point.
+    ///
+    /// # Errors
+    /// Returns error if failed to write previous page to stream.
+    pub fn begin_page(&mut self, width: Scalar, height: Scalar) -> io::Result<Canvas> {
+        self.end_page()?;
+        let page = Rc::new(RefCell::new(Page::new(width, height)));
+        self.current_page = Some(page.clone());
+        let device = PdfDevice::new(page, SurfaceProps::default());
+        Ok(Canvas::with_device(Box::new(device)))
+    }
+
+    /// Returns the page under construction, to append content stream operators to.
+    ///
+    /// # Panics
+    /// Panics if the page is being drawn by its canvas at the same time.
+    #[must_use]
+    #[inline]
+    pub fn current_page(&self) -> Option<RefMut<'_, Page>> {
+        self.current_page.as_ref().map(|page| page.borrow_mut())
+    }
+
+    /// Writes content stream and page object of current page, then releases it.
+    ///
+    /// Does nothing if no page is under construction.
+    /// Canvas of the page may still be alive, but its drawing is discarded.
+    ///
+    /// # Errors
+    /// Returns error if failed to write to stream.
+    pub fn end_page(&mut self) -> io::Result<()> {
+        if let Some(page) = self.current_page.take() {
+            let page = Rc::try_unwrap(page).map_or_else(
+                |page| page.replace(Page::new(0.0, 0.0)),
+                RefCell::into_inner,
+            );
+            self.add_page(page)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a finished page.
+    ///
+    /// # Errors
+    /// Returns error if failed to write to stream.
+    pub fn add_page(&mut self, page: Page) -> io::Result<()> {
+        let Page {
+            width,
+            height,
+            content,
+            mut resources,
+            forms,
+        } = page;
+        // Forms are written in the order added, so that nested forms are
+        // written before forms which use them.
+        for (index, (mut dict, form_content)) in forms.into_iter().enumerate() {
+            dict.insert("Resources", resources.clone());
+            let form_ref = self.add_stream(dict, &form_content)?;
+            resources
+                .dict_mut("XObject")
+                .insert(&form_name(index), form_ref);
+        }
+        let content_ref = self.add_stream(Dict::new(), &content)?;
+        drop(content);
+
+        let mut page_dict = Dict::with_type("Page");
+        page_dict.insert("Parent", self.pages_ref);
+        page_dict.insert(
+            "MediaBox",
+            vec![0.into(), 0.into(), width.into(), height.into()],
+        );
+        page_dict.insert("Resources", resources);
+        page_dict.insert("Contents", content_ref);
+        let page_ref = self.add(&page_dict.into())?;
+        self.pages.push(page_ref);
+        Ok(())
+    }
+
+    /// Writes page tree, catalog and cross-reference table, and returns the stream.
+    ///
+    /// Current page is finished first.
+    ///
+    /// # Errors
+    /// Returns error if a reference reserved by `reserve_ref()` is never emitted,
+    /// or if failed to write to stream.
+    pub fn close(mut self) -> io::Result<W> {
+        self.end_page()?;
+
+        let mut pages_dict = Dict::with_type("Pages");
+        pages_dict.insert(
+            "Kids",
+            self.pages
+                .iter()
+                .map(|r| Object::Ref(*r))
+                .collect::<Vec<_>>(),
+        );
+        pages_dict.insert("Count", self.pages.len());
+        self.emit(self.pages_ref, &pages_dict.into())?;
+
+        let document_id: Uuid = make_uuid();
+        let instance_id: Uuid = make_uuid();
+
+        let mut catalog = Dict::with_type("Catalog");
+        catalog.insert("Pages", self.pages_ref);
+        let metadata = &self.options.metadata;
+        if self.options.pdfa || !metadata.is_empty() {
+            let xmp = make_xmp(metadata, &document_id, &instance_id, self.options.pdfa);
+            let mut dict = Dict::with_type("Metadata");
+            dict.insert("Subtype", Object::name("XML"));
+            let metadata_ref = self.add_stream(dict, xmp.as_bytes())?;
+            catalog.insert("Metadata", metadata_ref);
+        }
+        if !self.options.metadata.language.is_empty() {
+            catalog.insert("Lang", Object::text(&self.options.metadata.language));
+        }
+        let catalog_ref = self.add(&catalog.into())?;
+
+        let mut info = Dict::new();
+        for (key, value) in document_information(&self.options.metadata) {
+            info.insert(key, Object::TextString(value));
+        }
+        let info_ref = if info.is_empty() {
+            None
+        } else {
+            Some(self.add(&info.into())?)
+        };
+
+        self.write_xref_and_trailer(catalog_ref, info_ref, &document_id, &instance_id)?;
+        self.stream.flush()?;
+        Ok(self.stream.inner)
+    }
+
+    fn begin_object(&mut self, reference: IndirectReference) -> io::Result<()> {
+        let offset = (reference.0 as usize)
+            .checked_sub(1)
+            .and_then(|index| self.offsets.get_mut(index))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "reference is not reserved")
+            })?;
+        if *offset != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "reference is already emitted",
+            ));
+        }
+        *offset = self.stream.bytes_written;
+        writeln!(self.stream, "{} 0 obj", reference.0)
+    }
+
+    fn end_object(&mut self) -> io::Result<()> {
+        self.stream.write_all(b"\nendobj\n")
+    }
+
+    fn write_xref_and_trailer(
+        &mut self,
+        catalog_ref: IndirectReference,
+        info_ref: Option<IndirectReference>,
+        document_id: &Uuid,
+        instance_id: &Uuid,
+    ) -> io::Result<()> {
+        if self.offsets.contains(&0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "reserved reference is never emitted",
+            ));
+        }
+        let xref_offset = self.stream.bytes_written;
+        let object_count = self.offsets.len() + 1;
+        write!(self.stream, "xref\n0 {object_count}\n0000000000 65535 f \n")?;
+        for offset in &self.offsets {
+            writeln!(self.stream, "{offset:010} 00000 n ")?;
+        }
+
+        let mut trailer = Dict::new();
+        trailer.insert("Size", object_count);
+        trailer.insert("Root", catalog_ref);
+        if let Some(info_ref) = info_ref {
+            trailer.insert("Info", info_ref);
+        }
+        trailer.insert(
+            "ID",
+            vec![
+                Object::ByteString(document_id.to_vec()),
+                Object::ByteString(instance_id.to_vec()),
+            ],
+        );
+        self.stream.write_all(b"trailer\n")?;
+        trailer.write_to(&mut self.stream)?;
+        write!(self.stream, "\nstartxref\n{xref_offset}\n%%EOF\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Replaces non-ASCII bytes with '?', keeping byte offsets.
+    fn to_ascii(pdf: &[u8]) -> String {
+        pdf.iter()
+            .map(|&byte| {
+                if byte.is_ascii() {
+                    char::from(byte)
+                } else {
+                    '?'
+                }
+            })
+            .collect()
+    }
+
+    /// Returns byte offset of "`number` 0 obj" in `pdf`.
+    fn find_object(pdf: &str, number: usize) -> Option<usize> {
+        let header = format!("{number} 0 obj\n");
+        pdf.match_indices(&header)
+            .map(|(offset, _)| offset)
+            .find(|&offset| offset == 0 || pdf.as_bytes()[offset - 1] == b'\n')
+    }
+
+    /// Checks that cross-reference table points to each object, and returns
+    /// number of entries.
+    fn check_xref(pdf: &str) -> usize {
+        let startxref = pdf.rfind("startxref\n").unwrap();
+        let xref_offset: usize = pdf[startxref + 10..]
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(pdf[xref_offset..].starts_with("xref\n0 "));
+        let mut lines = pdf[xref_offset..].lines().skip(1);
+        let count: usize = lines.next().unwrap()[2..].parse().unwrap();
+        assert_eq!(lines.next(), Some("0000000000 65535 f "));
+        for number in 1..count {
+            let line = lines.next().unwrap();
+            assert!(line.ends_with(" 00000 n "));
+            let offset: usize = line[..10].parse().unwrap();
+            assert_eq!(find_object(pdf, number), Some(offset));
+        }
+        assert_eq!(lines.next(), Some("trailer"));
+        count
+    }
+
+    #[test]
+    fn test_empty_document() {
+        let document = Document::new(Vec::new(), Options::default()).unwrap();
+        let pdf = document.close().unwrap();
+        let pdf = to_ascii(&pdf);
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Type /Pages\n/Kids []\n/Count 0\n"));
+        // Pages, catalog and document information with default producer.
+        assert_eq!(check_xref(&pdf), 4);
+        assert!(pdf.contains("/Size 4\n/Root 2 0 R\n/Info 3 0 R\n"));
+        assert!(pdf.contains("/Producer "));
+        assert!(!pdf.contains("/Metadata"));
+    }
+
+    #[test]
+    fn test_pages() {
+        let mut document = Document::new(Vec::new(), Options::default()).unwrap();
+        let mut page = Page::new(100.0, 50.5);
+        page.content_mut().extend_from_slice(b"0 0 m 10 10 l S");
+        let name = page.add_form(Dict::with_type("XObject"), b"q Q".to_vec());
+        assert_eq!(name, "Fm0");
+        document.add_page(page).unwrap();
+        let _canvas = document.begin_page(20.0, 30.0).unwrap();
+        document
+            .current_page()
+            .unwrap()
+            .content_mut()
+            .extend_from_slice(b"1 0 0 RG");
+        document.end_page().unwrap();
+        assert_eq!(document.page_count(), 2);
+        assert!(document.current_page().is_none());
+
+        let pdf = document.close().unwrap();
+        let pdf = to_ascii(&pdf);
+        assert!(pdf.contains("/Length 15\n>> stream\n0 0 m 10 10 l S\nendstream"));
+        assert!(pdf.contains("/Length 8\n>> stream\n1 0 0 RG\nendstream"));
+        assert!(pdf.contains("/MediaBox [0 0 100 50.5]"));
+        assert!(pdf.contains("/MediaBox [0 0 20 30]"));
+        assert!(pdf.contains("/Resources <</XObject <</Fm0 2 0 R\n>>\n>>"));
+        assert!(pdf.contains("/Kids [4 0 R 6 0 R]\n/Count 2\n"));
+        assert_eq!(check_xref(&pdf), 9);
+    }
+
+    #[test]
+    fn test_reserve_and_emit() {
+        let mut document = Document::new(Vec::new(), Options::default()).unwrap();
+        let reference = document.reserve_ref();
+        let array = document.add(&vec![reference.into()].into()).unwrap();
+        assert_eq!(array.value(), reference.value() + 1);
+        document.emit(reference, &Object::Int(42)).unwrap();
+
+        let error = document.emit(reference, &Object::Null).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let error = document
+            .emit(IndirectReference(100), &Object::Null)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let error = document
+            .emit(IndirectReference(0), &Object::Null)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        let pdf = document.close().unwrap();
+        let pdf = to_ascii(&pdf);
+        assert!(pdf.contains("2 0 obj\n42\nendobj\n"));
+        assert!(pdf.contains("3 0 obj\n[2 0 R]\nendobj\n"));
+        check_xref(&pdf);
+    }
+
+    #[test]
+    fn test_never_emitted_reference() {
+        let mut document = Document::new(Vec::new(), Options::default()).unwrap();
+        let _reference = document.reserve_ref();
+        let error = document.close().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let mut options = Options::default();
+        options.metadata.title = "Tést".to_owned();
+        options.metadata.language = "en".to_owned();
+        options.pdfa = true;
+        let document = Document::new(Vec::new(), options).unwrap();
+        let pdf = document.close().unwrap();
+        let pdf = to_ascii(&pdf);
+        assert!(pdf.contains("/Type /Metadata\n/Subtype /XML\n"));
+        assert!(pdf.contains("pdfaid:part"));
+        assert!(pdf.contains("/Lang (en)"));
+        assert!(pdf.contains("/Title <FEFF005400E900730074>"));
+        assert!(pdf.contains("/Info "));
+        check_xref(&pdf);
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Serializes `DocumentMetadata` into PDF document information dictionary and XMP.
+
+use std::fmt::Write;
+
+use crate::base::xml::escape;
+use crate::core::document_metadata::DocumentMetadata;
+use crate::core::time::DateTime;
+
+/// 128 bits identifier, used as document id and instance id of a PDF file.
+pub type Uuid = [u8; 16];
+
+/// Generates a random (version 4) uuid.
+#[must_use]
+pub fn make_uuid() -> Uuid {
+    let mut uuid: Uuid = rand::random();
+    uuid[6] = (uuid[6] & 0x0F) | 0x40;
+    uuid[8] = (uuid[8] & 0x3F) | 0x80;
+    uuid
+}
+
+/// Formats uuid as `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`.
+#[must_use]
+pub fn uuid_to_string(uuid: &Uuid) -> String {
+    let mut out = String::with_capacity(36);
+    for (index, byte) in uuid.iter().enumerate() {
+        if matches!(index, 4 | 6 | 8 | 10) {
+            out.push('-');
+        }
+        let _ = write!(out, "{byte:02x}");
+    }
+    out
+}
+
+/// Formats date time in PDF date format, `D:YYYYMMDDHHmmSS+HH'mm'`.
+#[must_use]
+pub fn pdf_date(date_time: &DateTime) -> String {
+    let (sign, hours, minutes) = date_time.time_zone_parts();
+    format!(
+        "D:{:04}{:02}{:02}{:02}{:02}{:02}{sign}{hours:02}'{minutes:02}'",
+        date_time.year,
+        date_time.month,
+        date_time.day,
+        date_time.hour,
+        date_time.minute,
+        date_time.second
+    )
+}
+
+/// Returns key-value pairs of the document information dictionary.
+///
+/// Values are plain text and shall be encoded as PDF text strings by the writer.
+#[must_use]
+pub fn document_information(metadata: &DocumentMetadata) -> Vec<(&'static str, String)> {
+    let mut entries = Vec::new();
+    let texts = [
+        ("Title", metadata.title.clone()),
+        ("Author", metadata.author.clone()),
+        ("Subject", metadata.subject.clone()),
+        ("Keywords", metadata.joined_keywords()),
+        ("Creator", metadata.creator.clone()),
+        ("Producer", metadata.producer_or_default()),
+    ];
+    for (key, value) in texts {
+        if !value.is_empty() {
+            entries.push((key, value));
+        }
+    }
+    if let Some(creation) = &metadata.creation {
+        entries.push(("CreationDate", pdf_date(creation)));
+    }
+    if let Some(modified) = &metadata.modified {
+        entries.push(("ModDate", pdf_date(modified)));
+    }
+    entries
+}
+
+/// Generates the XMP packet to be stored in the `/Metadata` stream of document catalog.
+///
+/// If `pdfa` is true, PDF/A-2b identification schema is included.
+#[must_use]
+pub fn make_xmp(
+    metadata: &DocumentMetadata,
+    document_id: &Uuid,
+    instance_id: &Uuid,
+    pdfa: bool,
+) -> String {
+    let mut xmp = String::new();
+    xmp.push_str(concat!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+        "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n",
+        "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+        "<rdf:Description rdf:about=\"\"\n",
+        " xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n",
+        " xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n",
+        " xmlns:xmpMM=\"http://ns.adobe.com/xap/1.0/mm/\"\n",
+        " xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\"\n",
+        " xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">\n",
+    ));
+    if pdfa {
+        xmp.push_str("<pdfaid:part>2</pdfaid:part>\n");
+        xmp.push_str("<pdfaid:conformance>B</pdfaid:conformance>\n");
+    }
+    if let Some(creation) = &metadata.creation {
+        let _ = writeln!(xmp, "<xmp:CreateDate>{creation}</xmp:CreateDate>");
+    }
+    if let Some(modified) = &metadata.modified {
+        let _ = writeln!(xmp, "<xmp:ModifyDate>{modified}</xmp:ModifyDate>");
+        let _ = writeln!(xmp, "<xmp:MetadataDate>{modified}</xmp:MetadataDate>");
+    }
+    if !metadata.creator.is_empty() {
+        let _ = writeln!(
+            xmp,
+            "<xmp:CreatorTool>{}</xmp:CreatorTool>",
+            escape(&metadata.creator)
+        );
+    }
+    xmp.push_str("<dc:format>application/pdf</dc:format>\n");
+    if !metadata.title.is_empty() {
+        let _ = writeln!(
+            xmp,
+            "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>",
+            escape(&metadata.title)
+        );
+    }
+    if !metadata.author.is_empty() {
+        let _ = writeln!(
+            xmp,
+            "<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>",
+            escape(&metadata.author)
+        );
+    }
+    if !metadata.subject.is_empty() {
+        let _ = writeln!(
+            xmp,
+            "<dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:description>",
+            escape(&metadata.subject)
+        );
+    }
+    if !metadata.keywords.is_empty() {
+        xmp.push_str("<dc:subject><rdf:Bag>");
+        for keyword in &metadata.keywords {
+            let _ = write!(xmp, "<rdf:li>{}</rdf:li>", escape(keyword));
+        }
+        xmp.push_str("</rdf:Bag></dc:subject>\n");
+        let _ = writeln!(
+            xmp,
+            "<pdf:Keywords>{}</pdf:Keywords>",
+            escape(&metadata.joined_keywords())
+        );
+    }
+    if !metadata.language.is_empty() {
+        let _ = writeln!(
+            xmp,
+            "<dc:language><rdf:Bag><rdf:li>{}</rdf:li></rdf:Bag></dc:language>",
+            escape(&metadata.language)
+        );
+    }
+    let _ = writeln!(
+        xmp,
+        "<pdf:Producer>{}</pdf:Producer>",
+        escape(&metadata.producer_or_default())
+    );
+    let _ = writeln!(
+        xmp,
+        "<xmpMM:DocumentID>uuid:{}</xmpMM:DocumentID>",
+        uuid_to_string(document_id)
+    );
+    let _ = writeln!(
+        xmp,
+        "<xmpMM:InstanceID>uuid:{}</xmpMM:InstanceID>",
+        uuid_to_string(instance_id)
+    );
+    xmp.push_str(concat!(
+        "</rdf:Description>\n",
+        "</rdf:RDF>\n",
+        "</x:xmpmeta>\n",
+        "<?xpacket end=\"w\"?>"
+    ));
+    xmp
+}
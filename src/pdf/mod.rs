@@ -1,3 +1,8 @@
 // Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
+
+pub mod document;
+pub mod metadata;
+pub mod pdf_device;
+pub mod types;
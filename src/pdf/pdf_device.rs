@@ -0,0 +1,458 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Device which records drawing as content stream operators of a PDF page.
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::core::blend_mode::BlendMode;
+use crate::core::clip_op::ClipOp;
+use crate::core::color::Color4f;
+use crate::core::color_space::ColorSpace;
+use crate::core::device::Device;
+use crate::core::geometry::{lerp, Conic};
+use crate::core::image_info::ImageInfo;
+use crate::core::paint::Paint;
+use crate::core::paint_types::{PaintStyle, StrokeCap, StrokeJoin};
+use crate::core::path::Path;
+use crate::core::path_builder::PathBuilder;
+use crate::core::path_types::PathVerb;
+use crate::core::point::Point;
+use crate::core::rect::Rect;
+use crate::core::scalar::Scalar;
+use crate::core::surface_props::SurfaceProps;
+use crate::pdf::document::Page;
+use crate::pdf::types::{write_name, write_scalar, Dict, Object};
+
+/// Number of quads used to approximate a conic, PDF has no rational curves.
+const CONIC_QUAD_COUNT: usize = 4;
+
+/// Device drawing into content stream of a page.
+///
+/// Page shares the y-down coordinate system of Canvas, with origin at top-left corner.
+#[derive(Debug)]
+pub struct PdfDevice {
+    info: ImageInfo,
+    props: SurfaceProps,
+    page: Rc<RefCell<Page>>,
+
+    /// Alpha bits and blend mode of graphic states added to page, indexed by name.
+    graphic_states: Vec<(u32, Option<&'static str>)>,
+
+    /// Layers saved by `save_layer()`, or None for states saved by `save()`.
+    saved_layers: Vec<Option<SavedLayer>>,
+}
+
+/// Layer saved by `save_layer()`, drawn into a transparency group form.
+#[derive(Debug)]
+struct SavedLayer {
+    /// Content stream below the layer.
+    content: Vec<u8>,
+    bounds: Option<Rect>,
+    paint: Option<Paint>,
+}
+
+impl PdfDevice {
+    /// Creates device drawing into `page`.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(page: Rc<RefCell<Page>>, props: SurfaceProps) -> Self {
+        let (width, height) = {
+            let page = page.borrow();
+            (page.width().ceil() as i32, page.height().ceil() as i32)
+        };
+        Self {
+            info: ImageInfo::new_unknown(width.max(0), height.max(0)),
+            props,
+            page,
+            graphic_states: Vec::new(),
+            saved_layers: Vec::new(),
+        }
+    }
+
+    /// Returns the page this device draws into.
+    #[must_use]
+    #[inline]
+    pub const fn page(&self) -> &Rc<RefCell<Page>> {
+        &self.page
+    }
+
+    /// Returns name of graphic state with `alpha` and `blend_mode`, adding it to
+    /// resources of page if not found.
+    ///
+    /// Returns None if the default graphic state can be used.
+    fn graphic_state(&mut self, alpha: Scalar, blend_mode: BlendMode) -> Option<String> {
+        let blend_name = blend_mode_name(blend_mode);
+        if alpha >= 1.0 && blend_name.is_none() {
+            return None;
+        }
+        let key = (alpha.to_bits(), blend_name);
+        let index = if let Some(index) = self.graphic_states.iter().position(|s| *s == key) {
+            index
+        } else {
+            let mut dict = Dict::with_type("ExtGState");
+            dict.insert("ca", alpha);
+            dict.insert("CA", alpha);
+            if let Some(blend_name) = blend_name {
+                dict.insert("BM", Object::name(blend_name));
+            }
+            let name = format!("G{}", self.graphic_states.len());
+            self.page
+                .borrow_mut()
+                .resources_mut()
+                .dict_mut("ExtGState")
+                .insert(&name, dict);
+            self.graphic_states.push(key);
+            self.graphic_states.len() - 1
+        };
+        Some(format!("G{index}"))
+    }
+
+    /// Ends drawing into `layer`, and draws the layer as a form into content below it.
+    fn write_layer(&mut self, layer: SavedLayer) -> io::Result<()> {
+        let SavedLayer {
+            content,
+            bounds,
+            paint,
+        } = layer;
+        let name = {
+            let mut page = self.page.borrow_mut();
+            let width = page.width();
+            let height = page.height();
+            let form_content = std::mem::replace(page.content_mut(), content);
+            // Bounds of layer in y-up coordinates of page.
+            let bbox = bounds.map_or([0.0, 0.0, width, height], |bounds| {
+                [
+                    bounds.left(),
+                    height - bounds.bottom(),
+                    bounds.right(),
+                    height - bounds.top(),
+                ]
+            });
+            let mut dict = Dict::with_type("XObject");
+            dict.insert("Subtype", Object::name("Form"));
+            dict.insert(
+                "BBox",
+                bbox.iter().map(|&v| v.into()).collect::<Vec<Object>>(),
+            );
+            let mut group = Dict::with_type("Group");
+            group.insert("S", Object::name("Transparency"));
+            dict.insert("Group", group);
+            page.add_form(dict, form_content)
+        };
+        let graphic_state =
+            paint.and_then(|paint| self.graphic_state(paint.get_alphaf(), paint.get_blend_mode()));
+
+        let mut page = self.page.borrow_mut();
+        let content = page.content_mut();
+        content.write_all(b"q\n")?;
+        if let Some(graphic_state) = graphic_state {
+            write_name(content, &graphic_state)?;
+            content.write_all(b" gs\n")?;
+        }
+        write_name(content, &name)?;
+        content.write_all(b" Do\nQ\n")
+    }
+
+    fn write_clip_operators(&self, path: &Path, op: ClipOp) -> io::Result<()> {
+        // Inverse fill type clips out the inside of path.
+        let difference = (op == ClipOp::Difference) != path.fill_type().is_inverse();
+        if difference && path.verbs().is_empty() {
+            return Ok(());
+        }
+
+        let mut page = self.page.borrow_mut();
+        let width = page.width();
+        let height = page.height();
+        let content = page.content_mut();
+        // Flip matrix is its own inverse, so applying it again restores CTM,
+        // while the clip is kept.
+        let mut flip = b"1 0 0 -1 0 ".to_vec();
+        write_scalar(&mut flip, height)?;
+        flip.extend_from_slice(b" cm\n");
+
+        content.write_all(&flip)?;
+        let operator: &[u8] = if difference {
+            // TODO(Shaohua): Handle overlapping contours of path with winding fill type.
+            write_scalars(content, &[0.0, 0.0, width, height])?;
+            content.write_all(b" re\n")?;
+            write_path(content, path)?;
+            b"W* n\n"
+        } else if path.verbs().is_empty() {
+            // Empty path clips out everything.
+            content.write_all(b"0 0 0 0 re\n")?;
+            b"W n\n"
+        } else {
+            write_path(content, path)?;
+            if path.fill_type().is_event_odd() {
+                b"W* n\n"
+            } else {
+                b"W n\n"
+            }
+        };
+        content.write_all(operator)?;
+        content.write_all(&flip)
+    }
+
+    fn write_path_operators(&mut self, path: &Path, paint: &Paint) -> io::Result<()> {
+        let color = paint_color(paint);
+        let graphic_state = self.graphic_state(color.alpha(), paint.get_blend_mode());
+
+        let mut page = self.page.borrow_mut();
+        let height = page.height();
+        let content = page.content_mut();
+        // Flip y axis so that path is in y-down coordinates of Canvas.
+        content.write_all(b"q\n1 0 0 -1 0 ")?;
+        write_scalar(content, height)?;
+        content.write_all(b" cm\n")?;
+        if let Some(graphic_state) = graphic_state {
+            write_name(content, &graphic_state)?;
+            content.write_all(b" gs\n")?;
+        }
+
+        let style = paint.get_style();
+        if style != PaintStyle::Fill {
+            write_scalars(content, &[paint.get_stroke_width()])?;
+            content.write_all(b" w ")?;
+            let cap = match paint.get_stroke_cap() {
+                StrokeCap::Butt => b"0",
+                StrokeCap::Round => b"1",
+                StrokeCap::Square => b"2",
+            };
+            content.write_all(cap)?;
+            content.write_all(b" J ")?;
+            let join = match paint.get_stroke_join() {
+                StrokeJoin::Miter => b"0",
+                StrokeJoin::Round => b"1",
+                StrokeJoin::Bevel => b"2",
+            };
+            content.write_all(join)?;
+            content.write_all(b" j ")?;
+            // PDF requires miter limit of at least 1.
+            write_scalars(content, &[paint.get_stroke_miter().max(1.0)])?;
+            content.write_all(b" M\n")?;
+        }
+        let rgb = [color.red(), color.green(), color.blue()];
+        if style != PaintStyle::Stroke {
+            write_scalars(content, &rgb)?;
+            content.write_all(b" rg\n")?;
+        }
+        if style != PaintStyle::Fill {
+            write_scalars(content, &rgb)?;
+            content.write_all(b" RG\n")?;
+        }
+
+        write_path(content, path)?;
+        let even_odd = path.fill_type().is_event_odd();
+        let operator: &[u8] = match (style, even_odd) {
+            (PaintStyle::Fill, false) => b"f",
+            (PaintStyle::Fill, true) => b"f*",
+            (PaintStyle::Stroke, _) => b"S",
+            (PaintStyle::StrokeAndFill, false) => b"B",
+            (PaintStyle::StrokeAndFill, true) => b"B*",
+        };
+        content.write_all(operator)?;
+        content.write_all(b"\nQ\n")
+    }
+}
+
+impl Device for PdfDevice {
+    fn image_info(&self) -> &ImageInfo {
+        &self.info
+    }
+
+    fn surface_props(&self) -> &SurfaceProps {
+        &self.props
+    }
+
+    fn save(&mut self) {
+        self.page
+            .borrow_mut()
+            .content_mut()
+            .extend_from_slice(b"q\n");
+        self.saved_layers.push(None);
+    }
+
+    // TODO(Shaohua): Support color filter and image filter of `paint`.
+    fn save_layer(&mut self, bounds: Option<&Rect>, paint: Option<&Paint>) {
+        let content = std::mem::take(self.page.borrow_mut().content_mut());
+        self.saved_layers.push(Some(SavedLayer {
+            content,
+            bounds: bounds.cloned(),
+            paint: paint.cloned(),
+        }));
+    }
+
+    fn restore(&mut self) {
+        match self.saved_layers.pop() {
+            Some(None) => self
+                .page
+                .borrow_mut()
+                .content_mut()
+                .extend_from_slice(b"Q\n"),
+            Some(Some(layer)) => {
+                // Writing to memory never fails.
+                let _ = self.write_layer(layer);
+            }
+            None => (),
+        }
+    }
+
+    // PDF clips are always anti-aliased.
+    fn clip_path(&mut self, path: &Path, op: ClipOp, _anti_alias: bool) {
+        // Writing to memory never fails.
+        let _ = self.write_clip_operators(path, op);
+    }
+
+    // TODO(Shaohua): Replace previous content if `color` is opaque.
+    fn erase(&mut self, color: &Color4f) {
+        let mut paint = Paint::from_color(color);
+        paint.set_blend_mode(BlendMode::Src);
+        let (width, height) = {
+            let page = self.page.borrow();
+            (page.width(), page.height())
+        };
+        let Some(path) = PathBuilder::from_rect(&Rect::from_wh(width, height)) else {
+            return;
+        };
+        // Writing to memory never fails.
+        let _ = self.write_path_operators(&path, &paint);
+    }
+
+    // TODO(Shaohua): Support shaders, filters and inverse fill types.
+    fn draw_path(&mut self, path: &Path, paint: &Paint) {
+        if paint.nothing_to_draw() || path.verbs().is_empty() {
+            return;
+        }
+        // Writing to memory never fails.
+        let _ = self.write_path_operators(path, paint);
+    }
+}
+
+/// Returns color of `paint`, filtered by its color filter.
+fn paint_color(paint: &Paint) -> Color4f {
+    let color = paint.get_color4f();
+    let Some(color_filter) = paint.get_color_filter() else {
+        return color.clone();
+    };
+    let mut dst_cs = ColorSpace::default();
+    color_filter.filter_color4f(color, &ColorSpace::default(), &mut dst_cs)
+}
+
+/// Returns name of PDF blend mode matching `blend_mode`.
+///
+/// Returns None for `BlendMode::SrcOver`, which is the default in PDF.
+// TODO(Shaohua): Support Porter-Duff modes other than SrcOver.
+const fn blend_mode_name(blend_mode: BlendMode) -> Option<&'static str> {
+    match blend_mode {
+        BlendMode::Multiply | BlendMode::Modulate => Some("Multiply"),
+        BlendMode::Screen => Some("Screen"),
+        BlendMode::Overlay => Some("Overlay"),
+        BlendMode::Darken => Some("Darken"),
+        BlendMode::Lighten => Some("Lighten"),
+        BlendMode::ColorDodge => Some("ColorDodge"),
+        BlendMode::ColorBurn => Some("ColorBurn"),
+        BlendMode::HardLight => Some("HardLight"),
+        BlendMode::SoftLight => Some("SoftLight"),
+        BlendMode::Difference => Some("Difference"),
+        BlendMode::Exclusion => Some("Exclusion"),
+        BlendMode::Hue => Some("Hue"),
+        BlendMode::Saturation => Some("Saturation"),
+        BlendMode::Color => Some("Color"),
+        BlendMode::Luminosity => Some("Luminosity"),
+        _ => None,
+    }
+}
+
+/// Writes scalars separated by spaces.
+fn write_scalars<W: Write + ?Sized>(stream: &mut W, values: &[Scalar]) -> io::Result<()> {
+    for (index, value) in values.iter().enumerate() {
+        if index > 0 {
+            stream.write_all(b" ")?;
+        }
+        write_scalar(stream, *value)?;
+    }
+    Ok(())
+}
+
+fn write_point_operator<W: Write + ?Sized>(
+    stream: &mut W,
+    points: &[Point],
+    operator: &[u8],
+) -> io::Result<()> {
+    for point in points {
+        write_scalars(stream, &[point.x(), point.y()])?;
+        stream.write_all(b" ")?;
+    }
+    stream.write_all(operator)?;
+    stream.write_all(b"\n")
+}
+
+/// Writes quad as a cubic, as PDF only has cubic curves.
+fn write_quad<W: Write + ?Sized>(stream: &mut W, pts: &[Point; 3]) -> io::Result<()> {
+    let ctrl1 = lerp(pts[0], pts[1], 2.0 / 3.0);
+    let ctrl2 = lerp(pts[2], pts[1], 2.0 / 3.0);
+    write_point_operator(stream, &[ctrl1, ctrl2, pts[2]], b"c")
+}
+
+/// Writes path construction operators of `path`.
+///
+/// # Errors
+/// Returns error if failed to write to stream.
+pub fn write_path<W: Write + ?Sized>(stream: &mut W, path: &Path) -> io::Result<()> {
+    let points = path.points();
+    let weights = path.conic_weights();
+    let mut point_index = 0;
+    let mut weight_index = 0;
+    let mut last = Point::new();
+
+    for verb in path.verbs() {
+        match verb {
+            PathVerb::Move => {
+                last = points[point_index];
+                write_point_operator(stream, &[last], b"m")?;
+                point_index += 1;
+            }
+            PathVerb::Line => {
+                last = points[point_index];
+                write_point_operator(stream, &[last], b"l")?;
+                point_index += 1;
+            }
+            PathVerb::Quad => {
+                let pts = [last, points[point_index], points[point_index + 1]];
+                write_quad(stream, &pts)?;
+                last = pts[2];
+                point_index += 2;
+            }
+            PathVerb::Conic => {
+                let weight = weights[weight_index];
+                let conic = Conic::new(last, points[point_index], points[point_index + 1], weight);
+                // Pieces of a conic are close to quads with same control points.
+                #[allow(clippy::cast_precision_loss)]
+                for index in 0..CONIC_QUAD_COUNT {
+                    let t0 = index as Scalar / CONIC_QUAD_COUNT as Scalar;
+                    let t1 = (index + 1) as Scalar / CONIC_QUAD_COUNT as Scalar;
+                    write_quad(stream, &conic.chop_range(t0, t1).pts)?;
+                }
+                last = conic.pts[2];
+                point_index += 2;
+                weight_index += 1;
+            }
+            PathVerb::Cubic => {
+                let pts = [
+                    points[point_index],
+                    points[point_index + 1],
+                    points[point_index + 2],
+                ];
+                write_point_operator(stream, &pts, b"c")?;
+                last = pts[2];
+                point_index += 3;
+            }
+            PathVerb::Close => stream.write_all(b"h\n")?,
+        }
+    }
+    Ok(())
+}
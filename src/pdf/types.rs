@@ -0,0 +1,356 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! PDF object model, see section 7.3 of ISO 32000.
+
+use std::io::{self, Write};
+
+use crate::core::scalar::Scalar;
+
+/// Reference to an indirect object, by its object number.
+///
+/// Generation number is always zero.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct IndirectReference(pub(crate) u32);
+
+impl IndirectReference {
+    #[must_use]
+    #[inline]
+    pub const fn value(self) -> u32 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Object {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Real(Scalar),
+
+    /// Name object, written without the leading slash.
+    Name(String),
+
+    /// Byte string, written as a literal string.
+    ByteString(Vec<u8>),
+
+    /// Text string, written as a literal string if ASCII, or as UTF-16BE with byte
+    /// order mark otherwise.
+    TextString(String),
+
+    Array(Vec<Self>),
+    Dict(Dict),
+    Ref(IndirectReference),
+}
+
+impl Object {
+    #[must_use]
+    #[inline]
+    pub fn name(name: &str) -> Self {
+        Self::Name(name.to_owned())
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn text(text: &str) -> Self {
+        Self::TextString(text.to_owned())
+    }
+
+    /// Writes serialized object to stream.
+    ///
+    /// # Errors
+    /// Returns error if failed to write to stream.
+    pub fn write_to<W: Write + ?Sized>(&self, stream: &mut W) -> io::Result<()> {
+        match self {
+            Self::Null => stream.write_all(b"null"),
+            Self::Bool(value) => stream.write_all(if *value { b"true" } else { b"false" }),
+            Self::Int(value) => write!(stream, "{value}"),
+            Self::Real(value) => write_scalar(stream, *value),
+            Self::Name(name) => write_name(stream, name),
+            Self::ByteString(bytes) => write_byte_string(stream, bytes),
+            Self::TextString(text) => write_text_string(stream, text),
+            Self::Array(array) => {
+                stream.write_all(b"[")?;
+                for (index, object) in array.iter().enumerate() {
+                    if index > 0 {
+                        stream.write_all(b" ")?;
+                    }
+                    object.write_to(stream)?;
+                }
+                stream.write_all(b"]")
+            }
+            Self::Dict(dict) => dict.write_to(stream),
+            Self::Ref(reference) => write!(stream, "{} 0 R", reference.0),
+        }
+    }
+}
+
+impl From<bool> for Object {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for Object {
+    fn from(value: i32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<usize> for Object {
+    #[allow(clippy::cast_possible_wrap)]
+    fn from(value: usize) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+impl From<Scalar> for Object {
+    fn from(value: Scalar) -> Self {
+        Self::Real(value)
+    }
+}
+
+impl From<IndirectReference> for Object {
+    fn from(reference: IndirectReference) -> Self {
+        Self::Ref(reference)
+    }
+}
+
+impl From<Dict> for Object {
+    fn from(dict: Dict) -> Self {
+        Self::Dict(dict)
+    }
+}
+
+impl From<Vec<Self>> for Object {
+    fn from(array: Vec<Self>) -> Self {
+        Self::Array(array)
+    }
+}
+
+/// Dictionary object, keys are kept in insertion order.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Dict {
+    entries: Vec<(String, Object)>,
+}
+
+impl Dict {
+    #[must_use]
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Creates a dictionary with `/Type` entry.
+    #[must_use]
+    pub fn with_type(type_name: &str) -> Self {
+        let mut dict = Self::new();
+        dict.insert("Type", Object::name(type_name));
+        dict
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Inserts or replaces an entry.
+    pub fn insert<T: Into<Object>>(&mut self, key: &str, value: T) {
+        let value = value.into();
+        if let Some(entry) = self.entries.iter_mut().find(|(k, _)| k == key) {
+            entry.1 = value;
+        } else {
+            self.entries.push((key.to_owned(), value));
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Object> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Returns the sub-dictionary of `key`, inserting an empty one if not found.
+    pub fn dict_mut(&mut self, key: &str) -> &mut Self {
+        let index = if let Some(index) = self.entries.iter().position(|(k, _)| k == key) {
+            index
+        } else {
+            self.entries
+                .push((key.to_owned(), Object::Dict(Self::new())));
+            self.entries.len() - 1
+        };
+        if !matches!(self.entries[index].1, Object::Dict(_)) {
+            self.entries[index].1 = Object::Dict(Self::new());
+        }
+        match &mut self.entries[index].1 {
+            Object::Dict(dict) => dict,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Writes serialized dictionary to stream.
+    ///
+    /// # Errors
+    /// Returns error if failed to write to stream.
+    pub fn write_to<W: Write + ?Sized>(&self, stream: &mut W) -> io::Result<()> {
+        stream.write_all(b"<<")?;
+        for (key, value) in &self.entries {
+            write_name(stream, key)?;
+            stream.write_all(b" ")?;
+            value.write_to(stream)?;
+            stream.write_all(b"\n")?;
+        }
+        stream.write_all(b">>")
+    }
+}
+
+/// Writes a number without exponent, as required by PDF.
+///
+/// # Errors
+/// Returns error if failed to write to stream.
+pub fn write_scalar<W: Write + ?Sized>(stream: &mut W, value: Scalar) -> io::Result<()> {
+    if !value.is_finite() {
+        return stream.write_all(b"0");
+    }
+    let text = format!("{value:.4}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "" | "-0" => stream.write_all(b"0"),
+        _ => stream.write_all(text.as_bytes()),
+    }
+}
+
+/// Writes a name object, escaping delimiters and irregular characters as `#xx`.
+///
+/// # Errors
+/// Returns error if failed to write to stream.
+pub fn write_name<W: Write + ?Sized>(stream: &mut W, name: &str) -> io::Result<()> {
+    stream.write_all(b"/")?;
+    for &byte in name.as_bytes() {
+        let is_delimiter = matches!(
+            byte,
+            b'#' | b'/' | b'%' | b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}'
+        );
+        if (b'!'..=b'~').contains(&byte) && !is_delimiter {
+            stream.write_all(&[byte])?;
+        } else {
+            write!(stream, "#{byte:02X}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a literal string.
+///
+/// # Errors
+/// Returns error if failed to write to stream.
+pub fn write_byte_string<W: Write + ?Sized>(stream: &mut W, bytes: &[u8]) -> io::Result<()> {
+    stream.write_all(b"(")?;
+    for &byte in bytes {
+        match byte {
+            b'(' | b')' | b'\\' => stream.write_all(&[b'\\', byte])?,
+            b'\n' => stream.write_all(b"\\n")?,
+            b'\r' => stream.write_all(b"\\r")?,
+            0x20..=0x7E => stream.write_all(&[byte])?,
+            _ => write!(stream, "\\{byte:03o}")?,
+        }
+    }
+    stream.write_all(b")")
+}
+
+/// Writes a text string, using UTF-16BE with byte order mark for non-ASCII text.
+///
+/// # Errors
+/// Returns error if failed to write to stream.
+pub fn write_text_string<W: Write + ?Sized>(stream: &mut W, text: &str) -> io::Result<()> {
+    if text.is_ascii() {
+        return write_byte_string(stream, text.as_bytes());
+    }
+    stream.write_all(b"<FEFF")?;
+    for unit in text.encode_utf16() {
+        write!(stream, "{unit:04X}")?;
+    }
+    stream.write_all(b">")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_string(object: &Object) -> String {
+        let mut buf = Vec::new();
+        object.write_to(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_scalar() {
+        assert_eq!(to_string(&1.5.into()), "1.5");
+        assert_eq!(to_string(&2.0.into()), "2");
+        assert_eq!(to_string(&(-0.00001).into()), "0");
+        assert_eq!(to_string(&1e10.into()), "10000000000");
+        assert_eq!(to_string(&0.12345.into()), "0.1235");
+        assert_eq!(to_string(&Scalar::NAN.into()), "0");
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(to_string(&Object::name("Type")), "/Type");
+        assert_eq!(to_string(&Object::name("A B#(x)")), "/A#20B#23#28x#29");
+        assert_eq!(to_string(&Object::name("é")), "/#C3#A9");
+    }
+
+    #[test]
+    fn test_strings() {
+        let bytes = Object::ByteString(b"a(b)\\\n\x01".to_vec());
+        assert_eq!(to_string(&bytes), "(a\\(b\\)\\\\\\n\\001)");
+        assert_eq!(to_string(&Object::text("Hello")), "(Hello)");
+        assert_eq!(to_string(&Object::text("Hé")), "<FEFF004800E9>");
+        assert_eq!(to_string(&Object::text("😀")), "<FEFFD83DDE00>");
+    }
+
+    #[test]
+    fn test_array_and_ref() {
+        let array: Object = vec![
+            Object::Null,
+            true.into(),
+            3.into(),
+            IndirectReference(7).into(),
+        ]
+        .into();
+        assert_eq!(to_string(&array), "[null true 3 7 0 R]");
+    }
+
+    #[test]
+    fn test_dict() {
+        let mut dict = Dict::with_type("Page");
+        dict.insert("Count", 1);
+        dict.insert("Count", 2);
+        assert_eq!(dict.len(), 2);
+        assert_eq!(dict.get("Count"), Some(&Object::Int(2)));
+        dict.dict_mut("Resources")
+            .dict_mut("XObject")
+            .insert("Fm0", IndirectReference(3));
+        dict.dict_mut("Resources")
+            .dict_mut("XObject")
+            .insert("Fm1", IndirectReference(4));
+        assert_eq!(
+            to_string(&dict.into()),
+            "<</Type /Page\n/Count 2\n/Resources <</XObject <</Fm0 3 0 R\n/Fm1 4 0 R\n>>\n>>\n>>"
+        );
+
+        // Replaces entries which are not dictionaries.
+        let mut dict = Dict::new();
+        dict.insert("Font", 1);
+        assert!(dict.dict_mut("Font").is_empty());
+    }
+}
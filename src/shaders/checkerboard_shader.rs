@@ -0,0 +1,66 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::{colors, Color4f};
+use crate::core::point::Point;
+use crate::core::scalar::Scalar;
+use crate::core::shader::Shader;
+
+/// Fills with alternating squares of two colors, commonly drawn behind images
+/// to preview transparency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckerboardShader {
+    color1: Color4f,
+    color2: Color4f,
+    size: Scalar,
+}
+
+impl Default for CheckerboardShader {
+    /// Creates the usual light gray transparency grid, with 8 pixel squares.
+    fn default() -> Self {
+        Self::new(colors::WHITE, Color4f::from_rgba(0.8, 0.8, 0.8, 1.0), 8.0)
+    }
+}
+
+impl CheckerboardShader {
+    /// Creates a checkerboard shader.
+    ///
+    /// # Parameters
+    /// - `color1` - color of the square at origin
+    /// - `color2` - color of the squares next to it
+    /// - `size` - width and height of each square; values not larger than zero
+    ///   are treated as 1
+    #[must_use]
+    pub fn new(color1: Color4f, color2: Color4f, size: Scalar) -> Self {
+        let size = if size > 0.0 { size } else { 1.0 };
+        Self {
+            color1,
+            color2,
+            size,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn size(&self) -> Scalar {
+        self.size
+    }
+}
+
+impl Shader for CheckerboardShader {
+    fn is_opaque(&self) -> bool {
+        self.color1.is_opaque() && self.color2.is_opaque()
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn color_at(&self, point: &Point) -> Color4f {
+        let column = (point.x() / self.size).floor() as i64;
+        let row = (point.y() / self.size).floor() as i64;
+        if (column + row) % 2 == 0 {
+            self.color1.clone()
+        } else {
+            self.color2.clone()
+        }
+    }
+}
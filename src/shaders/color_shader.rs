@@ -0,0 +1,41 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::Color4f;
+use crate::core::point::Point;
+use crate::core::shader::Shader;
+
+/// A Shader that represents a single color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorShader {
+    color: Color4f,
+}
+
+impl ColorShader {
+    #[must_use]
+    #[inline]
+    pub const fn new(color: Color4f) -> Self {
+        Self { color }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn color(&self) -> &Color4f {
+        &self.color
+    }
+}
+
+impl Shader for ColorShader {
+    fn is_opaque(&self) -> bool {
+        self.color.is_opaque()
+    }
+
+    fn as_a_color(&self) -> Option<Color4f> {
+        Some(self.color.clone())
+    }
+
+    fn color_at(&self, _point: &Point) -> Color4f {
+        self.color.clone()
+    }
+}
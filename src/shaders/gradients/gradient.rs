@@ -0,0 +1,389 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::{colors, Color4f};
+use crate::core::color_priv::{linear_to_srgb, srgb_to_linear};
+use crate::core::scalar::Scalar;
+use crate::core::tile_mode::TileMode;
+
+/// Color space in which colors between two stops are interpolated.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum InterpolationColorSpace {
+    /// Interpolates encoded sRGB values, as stored in destination pixels.
+    #[default]
+    Destination,
+
+    /// Interpolates linear sRGB values, which mixes colors as light does.
+    SrgbLinear,
+
+    /// Interpolates in Oklab, where lightness changes evenly along the gradient.
+    OkLab,
+}
+
+/// Describes how colors between two stops are interpolated.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Interpolation {
+    /// If true, colors are premultiplied before interpolation, so that
+    /// transparent stops do not tint their neighbours.
+    pub in_premul: bool,
+
+    pub color_space: InterpolationColorSpace,
+}
+
+/// Color stops, tile mode and interpolation shared by all gradient shaders.
+///
+/// Gradient shaders map a point to a position t, and the stops map t to a color.
+/// Positions of stops are in [0, 1].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    colors: Vec<Color4f>,
+    positions: Vec<Scalar>,
+    tile_mode: TileMode,
+    interpolation: Interpolation,
+
+    /// Colors converted to the interpolation color space, premultiplied if required.
+    stops: Vec<[f32; 4]>,
+}
+
+impl Gradient {
+    /// Creates stops of a gradient.
+    ///
+    /// # Parameters
+    /// - `colors` - colors of stops, at least one
+    /// - `positions` - position in [0, 1] of each color, or None to distribute colors
+    ///   evenly. Each position is clamped between the previous one and 1, two stops
+    ///   at the same position make a hard transition.
+    /// - `tile_mode` - how positions outside of [0, 1] are mapped
+    /// - `interpolation` - how colors between stops are interpolated
+    ///
+    /// Returns None if `colors` is empty, or if length of `positions` differs.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(
+        colors: &[Color4f],
+        positions: Option<&[Scalar]>,
+        tile_mode: TileMode,
+        interpolation: Interpolation,
+    ) -> Option<Self> {
+        if colors.is_empty() {
+            return None;
+        }
+        let mut colors = colors.to_vec();
+        let mut positions: Vec<Scalar> = if let Some(positions) = positions {
+            if positions.len() != colors.len() {
+                return None;
+            }
+            let mut previous = 0.0;
+            positions
+                .iter()
+                .map(|&position| {
+                    if !position.is_nan() {
+                        previous = position.clamp(previous, 1.0);
+                    }
+                    previous
+                })
+                .collect()
+        } else {
+            let last = (colors.len() - 1).max(1) as Scalar;
+            (0..colors.len())
+                .map(|index| index as Scalar / last)
+                .collect()
+        };
+
+        // Extend the first and the last colors to both ends.
+        if positions[0] > 0.0 {
+            positions.insert(0, 0.0);
+            colors.insert(0, colors[0].clone());
+        }
+        if positions[positions.len() - 1] < 1.0 {
+            positions.push(1.0);
+            colors.push(colors[colors.len() - 1].clone());
+        }
+
+        let stops = colors
+            .iter()
+            .map(|color| to_interpolation_space(color, interpolation))
+            .collect();
+        Some(Self {
+            colors,
+            positions,
+            tile_mode,
+            interpolation,
+            stops,
+        })
+    }
+
+    /// Returns colors of stops, including stops added at 0 and 1.
+    #[must_use]
+    #[inline]
+    pub fn colors(&self) -> &[Color4f] {
+        &self.colors
+    }
+
+    /// Returns positions of stops, including stops added at 0 and 1.
+    #[must_use]
+    #[inline]
+    pub fn positions(&self) -> &[Scalar] {
+        &self.positions
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn tile_mode(&self) -> TileMode {
+        self.tile_mode
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// Returns true if all colors are opaque, and no area is left undrawn by tile mode.
+    #[must_use]
+    pub fn is_opaque(&self) -> bool {
+        self.tile_mode != TileMode::Decal && self.colors.iter().all(Color4f::is_opaque)
+    }
+
+    /// Returns color at position `t`, before tile mode is applied.
+    ///
+    /// Returns transparent black if `t` is not finite, or if it is outside of [0, 1]
+    /// with `TileMode::Decal`.
+    #[must_use]
+    pub fn color_at(&self, t: Scalar) -> Color4f {
+        if !t.is_finite() {
+            return colors::TRANSPARENT;
+        }
+        let t = match self.tile_mode {
+            TileMode::Clamp => t.clamp(0.0, 1.0),
+            TileMode::Repeat => t - t.floor(),
+            TileMode::Mirror => {
+                let t = t.rem_euclid(2.0);
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+            TileMode::Decal => {
+                if !(0.0..=1.0).contains(&t) {
+                    return colors::TRANSPARENT;
+                }
+                t
+            }
+        };
+
+        // Index of the first stop after t, the first stop is always at 0.
+        let index = self.positions.partition_point(|&position| position <= t);
+        if index >= self.stops.len() {
+            return from_interpolation_space(&self.stops[self.stops.len() - 1], self.interpolation);
+        }
+        let (start, end) = (self.positions[index - 1], self.positions[index]);
+        let fraction = (t - start) / (end - start);
+        let (from, to) = (&self.stops[index - 1], &self.stops[index]);
+        let mut mixed = [0.0; 4];
+        for (channel, value) in mixed.iter_mut().enumerate() {
+            *value = (to[channel] - from[channel]).mul_add(fraction, from[channel]);
+        }
+        from_interpolation_space(&mixed, self.interpolation)
+    }
+}
+
+/// Linear sRGB to LMS cone response, as defined by Oklab.
+const LINEAR_SRGB_TO_LMS: [[f64; 3]; 3] = [
+    [0.412_221_470_8, 0.536_332_536_3, 0.051_445_992_9],
+    [0.211_903_498_2, 0.680_699_545_1, 0.107_396_956_6],
+    [0.088_302_461_9, 0.281_718_837_6, 0.629_978_700_5],
+];
+
+/// Non-linear LMS to Oklab.
+const LMS_TO_OKLAB: [[f64; 3]; 3] = [
+    [0.210_454_255_3, 0.793_617_785_0, -0.004_072_046_8],
+    [1.977_998_495_1, -2.428_592_205_0, 0.450_593_709_9],
+    [0.025_904_037_1, 0.782_771_766_2, -0.808_675_766_0],
+];
+
+/// Oklab to non-linear LMS.
+const OKLAB_TO_LMS: [[f64; 3]; 3] = [
+    [1.0, 0.396_337_777_4, 0.215_803_757_3],
+    [1.0, -0.105_561_345_8, -0.063_854_172_8],
+    [1.0, -0.089_484_177_5, -1.291_485_548_0],
+];
+
+/// LMS cone response to linear sRGB.
+const LMS_TO_LINEAR_SRGB: [[f64; 3]; 3] = [
+    [4.076_741_662_1, -3.307_711_591_3, 0.230_969_929_2],
+    [-1.268_438_004_6, 2.609_757_401_1, -0.341_319_396_5],
+    [-0.004_196_086_3, -0.703_418_614_7, 1.707_614_701_0],
+];
+
+fn transform(matrix: &[[f64; 3]; 3], value: [f64; 3]) -> [f64; 3] {
+    matrix.map(|row| row[0].mul_add(value[0], row[1].mul_add(value[1], row[2] * value[2])))
+}
+
+/// Converts unpremultiplied sRGB `color` to the color space of `interpolation`.
+#[allow(clippy::cast_possible_truncation)]
+fn to_interpolation_space(color: &Color4f, interpolation: Interpolation) -> [f32; 4] {
+    let rgb = [color.red(), color.green(), color.blue()].map(f64::from);
+    let values = match interpolation.color_space {
+        InterpolationColorSpace::Destination => rgb,
+        InterpolationColorSpace::SrgbLinear => rgb.map(srgb_to_linear),
+        InterpolationColorSpace::OkLab => {
+            let lms = transform(&LINEAR_SRGB_TO_LMS, rgb.map(srgb_to_linear));
+            transform(&LMS_TO_OKLAB, lms.map(f64::cbrt))
+        }
+    };
+    let alpha = color.alpha().clamp(0.0, 1.0);
+    let scale = if interpolation.in_premul { alpha } else { 1.0 };
+    [
+        values[0] as f32 * scale,
+        values[1] as f32 * scale,
+        values[2] as f32 * scale,
+        alpha,
+    ]
+}
+
+/// Converts `values` in the color space of `interpolation` back to unpremultiplied sRGB.
+#[allow(clippy::cast_possible_truncation)]
+fn from_interpolation_space(values: &[f32; 4], interpolation: Interpolation) -> Color4f {
+    let alpha = values[3];
+    if interpolation.in_premul && alpha <= 0.0 {
+        return colors::TRANSPARENT;
+    }
+    let scale = if interpolation.in_premul { alpha } else { 1.0 };
+    let values = [values[0] / scale, values[1] / scale, values[2] / scale].map(f64::from);
+    let rgb = match interpolation.color_space {
+        InterpolationColorSpace::Destination => values.map(|value| value.clamp(0.0, 1.0)),
+        InterpolationColorSpace::SrgbLinear => values.map(linear_to_srgb),
+        InterpolationColorSpace::OkLab => {
+            let lms = transform(&OKLAB_TO_LMS, values).map(|value| value * value * value);
+            transform(&LMS_TO_LINEAR_SRGB, lms).map(linear_to_srgb)
+        }
+    };
+    Color4f::from_rgba(rgb[0] as f32, rgb[1] as f32, rgb[2] as f32, alpha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color4f = Color4f::from_rgba(0.0, 0.0, 0.0, 1.0);
+    const WHITE: Color4f = Color4f::from_rgba(1.0, 1.0, 1.0, 1.0);
+
+    fn is_near(value: Scalar, expected: Scalar) -> bool {
+        (value - expected).abs() < 1e-3
+    }
+
+    fn gray(tile_mode: TileMode) -> Gradient {
+        Gradient::new(&[BLACK, WHITE], None, tile_mode, Interpolation::default()).unwrap()
+    }
+
+    #[test]
+    fn test_stops() {
+        assert!(Gradient::new(&[], None, TileMode::Clamp, Interpolation::default()).is_none());
+        let positions = [0.5];
+        assert!(Gradient::new(
+            &[BLACK, WHITE],
+            Some(&positions),
+            TileMode::Clamp,
+            Interpolation::default()
+        )
+        .is_none());
+
+        let colors = [BLACK, WHITE, BLACK];
+        let gradient =
+            Gradient::new(&colors, None, TileMode::Clamp, Interpolation::default()).unwrap();
+        assert_eq!(gradient.positions(), &[0.0, 0.5, 1.0]);
+
+        // Stops are extended to both ends, and positions never decrease.
+        let positions = [0.25, 0.1, 0.75];
+        let gradient = Gradient::new(
+            &colors,
+            Some(&positions),
+            TileMode::Clamp,
+            Interpolation::default(),
+        )
+        .unwrap();
+        assert_eq!(gradient.positions(), &[0.0, 0.25, 0.25, 0.75, 1.0]);
+        assert_eq!(gradient.colors().len(), 5);
+        assert!(is_near(gradient.color_at(0.1).red(), 0.0));
+    }
+
+    #[test]
+    fn test_hard_stop() {
+        let colors = [BLACK, BLACK, WHITE, WHITE];
+        let positions = [0.0, 0.5, 0.5, 1.0];
+        let gradient = Gradient::new(
+            &colors,
+            Some(&positions),
+            TileMode::Clamp,
+            Interpolation::default(),
+        )
+        .unwrap();
+        assert!(is_near(gradient.color_at(0.49).red(), 0.0));
+        assert!(is_near(gradient.color_at(0.51).red(), 1.0));
+    }
+
+    #[test]
+    fn test_tile_modes() {
+        assert!(is_near(gray(TileMode::Clamp).color_at(-0.5).red(), 0.0));
+        assert!(is_near(gray(TileMode::Clamp).color_at(1.5).red(), 1.0));
+        assert!(is_near(gray(TileMode::Repeat).color_at(1.25).red(), 0.25));
+        assert!(is_near(gray(TileMode::Repeat).color_at(-0.25).red(), 0.75));
+        assert!(is_near(gray(TileMode::Mirror).color_at(1.25).red(), 0.75));
+        assert!(is_near(gray(TileMode::Mirror).color_at(-0.25).red(), 0.25));
+        assert!(is_near(gray(TileMode::Decal).color_at(0.5).alpha(), 1.0));
+        assert_eq!(gray(TileMode::Decal).color_at(1.5), colors::TRANSPARENT);
+        assert_eq!(
+            gray(TileMode::Clamp).color_at(Scalar::NAN),
+            colors::TRANSPARENT
+        );
+
+        assert!(gray(TileMode::Clamp).is_opaque());
+        assert!(!gray(TileMode::Decal).is_opaque());
+    }
+
+    #[test]
+    fn test_interpolation_color_space() {
+        let middle = |color_space| {
+            let interpolation = Interpolation {
+                in_premul: false,
+                color_space,
+            };
+            let gradient = Gradient::new(&[BLACK, WHITE], None, TileMode::Clamp, interpolation);
+            gradient.unwrap().color_at(0.5).red()
+        };
+        assert!(is_near(middle(InterpolationColorSpace::Destination), 0.5));
+        // Linear 0.5 is 0.735 in sRGB.
+        assert!((middle(InterpolationColorSpace::SrgbLinear) - 0.735).abs() < 0.01);
+        // Oklab lightness 0.5 is linear 0.125, which is 0.389 in sRGB.
+        assert!((middle(InterpolationColorSpace::OkLab) - 0.389).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_interpolation_in_premul() {
+        let red = Color4f::from_rgba(1.0, 0.0, 0.0, 1.0);
+        let transparent_blue = Color4f::from_rgba(0.0, 0.0, 1.0, 0.0);
+        let middle = |in_premul| {
+            let interpolation = Interpolation {
+                in_premul,
+                color_space: InterpolationColorSpace::Destination,
+            };
+            let gradient = Gradient::new(
+                &[red.clone(), transparent_blue.clone()],
+                None,
+                TileMode::Clamp,
+                interpolation,
+            );
+            gradient.unwrap().color_at(0.5)
+        };
+        let color = middle(false);
+        assert!(is_near(color.red(), 0.5) && is_near(color.blue(), 0.5));
+        assert!(is_near(color.alpha(), 0.5));
+        // Transparent stop does not tint the color.
+        let color = middle(true);
+        assert!(is_near(color.red(), 1.0) && is_near(color.blue(), 0.0));
+        assert!(is_near(color.alpha(), 0.5));
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::Color4f;
+use crate::core::point::Point;
+use crate::core::shader::Shader;
+use crate::shaders::gradients::gradient::Gradient;
+
+/// Interpolates colors along the line from `start` to `end`.
+///
+/// Lines perpendicular to it have the same color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearGradient {
+    start: Point,
+    end: Point,
+    gradient: Gradient,
+}
+
+impl LinearGradient {
+    /// Creates a linear gradient.
+    ///
+    /// # Parameters
+    /// - `start` - point where position of stops is 0
+    /// - `end` - point where position of stops is 1
+    /// - `gradient` - color stops
+    ///
+    /// Returns None if `start` and `end` are the same point, or not finite.
+    #[must_use]
+    pub fn new(start: Point, end: Point, gradient: Gradient) -> Option<Self> {
+        if !(start.is_finite() && end.is_finite()) || (end - start).length_sqd() <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            start,
+            end,
+            gradient,
+        })
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn start(&self) -> &Point {
+        &self.start
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn end(&self) -> &Point {
+        &self.end
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn gradient(&self) -> &Gradient {
+        &self.gradient
+    }
+}
+
+impl Shader for LinearGradient {
+    fn is_opaque(&self) -> bool {
+        self.gradient.is_opaque()
+    }
+
+    fn color_at(&self, point: &Point) -> Color4f {
+        let direction = self.end - self.start;
+        let t = (*point - self.start).dot(&direction) / direction.length_sqd();
+        self.gradient.color_at(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::tile_mode::TileMode;
+    use crate::shaders::gradients::gradient::Interpolation;
+
+    fn gray() -> Gradient {
+        let colors = [
+            Color4f::from_rgba(0.0, 0.0, 0.0, 1.0),
+            Color4f::from_rgba(1.0, 1.0, 1.0, 1.0),
+        ];
+        Gradient::new(&colors, None, TileMode::Clamp, Interpolation::default()).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let point = Point::from_xy(1.0, 1.0);
+        assert!(LinearGradient::new(point, point, gray()).is_none());
+        let infinite = Point::from_xy(f32::INFINITY, 0.0);
+        assert!(LinearGradient::new(point, infinite, gray()).is_none());
+    }
+
+    #[test]
+    fn test_color_at() {
+        let shader = LinearGradient::new(
+            Point::from_xy(10.0, 0.0),
+            Point::from_xy(10.0, 20.0),
+            gray(),
+        )
+        .unwrap();
+        let red_at = |x, y| shader.color_at(&Point::from_xy(x, y)).red();
+        assert!((red_at(0.0, 5.0) - 0.25).abs() < 1e-4);
+        // Lines perpendicular to the gradient have the same color.
+        assert!((red_at(100.0, 5.0) - 0.25).abs() < 1e-4);
+        assert!((red_at(10.0, 15.0) - 0.75).abs() < 1e-4);
+        assert!((red_at(10.0, -5.0) - 0.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,9 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+pub mod gradient;
+pub mod linear_gradient;
+pub mod radial_gradient;
+pub mod sweep_gradient;
+pub mod two_point_conical_gradient;
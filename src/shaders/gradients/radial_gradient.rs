@@ -0,0 +1,105 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::Color4f;
+use crate::core::point::Point;
+use crate::core::scalar::Scalar;
+use crate::core::shader::Shader;
+use crate::shaders::gradients::gradient::Gradient;
+
+/// Interpolates colors from `center` to the circle with `radius`.
+///
+/// Circles around the center have the same color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadialGradient {
+    center: Point,
+    radius: Scalar,
+    gradient: Gradient,
+}
+
+impl RadialGradient {
+    /// Creates a radial gradient.
+    ///
+    /// # Parameters
+    /// - `center` - center of circles, where position of stops is 0
+    /// - `radius` - radius of the circle where position of stops is 1
+    /// - `gradient` - color stops
+    ///
+    /// Returns None if `radius` is not larger than zero, or values are not finite.
+    #[must_use]
+    pub fn new(center: Point, radius: Scalar, gradient: Gradient) -> Option<Self> {
+        if !(center.is_finite() && radius.is_finite() && radius > 0.0) {
+            return None;
+        }
+        Some(Self {
+            center,
+            radius,
+            gradient,
+        })
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn center(&self) -> &Point {
+        &self.center
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn radius(&self) -> Scalar {
+        self.radius
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn gradient(&self) -> &Gradient {
+        &self.gradient
+    }
+}
+
+impl Shader for RadialGradient {
+    fn is_opaque(&self) -> bool {
+        self.gradient.is_opaque()
+    }
+
+    fn color_at(&self, point: &Point) -> Color4f {
+        let t = (*point - self.center).length() / self.radius;
+        self.gradient.color_at(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::tile_mode::TileMode;
+    use crate::shaders::gradients::gradient::Interpolation;
+
+    fn gray(tile_mode: TileMode) -> Gradient {
+        let colors = [
+            Color4f::from_rgba(0.0, 0.0, 0.0, 1.0),
+            Color4f::from_rgba(1.0, 1.0, 1.0, 1.0),
+        ];
+        Gradient::new(&colors, None, tile_mode, Interpolation::default()).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let center = Point::from_xy(5.0, 5.0);
+        assert!(RadialGradient::new(center, 0.0, gray(TileMode::Clamp)).is_none());
+        assert!(RadialGradient::new(center, -1.0, gray(TileMode::Clamp)).is_none());
+    }
+
+    #[test]
+    fn test_color_at() {
+        let shader = RadialGradient::new(Point::from_xy(5.0, 5.0), 10.0, gray(TileMode::Repeat));
+        let shader = shader.unwrap();
+        let red_at = |x, y| shader.color_at(&Point::from_xy(x, y)).red();
+        assert!(red_at(5.0, 5.0).abs() < 1e-4);
+        assert!((red_at(11.0, 13.0) - 0.0).abs() < 1e-4);
+        assert!((red_at(5.0, 0.0) - 0.5).abs() < 1e-4);
+        assert!((red_at(0.0, 5.0) - 0.5).abs() < 1e-4);
+        // Distance of 12.5 repeats to 0.25.
+        assert!((red_at(5.0, 17.5) - 0.25).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::Color4f;
+use crate::core::point::Point;
+use crate::core::scalar::Scalar;
+use crate::core::shader::Shader;
+use crate::shaders::gradients::gradient::Gradient;
+
+/// Interpolates colors by angle around `center`.
+///
+/// Angles are in degrees, starting from the positive x axis and increasing
+/// clockwise, as y axis points down.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepGradient {
+    center: Point,
+    start_angle: Scalar,
+    end_angle: Scalar,
+    gradient: Gradient,
+}
+
+impl SweepGradient {
+    /// Creates a sweep gradient which goes around the full circle once.
+    ///
+    /// Returns None if `center` is not finite.
+    #[must_use]
+    pub fn new(center: Point, gradient: Gradient) -> Option<Self> {
+        Self::with_angles(center, 0.0, 360.0, gradient)
+    }
+
+    /// Creates a sweep gradient.
+    ///
+    /// # Parameters
+    /// - `center` - center of rotation
+    /// - `start_angle` - angle in degrees where position of stops is 0
+    /// - `end_angle` - angle in degrees where position of stops is 1
+    /// - `gradient` - color stops, its tile mode applies to angles outside of
+    ///   [`start_angle`, `end_angle`]
+    ///
+    /// Returns None if `start_angle` is not less than `end_angle`,
+    /// or values are not finite.
+    #[must_use]
+    pub fn with_angles(
+        center: Point,
+        start_angle: Scalar,
+        end_angle: Scalar,
+        gradient: Gradient,
+    ) -> Option<Self> {
+        if !(center.is_finite() && start_angle.is_finite() && end_angle.is_finite())
+            || start_angle >= end_angle
+        {
+            return None;
+        }
+        Some(Self {
+            center,
+            start_angle,
+            end_angle,
+            gradient,
+        })
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn center(&self) -> &Point {
+        &self.center
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn start_angle(&self) -> Scalar {
+        self.start_angle
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn end_angle(&self) -> Scalar {
+        self.end_angle
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn gradient(&self) -> &Gradient {
+        &self.gradient
+    }
+}
+
+impl Shader for SweepGradient {
+    fn is_opaque(&self) -> bool {
+        self.gradient.is_opaque()
+    }
+
+    fn color_at(&self, point: &Point) -> Color4f {
+        let vector = *point - self.center;
+        let angle = vector.y().atan2(vector.x()).to_degrees();
+        // Angle in [0, 360), -0.0 is mapped to 0 too.
+        let angle = if angle < 0.0 {
+            angle + 360.0
+        } else {
+            angle + 0.0
+        };
+        let t = (angle - self.start_angle) / (self.end_angle - self.start_angle);
+        self.gradient.color_at(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::tile_mode::TileMode;
+    use crate::shaders::gradients::gradient::Interpolation;
+
+    fn gray(tile_mode: TileMode) -> Gradient {
+        let colors = [
+            Color4f::from_rgba(0.0, 0.0, 0.0, 1.0),
+            Color4f::from_rgba(1.0, 1.0, 1.0, 1.0),
+        ];
+        Gradient::new(&colors, None, tile_mode, Interpolation::default()).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let center = Point::from_xy(5.0, 5.0);
+        assert!(SweepGradient::with_angles(center, 90.0, 90.0, gray(TileMode::Clamp)).is_none());
+        assert!(SweepGradient::with_angles(center, 90.0, 0.0, gray(TileMode::Clamp)).is_none());
+    }
+
+    #[test]
+    fn test_color_at() {
+        let shader = SweepGradient::new(Point::from_xy(5.0, 5.0), gray(TileMode::Clamp)).unwrap();
+        let red_at = |x, y| shader.color_at(&Point::from_xy(x, y)).red();
+        // Angles increase clockwise, as y-axis points down.
+        assert!(red_at(10.0, 5.0).abs() < 1e-4);
+        assert!((red_at(5.0, 10.0) - 0.25).abs() < 1e-4);
+        assert!((red_at(0.0, 5.0) - 0.5).abs() < 1e-4);
+        assert!((red_at(5.0, 0.0) - 0.75).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_color_at_with_angles() {
+        let shader = SweepGradient::with_angles(Point::new(), 0.0, 180.0, gray(TileMode::Decal));
+        let shader = shader.unwrap();
+        assert!((shader.color_at(&Point::from_xy(0.0, 1.0)).red() - 0.5).abs() < 1e-4);
+        assert!(shader.color_at(&Point::from_xy(0.0, -1.0)).alpha().abs() < 1e-4);
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::{colors, Color4f};
+use crate::core::point::Point;
+use crate::core::scalar::Scalar;
+use crate::core::shader::Shader;
+use crate::shaders::gradients::gradient::Gradient;
+
+/// Interpolates colors between two circles.
+///
+/// The color at position t is drawn on the circle whose center and radius are
+/// interpolated between the start and end circles by t, with circles of larger
+/// t drawn over smaller ones. Points not on any circle with non-negative radius
+/// are transparent, as in the HTML canvas `createRadialGradient()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwoPointConicalGradient {
+    start: Point,
+    start_radius: Scalar,
+    end: Point,
+    end_radius: Scalar,
+    gradient: Gradient,
+}
+
+impl TwoPointConicalGradient {
+    /// Creates a two point conical gradient.
+    ///
+    /// # Parameters
+    /// - `start` - center of the circle where position of stops is 0
+    /// - `start_radius` - radius of the start circle
+    /// - `end` - center of the circle where position of stops is 1
+    /// - `end_radius` - radius of the end circle
+    /// - `gradient` - color stops
+    ///
+    /// Returns None if radius is negative, if both circles are the same,
+    /// or values are not finite.
+    #[must_use]
+    #[allow(clippy::float_cmp)]
+    pub fn new(
+        start: Point,
+        start_radius: Scalar,
+        end: Point,
+        end_radius: Scalar,
+        gradient: Gradient,
+    ) -> Option<Self> {
+        if !(start.is_finite()
+            && end.is_finite()
+            && start_radius.is_finite()
+            && end_radius.is_finite())
+            || start_radius < 0.0
+            || end_radius < 0.0
+            || (start == end && start_radius == end_radius)
+        {
+            return None;
+        }
+        Some(Self {
+            start,
+            start_radius,
+            end,
+            end_radius,
+            gradient,
+        })
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn start(&self) -> &Point {
+        &self.start
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn start_radius(&self) -> Scalar {
+        self.start_radius
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn end(&self) -> &Point {
+        &self.end
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn end_radius(&self) -> Scalar {
+        self.end_radius
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn gradient(&self) -> &Gradient {
+        &self.gradient
+    }
+
+    /// Returns the largest t whose circle passes through `point` and has
+    /// non-negative radius.
+    fn position_at(&self, point: Point) -> Option<Scalar> {
+        // Solves |point - center(t)| = radius(t), which is
+        // a * t^2 - 2 * b * t + c = 0.
+        let center_delta = self.end - self.start;
+        let radius_delta = self.end_radius - self.start_radius;
+        let offset = point - self.start;
+        let a = radius_delta.mul_add(-radius_delta, center_delta.length_sqd());
+        let b = self
+            .start_radius
+            .mul_add(radius_delta, offset.dot(&center_delta));
+        let c = self
+            .start_radius
+            .mul_add(-self.start_radius, offset.length_sqd());
+        let radius_at = |t: Scalar| radius_delta.mul_add(t, self.start_radius);
+
+        if a.abs() <= Scalar::EPSILON * center_delta.length_sqd().max(radius_delta * radius_delta) {
+            // The circles touch each other, only one circle passes through the point.
+            let t = c / (2.0 * b);
+            return (t.is_finite() && radius_at(t) >= 0.0).then_some(t);
+        }
+        let discriminant = b.mul_add(b, -a * c);
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (t0, t1) = ((b + root) / a, (b - root) / a);
+        let (larger, smaller) = if t0 > t1 { (t0, t1) } else { (t1, t0) };
+        if radius_at(larger) >= 0.0 {
+            Some(larger)
+        } else if radius_at(smaller) >= 0.0 {
+            Some(smaller)
+        } else {
+            None
+        }
+    }
+}
+
+impl Shader for TwoPointConicalGradient {
+    fn color_at(&self, point: &Point) -> Color4f {
+        self.position_at(*point)
+            .map_or(colors::TRANSPARENT, |t| self.gradient.color_at(t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::tile_mode::TileMode;
+    use crate::shaders::gradients::gradient::Interpolation;
+
+    fn gray() -> Gradient {
+        let colors = [
+            Color4f::from_rgba(0.0, 0.0, 0.0, 1.0),
+            Color4f::from_rgba(1.0, 1.0, 1.0, 1.0),
+        ];
+        Gradient::new(&colors, None, TileMode::Clamp, Interpolation::default()).unwrap()
+    }
+
+    fn red_at(shader: &TwoPointConicalGradient, x: Scalar, y: Scalar) -> Scalar {
+        shader.color_at(&Point::from_xy(x, y)).red()
+    }
+
+    #[test]
+    fn test_new() {
+        let center = Point::from_xy(5.0, 5.0);
+        assert!(TwoPointConicalGradient::new(center, 2.0, center, 2.0, gray()).is_none());
+        assert!(TwoPointConicalGradient::new(center, -1.0, Point::new(), 2.0, gray()).is_none());
+    }
+
+    #[test]
+    fn test_concentric() {
+        let center = Point::from_xy(5.0, 5.0);
+        let shader = TwoPointConicalGradient::new(center, 0.0, center, 10.0, gray()).unwrap();
+        assert!((red_at(&shader, 10.0, 5.0) - 0.5).abs() < 1e-4);
+        assert!((red_at(&shader, 5.0, 13.0) - 0.8).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_equal_radii() {
+        let shader =
+            TwoPointConicalGradient::new(Point::new(), 5.0, Point::from_xy(10.0, 0.0), 5.0, gray());
+        let shader = shader.unwrap();
+        // Circles centered at (7, 0) and (-3, 0) pass through (2, 0), the larger t wins.
+        assert!((red_at(&shader, 2.0, 0.0) - 0.7).abs() < 1e-4);
+        // No circle passes through points outside of the cylinder.
+        let color = shader.color_at(&Point::from_xy(0.0, 20.0));
+        assert_eq!(color, colors::TRANSPARENT);
+    }
+
+    #[test]
+    fn test_touching_circles() {
+        // Start circle touches end circle from inside at (0, 0).
+        let shader =
+            TwoPointConicalGradient::new(Point::new(), 0.0, Point::from_xy(5.0, 0.0), 5.0, gray());
+        let shader = shader.unwrap();
+        assert!((red_at(&shader, 10.0, 0.0) - 1.0).abs() < 1e-4);
+        assert!((red_at(&shader, 5.0, 0.0) - 0.5).abs() < 1e-4);
+    }
+}
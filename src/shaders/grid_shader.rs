@@ -0,0 +1,71 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::Color4f;
+use crate::core::point::Point;
+use crate::core::scalar::Scalar;
+use crate::core::shader::Shader;
+
+/// Draws horizontal and vertical lines at regular spacing over a background color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridShader {
+    background: Color4f,
+    line_color: Color4f,
+    spacing_x: Scalar,
+    spacing_y: Scalar,
+    line_width: Scalar,
+}
+
+impl GridShader {
+    /// Creates a grid shader, lines pass through the origin.
+    ///
+    /// # Parameters
+    /// - `background` - color between lines
+    /// - `line_color` - color of lines
+    /// - `spacing_x` - distance between vertical lines
+    /// - `spacing_y` - distance between horizontal lines
+    /// - `line_width` - width of lines, edges are antialiased
+    ///
+    /// Returns None if spacing is not larger than zero or `line_width` is negative.
+    #[must_use]
+    pub fn new(
+        background: Color4f,
+        line_color: Color4f,
+        spacing_x: Scalar,
+        spacing_y: Scalar,
+        line_width: Scalar,
+    ) -> Option<Self> {
+        if !(spacing_x > 0.0 && spacing_y > 0.0 && line_width >= 0.0) {
+            return None;
+        }
+        Some(Self {
+            background,
+            line_color,
+            spacing_x,
+            spacing_y,
+            line_width,
+        })
+    }
+}
+
+/// Returns coverage of a line with `line_width` repeated every `spacing` units,
+/// at `value`, with one unit antialiasing ramp.
+#[must_use]
+pub(crate) fn line_coverage(value: Scalar, spacing: Scalar, line_width: Scalar) -> Scalar {
+    let offset = value.rem_euclid(spacing);
+    let distance = offset.min(spacing - offset);
+    (line_width / 2.0 + 0.5 - distance).clamp(0.0, 1.0)
+}
+
+impl Shader for GridShader {
+    fn is_opaque(&self) -> bool {
+        self.background.is_opaque() && self.line_color.is_opaque()
+    }
+
+    fn color_at(&self, point: &Point) -> Color4f {
+        let coverage = line_coverage(point.x(), self.spacing_x, self.line_width)
+            .max(line_coverage(point.y(), self.spacing_y, self.line_width));
+        self.background.lerp(&self.line_color, coverage)
+    }
+}
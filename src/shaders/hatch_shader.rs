@@ -0,0 +1,79 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::Color4f;
+use crate::core::point::Point;
+use crate::core::scalar::Scalar;
+use crate::core::shader::Shader;
+use crate::shaders::grid_shader::line_coverage;
+
+/// Draws parallel lines at an angle over a background color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HatchShader {
+    background: Color4f,
+    line_color: Color4f,
+    spacing: Scalar,
+    line_width: Scalar,
+
+    /// Sine and cosine of line angle.
+    sin: Scalar,
+    cos: Scalar,
+
+    /// Also draws lines perpendicular to the first ones.
+    cross: bool,
+}
+
+impl HatchShader {
+    /// Creates a hatch shader, one line passes through the origin.
+    ///
+    /// # Parameters
+    /// - `background` - color between lines
+    /// - `line_color` - color of lines
+    /// - `spacing` - distance between lines, measured perpendicular to them
+    /// - `line_width` - width of lines, edges are antialiased
+    /// - `degrees` - angle of lines, 0 for horizontal lines, clockwise
+    /// - `cross` - if true, also draws perpendicular lines
+    ///
+    /// Returns None if spacing is not larger than zero or `line_width` is negative.
+    #[must_use]
+    pub fn new(
+        background: Color4f,
+        line_color: Color4f,
+        spacing: Scalar,
+        line_width: Scalar,
+        degrees: Scalar,
+        cross: bool,
+    ) -> Option<Self> {
+        if !(spacing > 0.0 && line_width >= 0.0 && degrees.is_finite()) {
+            return None;
+        }
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Some(Self {
+            background,
+            line_color,
+            spacing,
+            line_width,
+            sin,
+            cos,
+            cross,
+        })
+    }
+}
+
+impl Shader for HatchShader {
+    fn is_opaque(&self) -> bool {
+        self.background.is_opaque() && self.line_color.is_opaque()
+    }
+
+    fn color_at(&self, point: &Point) -> Color4f {
+        // Distance across lines, and along lines.
+        let across = point.y().mul_add(self.cos, -point.x() * self.sin);
+        let mut coverage = line_coverage(across, self.spacing, self.line_width);
+        if self.cross {
+            let along = point.x().mul_add(self.cos, point.y() * self.sin);
+            coverage = coverage.max(line_coverage(along, self.spacing, self.line_width));
+        }
+        self.background.lerp(&self.line_color, coverage)
+    }
+}
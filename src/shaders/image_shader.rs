@@ -0,0 +1,301 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::alpha_type::AlphaType;
+use crate::core::color::{colors, Color4f};
+use crate::core::matrix::Matrix;
+use crate::core::pixmap::Pixmap;
+use crate::core::point::Point;
+use crate::core::sampling_options::{FilterMode, SamplingOptions};
+use crate::core::shader::Shader;
+use crate::core::tile_mode::TileMode;
+
+/// Fills with pixels of an image, used as a pattern.
+///
+/// The image covers the rectangle from the origin to its width and height in
+/// local coordinates, and tile modes decide what is drawn outside of it.
+#[derive(Debug, Clone)]
+pub struct ImageShader {
+    pixmap: Pixmap,
+    tile_x: TileMode,
+    tile_y: TileMode,
+    filter: FilterMode,
+    local_matrix: Matrix,
+
+    /// Inverse of `local_matrix`, maps points to image coordinates.
+    inverse: Matrix,
+}
+
+impl ImageShader {
+    /// Creates an image shader.
+    ///
+    /// Use `Bitmap::pixmap()` or `Image::to_pixmap()` to get pixels of bitmaps and images.
+    ///
+    /// # Parameters
+    /// - `pixmap` - pixels of the pattern
+    /// - `tile_x` - tiling on x-axis
+    /// - `tile_y` - tiling on y-axis
+    /// - `sampling` - how pixels are sampled, only nearest and linear filtering is
+    ///   supported; cubic resampling and mipmaps are not
+    /// - `local_matrix` - maps image coordinates to local coordinates of shader,
+    ///   or None for identity
+    ///
+    /// Returns None if `pixmap` is empty or its color type is not supported, or if
+    /// `local_matrix` is not invertible.
+    #[must_use]
+    pub fn new(
+        pixmap: &Pixmap,
+        tile_x: TileMode,
+        tile_y: TileMode,
+        sampling: &SamplingOptions,
+        local_matrix: Option<&Matrix>,
+    ) -> Option<Self> {
+        if pixmap.width() <= 0 || pixmap.height() <= 0 {
+            return None;
+        }
+        pixmap.get_color4f(0, 0)?;
+        let local_matrix = local_matrix.cloned().unwrap_or_default();
+        let mut inverse = Matrix::identity();
+        if !local_matrix.invert(&mut inverse) {
+            return None;
+        }
+        // TODO(Shaohua): Support cubic resampler and mipmaps.
+        Some(Self {
+            pixmap: pixmap.clone(),
+            tile_x,
+            tile_y,
+            filter: sampling.filter(),
+            local_matrix,
+            inverse,
+        })
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn pixmap(&self) -> &Pixmap {
+        &self.pixmap
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn tile_x(&self) -> TileMode {
+        self.tile_x
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn tile_y(&self) -> TileMode {
+        self.tile_y
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn local_matrix(&self) -> &Matrix {
+        &self.local_matrix
+    }
+
+    /// Returns premultiplied color of pixel at (x, y) after tiling, or None if it is
+    /// outside of image with `TileMode::Decal`.
+    fn premul_pixel_at(&self, x: i32, y: i32) -> Option<[f32; 4]> {
+        let x = tile(x, self.pixmap.width(), self.tile_x)?;
+        let y = tile(y, self.pixmap.height(), self.tile_y)?;
+        let color = self.pixmap.get_color4f(x, y)?;
+        let alpha = color.alpha();
+        Some([
+            color.red() * alpha,
+            color.green() * alpha,
+            color.blue() * alpha,
+            alpha,
+        ])
+    }
+}
+
+/// Maps pixel `index` into [0, `size`) with `tile_mode`.
+pub(crate) const fn tile(index: i32, size: i32, tile_mode: TileMode) -> Option<i32> {
+    match tile_mode {
+        TileMode::Clamp => Some(if index < 0 {
+            0
+        } else if index >= size {
+            size - 1
+        } else {
+            index
+        }),
+        TileMode::Repeat => Some(index.rem_euclid(size)),
+        TileMode::Mirror => {
+            let index = index.rem_euclid(size * 2);
+            Some(if index >= size {
+                size * 2 - 1 - index
+            } else {
+                index
+            })
+        }
+        TileMode::Decal => {
+            if index >= 0 && index < size {
+                Some(index)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+impl Shader for ImageShader {
+    fn is_opaque(&self) -> bool {
+        self.pixmap.alpha_type() == AlphaType::Opaque
+            && self.tile_x != TileMode::Decal
+            && self.tile_y != TileMode::Decal
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn color_at(&self, point: &Point) -> Color4f {
+        let point = self.inverse.map_xy(point.x(), point.y());
+        if !point.is_finite() {
+            return colors::TRANSPARENT;
+        }
+        let premul = match self.filter {
+            FilterMode::Nearest => {
+                let x = point.x().floor() as i32;
+                let y = point.y().floor() as i32;
+                self.premul_pixel_at(x, y).unwrap_or_default()
+            }
+            FilterMode::Linear => {
+                // Pixel centers are at half integers.
+                let x = point.x() - 0.5;
+                let y = point.y() - 0.5;
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i32, y0 as i32);
+                let mut premul = [0.0; 4];
+                for (dx, dy, weight) in [
+                    (0, 0, (1.0 - fx) * (1.0 - fy)),
+                    (1, 0, fx * (1.0 - fy)),
+                    (0, 1, (1.0 - fx) * fy),
+                    (1, 1, fx * fy),
+                ] {
+                    if let Some(pixel) = self.premul_pixel_at(x0 + dx, y0 + dy) {
+                        for (value, channel) in premul.iter_mut().zip(pixel) {
+                            *value = channel.mul_add(weight, *value);
+                        }
+                    }
+                }
+                premul
+            }
+        };
+        unpremultiply(premul)
+    }
+}
+
+fn unpremultiply(premul: [f32; 4]) -> Color4f {
+    let alpha = premul[3];
+    if alpha <= 0.0 {
+        return colors::TRANSPARENT;
+    }
+    Color4f::from_rgba(
+        (premul[0] / alpha).clamp(0.0, 1.0),
+        (premul[1] / alpha).clamp(0.0, 1.0),
+        (premul[2] / alpha).clamp(0.0, 1.0),
+        alpha.min(1.0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::image_info::ImageInfo;
+
+    /// Returns a 2x1 image, left pixel is black and right one is white.
+    fn black_white() -> Pixmap {
+        let info = ImageInfo::new_n32(2, 1, AlphaType::Opaque, None);
+        Pixmap::from_vec(info, 8, vec![0, 0, 0, 255, 255, 255, 255, 255])
+    }
+
+    fn shader(
+        tile_mode: TileMode,
+        filter: FilterMode,
+        local_matrix: Option<&Matrix>,
+    ) -> ImageShader {
+        let sampling = SamplingOptions::with_filter(filter);
+        ImageShader::new(
+            &black_white(),
+            tile_mode,
+            tile_mode,
+            &sampling,
+            local_matrix,
+        )
+        .unwrap()
+    }
+
+    fn red_at(shader: &ImageShader, x: f32, y: f32) -> f32 {
+        shader.color_at(&Point::from_xy(x, y)).red()
+    }
+
+    #[test]
+    fn test_new() {
+        let sampling = SamplingOptions::new();
+        let clamp = TileMode::Clamp;
+        assert!(ImageShader::new(&Pixmap::new(), clamp, clamp, &sampling, None).is_none());
+        let matrix = Matrix::from_scale(0.0, 1.0);
+        assert!(ImageShader::new(&black_white(), clamp, clamp, &sampling, Some(&matrix)).is_none());
+    }
+
+    #[test]
+    fn test_nearest() {
+        let shader = shader(TileMode::Clamp, FilterMode::Nearest, None);
+        assert!(red_at(&shader, 0.5, 0.5).abs() < 1e-4);
+        assert!((red_at(&shader, 1.5, 0.5) - 1.0).abs() < 1e-4);
+        assert!(shader.is_opaque());
+    }
+
+    #[test]
+    fn test_tile_modes() {
+        let clamp = shader(TileMode::Clamp, FilterMode::Nearest, None);
+        assert!(red_at(&clamp, -3.0, 0.5).abs() < 1e-4);
+        assert!((red_at(&clamp, 5.0, 7.0) - 1.0).abs() < 1e-4);
+
+        let repeat = shader(TileMode::Repeat, FilterMode::Nearest, None);
+        assert!(red_at(&repeat, 2.5, 0.5).abs() < 1e-4);
+        assert!((red_at(&repeat, -0.5, 0.5) - 1.0).abs() < 1e-4);
+
+        let mirror = shader(TileMode::Mirror, FilterMode::Nearest, None);
+        assert!((red_at(&mirror, 2.5, 0.5) - 1.0).abs() < 1e-4);
+        assert!(red_at(&mirror, -0.5, 0.5).abs() < 1e-4);
+
+        let decal = shader(TileMode::Decal, FilterMode::Nearest, None);
+        assert_eq!(
+            decal.color_at(&Point::from_xy(-0.5, 0.5)),
+            colors::TRANSPARENT
+        );
+        assert_eq!(
+            decal.color_at(&Point::from_xy(0.5, 1.5)),
+            colors::TRANSPARENT
+        );
+        assert!(!decal.is_opaque());
+    }
+
+    #[test]
+    fn test_linear() {
+        let shader = shader(TileMode::Clamp, FilterMode::Linear, None);
+        assert!(red_at(&shader, 0.5, 0.5).abs() < 1e-4);
+        assert!((red_at(&shader, 1.0, 0.5) - 0.5).abs() < 1e-4);
+        assert!((red_at(&shader, 1.25, 0.5) - 0.75).abs() < 1e-4);
+
+        // Half of samples fall outside of image.
+        let decal = self::shader(TileMode::Decal, FilterMode::Linear, None);
+        let color = decal.color_at(&Point::from_xy(1.5, 1.0));
+        assert!((color.red() - 1.0).abs() < 1e-4);
+        assert!((color.alpha() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_local_matrix() {
+        let matrix = Matrix::from_scale(10.0, 10.0);
+        let shader = shader(TileMode::Decal, FilterMode::Nearest, Some(&matrix));
+        assert!(red_at(&shader, 5.0, 5.0).abs() < 1e-4);
+        assert!((red_at(&shader, 15.0, 5.0) - 1.0).abs() < 1e-4);
+        assert_eq!(
+            shader.color_at(&Point::from_xy(25.0, 5.0)),
+            colors::TRANSPARENT
+        );
+    }
+}
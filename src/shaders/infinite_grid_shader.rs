@@ -0,0 +1,145 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use crate::core::color::Color4f;
+use crate::core::point::Point;
+use crate::core::scalar::Scalar;
+use crate::core::shader::Shader;
+use crate::shaders::grid_shader::line_coverage;
+
+/// Draws a grid over an infinite canvas, whose spacing adapts to zoom level.
+///
+/// Lines are placed in canvas coordinates, at multiples of `spacing` times a power
+/// of `subdivisions`. The power is chosen so that minor lines are at least
+/// `min_pixel_spacing` pixels apart, and every `subdivisions`-th line is a major
+/// line. Minor lines fade in as zooming in moves them apart, so that levels change
+/// smoothly.
+///
+/// Since it is a shader, the grid covers whatever is drawn with it, usually the
+/// visible viewport.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfiniteGridShader {
+    background: Color4f,
+    line_color: Color4f,
+    spacing: Scalar,
+    subdivisions: Scalar,
+    min_pixel_spacing: Scalar,
+    line_width: Scalar,
+
+    /// Viewport transform, pixel = canvas point * scale + offset.
+    scale: Scalar,
+    offset: Point,
+}
+
+impl InfiniteGridShader {
+    /// Creates an infinite grid shader, with 1 pixel wide lines at least 8 pixels apart,
+    /// and an identity viewport transform.
+    ///
+    /// # Parameters
+    /// - `background` - color between lines
+    /// - `line_color` - color of lines
+    /// - `spacing` - distance between lines in canvas coordinates at zoom level 1
+    /// - `subdivisions` - number of minor cells in each major cell, at least 2
+    ///
+    /// Returns None if spacing is not larger than zero or `subdivisions` is less than 2.
+    #[must_use]
+    pub fn new(
+        background: Color4f,
+        line_color: Color4f,
+        spacing: Scalar,
+        subdivisions: u16,
+    ) -> Option<Self> {
+        if !(spacing > 0.0 && spacing.is_finite() && subdivisions >= 2) {
+            return None;
+        }
+        Some(Self {
+            background,
+            line_color,
+            spacing,
+            subdivisions: Scalar::from(subdivisions),
+            min_pixel_spacing: 8.0,
+            line_width: 1.0,
+            scale: 1.0,
+            offset: Point::new(),
+        })
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn scale(&self) -> Scalar {
+        self.scale
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn offset(&self) -> &Point {
+        &self.offset
+    }
+
+    /// Sets viewport transform, where pixel = canvas point * `scale` + `offset`.
+    ///
+    /// Invalid values are ignored.
+    pub fn set_viewport(&mut self, scale: Scalar, offset: Point) {
+        if scale > 0.0 && scale.is_finite() && offset.is_finite() {
+            self.scale = scale;
+            self.offset = offset;
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn min_pixel_spacing(&self) -> Scalar {
+        self.min_pixel_spacing
+    }
+
+    /// Sets minimum distance between minor lines in pixels.
+    ///
+    /// Values not larger than zero are ignored.
+    pub fn set_min_pixel_spacing(&mut self, min_pixel_spacing: Scalar) {
+        if min_pixel_spacing > 0.0 && min_pixel_spacing.is_finite() {
+            self.min_pixel_spacing = min_pixel_spacing;
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn line_width(&self) -> Scalar {
+        self.line_width
+    }
+
+    /// Sets width of lines in pixels.
+    ///
+    /// Negative values are ignored.
+    pub fn set_line_width(&mut self, line_width: Scalar) {
+        if line_width >= 0.0 && line_width.is_finite() {
+            self.line_width = line_width;
+        }
+    }
+
+    /// Returns spacing of minor lines in pixels, and opacity of minor lines.
+    fn minor_level(&self) -> (Scalar, Scalar) {
+        let pixel_spacing = self.spacing * self.scale;
+        let level = (self.min_pixel_spacing / pixel_spacing).log(self.subdivisions);
+        let minor = level.ceil();
+        (pixel_spacing * self.subdivisions.powf(minor), minor - level)
+    }
+}
+
+impl Shader for InfiniteGridShader {
+    fn is_opaque(&self) -> bool {
+        self.background.is_opaque() && self.line_color.is_opaque()
+    }
+
+    fn color_at(&self, point: &Point) -> Color4f {
+        let (minor_spacing, fade) = self.minor_level();
+        let major_spacing = minor_spacing * self.subdivisions;
+        let position = *point - self.offset;
+        let coverage_at = |spacing: Scalar| {
+            let vertical = line_coverage(position.x(), spacing, self.line_width);
+            vertical.max(line_coverage(position.y(), spacing, self.line_width))
+        };
+        let coverage = coverage_at(major_spacing).max(coverage_at(minor_spacing) * fade);
+        self.background.lerp(&self.line_color, coverage)
+    }
+}
@@ -1,3 +1,11 @@
 // Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
+
+pub mod checkerboard_shader;
+pub mod color_shader;
+pub mod gradients;
+pub mod grid_shader;
+pub mod hatch_shader;
+pub mod image_shader;
+pub mod infinite_grid_shader;
@@ -0,0 +1,59 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+use std::fmt::Write;
+
+use crate::base::xml::escape;
+use crate::core::document_metadata::DocumentMetadata;
+
+/// Serializes metadata into a `<title>` and a `<metadata>` element with Dublin Core RDF,
+/// suitable to be placed as the first children of root `<svg>` element.
+///
+/// Returns an empty string if metadata is empty.
+#[must_use]
+pub fn to_metadata_element(metadata: &DocumentMetadata) -> String {
+    if metadata.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    if !metadata.title.is_empty() {
+        let _ = writeln!(out, "<title>{}</title>", escape(&metadata.title));
+    }
+    out.push_str(concat!(
+        "<metadata>\n",
+        "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"",
+        " xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n",
+        "<rdf:Description rdf:about=\"\">\n",
+        "<dc:format>image/svg+xml</dc:format>\n",
+    ));
+
+    let producer = metadata.producer_or_default();
+    let elements = [
+        ("dc:title", &metadata.title),
+        ("dc:creator", &metadata.author),
+        ("dc:description", &metadata.subject),
+        ("dc:publisher", &producer),
+        ("dc:source", &metadata.creator),
+        ("dc:language", &metadata.language),
+    ];
+    for (tag, value) in elements {
+        if !value.is_empty() {
+            let _ = writeln!(out, "<{tag}>{}</{tag}>", escape(value));
+        }
+    }
+    if let Some(creation) = &metadata.creation {
+        let _ = writeln!(out, "<dc:date>{creation}</dc:date>");
+    }
+    if !metadata.keywords.is_empty() {
+        out.push_str("<dc:subject><rdf:Bag>");
+        for keyword in &metadata.keywords {
+            let _ = write!(out, "<rdf:li>{}</rdf:li>", escape(keyword));
+        }
+        out.push_str("</rdf:Bag></dc:subject>\n");
+    }
+
+    out.push_str("</rdf:Description>\n</rdf:RDF>\n</metadata>\n");
+    out
+}
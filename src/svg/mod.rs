@@ -2,4 +2,7 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+pub mod metadata;
 pub mod svg_canvas;
+pub mod svg_dom;
+pub mod svg_device;
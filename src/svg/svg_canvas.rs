@@ -2,10 +2,14 @@
 // Use of this source is governed by Lesser General Public License that can be found
 // in the LICENSE file.
 
+use std::io::Write;
+
 use bitflags::bitflags;
 
 use crate::core::canvas::Canvas;
+use crate::core::document_metadata::DocumentMetadata;
 use crate::core::rect::Rect;
+use crate::svg::svg_device::SvgDevice;
 
 bitflags! {
     #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
@@ -24,15 +28,24 @@ bitflags! {
 /// Returns a new canvas that will generate SVG commands from its draw calls, and send
 /// them to the provided stream.
 ///
-/// Ownership of the stream is not transfered, and it must remain valid
-/// for the lifetime of the returned canvas.
+/// Ownership of the stream is transfered to the returned canvas.
 ///
 /// The canvas may buffer some drawing calls, so the output is not guaranteed to be valid
-/// or complete until the canvas instance is deleted.
+/// or complete until the canvas instance is dropped.
 ///
 /// The 'bounds' parameter defines an initial SVG viewport (`viewBox` attribute on the root
 /// SVG element).
+///
+/// If `metadata` is not None, it is written as `<title>` and `<metadata>` elements
+/// at the beginning of the root SVG element.
+// TODO(Shaohua): Support ConvertTextToPaths flag when text is drawn.
 #[must_use]
-pub fn make(_bounds: &Rect, /* WStream* stream */ _flags: Flag) -> Canvas {
-    unimplemented!()
+pub fn make(
+    bounds: &Rect,
+    stream: Box<dyn Write>,
+    flags: Flag,
+    metadata: Option<&DocumentMetadata>,
+) -> Canvas {
+    let device = SvgDevice::new(bounds, stream, flags, metadata);
+    Canvas::with_device(Box::new(device))
 }
@@ -0,0 +1,644 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Device which writes drawing as SVG elements.
+
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+
+use crate::core::blend_mode::BlendMode;
+use crate::core::clip_op::ClipOp;
+use crate::core::color::Color4f;
+use crate::core::color_space::ColorSpace;
+use crate::core::device::Device;
+use crate::core::document_metadata::DocumentMetadata;
+use crate::core::geometry::Conic;
+use crate::core::image_info::ImageInfo;
+use crate::core::paint::Paint;
+use crate::core::paint_types::{PaintStyle, StrokeCap, StrokeJoin};
+use crate::core::path::Path;
+use crate::core::path_builder::PathBuilder;
+use crate::core::path_types::PathVerb;
+use crate::core::point::Point;
+use crate::core::rect::Rect;
+use crate::core::surface_props::SurfaceProps;
+use crate::svg::metadata::to_metadata_element;
+use crate::svg::svg_canvas::Flag;
+
+/// Number of quads used to approximate a conic, SVG has no rational curves.
+const CONIC_QUAD_COUNT: usize = 4;
+
+/// Device writing each draw call as an SVG element to a stream.
+///
+/// Root `<svg>` element is opened when the device is created, and closed
+/// when it is dropped.
+pub struct SvgDevice {
+    info: ImageInfo,
+    props: SurfaceProps,
+    bounds: Rect,
+    flags: Flag,
+    stream: Box<dyn Write>,
+
+    /// Number of `<clipPath>` elements written, used to name them.
+    clip_count: usize,
+
+    /// Number of `<g>` elements opened by clips and not closed yet.
+    open_groups: usize,
+
+    /// Values of `open_groups` when `save()` was called.
+    saved_groups: Vec<usize>,
+}
+
+impl fmt::Debug for SvgDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SvgDevice")
+            .field("info", &self.info)
+            .field("props", &self.props)
+            .field("bounds", &self.bounds)
+            .field("flags", &self.flags)
+            .field("clip_count", &self.clip_count)
+            .field("open_groups", &self.open_groups)
+            .field("saved_groups", &self.saved_groups)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SvgDevice {
+    /// Creates device writing to `stream`, with `bounds` as viewport.
+    ///
+    /// If `metadata` is not None, it is written as `<title>` and `<metadata>`
+    /// elements right after the root `<svg>` element.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(
+        bounds: &Rect,
+        stream: Box<dyn Write>,
+        flags: Flag,
+        metadata: Option<&DocumentMetadata>,
+    ) -> Self {
+        let width = bounds.width().ceil() as i32;
+        let height = bounds.height().ceil() as i32;
+        let mut device = Self {
+            info: ImageInfo::new_unknown(width.max(0), height.max(0)),
+            props: SurfaceProps::default(),
+            bounds: bounds.clone(),
+            flags,
+            stream,
+            clip_count: 0,
+            open_groups: 0,
+            saved_groups: Vec::new(),
+        };
+        // Errors of stream are ignored, drawing has no way to report them.
+        let _ = device.write_header(metadata);
+        device
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn flags(&self) -> Flag {
+        self.flags
+    }
+
+    const fn newline(&self) -> &'static str {
+        if self.flags.contains(Flag::NoPrettyXml) {
+            ""
+        } else {
+            "\n"
+        }
+    }
+
+    fn write_header(&mut self, metadata: Option<&DocumentMetadata>) -> io::Result<()> {
+        let newline = self.newline();
+        let bounds = &self.bounds;
+        write!(
+            self.stream,
+            "<?xml version=\"1.0\" encoding=\"utf-8\" ?>{newline}\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">{newline}",
+            bounds.width(),
+            bounds.height(),
+            bounds.left(),
+            bounds.top(),
+            bounds.width(),
+            bounds.height()
+        )?;
+        if let Some(metadata) = metadata {
+            self.stream
+                .write_all(to_metadata_element(metadata).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes a `<clipPath>` element of `path`, and opens a group clipped by it.
+    fn write_clip_group(&mut self, path: &Path, op: ClipOp) -> io::Result<()> {
+        // Inverse fill type clips out the inside of path.
+        let difference = (op == ClipOp::Difference) != path.fill_type().is_inverse();
+        if difference && path.verbs().is_empty() {
+            return Ok(());
+        }
+
+        let relative = self.flags.contains(Flag::RelativePathEncoding);
+        let (data, even_odd) = if difference {
+            // TODO(Shaohua): Handle overlapping contours of path with winding fill type.
+            let bounds = PathBuilder::from_rect(&self.bounds).unwrap_or_default();
+            let data = format!(
+                "{} {}",
+                path_data(&bounds, relative),
+                path_data(path, relative)
+            );
+            (data, true)
+        } else {
+            (path_data(path, relative), path.fill_type().is_event_odd())
+        };
+        let clip_rule = if even_odd {
+            " clip-rule=\"evenodd\""
+        } else {
+            ""
+        };
+        let id = self.clip_count;
+        let newline = self.newline();
+        write!(
+            self.stream,
+            "<clipPath id=\"clip{id}\"><path{clip_rule} d=\"{data}\"/></clipPath>{newline}\
+             <g clip-path=\"url(#clip{id})\">{newline}"
+        )?;
+        self.clip_count += 1;
+        self.open_groups += 1;
+        Ok(())
+    }
+
+    /// Closes groups opened by clips, until `open_groups` groups are left.
+    fn close_groups(&mut self, open_groups: usize) {
+        let newline = self.newline();
+        while self.open_groups > open_groups {
+            let _ = write!(self.stream, "</g>{newline}");
+            self.open_groups -= 1;
+        }
+    }
+
+    fn write_path_element(&mut self, path: &Path, paint: &Paint) -> io::Result<()> {
+        let data = path_data(path, self.flags.contains(Flag::RelativePathEncoding));
+        let attributes = paint_attributes(paint, path.fill_type().is_event_odd());
+        let newline = self.newline();
+        write!(self.stream, "<path{attributes} d=\"{data}\"/>{newline}")
+    }
+}
+
+impl Device for SvgDevice {
+    fn image_info(&self) -> &ImageInfo {
+        &self.info
+    }
+
+    fn surface_props(&self) -> &SurfaceProps {
+        &self.props
+    }
+
+    fn save(&mut self) {
+        self.saved_groups.push(self.open_groups);
+    }
+
+    // TODO(Shaohua): Clip layer to `bounds`, and support color filter and image filter.
+    fn save_layer(&mut self, _bounds: Option<&Rect>, paint: Option<&Paint>) {
+        self.saved_groups.push(self.open_groups);
+        // Group is isolated, so that its children are blended with each other first.
+        let mut opacity = String::new();
+        let mut style = String::from("isolation:isolate");
+        if let Some(paint) = paint {
+            let alpha = paint.get_alphaf();
+            if alpha < 1.0 {
+                let _ = write!(opacity, " opacity=\"{alpha}\"");
+            }
+            if let Some(blend_name) = blend_mode_name(paint.get_blend_mode()) {
+                let _ = write!(style, ";mix-blend-mode:{blend_name}");
+            }
+        }
+        let newline = self.newline();
+        let _ = write!(self.stream, "<g{opacity} style=\"{style}\">{newline}");
+        self.open_groups += 1;
+    }
+
+    fn restore(&mut self) {
+        if let Some(open_groups) = self.saved_groups.pop() {
+            self.close_groups(open_groups);
+        }
+    }
+
+    // TODO(Shaohua): Set shape-rendering of clip path if `anti_alias` is false.
+    fn clip_path(&mut self, path: &Path, op: ClipOp, _anti_alias: bool) {
+        let _ = self.write_clip_group(path, op);
+    }
+
+    // TODO(Shaohua): Replace previous elements if `color` is opaque.
+    fn erase(&mut self, color: &Color4f) {
+        let paint = Paint::from_color(color);
+        let Some(path) = PathBuilder::from_rect(&self.bounds) else {
+            return;
+        };
+        let _ = self.write_path_element(&path, &paint);
+    }
+
+    // TODO(Shaohua): Support shaders, filters and inverse fill types.
+    fn draw_path(&mut self, path: &Path, paint: &Paint) {
+        if paint.nothing_to_draw() || path.verbs().is_empty() {
+            return;
+        }
+        let _ = self.write_path_element(path, paint);
+    }
+}
+
+impl Drop for SvgDevice {
+    fn drop(&mut self) {
+        self.close_groups(0);
+        let newline = self.newline();
+        let _ = write!(self.stream, "</svg>{newline}");
+        let _ = self.stream.flush();
+    }
+}
+
+/// Returns color of `paint`, filtered by its color filter.
+fn paint_color(paint: &Paint) -> Color4f {
+    let color = paint.get_color4f();
+    let Some(color_filter) = paint.get_color_filter() else {
+        return color.clone();
+    };
+    let mut dst_cs = ColorSpace::default();
+    color_filter.filter_color4f(color, &ColorSpace::default(), &mut dst_cs)
+}
+
+/// Formats `color` as `rgb(r,g,b)`, ignoring alpha.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn svg_color(color: &Color4f) -> String {
+    let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "rgb({},{},{})",
+        to_byte(color.red()),
+        to_byte(color.green()),
+        to_byte(color.blue())
+    )
+}
+
+/// Returns CSS name of `mix-blend-mode` matching `blend_mode`.
+///
+/// Returns None for `BlendMode::SrcOver`, which is the default in SVG.
+// TODO(Shaohua): Support Porter-Duff modes other than SrcOver.
+const fn blend_mode_name(blend_mode: BlendMode) -> Option<&'static str> {
+    match blend_mode {
+        BlendMode::Multiply | BlendMode::Modulate => Some("multiply"),
+        BlendMode::Screen => Some("screen"),
+        BlendMode::Overlay => Some("overlay"),
+        BlendMode::Darken => Some("darken"),
+        BlendMode::Lighten => Some("lighten"),
+        BlendMode::ColorDodge => Some("color-dodge"),
+        BlendMode::ColorBurn => Some("color-burn"),
+        BlendMode::HardLight => Some("hard-light"),
+        BlendMode::SoftLight => Some("soft-light"),
+        BlendMode::Difference => Some("difference"),
+        BlendMode::Exclusion => Some("exclusion"),
+        BlendMode::Hue => Some("hue"),
+        BlendMode::Saturation => Some("saturation"),
+        BlendMode::Color => Some("color"),
+        BlendMode::Luminosity => Some("luminosity"),
+        _ => None,
+    }
+}
+
+/// Returns presentation attributes of `paint`, each with a leading space.
+fn paint_attributes(paint: &Paint, even_odd: bool) -> String {
+    let color = paint_color(paint);
+    let svg_color = svg_color(&color);
+    let alpha = color.alpha();
+    let style = paint.get_style();
+
+    let mut attributes = String::new();
+    if style == PaintStyle::Stroke {
+        attributes.push_str(" fill=\"none\"");
+    } else {
+        let _ = write!(attributes, " fill=\"{svg_color}\"");
+        if alpha < 1.0 {
+            let _ = write!(attributes, " fill-opacity=\"{alpha}\"");
+        }
+        if even_odd {
+            attributes.push_str(" fill-rule=\"evenodd\"");
+        }
+    }
+    if style != PaintStyle::Fill {
+        let _ = write!(attributes, " stroke=\"{svg_color}\"");
+        if alpha < 1.0 {
+            let _ = write!(attributes, " stroke-opacity=\"{alpha}\"");
+        }
+        let width = paint.get_stroke_width();
+        if width > 0.0 {
+            let _ = write!(attributes, " stroke-width=\"{width}\"");
+        } else {
+            // Hairlines are one pixel wide regardless of transformation.
+            attributes.push_str(" stroke-width=\"1\" vector-effect=\"non-scaling-stroke\"");
+        }
+        match paint.get_stroke_cap() {
+            StrokeCap::Butt => (),
+            StrokeCap::Round => attributes.push_str(" stroke-linecap=\"round\""),
+            StrokeCap::Square => attributes.push_str(" stroke-linecap=\"square\""),
+        }
+        match paint.get_stroke_join() {
+            StrokeJoin::Miter => {
+                let _ = write!(
+                    attributes,
+                    " stroke-miterlimit=\"{}\"",
+                    paint.get_stroke_miter().max(1.0)
+                );
+            }
+            StrokeJoin::Round => attributes.push_str(" stroke-linejoin=\"round\""),
+            StrokeJoin::Bevel => attributes.push_str(" stroke-linejoin=\"bevel\""),
+        }
+    }
+    if let Some(blend_name) = blend_mode_name(paint.get_blend_mode()) {
+        let _ = write!(attributes, " style=\"mix-blend-mode:{blend_name}\"");
+    }
+    attributes
+}
+
+/// Appends a path command with `points` to `data`.
+///
+/// If `relative` is true, points are written relative to `last`.
+fn push_command(data: &mut String, command: char, points: &[Point], last: Point, relative: bool) {
+    if !data.is_empty() {
+        data.push(' ');
+    }
+    if relative {
+        data.push(command.to_ascii_lowercase());
+    } else {
+        data.push(command);
+    }
+    for (index, point) in points.iter().enumerate() {
+        let point = if relative { *point - last } else { *point };
+        if index > 0 {
+            data.push(' ');
+        }
+        let _ = write!(data, "{} {}", point.x(), point.y());
+    }
+}
+
+/// Returns path data of `path`, for `d` attribute of `<path>` element.
+///
+/// If `relative` is true, commands are written in relative coordinates.
+#[must_use]
+pub fn path_data(path: &Path, relative: bool) -> String {
+    let points = path.points();
+    let weights = path.conic_weights();
+    let mut point_index = 0;
+    let mut weight_index = 0;
+    let mut last = Point::new();
+    let mut contour_start = Point::new();
+    let mut data = String::new();
+
+    for verb in path.verbs() {
+        match verb {
+            PathVerb::Move => {
+                let point = points[point_index];
+                push_command(&mut data, 'M', &[point], last, relative);
+                last = point;
+                contour_start = point;
+                point_index += 1;
+            }
+            PathVerb::Line => {
+                let point = points[point_index];
+                push_command(&mut data, 'L', &[point], last, relative);
+                last = point;
+                point_index += 1;
+            }
+            PathVerb::Quad => {
+                let pts = [points[point_index], points[point_index + 1]];
+                push_command(&mut data, 'Q', &pts, last, relative);
+                last = pts[1];
+                point_index += 2;
+            }
+            PathVerb::Conic => {
+                let weight = weights[weight_index];
+                let conic = Conic::new(last, points[point_index], points[point_index + 1], weight);
+                // Pieces of a conic are close to quads with same control points.
+                #[allow(clippy::cast_precision_loss)]
+                for index in 0..CONIC_QUAD_COUNT {
+                    let t0 = index as f32 / CONIC_QUAD_COUNT as f32;
+                    let t1 = (index + 1) as f32 / CONIC_QUAD_COUNT as f32;
+                    let piece = conic.chop_range(t0, t1);
+                    push_command(&mut data, 'Q', &piece.pts[1..], last, relative);
+                    last = piece.pts[2];
+                }
+                point_index += 2;
+                weight_index += 1;
+            }
+            PathVerb::Cubic => {
+                let pts = [
+                    points[point_index],
+                    points[point_index + 1],
+                    points[point_index + 2],
+                ];
+                push_command(&mut data, 'C', &pts, last, relative);
+                last = pts[2];
+                point_index += 3;
+            }
+            PathVerb::Close => {
+                push_command(&mut data, 'Z', &[], last, relative);
+                last = contour_start;
+            }
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::core::path_types::PathFillType;
+    use crate::svg::svg_canvas;
+
+    /// Stream whose content can be read after canvas is dropped.
+    #[derive(Debug, Default, Clone)]
+    struct SharedStream(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedStream {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    fn triangle() -> Path {
+        let mut builder = PathBuilder::new();
+        builder
+            .move_to(10.0, 10.0)
+            .line_to(20.0, 10.0)
+            .line_to(20.0, 30.0)
+            .close();
+        builder.finish().unwrap()
+    }
+
+    fn red_paint() -> Paint {
+        Paint::from_color(&Color4f::from_rgba(1.0, 0.0, 0.0, 1.0))
+    }
+
+    #[test]
+    fn test_path_data() {
+        assert_eq!(path_data(&triangle(), false), "M10 10 L20 10 L20 30 Z");
+        assert_eq!(path_data(&triangle(), true), "m10 10 l10 0 l0 20 z");
+
+        let mut builder = PathBuilder::new();
+        builder
+            .move_to(0.0, 0.0)
+            .quad_to(5.0, 10.0, 10.0, 0.0)
+            .cubic_to(10.0, 5.0, 15.0, 5.0, 15.0, 0.0)
+            .move_to(1.0, 1.0)
+            .line_to(2.0, 2.0);
+        let path = builder.finish().unwrap();
+        assert_eq!(
+            path_data(&path, false),
+            "M0 0 Q5 10 10 0 C10 5 15 5 15 0 M1 1 L2 2"
+        );
+        assert_eq!(
+            path_data(&path, true),
+            "m0 0 q5 10 10 0 c0 5 5 5 5 0 m-14 1 l1 1"
+        );
+    }
+
+    #[test]
+    fn test_conic_path_data() {
+        let mut builder = PathBuilder::new();
+        builder
+            .move_to(10.0, 0.0)
+            .conic_to(10.0, 10.0, 0.0, 10.0, std::f32::consts::FRAC_1_SQRT_2);
+        let path = builder.finish().unwrap();
+        let data = path_data(&path, false);
+        assert_eq!(data.matches('Q').count(), CONIC_QUAD_COUNT);
+        assert!(data.ends_with(" 0 10"));
+    }
+
+    #[test]
+    fn test_paint_attributes() {
+        let mut paint = red_paint();
+        paint.set_alphaf(0.5);
+        assert_eq!(
+            paint_attributes(&paint, true),
+            " fill=\"rgb(255,0,0)\" fill-opacity=\"0.5\" fill-rule=\"evenodd\""
+        );
+
+        let mut paint = red_paint();
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(2.5);
+        paint.set_stroke_cap(StrokeCap::Round);
+        paint.set_stroke_join(StrokeJoin::Bevel);
+        paint.set_blend_mode(BlendMode::Multiply);
+        assert_eq!(
+            paint_attributes(&paint, true),
+            " fill=\"none\" stroke=\"rgb(255,0,0)\" stroke-width=\"2.5\" \
+             stroke-linecap=\"round\" stroke-linejoin=\"bevel\" \
+             style=\"mix-blend-mode:multiply\""
+        );
+
+        let mut paint = red_paint();
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(0.0);
+        paint.set_stroke_miter(3.0);
+        assert_eq!(
+            paint_attributes(&paint, false),
+            " fill=\"none\" stroke=\"rgb(255,0,0)\" stroke-width=\"1\" \
+             vector-effect=\"non-scaling-stroke\" stroke-miterlimit=\"3\""
+        );
+    }
+
+    #[test]
+    fn test_canvas() {
+        let stream = SharedStream::default();
+        let bounds = Rect::from_xywh(0.0, 0.0, 100.0, 50.0);
+        let mut canvas =
+            svg_canvas::make(&bounds, Box::new(stream.clone()), Flag::NoPrettyXml, None);
+        canvas.draw_path(&triangle(), &red_paint());
+        canvas.save();
+        canvas.clip_path(&triangle(), ClipOp::Intersect, true);
+        canvas.draw_path(&triangle(), &red_paint());
+        canvas.restore();
+        drop(canvas);
+
+        assert_eq!(
+            stream.contents(),
+            "<?xml version=\"1.0\" encoding=\"utf-8\" ?>\
+             <svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"100\" height=\"50\" viewBox=\"0 0 100 50\">\
+             <path fill=\"rgb(255,0,0)\" d=\"M10 10 L20 10 L20 30 Z\"/>\
+             <clipPath id=\"clip0\"><path d=\"M10 10 L20 10 L20 30 Z\"/></clipPath>\
+             <g clip-path=\"url(#clip0)\">\
+             <path fill=\"rgb(255,0,0)\" d=\"M10 10 L20 10 L20 30 Z\"/>\
+             </g></svg>"
+        );
+    }
+
+    #[test]
+    fn test_canvas_closes_groups_on_drop() {
+        let stream = SharedStream::default();
+        let bounds = Rect::from_xywh(0.0, 0.0, 10.0, 10.0);
+        let mut canvas = svg_canvas::make(&bounds, Box::new(stream.clone()), Flag::empty(), None);
+        let mut paint = red_paint();
+        paint.set_alphaf(0.5);
+        canvas.save_layer(None, Some(&paint));
+        canvas.clip_path(&triangle(), ClipOp::Difference, true);
+        drop(canvas);
+
+        let svg = stream.contents();
+        assert!(svg.contains("<g opacity=\"0.5\" style=\"isolation:isolate\">\n"));
+        assert!(svg.contains(
+            "<clipPath id=\"clip0\"><path clip-rule=\"evenodd\" \
+             d=\"M0 0 L10 0 L10 10 L0 10 Z M10 10 L20 10 L20 30 Z\"/></clipPath>\n"
+        ));
+        assert!(svg.ends_with("</g>\n</g>\n</svg>\n"));
+    }
+
+    #[test]
+    fn test_draw_nothing() {
+        let stream = SharedStream::default();
+        let bounds = Rect::from_xywh(0.0, 0.0, 10.0, 10.0);
+        let mut canvas =
+            svg_canvas::make(&bounds, Box::new(stream.clone()), Flag::NoPrettyXml, None);
+        let mut paint = red_paint();
+        paint.set_alphaf(0.0);
+        canvas.draw_path(&triangle(), &paint);
+        let mut builder = PathBuilder::new();
+        builder.set_fill_type(PathFillType::EvenOdd);
+        canvas.draw_path(&builder.finish().unwrap_or_default(), &red_paint());
+        drop(canvas);
+        assert!(!stream.contents().contains("<path"));
+    }
+
+    #[test]
+    fn test_metadata() {
+        let stream = SharedStream::default();
+        let bounds = Rect::from_xywh(0.0, 0.0, 10.0, 10.0);
+        let metadata = DocumentMetadata {
+            title: "A & B".to_owned(),
+            ..DocumentMetadata::default()
+        };
+        let canvas = svg_canvas::make(
+            &bounds,
+            Box::new(stream.clone()),
+            Flag::empty(),
+            Some(&metadata),
+        );
+        drop(canvas);
+        let svg = stream.contents();
+        let title = svg.find("<title>A &amp; B</title>").unwrap();
+        assert!(svg.find("<svg ").unwrap() < title);
+        assert!(svg.contains("<metadata>"));
+    }
+}
@@ -0,0 +1,994 @@
+// Copyright (c) 2024 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by Lesser General Public License that can be found
+// in the LICENSE file.
+
+//! Loads SVG documents and renders them into Canvas.
+//!
+//! Supported are `<svg>`, `<g>`, `<path>` and basic shapes, transforms, and solid
+//! fills and strokes given as presentation attributes or inline style.
+
+use crate::base::xml::{self, XmlElement};
+use crate::core::canvas::Canvas;
+use crate::core::color::Color;
+use crate::core::matrix::Matrix;
+use crate::core::paint::Paint;
+use crate::core::paint_types::{PaintStyle, StrokeCap, StrokeJoin};
+use crate::core::path::Path;
+use crate::core::path_builder::PathBuilder;
+use crate::core::path_types::{ArcSize, PathDirection, PathFillType};
+use crate::core::point::Point;
+use crate::core::rect::Rect;
+use crate::core::rrect::RRect;
+use crate::core::scalar::Scalar;
+use crate::core::size::Size;
+
+/// Parsed SVG document, which can be drawn into any Canvas.
+///
+/// Shapes are stored in document order, with their transforms already applied.
+#[derive(Debug, Clone)]
+pub struct SvgDom {
+    container_size: Size,
+    shapes: Vec<(Path, Paint)>,
+}
+
+impl SvgDom {
+    /// Parses SVG document `text`.
+    ///
+    /// Unsupported elements and attributes are ignored.
+    ///
+    /// Returns None if `text` is not well-formed XML, or if its root element is not `<svg>`.
+    // TODO(Shaohua): Support <use>, gradients, clip paths and CSS style sheets.
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let root = xml::parse(text)?;
+        if root.name != "svg" {
+            return None;
+        }
+        let view_box = root.attribute("viewBox").and_then(parse_view_box);
+        let width = root.attribute("width").and_then(parse_length);
+        let height = root.attribute("height").and_then(parse_length);
+        let container_size = match (&view_box, width, height) {
+            (_, Some(width), Some(height)) => Size::from_wh(width, height),
+            (Some(view_box), width, height) => Size::from_wh(
+                width.unwrap_or_else(|| view_box.width()),
+                height.unwrap_or_else(|| view_box.height()),
+            ),
+            (None, width, height) => Size::from_wh(width.unwrap_or(0.0), height.unwrap_or(0.0)),
+        };
+
+        let mut state = State::default();
+        if let Some(view_box) = view_box {
+            let preserve = root.attribute("preserveAspectRatio").unwrap_or("");
+            state.matrix = view_box_matrix(&view_box, &container_size, preserve);
+        }
+        let mut dom = Self {
+            container_size,
+            shapes: Vec::new(),
+        };
+        if state.apply_element(&root) {
+            dom.visit_children(&root, &state);
+        }
+        Some(dom)
+    }
+
+    /// Returns width and height of document, from its `width` and `height` attributes,
+    /// or from its `viewBox` if they are not set.
+    #[must_use]
+    #[inline]
+    pub const fn container_size(&self) -> &Size {
+        &self.container_size
+    }
+
+    /// Returns shapes of document in drawing order.
+    #[must_use]
+    #[inline]
+    pub fn shapes(&self) -> &[(Path, Paint)] {
+        &self.shapes
+    }
+
+    /// Draws document into `canvas`, with its top-left corner at origin.
+    pub fn render(&self, canvas: &mut Canvas) {
+        for (path, paint) in &self.shapes {
+            canvas.draw_path(path, paint);
+        }
+    }
+
+    fn visit_children(&mut self, element: &XmlElement, state: &State) {
+        for child in &element.children {
+            self.visit(child, state);
+        }
+    }
+
+    fn visit(&mut self, element: &XmlElement, parent: &State) {
+        let mut state = parent.clone();
+        if !state.apply_element(element) {
+            return;
+        }
+        let path = match element.name.as_str() {
+            "svg" => {
+                // Nested viewport is placed at (x, y).
+                // TODO(Shaohua): Clip to viewport and map its viewBox.
+                let x = attribute_length(element, "x").unwrap_or(0.0);
+                let y = attribute_length(element, "y").unwrap_or(0.0);
+                state.matrix = Matrix::from_concat(&state.matrix, &Matrix::from_translate(x, y));
+                self.visit_children(element, &state);
+                return;
+            }
+            "g" => {
+                self.visit_children(element, &state);
+                return;
+            }
+            "path" => element.attribute("d").and_then(parse_path_data),
+            "rect" => rect_path(element),
+            "circle" => circle_path(element),
+            "ellipse" => ellipse_path(element),
+            "line" => line_path(element),
+            "polyline" => polygon_path(element, false),
+            "polygon" => polygon_path(element, true),
+            _ => None,
+        };
+        if let Some(path) = path {
+            self.add_shape(&path, &state);
+        }
+    }
+
+    fn add_shape(&mut self, path: &Path, state: &State) {
+        let mut path = path.transform(&state.matrix);
+        if state.fill_rule_even_odd {
+            let mut builder = PathBuilder::from_path(path);
+            builder.set_fill_type(PathFillType::EvenOdd);
+            let Some(even_odd) = builder.finish() else {
+                return;
+            };
+            path = even_odd;
+        }
+        if state.fill.alpha() > 0 {
+            let mut paint = state.paint(state.fill, state.fill_opacity);
+            paint.set_style(PaintStyle::Fill);
+            self.shapes.push((path.clone(), paint));
+        }
+        if state.stroke.alpha() > 0 && state.stroke_width > 0.0 {
+            let mut paint = state.paint(state.stroke, state.stroke_opacity);
+            paint.set_style(PaintStyle::Stroke);
+            // TODO(Shaohua): Stroke in local coordinates for non-uniform scales.
+            let [sx, kx, ky, sy] = [
+                state.matrix.scale_x(),
+                state.matrix.skew_x(),
+                state.matrix.skew_y(),
+                state.matrix.scale_y(),
+            ];
+            let scale = sx.mul_add(sy, -kx * ky).abs().sqrt();
+            paint.set_stroke_width(state.stroke_width * scale);
+            paint.set_stroke_cap(state.stroke_cap);
+            paint.set_stroke_join(state.stroke_join);
+            paint.set_stroke_miter(state.stroke_miter);
+            self.shapes.push((path, paint));
+        }
+    }
+}
+
+/// Inherited properties of an element.
+#[derive(Debug, Clone)]
+struct State {
+    matrix: Matrix,
+    color: Color,
+    /// Transparent if fill is `none`.
+    fill: Color,
+    fill_opacity: Scalar,
+    fill_rule_even_odd: bool,
+    /// Transparent if stroke is `none`.
+    stroke: Color,
+    stroke_opacity: Scalar,
+    stroke_width: Scalar,
+    stroke_cap: StrokeCap,
+    stroke_join: StrokeJoin,
+    stroke_miter: Scalar,
+
+    /// Product of `opacity` of element and its ancestors.
+    // TODO(Shaohua): Draw groups with opacity into layers.
+    opacity: Scalar,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            matrix: Matrix::identity(),
+            color: Color::from_argb(0xFF, 0x00, 0x00, 0x00),
+            fill: Color::from_argb(0xFF, 0x00, 0x00, 0x00),
+            fill_opacity: 1.0,
+            fill_rule_even_odd: false,
+            stroke: Color::from_argb(0x00, 0x00, 0x00, 0x00),
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            stroke_cap: StrokeCap::Butt,
+            stroke_join: StrokeJoin::Miter,
+            stroke_miter: 4.0,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl State {
+    /// Updates state with attributes and inline style of `element`.
+    ///
+    /// Returns false if element is not displayed.
+    fn apply_element(&mut self, element: &XmlElement) -> bool {
+        if let Some(transform) = element.attribute("transform").and_then(parse_transform) {
+            self.matrix = Matrix::from_concat(&self.matrix, &transform);
+        }
+
+        // `color` is applied first, as other properties may refer to it.
+        let style = element
+            .attribute("style")
+            .map(parse_style)
+            .unwrap_or_default();
+        let value_of = |name: &str| {
+            style
+                .iter()
+                .rev()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+                .or_else(|| element.attribute(name))
+        };
+        if let Some(color) = value_of("color").and_then(parse_color) {
+            self.color = color;
+        }
+        for (name, _) in &element.attributes {
+            if let Some(value) = value_of(name) {
+                if !self.apply_property(name, value.trim()) {
+                    return false;
+                }
+            }
+        }
+        for (name, value) in &style {
+            if element.attribute(name).is_none() && !self.apply_property(name, value.trim()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns false if property hides the element.
+    fn apply_property(&mut self, name: &str, value: &str) -> bool {
+        match name {
+            "display" => return value != "none",
+            "fill" => {
+                if let Some(paint) = self.parse_paint(value) {
+                    self.fill = paint;
+                }
+            }
+            "stroke" => {
+                if let Some(paint) = self.parse_paint(value) {
+                    self.stroke = paint;
+                }
+            }
+            "fill-opacity" => {
+                if let Some(opacity) = parse_opacity(value) {
+                    self.fill_opacity = opacity;
+                }
+            }
+            "stroke-opacity" => {
+                if let Some(opacity) = parse_opacity(value) {
+                    self.stroke_opacity = opacity;
+                }
+            }
+            "opacity" => {
+                if let Some(opacity) = parse_opacity(value) {
+                    self.opacity *= opacity;
+                }
+            }
+            "fill-rule" => self.fill_rule_even_odd = value == "evenodd",
+            "stroke-width" => {
+                if let Some(width) = parse_length(value).filter(|width| *width >= 0.0) {
+                    self.stroke_width = width;
+                }
+            }
+            "stroke-linecap" => match value {
+                "butt" => self.stroke_cap = StrokeCap::Butt,
+                "round" => self.stroke_cap = StrokeCap::Round,
+                "square" => self.stroke_cap = StrokeCap::Square,
+                _ => (),
+            },
+            "stroke-linejoin" => match value {
+                "miter" => self.stroke_join = StrokeJoin::Miter,
+                "round" => self.stroke_join = StrokeJoin::Round,
+                "bevel" => self.stroke_join = StrokeJoin::Bevel,
+                _ => (),
+            },
+            "stroke-miterlimit" => {
+                if let Some(miter) = parse_number(value).filter(|miter| *miter >= 1.0) {
+                    self.stroke_miter = miter;
+                }
+            }
+            _ => (),
+        }
+        true
+    }
+
+    /// Parses value of `fill` or `stroke`, where `none` is transparent.
+    fn parse_paint(&self, value: &str) -> Option<Color> {
+        match value {
+            "none" => Some(Color::from_argb(0x00, 0x00, 0x00, 0x00)),
+            "currentColor" => Some(self.color),
+            _ => parse_color(value),
+        }
+    }
+
+    fn paint(&self, color: Color, opacity: Scalar) -> Paint {
+        let mut paint = Paint::new();
+        paint.set_anti_alias(true);
+        paint.set_color(color);
+        paint.set_alphaf(paint.get_alphaf() * opacity * self.opacity);
+        paint
+    }
+}
+
+/// Splits inline style into property declarations.
+fn parse_style(style: &str) -> Vec<(&str, &str)> {
+    style
+        .split(';')
+        .filter_map(|declaration| {
+            let (name, value) = declaration.split_once(':')?;
+            Some((name.trim(), value.trim()))
+        })
+        .collect()
+}
+
+fn parse_number(value: &str) -> Option<Scalar> {
+    let mut cursor = Cursor::new(value);
+    let number = cursor.number()?;
+    cursor.skip_separators();
+    cursor.is_end().then_some(number)
+}
+
+/// Parses a length in user units.
+///
+/// Returns None for percentages and relative units.
+fn parse_length(value: &str) -> Option<Scalar> {
+    let value = value.trim();
+    let units = [
+        ("px", 1.0),
+        ("pt", 4.0 / 3.0),
+        ("pc", 16.0),
+        ("mm", 96.0 / 25.4),
+        ("cm", 96.0 / 2.54),
+        ("in", 96.0),
+    ];
+    for (unit, scale) in units {
+        if let Some(number) = value.strip_suffix(unit) {
+            return parse_number(number).map(|number| number * scale);
+        }
+    }
+    parse_number(value)
+}
+
+fn parse_opacity(value: &str) -> Option<Scalar> {
+    let opacity = value.strip_suffix('%').map_or_else(
+        || parse_number(value),
+        |percent| parse_number(percent).map(|p| p / 100.0),
+    );
+    opacity.map(|opacity| opacity.clamp(0.0, 1.0))
+}
+
+fn parse_view_box(value: &str) -> Option<Rect> {
+    let mut cursor = Cursor::new(value);
+    let x = cursor.number()?;
+    let y = cursor.number()?;
+    let width = cursor.number()?;
+    let height = cursor.number()?;
+    if width <= 0.0 || height <= 0.0 {
+        return None;
+    }
+    Some(Rect::from_xywh(x, y, width, height))
+}
+
+/// Returns matrix mapping `view_box` to viewport of `size`, following `preserveAspectRatio`.
+// TODO(Shaohua): Support alignments other than xMidYMid, and slice.
+fn view_box_matrix(view_box: &Rect, size: &Size, preserve_aspect_ratio: &str) -> Matrix {
+    let sx = size.width() / view_box.width();
+    let sy = size.height() / view_box.height();
+    if preserve_aspect_ratio.trim() == "none" {
+        let translate = Matrix::from_translate(-view_box.left() * sx, -view_box.top() * sy);
+        return Matrix::from_concat(&translate, &Matrix::from_scale(sx, sy));
+    }
+    // Scale uniformly and center view box in viewport.
+    let scale = sx.min(sy);
+    let dx = view_box.width().mul_add(-scale, size.width()) / 2.0;
+    let dy = view_box.height().mul_add(-scale, size.height()) / 2.0;
+    let translate = Matrix::from_translate(
+        view_box.left().mul_add(-scale, dx),
+        view_box.top().mul_add(-scale, dy),
+    );
+    Matrix::from_concat(&translate, &Matrix::from_scale(scale, scale))
+}
+
+/// Parses a `transform` attribute.
+fn parse_transform(value: &str) -> Option<Matrix> {
+    let mut matrix = Matrix::identity();
+    let mut rest = value.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    while !rest.is_empty() {
+        let open = rest.find('(')?;
+        let close = open + 1 + rest[open + 1..].find(')')?;
+        let name = rest[..open].trim();
+        let mut cursor = Cursor::new(&rest[open + 1..close]);
+        let mut args = Vec::new();
+        while let Some(arg) = cursor.number() {
+            args.push(arg);
+        }
+        if !cursor.is_end() {
+            return None;
+        }
+        let transform = match (name, args.as_slice()) {
+            ("matrix", &[scale_x, skew_y, skew_x, scale_y, trans_x, trans_y]) => Matrix::from_all(
+                scale_x, skew_x, trans_x, skew_y, scale_y, trans_y, 0.0, 0.0, 1.0,
+            ),
+            ("translate", &[tx]) => Matrix::from_translate(tx, 0.0),
+            ("translate", &[tx, ty]) => Matrix::from_translate(tx, ty),
+            ("scale", &[s]) => Matrix::from_scale(s, s),
+            ("scale", &[sx, sy]) => Matrix::from_scale(sx, sy),
+            ("rotate", &[angle]) => Matrix::from_rotate(angle),
+            ("rotate", &[angle, cx, cy]) => Matrix::from_concat(
+                &Matrix::from_concat(&Matrix::from_translate(cx, cy), &Matrix::from_rotate(angle)),
+                &Matrix::from_translate(-cx, -cy),
+            ),
+            ("skewX", &[angle]) => Matrix::from_all(
+                1.0,
+                angle.to_radians().tan(),
+                0.0,
+                0.0,
+                1.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+            ),
+            ("skewY", &[angle]) => Matrix::from_all(
+                1.0,
+                0.0,
+                0.0,
+                angle.to_radians().tan(),
+                1.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+            ),
+            _ => return None,
+        };
+        matrix = Matrix::from_concat(&matrix, &transform);
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Some(matrix)
+}
+
+/// Parses a color in `#rgb`, `#rrggbb`, `rgb()`, `rgba()` or keyword form.
+fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        let digit = |index: usize| u8::from_str_radix(hex.get(index..=index)?, 16).ok();
+        let byte = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+        return match hex.len() {
+            3 => Some(Color::from_rgb(
+                digit(0)? * 0x11,
+                digit(1)? * 0x11,
+                digit(2)? * 0x11,
+            )),
+            6 => Some(Color::from_rgb(byte(0)?, byte(2)?, byte(4)?)),
+            8 => Some(Color::from_argb(byte(6)?, byte(0)?, byte(2)?, byte(4)?)),
+            _ => None,
+        };
+    }
+    if let Some(args) = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))
+    {
+        return parse_rgb_function(args.strip_suffix(')')?);
+    }
+    let rgb = match value.to_ascii_lowercase().as_str() {
+        "transparent" => return Some(Color::from_argb(0x00, 0x00, 0x00, 0x00)),
+        "black" => 0x00_00_00,
+        "silver" => 0xC0_C0_C0,
+        "gray" | "grey" => 0x80_80_80,
+        "white" => 0xFF_FF_FF,
+        "maroon" => 0x80_00_00,
+        "red" => 0xFF_00_00,
+        "purple" => 0x80_00_80,
+        "fuchsia" | "magenta" => 0xFF_00_FF,
+        "green" => 0x00_80_00,
+        "lime" => 0x00_FF_00,
+        "olive" => 0x80_80_00,
+        "yellow" => 0xFF_FF_00,
+        "navy" => 0x00_00_80,
+        "blue" => 0x00_00_FF,
+        "teal" => 0x00_80_80,
+        "aqua" | "cyan" => 0x00_FF_FF,
+        "orange" => 0xFF_A5_00,
+        _ => return None,
+    };
+    let [_, red, green, blue] = u32::to_be_bytes(rgb);
+    Some(Color::from_rgb(red, green, blue))
+}
+
+/// Parses arguments of `rgb()` and `rgba()`, as numbers or percentages.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn parse_rgb_function(args: &str) -> Option<Color> {
+    let mut channels = [0_u8; 4];
+    channels[3] = 0xFF;
+    let args: Vec<&str> = args.split(',').map(str::trim).collect();
+    if args.len() != 3 && args.len() != 4 {
+        return None;
+    }
+    for (index, arg) in args.iter().enumerate() {
+        let value = if let Some(percent) = arg.strip_suffix('%') {
+            parse_number(percent)? / 100.0
+        } else if index == 3 {
+            parse_number(arg)?
+        } else {
+            parse_number(arg)? / 255.0
+        };
+        channels[index] = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+    let [red, green, blue, alpha] = channels;
+    Some(Color::from_argb(alpha, red, green, blue))
+}
+
+fn attribute_length(element: &XmlElement, name: &str) -> Option<Scalar> {
+    element.attribute(name).and_then(parse_length)
+}
+
+fn rect_path(element: &XmlElement) -> Option<Path> {
+    let x = attribute_length(element, "x").unwrap_or(0.0);
+    let y = attribute_length(element, "y").unwrap_or(0.0);
+    let width = attribute_length(element, "width")?;
+    let height = attribute_length(element, "height")?;
+    if width <= 0.0 || height <= 0.0 {
+        return None;
+    }
+    let rect = Rect::from_xywh(x, y, width, height);
+    let rx = attribute_length(element, "rx").filter(|rx| *rx > 0.0);
+    let ry = attribute_length(element, "ry").filter(|ry| *ry > 0.0);
+    let (rx, ry) = match (rx, ry) {
+        (None, None) => return PathBuilder::from_rect(&rect),
+        (Some(rx), None) => (rx, rx),
+        (None, Some(ry)) => (ry, ry),
+        (Some(rx), Some(ry)) => (rx, ry),
+    };
+    let rounded = RRect::from_rect_xy(&rect, rx.min(width / 2.0), ry.min(height / 2.0));
+    let mut builder = PathBuilder::new();
+    builder.add_rrect(&rounded);
+    builder.finish()
+}
+
+fn circle_path(element: &XmlElement) -> Option<Path> {
+    let cx = attribute_length(element, "cx").unwrap_or(0.0);
+    let cy = attribute_length(element, "cy").unwrap_or(0.0);
+    let r = attribute_length(element, "r")?;
+    if r <= 0.0 {
+        return None;
+    }
+    PathBuilder::from_circle(cx, cy, r)
+}
+
+fn ellipse_path(element: &XmlElement) -> Option<Path> {
+    let cx = attribute_length(element, "cx").unwrap_or(0.0);
+    let cy = attribute_length(element, "cy").unwrap_or(0.0);
+    let rx = attribute_length(element, "rx")?;
+    let ry = attribute_length(element, "ry")?;
+    if rx <= 0.0 || ry <= 0.0 {
+        return None;
+    }
+    PathBuilder::from_oval(&Rect::from_ltrb(cx - rx, cy - ry, cx + rx, cy + ry))
+}
+
+fn line_path(element: &XmlElement) -> Option<Path> {
+    let x1 = attribute_length(element, "x1").unwrap_or(0.0);
+    let y1 = attribute_length(element, "y1").unwrap_or(0.0);
+    let x2 = attribute_length(element, "x2").unwrap_or(0.0);
+    let y2 = attribute_length(element, "y2").unwrap_or(0.0);
+    let mut builder = PathBuilder::new();
+    builder.move_to(x1, y1).line_to(x2, y2);
+    builder.finish()
+}
+
+fn polygon_path(element: &XmlElement, is_closed: bool) -> Option<Path> {
+    let mut cursor = Cursor::new(element.attribute("points")?);
+    let mut points = Vec::new();
+    while let Some(x) = cursor.number() {
+        let y = cursor.number()?;
+        points.push(Point::from_xy(x, y));
+    }
+    if points.len() < 2 {
+        return None;
+    }
+    let mut builder = PathBuilder::new();
+    builder.add_polygon(&points, is_closed);
+    builder.finish()
+}
+
+/// Parses path data of `d` attribute.
+///
+/// Parsing stops at the first error, and the path up to that point is returned,
+/// as required by SVG.
+#[must_use]
+pub fn parse_path_data(data: &str) -> Option<Path> {
+    let mut builder = PathBuilder::new();
+    let mut cursor = Cursor::new(data);
+    let mut current = Point::new();
+    let mut contour_start = Point::new();
+    // Reflected control point for smooth curves, and whether previous segment was a cubic.
+    let mut last_ctrl: Option<(Point, bool)> = None;
+    let mut command: Option<char> = None;
+    let mut needs_move = false;
+
+    loop {
+        cursor.skip_separators();
+        if cursor.is_end() {
+            break;
+        }
+        let cmd = match cursor.command() {
+            // Path data must start with a moveto.
+            Some(cmd) if command.is_some() || cmd == 'M' || cmd == 'm' => cmd,
+            Some(_) => break,
+            // Repeated arguments of previous command, moveto repeats as lineto.
+            None => match command {
+                Some('M') => 'L',
+                Some('m') => 'l',
+                Some(cmd) if cmd != 'Z' && cmd != 'z' => cmd,
+                _ => break,
+            },
+        };
+        command = Some(cmd);
+        let relative = cmd.is_ascii_lowercase();
+        let origin = if relative { current } else { Point::new() };
+        let upper = cmd.to_ascii_uppercase();
+        if needs_move && upper != 'M' {
+            builder.move_to_point(contour_start);
+        }
+        needs_move = false;
+
+        let Some(ctrl) =
+            parse_segment(&mut cursor, &mut builder, upper, origin, current, last_ctrl)
+        else {
+            break;
+        };
+        last_ctrl = ctrl;
+        if upper == 'Z' {
+            builder.close();
+            current = contour_start;
+            needs_move = true;
+        } else if let Some(point) = builder.last_point() {
+            current = point;
+            if upper == 'M' {
+                contour_start = point;
+            }
+        }
+    }
+    builder.finish()
+}
+
+/// Parses arguments of one path segment and appends it to `builder`.
+///
+/// Returns None on error, or control point to be reflected by next segment.
+#[allow(clippy::option_option)]
+fn parse_segment(
+    cursor: &mut Cursor,
+    builder: &mut PathBuilder,
+    command: char,
+    origin: Point,
+    current: Point,
+    last_ctrl: Option<(Point, bool)>,
+) -> Option<Option<(Point, bool)>> {
+    let point = |cursor: &mut Cursor| -> Option<Point> {
+        let x = cursor.number()?;
+        let y = cursor.number()?;
+        Some(Point::from_xy(x, y) + origin)
+    };
+    let reflect = |is_cubic: bool| match last_ctrl {
+        Some((ctrl, was_cubic)) if was_cubic == is_cubic => current * 2.0 - ctrl,
+        _ => current,
+    };
+    match command {
+        'M' => {
+            builder.move_to_point(point(cursor)?);
+        }
+        'L' => {
+            builder.line_to_point(point(cursor)?);
+        }
+        'H' => {
+            let x = cursor.number()? + origin.x();
+            builder.line_to(x, current.y());
+        }
+        'V' => {
+            let y = cursor.number()? + origin.y();
+            builder.line_to(current.x(), y);
+        }
+        'C' => {
+            let ctrl1 = point(cursor)?;
+            let ctrl2 = point(cursor)?;
+            builder.cubic_to_point(ctrl1, ctrl2, point(cursor)?);
+            return Some(Some((ctrl2, true)));
+        }
+        'S' => {
+            let ctrl1 = reflect(true);
+            let ctrl2 = point(cursor)?;
+            builder.cubic_to_point(ctrl1, ctrl2, point(cursor)?);
+            return Some(Some((ctrl2, true)));
+        }
+        'Q' => {
+            let ctrl = point(cursor)?;
+            builder.quad_to_point(ctrl, point(cursor)?);
+            return Some(Some((ctrl, false)));
+        }
+        'T' => {
+            let ctrl = reflect(false);
+            builder.quad_to_point(ctrl, point(cursor)?);
+            return Some(Some((ctrl, false)));
+        }
+        'A' => {
+            let rx = cursor.number()?;
+            let ry = cursor.number()?;
+            let x_axis_rotate = cursor.number()?;
+            let large_arc = if cursor.flag()? {
+                ArcSize::Large
+            } else {
+                ArcSize::Small
+            };
+            let sweep = if cursor.flag()? {
+                PathDirection::Cw
+            } else {
+                PathDirection::Ccw
+            };
+            let end = point(cursor)?;
+            builder.arc_to_rotate(Point::from_xy(rx, ry), x_axis_rotate, large_arc, sweep, end);
+        }
+        'Z' => (),
+        _ => return None,
+    }
+    Some(None)
+}
+
+/// Reads numbers, flags and commands of path data and other attribute values.
+#[derive(Debug, Clone)]
+struct Cursor<'a> {
+    text: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    const fn new(text: &'a str) -> Self {
+        Self { text }
+    }
+
+    const fn is_end(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Skips whitespace and at most one comma.
+    fn skip_separators(&mut self) {
+        self.text = self.text.trim_start();
+        if let Some(rest) = self.text.strip_prefix(',') {
+            self.text = rest.trim_start();
+        }
+    }
+
+    /// Reads a path command letter.
+    fn command(&mut self) -> Option<char> {
+        let c = self.text.chars().next().filter(char::is_ascii_alphabetic)?;
+        self.text = &self.text[1..];
+        Some(c)
+    }
+
+    /// Reads an arc flag, which may not be followed by a separator.
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.text.chars().next()? {
+            '0' => false,
+            '1' => true,
+            _ => return None,
+        };
+        self.text = &self.text[1..];
+        self.skip_separators();
+        Some(flag)
+    }
+
+    /// Reads a number, followed by optional separators.
+    fn number(&mut self) -> Option<Scalar> {
+        self.skip_separators();
+        let bytes = self.text.as_bytes();
+        let mut end = 0;
+        if matches!(bytes.first(), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        let digits_start = end;
+        while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+            end += 1;
+        }
+        if bytes.get(end) == Some(&b'.') {
+            end += 1;
+            while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+                end += 1;
+            }
+        }
+        if end == digits_start || (end == digits_start + 1 && bytes[digits_start] == b'.') {
+            return None;
+        }
+        if matches!(bytes.get(end), Some(b'e' | b'E')) {
+            let mut exponent_end = end + 1;
+            if matches!(bytes.get(exponent_end), Some(b'+' | b'-')) {
+                exponent_end += 1;
+            }
+            let exponent_digits = exponent_end;
+            while bytes.get(exponent_end).is_some_and(u8::is_ascii_digit) {
+                exponent_end += 1;
+            }
+            if exponent_end > exponent_digits {
+                end = exponent_end;
+            }
+        }
+        let number = self.text[..end].parse().ok()?;
+        self.text = &self.text[end..];
+        self.skip_separators();
+        Some(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_point(matrix: &Matrix, x: Scalar, y: Scalar, expected: (Scalar, Scalar)) {
+        let point = matrix.map_xy(x, y);
+        assert!(
+            (point.x() - expected.0).abs() < 1e-4 && (point.y() - expected.1).abs() < 1e-4,
+            "{point:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_parse_transform() {
+        let matrix = parse_transform("translate(10, 20) scale(2)").unwrap();
+        assert_point(&matrix, 1.0, 1.0, (12.0, 22.0));
+
+        let matrix = parse_transform("rotate(90 10 10)").unwrap();
+        assert_point(&matrix, 20.0, 10.0, (10.0, 20.0));
+
+        let matrix = parse_transform("matrix(1 0 0 1 5 6),translate(1)").unwrap();
+        assert_point(&matrix, 0.0, 0.0, (6.0, 6.0));
+
+        let matrix = parse_transform("skewX(45)").unwrap();
+        assert_point(&matrix, 0.0, 10.0, (10.0, 10.0));
+
+        assert!(parse_transform("").unwrap().is_identity());
+    }
+
+    #[test]
+    fn test_parse_transform_malformed() {
+        assert!(parse_transform(") scale(2)").is_none());
+        assert!(parse_transform("scale(2").is_none());
+        assert!(parse_transform("scale(2 3 4)").is_none());
+        assert!(parse_transform("scale(a)").is_none());
+        assert!(parse_transform("shear(1)").is_none());
+        assert!(parse_transform("scale(2) )").is_none());
+    }
+
+    #[test]
+    fn test_parse_path_data() {
+        let path = parse_path_data("M10 10 h20 v20 H10 z").unwrap();
+        assert_eq!(
+            path.points(),
+            &[
+                Point::from_xy(10.0, 10.0),
+                Point::from_xy(30.0, 10.0),
+                Point::from_xy(30.0, 30.0),
+                Point::from_xy(10.0, 30.0),
+            ]
+        );
+
+        // Repeated moveto arguments are treated as lineto.
+        let path = parse_path_data("m1,1 2,2 3-3").unwrap();
+        assert_eq!(
+            path.points(),
+            &[
+                Point::from_xy(1.0, 1.0),
+                Point::from_xy(3.0, 3.0),
+                Point::from_xy(6.0, 0.0),
+            ]
+        );
+
+        let path =
+            parse_path_data("M0 0C1 2 3 4 5 6S9 10 11 12Q1 1 2 2T4 4A5 5 0 1 0 14 4").unwrap();
+        assert!(path.points().len() > 10);
+    }
+
+    #[test]
+    fn test_parse_path_data_malformed() {
+        // Path data must start with a moveto.
+        assert!(parse_path_data("L10 10").map_or(true, |path| path.points().is_empty()));
+
+        // Path up to the first error is kept.
+        let path = parse_path_data("M0 0 L10 10 L20 x L30 30").unwrap();
+        assert_eq!(
+            path.points(),
+            &[Point::from_xy(0.0, 0.0), Point::from_xy(10.0, 10.0)]
+        );
+
+        // Arc flags must be 0 or 1.
+        let path = parse_path_data("M0 0 L5 5 A5 5 0 2 0 10 0").unwrap();
+        assert_eq!(
+            path.points(),
+            &[Point::from_xy(0.0, 0.0), Point::from_xy(5.0, 5.0)]
+        );
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#f80"), Some(Color::from_rgb(0xFF, 0x88, 0x00)));
+        assert_eq!(
+            parse_color("#102030"),
+            Some(Color::from_rgb(0x10, 0x20, 0x30))
+        );
+        assert_eq!(
+            parse_color("#10203080"),
+            Some(Color::from_argb(0x80, 0x10, 0x20, 0x30))
+        );
+        assert_eq!(
+            parse_color("rgb(255, 50%, 0)"),
+            Some(Color::from_rgb(0xFF, 0x80, 0x00))
+        );
+        assert_eq!(
+            parse_color("rgba(0, 0, 255, 0.5)"),
+            Some(Color::from_argb(0x80, 0x00, 0x00, 0xFF))
+        );
+        assert_eq!(
+            parse_color(" Red "),
+            Some(Color::from_rgb(0xFF, 0x00, 0x00))
+        );
+
+        assert_eq!(parse_color("#ff"), None);
+        assert_eq!(parse_color("#gggggg"), None);
+        assert_eq!(parse_color("#\u{e9}\u{e9}\u{e9}"), None);
+        assert_eq!(parse_color("rgb(1, 2)"), None);
+        assert_eq!(parse_color("rgb(1, 2, 3"), None);
+        assert_eq!(parse_color("nocolor"), None);
+    }
+
+    #[test]
+    fn test_parse_document() {
+        let dom = SvgDom::parse(concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50">"#,
+            r#"<rect x="10" y="10" width="20" height="20" fill="blue"/>"#,
+            r#"<g transform="translate(5 5)"><circle cx="50" cy="25" r="10"/></g>"#,
+            r#"<path d="M0 0 L10 10" stroke="red" fill="none"/>"#,
+            "</svg>"
+        ))
+        .unwrap();
+        assert_eq!(dom.container_size(), &Size::from_wh(100.0, 50.0));
+        assert_eq!(dom.shapes().len(), 3);
+        assert_eq!(
+            dom.shapes()[0].1.get_color(),
+            Color::from_rgb(0x00, 0x00, 0xFF)
+        );
+        let points = dom.shapes()[1].0.points();
+        let left = points.iter().map(Point::x).fold(Scalar::MAX, Scalar::min);
+        let top = points.iter().map(Point::y).fold(Scalar::MAX, Scalar::min);
+        assert!((left - 45.0).abs() < 1e-4 && (top - 20.0).abs() < 1e-4);
+        assert_eq!(dom.shapes()[2].1.get_style(), PaintStyle::Stroke);
+    }
+
+    #[test]
+    fn test_parse_document_malformed() {
+        assert!(SvgDom::parse("").is_none());
+        assert!(SvgDom::parse("<svg").is_none());
+        assert!(SvgDom::parse("<html></html>").is_none());
+
+        // Invalid attributes are ignored instead of rejecting the document.
+        let dom = SvgDom::parse(concat!(
+            r#"<svg width="10" height="10">"#,
+            r#"<rect width="5" height="5" transform=") scale(2)" fill="bad"/>"#,
+            r#"<path d="Q"/>"#,
+            "</svg>"
+        ))
+        .unwrap();
+        assert!(dom.shapes().len() <= 1);
+    }
+}